@@ -0,0 +1,248 @@
+// `auth login`：交互式输入凭据，验证并确认可访问的区域后写入配置文件
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+
+use cloudflare_ddns::cloudflare::{CloudflareClient, Zone};
+use cloudflare_ddns::config::{self, DEFAULT_TIMEOUT};
+use cloudflare_ddns::credentials;
+use cloudflare_ddns::i18n::tr;
+use cloudflare_ddns::summary::{FailureKind, RunError};
+use cloudflare_ddns::state;
+
+/// `auth` 子命令的操作
+#[derive(Debug, clap::Subcommand)]
+pub enum AuthAction {
+    /// 输入 API Token（或邮箱 + 全局 API Key），验证并列出可访问的区域后写入配置文件
+    Login {
+        /// 使用邮箱 + 全局 API Key 认证，默认使用 API Token
+        #[arg(long)]
+        email_key: bool,
+
+        /// 把 API Token 保存到系统密钥环的该服务名下，配置文件只记录服务名 (api_token_keyring)，需要 keyring 功能
+        #[arg(long, value_name = "SERVICE", conflicts_with = "email_key")]
+        keyring: Option<String>,
+    },
+}
+
+/// 输入的凭据
+enum Credentials {
+    Token(String),
+    EmailKey { email: String, key: String },
+    /// 已保存到系统密钥环的 API Token，值为服务名
+    Keyring(String),
+}
+
+pub async fn run(action: &AuthAction, config_path: &str) -> Result<(), RunError> {
+    match action {
+        AuthAction::Login { email_key, keyring } => login(config_path, *email_key, keyring.as_deref()).await,
+    }
+}
+
+async fn login(config_path: &str, email_key: bool, keyring: Option<&str>) -> Result<(), RunError> {
+    let credentials = if email_key {
+        let email = prompt(&tr!("账户邮箱", "Account email"))?;
+        let key = prompt_secret(&tr!("全局 API Key", "Global API Key"))?;
+        Credentials::EmailKey { email, key }
+    } else {
+        Credentials::Token(prompt_secret("API Token")?)
+    };
+    let client = match &credentials {
+        Credentials::Token(token) => CloudflareClient::new_with_token(token.clone(), DEFAULT_TIMEOUT),
+        Credentials::EmailKey { email, key } => CloudflareClient::new(email.clone(), key.clone(), DEFAULT_TIMEOUT),
+        Credentials::Keyring(_) => unreachable!("keyring credentials are only created after verification"),
+    };
+
+    let detail = client.verify_credentials().await?;
+    println!("{}", tr!("凭据有效（{}）", "Credentials are valid ({})", detail));
+    let zones = client.list_zones().await?;
+    if zones.is_empty() {
+        println!("{}", tr!("凭据无法访问任何区域，请检查令牌的区域资源设置", "The credentials cannot see any zone, check the token's zone resources"));
+    } else {
+        println!("{}", tr!("可访问的区域:", "Visible zones:"));
+        for zone in &zones {
+            println!("  {}  {}", zone.name, zone.id);
+        }
+    }
+    let credentials = match (keyring, credentials) {
+        (Some(service), Credentials::Token(token)) => {
+            credentials::keyring_set(service, &token)?;
+            println!("{}", tr!("已把 API Token 保存到系统密钥环（服务名 {}）", "API Token saved to the OS keyring (service {})", service));
+            Credentials::Keyring(service.to_string())
+        }
+        (_, credentials) => credentials,
+    };
+    store(config_path, &credentials, &zones)
+}
+
+/// 把凭据写入配置文件的 cloudflare 段；配置文件不存在时新建，区域取第一个可访问的区域
+fn store(config_path: &str, credentials: &Credentials, zones: &[Zone]) -> Result<(), RunError> {
+    let config_error = |e: String| RunError::new(FailureKind::Config, e);
+    let path = Path::new(config_path);
+    let (mut value, created) = match std::fs::read_to_string(path) {
+        Ok(content) if cloudflare_ddns::encryption::is_encrypted(content.as_bytes()) => {
+            return Err(config_error(tr!(
+                "配置文件 {} 已加密，请先用 config decrypt 解密后再写入凭据",
+                "Config file {} is encrypted, decrypt it with config decrypt before storing credentials",
+                config_path
+            )));
+        }
+        Ok(content) => {
+            let stripped = config::strip_comments(&content);
+            if stripped != content {
+                println!("{}", tr!("注意: 写回配置文件时其中的注释会被移除", "Note: comments in the configuration file are removed when it is written back"));
+            }
+            let value: serde_json::Value = serde_json::from_str(&stripped)
+                .map_err(|e| config_error(tr!("无法解析配置文件 {}: {}", "Failed to parse config file {}: {}", config_path, e)))?;
+            (value, false)
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            let zone_name = zones.first().map(|zone| zone.name.as_str()).unwrap_or_default();
+            (serde_json::json!({ "cloudflare": { "zone_name": zone_name }, "dns_records": [] }), true)
+        }
+        Err(e) => return Err(config_error(tr!("无法读取配置文件 {}: {}", "Failed to read config file {}: {}", config_path, e))),
+    };
+
+    let Some(cloudflare) = value.as_object_mut().map(|root| root.entry("cloudflare").or_insert_with(|| serde_json::json!({})))
+        .and_then(|cloudflare| cloudflare.as_object_mut())
+    else {
+        return Err(config_error(tr!("配置文件 {} 的格式无效", "Config file {} has an invalid structure", config_path)));
+    };
+    match credentials {
+        Credentials::Token(token) => {
+            cloudflare.insert("auth_type".to_string(), "token".into());
+            cloudflare.insert("api_token".to_string(), token.as_str().into());
+            cloudflare.remove("api_token_env");
+            cloudflare.remove("api_token_keyring");
+            cloudflare.remove("auth_email");
+            cloudflare.remove("auth_key");
+        }
+        Credentials::Keyring(service) => {
+            cloudflare.insert("auth_type".to_string(), "token".into());
+            cloudflare.insert("api_token_keyring".to_string(), service.as_str().into());
+            // 其他令牌来源优先于密钥环
+            for field in ["api_token", "api_token_env", "api_token_credential"] {
+                cloudflare.remove(field);
+            }
+            cloudflare.remove("auth_email");
+            cloudflare.remove("auth_key");
+        }
+        Credentials::EmailKey { email, key } => {
+            cloudflare.insert("auth_type".to_string(), "emailkey".into());
+            cloudflare.insert("auth_email".to_string(), email.as_str().into());
+            cloudflare.insert("auth_key".to_string(), key.as_str().into());
+            cloudflare.remove("api_token");
+            cloudflare.remove("api_token_env");
+            cloudflare.remove("api_token_keyring");
+        }
+    }
+    let zone_name = cloudflare.get("zone_name").and_then(|zone| zone.as_str()).unwrap_or_default();
+    if !zone_name.is_empty() && !zones.iter().any(|zone| zone.name == zone_name) {
+        println!("{}", tr!("警告: 凭据无法访问配置中的区域 {}", "Warning: the credentials cannot see the configured zone {}", zone_name));
+    }
+
+    let content = serde_json::to_string_pretty(&value).unwrap_or_default() + "\n";
+    state::write_atomic_private(path, content.as_bytes())
+        .map_err(|e| config_error(tr!("无法写入配置文件 {}: {}", "Failed to write config file {}: {}", config_path, e)))?;
+    println!("{}", tr!("已把凭据写入 {}", "Credentials written to {}", config_path));
+    if created {
+        println!("{}", tr!("已新建配置文件，请在 dns_records 中添加要更新的记录", "Created a new configuration file, add the records to update under dns_records"));
+    }
+    Ok(())
+}
+
+/// 显示提示并读取一行输入，输入为空时返回错误
+fn prompt(label: &str) -> Result<String, RunError> {
+    print!("{}: ", label);
+    let _ = io::stdout().flush();
+    let mut line = String::new();
+    io::stdin().lock().read_line(&mut line)
+        .map_err(|e| RunError::new(FailureKind::Config, tr!("无法读取输入: {}", "Failed to read input: {}", e)))?;
+    let line = line.trim().to_string();
+    if line.is_empty() {
+        return Err(RunError::new(FailureKind::Config, tr!("{} 不能为空", "{} must not be empty", label)));
+    }
+    Ok(line)
+}
+
+/// 与 `prompt` 相同，但终端不回显输入
+fn prompt_secret(label: &str) -> Result<String, RunError> {
+    let echo = EchoOff::new();
+    let result = prompt(label);
+    if echo.is_some() {
+        // 回车也没有回显，补上换行
+        println!();
+    }
+    result
+}
+
+/// 关闭终端回显，离开作用域时恢复；标准输入不是终端时不做处理
+#[cfg(unix)]
+struct EchoOff(libc::termios);
+
+#[cfg(unix)]
+impl EchoOff {
+    fn new() -> Option<Self> {
+        let mut termios = unsafe { std::mem::zeroed::<libc::termios>() };
+        if unsafe { libc::tcgetattr(libc::STDIN_FILENO, &mut termios) } != 0 {
+            return None;
+        }
+        let mut silent = termios;
+        silent.c_lflag &= !libc::ECHO;
+        if unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &silent) } != 0 {
+            return None;
+        }
+        Some(Self(termios))
+    }
+}
+
+#[cfg(unix)]
+impl Drop for EchoOff {
+    fn drop(&mut self) {
+        unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &self.0) };
+    }
+}
+
+#[cfg(windows)]
+struct EchoOff(u32);
+
+#[cfg(windows)]
+impl EchoOff {
+    fn new() -> Option<Self> {
+        use windows_sys::Win32::System::Console::{ENABLE_ECHO_INPUT, GetConsoleMode, GetStdHandle, STD_INPUT_HANDLE, SetConsoleMode};
+        let handle = unsafe { GetStdHandle(STD_INPUT_HANDLE) };
+        let mut mode = 0;
+        if unsafe { GetConsoleMode(handle, &mut mode) } == 0 || unsafe { SetConsoleMode(handle, mode & !ENABLE_ECHO_INPUT) } == 0 {
+            return None;
+        }
+        Some(Self(mode))
+    }
+}
+
+#[cfg(windows)]
+impl Drop for EchoOff {
+    fn drop(&mut self) {
+        use windows_sys::Win32::System::Console::{GetStdHandle, STD_INPUT_HANDLE, SetConsoleMode};
+        unsafe { SetConsoleMode(GetStdHandle(STD_INPUT_HANDLE), self.0) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn store_replaces_credentials_in_existing_config() {
+        let path = std::env::temp_dir().join(format!("cloudflare_ddns_auth_{}.json", std::process::id()));
+        let config = "{\n  // 旧凭据\n  \"cloudflare\": { \"auth_type\": \"emailkey\", \"auth_email\": \"a@example.com\", \"auth_key\": \"k\", \"zone_name\": \"example.com\" },\n  \"dns_records\": []\n}\n";
+        std::fs::write(&path, config).unwrap();
+        store(path.to_str().unwrap(), &Credentials::Token("t".to_string()), &[]).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(value["cloudflare"], serde_json::json!({ "auth_type": "token", "zone_name": "example.com", "api_token": "t" }));
+
+        // 改用密钥环后配置中不再保留明文令牌
+        store(path.to_str().unwrap(), &Credentials::Keyring("cf-ddns".to_string()), &[]).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(value["cloudflare"], serde_json::json!({ "auth_type": "token", "zone_name": "example.com", "api_token_keyring": "cf-ddns" }));
+    }
+}
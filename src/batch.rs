@@ -0,0 +1,196 @@
+// `batch` 子命令：从标准输入逐行读取 JSON 记录操作并应用，供外部系统把本程序当作 DNS 记录写入工具
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tracing::{info, warn};
+
+use cloudflare_ddns::cloudflare::{CloudflareClient, DnsRecord, UpdateDnsRecordParams};
+use cloudflare_ddns::config::Config;
+use cloudflare_ddns::error::ErrorCategory;
+use cloudflare_ddns::i18n::tr;
+use cloudflare_ddns::summary::{self, FailureKind, RecordAction, RunError};
+use cloudflare_ddns::update::{connect, load_config};
+use cloudflare_ddns::{history, logging, overrides, state};
+
+/// 一行输入：把名称与类型对应的记录设置为 `content`，不存在时新建
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Operation {
+    name: String,
+    #[serde(rename = "type")]
+    record_type: String,
+    content: String,
+    /// 未指定时沿用已有记录的设置，新建时为 1（自动）
+    ttl: Option<u32>,
+    /// 未指定时沿用已有记录的设置，新建时为 false
+    proxied: Option<bool>,
+}
+
+/// 每行输入对应输出的一行结果
+#[derive(Debug, Serialize)]
+struct OperationResult {
+    /// 输入中的行号，从 1 开始
+    line: usize,
+    name: Option<String>,
+    #[serde(rename = "type")]
+    record_type: Option<String>,
+    action: RecordAction,
+    /// 更新前的内容，新建时为空
+    previous: Option<String>,
+    content: Option<String>,
+    error: Option<String>,
+    error_category: Option<ErrorCategory>,
+}
+
+/// 逐行应用标准输入中的操作，并以 JSON Lines 输出每行的结果；某行失败后继续处理后续行
+///
+/// 退出码与单次运行相同：有失败时按第一个失败的错误类别，有更新或新建时为 10，否则为 0。
+pub async fn run(config_path: &str, dry_run: bool) -> Result<u8, RunError> {
+    let _lock = match dry_run {
+        true => None,
+        false => crate::lock::acquire(config_path).await,
+    };
+    let config = load_config(config_path)?;
+    let (client, zone_id) = connect(&config).await?;
+    let mut records = client.list_dns_records(&zone_id).await?;
+
+    let mut first_failure = None;
+    let mut changed = false;
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    let mut number = 0;
+    while let Some(line) = lines.next_line().await
+        .map_err(|e| RunError::new(FailureKind::Config, tr!("无法读取标准输入: {}", "Failed to read standard input: {}", e)))?
+    {
+        number += 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let result = match serde_json::from_str::<Operation>(&line) {
+            Ok(operation) => apply(&client, &zone_id, &config, &mut records, operation, number, dry_run).await,
+            Err(e) => OperationResult {
+                line: number,
+                name: None,
+                record_type: None,
+                action: RecordAction::Failed,
+                previous: None,
+                content: None,
+                error: Some(tr!("无法解析第 {} 行: {}", "Failed to parse line {}: {}", number, e)),
+                error_category: Some(ErrorCategory::Parse),
+            },
+        };
+        if let Some(error) = &result.error {
+            warn!(line = number, error = %error, "{}", tr!("记录操作失败", "Record operation failed"));
+        }
+        first_failure = first_failure.or(result.error_category);
+        changed |= matches!(result.action, RecordAction::Updated | RecordAction::Created | RecordAction::WouldUpdate | RecordAction::WouldCreate);
+        println!("{}", serde_json::to_string(&result).unwrap_or_default());
+    }
+
+    Ok(match first_failure {
+        Some(category) => summary::exit_code(FailureKind::Api, category),
+        None if changed => summary::EXIT_UPDATED,
+        None => 0,
+    })
+}
+
+/// 应用一行操作；`records` 为区域中的现有记录，写入成功后同步更新
+async fn apply(
+    client: &CloudflareClient,
+    zone_id: &str,
+    config: &Config,
+    records: &mut Vec<DnsRecord>,
+    operation: Operation,
+    line: usize,
+    dry_run: bool,
+) -> OperationResult {
+    let mut result = OperationResult {
+        line,
+        name: Some(operation.name.clone()),
+        record_type: Some(operation.record_type.clone()),
+        action: RecordAction::Unchanged,
+        previous: None,
+        content: Some(operation.content.clone()),
+        error: None,
+        error_category: None,
+    };
+    if !overrides::selects(&operation.name) {
+        result.action = RecordAction::Skipped;
+        return result;
+    }
+
+    let index = records.iter().position(|r| r.name == operation.name && r.r#type == operation.record_type);
+    let existing = index.map(|i| &records[i]);
+    let ttl = overrides::ttl().or(operation.ttl).or(existing.map(|r| r.ttl)).unwrap_or(1);
+    let proxied = overrides::proxied().or(operation.proxied).or(existing.map(|r| r.proxied)).unwrap_or(false);
+    result.previous = existing.map(|r| r.content.clone());
+
+    let written = match existing {
+        Some(existing) if existing.content == operation.content && existing.ttl == ttl && existing.proxied == proxied => return result,
+        Some(_) if dry_run => {
+            result.action = RecordAction::WouldUpdate;
+            return result;
+        }
+        None if dry_run => {
+            result.action = RecordAction::WouldCreate;
+            return result;
+        }
+        Some(existing) => {
+            result.action = RecordAction::Updated;
+            client.update_dns_record(UpdateDnsRecordParams {
+                zone_id,
+                record_id: &existing.id,
+                record_type: &operation.record_type,
+                name: &operation.name,
+                content: &operation.content,
+                ttl,
+                proxied,
+                comment: None,
+            }).await
+        }
+        None => {
+            result.action = RecordAction::Created;
+            client.create_dns_record(zone_id, &operation.record_type, &operation.name, &operation.content, ttl, proxied).await
+        }
+    };
+
+    match written {
+        Ok(record) => {
+            match &result.previous {
+                Some(previous) => info!(target: logging::CHANGE_TARGET, record = %operation.name, r#type = %operation.record_type, old_ip = %previous, ip = %record.content, "{}", tr!("DNS 记录更新成功", "DNS record updated")),
+                None => info!(target: logging::CHANGE_TARGET, record = %operation.name, r#type = %operation.record_type, ip = %record.content, "{}", tr!("新的 DNS 记录已创建", "DNS record created")),
+            }
+            let store = state::open(config);
+            if config.history.enabled {
+                let entry = history::HistoryEntry::new(&operation.name, config.zone_name(), &operation.record_type, result.previous.as_deref(), &record.content);
+                if let Err(e) = store.append_history(&entry) {
+                    warn!(error = %e, "{}", tr!("无法写入历史文件", "Failed to write history file"));
+                }
+            }
+            let published = state::PublishedRecord::new(&operation.name, &operation.record_type, config.zone_name(), &record.content);
+            if let Err(e) = store.save_published(&published) {
+                warn!(error = %e, "{}", tr!("无法写入状态文件", "Failed to write state file"));
+            }
+            match index {
+                Some(i) => records[i] = record,
+                None => records.push(record),
+            }
+        }
+        Err(e) => {
+            result.action = RecordAction::Failed;
+            result.error_category = Some(e.category());
+            result.error = Some(e.to_string());
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn operation_fields() {
+        let operation: Operation = serde_json::from_str(r#"{"name":"txt.example.com","type":"TXT","content":"v=spf1 -all","ttl":300}"#).unwrap();
+        assert_eq!((operation.record_type.as_str(), operation.ttl, operation.proxied), ("TXT", Some(300), None));
+        assert!(serde_json::from_str::<Operation>(r#"{"name":"a.example.com","type":"A","content":"1.2.3.4","comment":"x"}"#).is_err());
+    }
+}
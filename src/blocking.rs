@@ -0,0 +1,80 @@
+//! 同步接口：供不使用 async 的程序调用检测与更新流程
+//!
+//! 每次调用都会创建一个单线程的 tokio 运行时并阻塞到完成，因此不能在 async 上下文中调用，
+//! 否则 tokio 会 panic；已有运行时的程序应直接使用 [`crate::update`] 与 [`crate::ip_utils`] 中的 async 函数。
+//!
+//! ```no_run
+//! # fn example() -> Result<(), cloudflare_ddns::Error> {
+//! let config = cloudflare_ddns::load_config("config.json")?;
+//! let (ips, times) = cloudflare_ddns::blocking::detect_for(&config)?;
+//! for result in cloudflare_ddns::blocking::reconcile(&config, &ips, &times, false)? {
+//!     println!("{}: {:?}", result.name, result.outcome);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+use std::future::Future;
+use std::time::Duration;
+
+use crate::config::{Config, IpDetectionConfig, IpVersion};
+use crate::error::Error;
+use crate::i18n::tr;
+use crate::ip_utils::{DetectedIps, DetectionTimes};
+use crate::summary::{RunError, RunSummary};
+use crate::update::{Plan, RecordResult};
+use crate::{ip_utils, update};
+
+/// 在新建的单线程运行时中执行 `future`，无法创建运行时时返回 [`Error::Scheduler`]
+fn block_on<T, E: From<Error>>(future: impl Future<Output = Result<T, E>>) -> Result<T, E> {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| Error::scheduler(tr!("无法创建 tokio 运行时: {}", "Failed to build tokio runtime: {}", e)))?
+        .block_on(future)
+}
+
+/// [`update::run_once`] 的同步版本
+pub fn run_once(config_path: &str, force: bool, check_only: bool, dry_run: bool) -> Result<RunSummary, RunError> {
+    block_on(update::run_once(config_path, force, check_only, dry_run))
+}
+
+/// [`ip_utils::detect_ips`] 的同步版本
+pub fn detect_ips(versions: &[IpVersion], detection: &IpDetectionConfig, timeout: Duration) -> Result<(DetectedIps, DetectionTimes), Error> {
+    block_on(ip_utils::detect_ips(versions, detection, timeout))
+}
+
+/// [`ip_utils::detect_for`] 的同步版本
+pub fn detect_for(config: &Config) -> Result<(DetectedIps, DetectionTimes), Error> {
+    block_on(ip_utils::detect_for(config))
+}
+
+/// [`ip_utils::get_external_ipv4`] 的同步版本
+pub fn get_external_ipv4(timeout: Duration) -> Result<String, Error> {
+    block_on(ip_utils::get_external_ipv4(timeout))
+}
+
+/// [`ip_utils::get_external_ipv6`] 的同步版本
+pub fn get_external_ipv6(timeout: Duration) -> Result<String, Error> {
+    block_on(ip_utils::get_external_ipv6(timeout))
+}
+
+/// [`update::reconcile`] 的同步版本
+pub fn reconcile(config: &Config, ips: &DetectedIps, times: &DetectionTimes, force: bool) -> Result<Vec<RecordResult>, Error> {
+    block_on(update::reconcile(config, ips, times, force))
+}
+
+/// [`update::plan`] 的同步版本
+pub fn plan(config: &Config, ips: &DetectedIps, times: &DetectionTimes, force: bool) -> Result<Plan, Error> {
+    block_on(update::plan(config, ips, times, force))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runs_without_runtime() {
+        let (ips, _) = detect_ips(&[], &IpDetectionConfig::default(), Duration::from_secs(1)).unwrap();
+        assert_eq!(ips, DetectedIps::default());
+    }
+}
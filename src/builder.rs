@@ -0,0 +1,312 @@
+//! 在代码中构建配置与更新器，不需要先写出 JSON 配置文件
+//!
+//! ```no_run
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! use cloudflare_ddns::DdnsUpdater;
+//!
+//! let updater = DdnsUpdater::builder()
+//!     .api_token("your-api-token")
+//!     .zone("example.com")
+//!     .a_record("home.example.com")
+//!     .aaaa_record("home.example.com")
+//!     .updater()?;
+//! let summary = updater.run(false).await?;
+//! println!("{}", summary.success);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! 构建的配置默认不写入历史与状态文件，需要时用 [`ConfigBuilder::history_file`] 与 [`ConfigBuilder::state_file`] 指定。
+use std::time::Duration;
+
+use crate::config::{self, CloudflareConfig, CloudflareCredentials, Config, DnsRecordConfig, IpVersion, ZoneConfig};
+use crate::error::{Error, Result};
+use crate::i18n::tr;
+use crate::ip_utils::{self, DetectedIps, DetectionTimes};
+use crate::logging;
+use crate::summary::{RunError, RunSummary};
+use crate::update::{self, Plan, RecordResult};
+
+/// [`Config`] 的构建器，由 [`Config::builder`] 或 [`DdnsUpdater::builder`] 创建
+#[derive(Debug, Default)]
+pub struct ConfigBuilder {
+    credentials: Option<CloudflareCredentials>,
+    endpoint: Option<String>,
+    zone_name: Option<String>,
+    /// 主区域的记录，以及是否在 build 时套用 `ttl` 与 `proxied`
+    records: Vec<(DnsRecordConfig, bool)>,
+    zones: Vec<ZoneConfig>,
+    ttl: Option<u32>,
+    proxied: bool,
+    ip_detection: config::IpDetectionConfig,
+    timeout: Option<Duration>,
+    history_file: Option<String>,
+    state_file: Option<String>,
+}
+
+impl Config {
+    /// 创建配置构建器
+    pub fn builder() -> ConfigBuilder {
+        ConfigBuilder::default()
+    }
+}
+
+impl ConfigBuilder {
+    /// 使用 API 令牌认证
+    pub fn api_token(mut self, token: impl Into<String>) -> Self {
+        self.credentials = Some(CloudflareCredentials {
+            auth_type: "token".to_string(),
+            auth_email: None,
+            auth_key: None,
+            auth_key_env: None,
+            api_token: Some(token.into()),
+            api_token_env: None,
+            api_token_credential: None,
+            api_token_keyring: None,
+            endpoint: None,
+        });
+        self
+    }
+
+    /// 使用邮箱与 Global API Key 认证
+    pub fn global_api_key(mut self, email: impl Into<String>, key: impl Into<String>) -> Self {
+        self.credentials = Some(CloudflareCredentials {
+            auth_type: "emailkey".to_string(),
+            auth_email: Some(email.into()),
+            auth_key: Some(key.into()),
+            auth_key_env: None,
+            api_token: None,
+            api_token_env: None,
+            api_token_credential: None,
+            api_token_keyring: None,
+            endpoint: None,
+        });
+        self
+    }
+
+    /// CloudFlare API 地址，默认为 [`crate::cloudflare::API_ENDPOINT`]
+    pub fn endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.endpoint = Some(endpoint.into());
+        self
+    }
+
+    /// 主区域（CloudFlare）的名称
+    pub fn zone(mut self, zone_name: impl Into<String>) -> Self {
+        self.zone_name = Some(zone_name.into());
+        self
+    }
+
+    /// 在主区域中添加一条指向 IPv4 地址的 A 记录
+    pub fn a_record(self, name: impl Into<String>) -> Self {
+        self.address_record(name.into(), IpVersion::V4)
+    }
+
+    /// 在主区域中添加一条指向 IPv6 地址的 AAAA 记录
+    pub fn aaaa_record(self, name: impl Into<String>) -> Self {
+        self.address_record(name.into(), IpVersion::V6)
+    }
+
+    fn address_record(mut self, name: String, version: IpVersion) -> Self {
+        let (record_type, ip_version) = match version {
+            IpVersion::V4 => ("A", "v4"),
+            IpVersion::V6 => ("AAAA", "v6"),
+        };
+        let record = DnsRecordConfig {
+            name,
+            r#type: record_type.to_string(),
+            ttl: 1,
+            proxied: false,
+            ip_version: ip_version.to_string(),
+            uplink: None,
+            members: Vec::new(),
+            failover: None,
+            also_update: Vec::new(),
+            notify: Vec::new(),
+            cooldown: None,
+            alias_of: None,
+        };
+        self.records.push((record, true));
+        self
+    }
+
+    /// 在主区域中添加一条完整设置的记录，不受 [`ttl`](Self::ttl) 与 [`proxied`](Self::proxied) 影响
+    pub fn record(mut self, record: DnsRecordConfig) -> Self {
+        self.records.push((record, false));
+        self
+    }
+
+    /// 添加一个使用其他 DNS 服务商的区域，与配置文件中的 `zones` 相同
+    pub fn extra_zone(mut self, zone: ZoneConfig) -> Self {
+        self.zones.push(zone);
+        self
+    }
+
+    /// [`a_record`](Self::a_record) 与 [`aaaa_record`](Self::aaaa_record) 添加的记录的 TTL，默认为 1（自动）
+    pub fn ttl(mut self, ttl: u32) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// [`a_record`](Self::a_record) 与 [`aaaa_record`](Self::aaaa_record) 添加的记录是否经过 CloudFlare 代理，默认不代理
+    pub fn proxied(mut self, proxied: bool) -> Self {
+        self.proxied = proxied;
+        self
+    }
+
+    /// IPv4 检测服务的地址，服务需要以纯文本返回请求方的地址
+    pub fn ipv4_url(mut self, url: impl Into<String>) -> Self {
+        self.ip_detection.ipv4_url = Some(url.into());
+        self
+    }
+
+    /// IPv6 检测服务的地址
+    pub fn ipv6_url(mut self, url: impl Into<String>) -> Self {
+        self.ip_detection.ipv6_url = Some(url.into());
+        self
+    }
+
+    /// 单个网络请求的超时时间，默认为 [`config::DEFAULT_TIMEOUT`]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// 记录 IP 变化历史的文件
+    pub fn history_file(mut self, path: impl Into<String>) -> Self {
+        self.history_file = Some(path.into());
+        self
+    }
+
+    /// 保存持久化状态的文件
+    pub fn state_file(mut self, path: impl Into<String>) -> Self {
+        self.state_file = Some(path.into());
+        self
+    }
+
+    /// 检查设置并生成配置
+    pub fn build(self) -> Result<Config> {
+        let ttl = self.ttl.unwrap_or(1);
+        let records: Vec<DnsRecordConfig> = self.records.into_iter()
+            .map(|(record, defaults)| match defaults {
+                true => DnsRecordConfig { ttl, proxied: self.proxied, ..record },
+                false => record,
+            })
+            .collect();
+
+        let cloudflare = match (self.zone_name, self.credentials) {
+            (Some(zone_name), Some(mut credentials)) => {
+                credentials.endpoint = self.endpoint;
+                Some(CloudflareConfig { credentials, zone_name })
+            }
+            (Some(_), None) => {
+                return Err(Error::config(tr!("未设置 CloudFlare 认证信息", "CloudFlare credentials are not set")));
+            }
+            (None, _) if !records.is_empty() => {
+                return Err(Error::config(tr!("添加记录前需要设置区域", "A zone is required before adding records")));
+            }
+            (None, _) => None,
+        };
+
+        let mut config = Config {
+            cloudflare,
+            dns_records: records,
+            zones: self.zones,
+            ip_detection: self.ip_detection,
+            uplinks: Vec::new(),
+            schedule: config::ScheduleConfig { timeout: self.timeout, state_file: self.state_file, ..Default::default() },
+            notifications: Default::default(),
+            mqtt: None,
+            logging: Default::default(),
+            metrics: None,
+            http: None,
+            history: Default::default(),
+            dual_stack: Default::default(),
+            drift: Default::default(),
+            update_limit: Default::default(),
+        };
+        config.history.file = self.history_file;
+        if config.records().next().is_none() {
+            return Err(Error::config(tr!("没有添加任何记录", "No records were added")));
+        }
+        config.ip_versions().map_err(|e| Error::config(tr!("IP 版本无效: {}", "Invalid IP version: {}", e)))?;
+        logging::redact::register(config.secrets());
+        Ok(config)
+    }
+
+    /// 检查设置并创建更新器
+    pub fn updater(self) -> Result<DdnsUpdater> {
+        self.build().map(DdnsUpdater::new)
+    }
+}
+
+/// 持有配置的更新器，供嵌入到其他程序中反复调用
+#[derive(Debug, Clone)]
+pub struct DdnsUpdater {
+    config: Config,
+}
+
+impl DdnsUpdater {
+    /// 创建构建器，最后调用 [`ConfigBuilder::updater`] 得到更新器
+    pub fn builder() -> ConfigBuilder {
+        ConfigBuilder::default()
+    }
+
+    pub fn new(config: Config) -> Self {
+        Self { config }
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// 检测记录用到的各版本公网 IP，包括各上行线路的地址
+    pub async fn detect(&self) -> Result<(DetectedIps, DetectionTimes)> {
+        ip_utils::detect_for(&self.config).await
+    }
+
+    /// 使用已检测到的 IP 计算每条记录将要执行的变更，不写入记录
+    pub async fn plan(&self, ips: &DetectedIps, times: &DetectionTimes, force: bool) -> Result<Plan> {
+        update::plan(&self.config, ips, times, force).await
+    }
+
+    /// 使用已检测到的 IP 核对并更新所有记录
+    pub async fn reconcile(&self, ips: &DetectedIps, times: &DetectionTimes, force: bool) -> Result<Vec<RecordResult>> {
+        update::reconcile(&self.config, ips, times, force).await
+    }
+
+    /// 检测 IP 并核对所有记录，与命令行的单次运行相同
+    pub async fn run(&self, force: bool) -> std::result::Result<RunSummary, RunError> {
+        update::run(&self.config, force, false, false).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_primary_zone_records() {
+        let config = Config::builder()
+            .api_token("builder-test-token")
+            .zone("example.com")
+            .ttl(300)
+            .a_record("home.example.com")
+            .aaaa_record("home.example.com")
+            .ipv4_url("http://127.0.0.1/ip")
+            .build()
+            .unwrap();
+
+        assert_eq!(config.zone_name(), "example.com");
+        let records: Vec<_> = config.records().map(|r| (r.r#type.as_str(), r.ttl, r.ip_version.as_str())).collect();
+        assert_eq!(records, [("A", 300, "v4"), ("AAAA", 300, "v6")]);
+        assert_eq!(config.ip_detection.url(IpVersion::V4), "http://127.0.0.1/ip");
+        assert!(config.history.file.is_none());
+    }
+
+    #[test]
+    fn missing_zone_or_credentials_is_an_error() {
+        assert!(Config::builder().api_token("token").a_record("home.example.com").build().is_err());
+        assert!(Config::builder().zone("example.com").a_record("home.example.com").build().is_err());
+        assert!(Config::builder().api_token("token").zone("example.com").build().is_err());
+    }
+}
@@ -1,13 +1,20 @@
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
-#[derive(Debug, Deserialize)]
+use crate::error::{Error, ErrorCategory, Result};
+use crate::i18n::tr;
+use crate::provider::{DnsProvider, ProviderFuture, Record};
+
+#[derive(Debug, Deserialize, Serialize)]
 #[allow(dead_code)]
 pub struct Zone {
     pub id: String,
     pub name: String,
+    #[serde(default)]
+    pub status: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 #[allow(dead_code)]
 pub struct DnsRecord {
     pub id: String,
@@ -16,6 +23,9 @@ pub struct DnsRecord {
     pub r#type: String,
     pub ttl: u32,
     pub proxied: bool,
+    /// 记录的备注，本程序在其中保存记录集成员的标签
+    #[serde(default)]
+    pub comment: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -28,6 +38,8 @@ pub struct UpdateDnsRecordRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub priority: Option<u16>,
     pub proxied: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
 }
 
 /// 更新 DNS 记录时使用的参数结构体
@@ -40,6 +52,8 @@ pub struct UpdateDnsRecordParams<'a> {
     pub content: &'a str,
     pub ttl: u32,
     pub proxied: bool,
+    /// 为空时不修改备注
+    pub comment: Option<&'a str>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -88,24 +102,33 @@ pub struct ResultInfo {
     pub total_count: u32,
 }
 
+/// CloudFlare API 的默认地址
+pub const API_ENDPOINT: &str = "https://api.cloudflare.com/client/v4";
+
 pub struct CloudflareClient {
     client: reqwest::Client,
     auth_email: String,
     auth_key: String,
+    endpoint: String,
 }
 
 impl CloudflareClient {
-    pub fn new(auth_email: String, auth_key: String) -> Self {
+    pub fn new(auth_email: String, auth_key: String, timeout: Duration) -> Self {
         Self {
-            client: reqwest::Client::new(),
+            client: crate::tls::client_builder()
+                .timeout(timeout)
+                .build()
+                .expect("Failed to build client"),
             auth_email,
             auth_key,
+            endpoint: API_ENDPOINT.to_string(),
         }
     }
 
     /// 使用 Bearer Token 的 CloudflareClient
-    pub fn new_with_token(token: String) -> Self {
-        let client = reqwest::Client::builder()
+    pub fn new_with_token(token: String, timeout: Duration) -> Self {
+        let client = crate::tls::client_builder()
+            .timeout(timeout)
             .default_headers({
                 let mut headers = reqwest::header::HeaderMap::new();
                 headers.insert(
@@ -122,12 +145,19 @@ impl CloudflareClient {
             client,
             auth_email: String::new(),
             auth_key: token,
+            endpoint: API_ENDPOINT.to_string(),
         }
     }
 
+    /// 使用其他 API 地址，如反向代理或测试用的模拟服务
+    pub fn with_endpoint(mut self, endpoint: &str) -> Self {
+        self.endpoint = endpoint.trim_end_matches('/').to_string();
+        self
+    }
+
     /// 获取 Zone ID
-    pub async fn get_zone_id(&self, zone_name: &str) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-        let url = format!("https://api.cloudflare.com/client/v4/zones?name={}", zone_name);
+    pub async fn get_zone_id(&self, zone_name: &str) -> Result<String> {
+        let url = format!("{}/zones?name={}", self.endpoint, zone_name);
         
         let response = if !self.auth_email.is_empty() {
             // 使用 Email + API Key 认证
@@ -147,8 +177,11 @@ impl CloudflareClient {
         let _response_text = response.text().await?;
         
         // 检查响应状态码
+        if matches!(status, reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN) {
+            return Err(Error::provider(ErrorCategory::Auth, tr!("API 凭据无效或权限不足，状态码 {}", "API credentials are invalid or lack permission (status {})", status)));
+        }
         if !status.is_success() {
-            return Err(format!("API 请求失败，状态码 {}。请检查您的 API 凭据。", status).into());
+            return Err(Error::provider(ErrorCategory::from_status(status), tr!("API 请求失败，状态码 {}。请检查您的 API 凭据。", "API request failed with status {}. Check your API credentials.", status)));
         }
         
         let zones_response: Result<ListZonesResponse, _> = serde_json::from_str(&_response_text);
@@ -157,66 +190,21 @@ impl CloudflareClient {
                 if zones_response.success && !zones_response.result.is_empty() {
                     Ok(zones_response.result[0].id.clone())
                 } else {
-                    Err("无法获取区域 ID".to_string().into())
+                    Err(Error::provider(ErrorCategory::NotFound, tr!("无法获取区域 ID", "Failed to get zone ID")))
                 }
             }
             Err(_) => {
                 // 解析失败，可能是认证错误或无效的响应格式
-                Err("API 认证失败或凭据无效。请检查您的 API 凭据。".to_string().into())
-            }
-        }
-    }
-
-    /// 获取 DNS 记录 ID
-    pub async fn get_dns_record_id(&self, zone_id: &str, record_name: &str) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-        let url = format!(
-            "https://api.cloudflare.com/client/v4/zones/{}/dns_records?name={}",
-            zone_id, record_name
-        );
-
-        let response = if !self.auth_email.is_empty() {
-            // 使用 Email + API Key 认证
-            self.client
-                .get(&url)
-                .header("X-Auth-Email", &self.auth_email)
-                .header("X-Auth-Key", &self.auth_key)
-                .header("Content-Type", "application/json")
-                .send()
-                .await?
-        } else {
-            // 使用 API Token 认证
-            self.client.get(&url).send().await?
-        };
-
-        let status = response.status();
-        let response_text = response.text().await?;
-        
-        // 检查响应状态码
-        if !status.is_success() {
-            return Err(format!("API 请求失败，状态码 {}: {}。请检查您的 API 凭据。", status, response_text).into());
-        }
-        
-        let dns_response: Result<ListDnsRecordsResponse, _> = serde_json::from_str(&response_text);
-        match dns_response {
-            Ok(dns_response) => {
-                if dns_response.success && !dns_response.result.is_empty() {
-                    Ok(dns_response.result[0].id.clone())
-                } else {
-                    Err(format!("无法获取 DNS 记录 ID: {:?}", dns_response.errors).into())
-                }
-            }
-            Err(_) => {
-                // 解析失败，可能是认证错误或无效的响应格式
-                Err(format!("无法解析 API 响应。请检查您的 API 凭据。\n响应: {}", response_text).into())
+                Err(Error::provider(ErrorCategory::Parse, tr!("API 认证失败或凭据无效。请检查您的 API 凭据。", "API authentication failed or credentials are invalid. Check your API credentials.")))
             }
         }
     }
 
     /// 获取 DNS 记录详情
-    pub async fn get_dns_record(&self, zone_id: &str, record_id: &str) -> Result<DnsRecord, Box<dyn std::error::Error + Send + Sync>> {
+    pub async fn get_dns_record(&self, zone_id: &str, record_id: &str) -> Result<DnsRecord> {
         let url = format!(
-            "https://api.cloudflare.com/client/v4/zones/{}/dns_records/{}",
-            zone_id, record_id
+            "{}/zones/{}/dns_records/{}",
+            self.endpoint, zone_id, record_id
         );
 
         let response = if !self.auth_email.is_empty() {
@@ -238,7 +226,7 @@ impl CloudflareClient {
         
         // 检查响应状态码
         if !status.is_success() {
-            return Err(format!("API 请求失败，状态码 {}: {}。请检查您的 API 凭据。", status, response_text).into());
+            return Err(Error::provider(ErrorCategory::from_status(status), tr!("API 请求失败，状态码 {}: {}。请检查您的 API 凭据。", "API request failed with status {}: {}. Check your API credentials.", status, response_text)));
         }
         
         let response_data: Result<ApiResponse<DnsRecord>, _> = serde_json::from_str(&response_text);
@@ -247,12 +235,12 @@ impl CloudflareClient {
                 if response_data.success {
                     Ok(response_data.result)
                 } else {
-                    Err(format!("无法获取 DNS 记录: {:?}", response_data.errors).into())
+                    Err(Error::provider(ErrorCategory::Other, tr!("无法获取 DNS 记录: {:?}", "Failed to get DNS record: {:?}", response_data.errors)))
                 }
             }
             Err(_) => {
                 // 解析失败，可能是认证错误或无效的响应格式
-                Err(format!("无法解析 API 响应。请检查您的 API 凭据。\n响应: {}", response_text).into())
+                Err(Error::provider(ErrorCategory::Parse, tr!("无法解析 API 响应。请检查您的 API 凭据。\n响应: {}", "Failed to parse API response. Check your API credentials.\nResponse: {}", response_text)))
             }
         }
     }
@@ -261,10 +249,10 @@ impl CloudflareClient {
     pub async fn update_dns_record(
         &self,
         params: UpdateDnsRecordParams<'_>,
-    ) -> Result<DnsRecord, Box<dyn std::error::Error + Send + Sync>> {
+    ) -> Result<DnsRecord> {
         let url = format!(
-            "https://api.cloudflare.com/client/v4/zones/{}/dns_records/{}",
-            params.zone_id, params.record_id
+            "{}/zones/{}/dns_records/{}",
+            self.endpoint, params.zone_id, params.record_id
         );
 
         let update_request = UpdateDnsRecordRequest {
@@ -274,6 +262,7 @@ impl CloudflareClient {
             ttl: params.ttl,
             priority: None,
             proxied: params.proxied,
+            comment: params.comment.map(str::to_string),
         };
 
         let response = if !self.auth_email.is_empty() {
@@ -296,7 +285,7 @@ impl CloudflareClient {
         
         // 检查响应状态码
         if !status.is_success() {
-            return Err(format!("API 请求失败，状态码 {}: {}。请检查您的 API 凭据。", status, response_text).into());
+            return Err(Error::provider(ErrorCategory::from_status(status), tr!("API 请求失败，状态码 {}: {}。请检查您的 API 凭据。", "API request failed with status {}: {}. Check your API credentials.", status, response_text)));
         }
         
         let response_data: Result<ApiResponse<DnsRecord>, _> = serde_json::from_str(&response_text);
@@ -305,12 +294,12 @@ impl CloudflareClient {
                 if response_data.success {
                     Ok(response_data.result)
                 } else {
-                    Err(format!("无法更新 DNS 记录: {:?}", response_data.errors).into())
+                    Err(Error::provider(ErrorCategory::Other, tr!("无法更新 DNS 记录: {:?}", "Failed to update DNS record: {:?}", response_data.errors)))
                 }
             }
             Err(_) => {
                 // 解析失败，可能是认证错误或无效的响应格式
-                Err(format!("无法解析 API 响应。请检查您的 API 凭据。\n响应: {}", response_text).into())
+                Err(Error::provider(ErrorCategory::Parse, tr!("无法解析 API 响应。请检查您的 API 凭据。\n响应: {}", "Failed to parse API response. Check your API credentials.\nResponse: {}", response_text)))
             }
         }
     }
@@ -324,12 +313,7 @@ impl CloudflareClient {
         content: &str,
         ttl: u32,
         proxied: bool,
-    ) -> Result<DnsRecord, Box<dyn std::error::Error + Send + Sync>> {
-        let url = format!(
-            "https://api.cloudflare.com/client/v4/zones/{}/dns_records",
-            zone_id
-        );
-
+    ) -> Result<DnsRecord> {
         let create_request = UpdateDnsRecordRequest {
             record_type: record_type.to_string(),
             name: name.to_string(),
@@ -337,7 +321,17 @@ impl CloudflareClient {
             ttl,
             priority: None,
             proxied,
+            comment: None,
         };
+        self.create_dns_record_from(zone_id, &create_request).await
+    }
+
+    /// 按完整的请求内容创建 DNS 记录，可以带备注
+    pub async fn create_dns_record_from(&self, zone_id: &str, create_request: &UpdateDnsRecordRequest) -> Result<DnsRecord> {
+        let url = format!(
+            "{}/zones/{}/dns_records",
+            self.endpoint, zone_id
+        );
 
         let response = if !self.auth_email.is_empty() {
             // 使用 Email + API Key 认证
@@ -346,12 +340,12 @@ impl CloudflareClient {
                 .header("X-Auth-Email", &self.auth_email)
                 .header("X-Auth-Key", &self.auth_key)
                 .header("Content-Type", "application/json")
-                .json(&create_request)
+                .json(create_request)
                 .send()
                 .await?
         } else {
             // 使用 API Token 认证
-            self.client.post(&url).json(&create_request).send().await?
+            self.client.post(&url).json(create_request).send().await?
         };
 
         let status = response.status();
@@ -359,7 +353,7 @@ impl CloudflareClient {
         
         // 检查响应状态码
         if !status.is_success() {
-            return Err(format!("API 请求失败，状态码 {}: {}。请检查您的 API 凭据。", status, response_text).into());
+            return Err(Error::provider(ErrorCategory::from_status(status), tr!("API 请求失败，状态码 {}: {}。请检查您的 API 凭据。", "API request failed with status {}: {}. Check your API credentials.", status, response_text)));
         }
         
         let response_data: Result<ApiResponse<DnsRecord>, _> = serde_json::from_str(&response_text);
@@ -368,13 +362,214 @@ impl CloudflareClient {
                 if response_data.success {
                     Ok(response_data.result)
                 } else {
-                    Err(format!("无法创建 DNS 记录: {:?}", response_data.errors).into())
+                    Err(Error::provider(ErrorCategory::Other, tr!("无法创建 DNS 记录: {:?}", "Failed to create DNS record: {:?}", response_data.errors)))
                 }
             }
             Err(_) => {
                 // 解析失败，可能是认证错误或无效的响应格式
-                Err(format!("无法解析 API 响应。请检查您的 API 凭据。\n响应: {}", response_text).into())
+                Err(Error::provider(ErrorCategory::Parse, tr!("无法解析 API 响应。请检查您的 API 凭据。\n响应: {}", "Failed to parse API response. Check your API credentials.\nResponse: {}", response_text)))
+            }
+        }
+    }
+
+    /// 查询名称与类型都匹配的记录，不存在时返回 `None`
+    pub async fn find_dns_record(&self, zone_id: &str, name: &str, record_type: &str) -> Result<Option<DnsRecord>> {
+        Ok(self.find_dns_records(zone_id, name, record_type).await?.into_iter().next())
+    }
+
+    /// 查询名称与类型都匹配的所有记录
+    pub async fn find_dns_records(&self, zone_id: &str, name: &str, record_type: &str) -> Result<Vec<DnsRecord>> {
+        let url = format!(
+            "{}/zones/{}/dns_records?name={}&type={}",
+            self.endpoint, zone_id, name, record_type
+        );
+        self.get_all(&url).await
+    }
+
+    /// 删除 DNS 记录
+    pub async fn delete_dns_record(&self, zone_id: &str, record_id: &str) -> Result<()> {
+        let url = format!(
+            "{}/zones/{}/dns_records/{}",
+            self.endpoint, zone_id, record_id
+        );
+        let response = self.request(reqwest::Method::DELETE, &url).send().await?;
+        let status = response.status();
+        let response_text = response.text().await?;
+        if !status.is_success() {
+            return Err(Error::provider(ErrorCategory::from_status(status), tr!("API 请求失败，状态码 {}: {}。请检查您的 API 凭据。", "API request failed with status {}: {}. Check your API credentials.", status, response_text)));
+        }
+        Ok(())
+    }
+
+    /// 验证凭据：API Token 调用 `/user/tokens/verify`，邮箱 + 密钥调用 `/user`，返回令牌状态或账户邮箱
+    pub async fn verify_credentials(&self) -> Result<String> {
+        #[derive(Deserialize)]
+        struct VerifyResponse {
+            success: bool,
+            errors: Vec<ApiError>,
+            result: Option<serde_json::Value>,
+        }
+
+        let url = if self.auth_email.is_empty() {
+            format!("{}/user/tokens/verify", self.endpoint)
+        } else {
+            format!("{}/user", self.endpoint)
+        };
+        let response = self.get(&url).send().await?;
+        let status = response.status();
+        let response_text = response.text().await?;
+        if matches!(status, reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN) {
+            return Err(Error::provider(ErrorCategory::Auth, tr!("API 凭据无效或权限不足，状态码 {}", "API credentials are invalid or lack permission (status {})", status)));
+        }
+        if !status.is_success() {
+            return Err(Error::provider(ErrorCategory::from_status(status), tr!("API 请求失败，状态码 {}: {}。请检查您的 API 凭据。", "API request failed with status {}: {}. Check your API credentials.", status, response_text)));
+        }
+        let data: VerifyResponse = serde_json::from_str(&response_text)
+            .map_err(|_| Error::provider(ErrorCategory::Parse, tr!("无法解析 API 响应。请检查您的 API 凭据。\n响应: {}", "Failed to parse API response. Check your API credentials.\nResponse: {}", response_text)))?;
+        if !data.success {
+            return Err(Error::provider(ErrorCategory::Auth, tr!("凭据验证失败: {:?}", "Credential verification failed: {:?}", data.errors)));
+        }
+        let result = data.result.unwrap_or_default();
+        let field = if self.auth_email.is_empty() { "status" } else { "email" };
+        Ok(result[field].as_str().unwrap_or_default().to_string())
+    }
+
+    /// 按认证方式构造 GET 请求
+    fn get(&self, url: &str) -> reqwest::RequestBuilder {
+        self.request(reqwest::Method::GET, url)
+    }
+
+    /// 按认证方式构造请求
+    fn request(&self, method: reqwest::Method, url: &str) -> reqwest::RequestBuilder {
+        let request = self.client.request(method, url);
+        if self.auth_email.is_empty() {
+            request
+        } else {
+            request
+                .header("X-Auth-Email", &self.auth_email)
+                .header("X-Auth-Key", &self.auth_key)
+        }
+    }
+
+    /// 逐页请求列表接口，直到取完所有结果
+    async fn get_all<T: serde::de::DeserializeOwned>(&self, url: &str) -> Result<Vec<T>> {
+        #[derive(Deserialize)]
+        struct Page<T> {
+            success: bool,
+            errors: Vec<ApiError>,
+            result: Vec<T>,
+            result_info: ResultInfo,
+        }
+
+        let mut items = Vec::new();
+        let mut page = 1;
+        loop {
+            let response = self.get(url).query(&[("page", page), ("per_page", 100)]).send().await?;
+            let status = response.status();
+            let response_text = response.text().await?;
+            if !status.is_success() {
+                return Err(Error::provider(ErrorCategory::from_status(status), tr!("API 请求失败，状态码 {}: {}。请检查您的 API 凭据。", "API request failed with status {}: {}. Check your API credentials.", status, response_text)));
+            }
+            let data: Page<T> = serde_json::from_str(&response_text)
+                .map_err(|_| Error::provider(ErrorCategory::Parse, tr!("无法解析 API 响应。请检查您的 API 凭据。\n响应: {}", "Failed to parse API response. Check your API credentials.\nResponse: {}", response_text)))?;
+            if !data.success {
+                return Err(Error::provider(ErrorCategory::Other, tr!("API 请求失败: {:?}", "API request failed: {:?}", data.errors)));
             }
+            items.extend(data.result);
+            if page >= data.result_info.total_pages {
+                return Ok(items);
+            }
+            page += 1;
+        }
+    }
+
+    /// 列出凭据可访问的所有区域
+    pub async fn list_zones(&self) -> Result<Vec<Zone>> {
+        self.get_all(&format!("{}/zones", self.endpoint)).await
+    }
+
+    /// 列出区域中的所有 DNS 记录
+    pub async fn list_dns_records(&self, zone_id: &str) -> Result<Vec<DnsRecord>> {
+        self.get_all(&format!("{}/zones/{}/dns_records", self.endpoint, zone_id)).await
+    }
+}
+
+/// 绑定到一个区域的 CloudFlare 服务商
+pub struct CloudflareProvider {
+    client: CloudflareClient,
+    zone_id: String,
+}
+
+impl CloudflareProvider {
+    pub fn new(client: CloudflareClient, zone_id: String) -> Self {
+        Self { client, zone_id }
+    }
+}
+
+impl From<DnsRecord> for Record {
+    fn from(record: DnsRecord) -> Self {
+        Self {
+            id: record.id,
+            name: record.name,
+            r#type: record.r#type,
+            content: record.content,
+            ttl: record.ttl,
+            proxied: record.proxied,
+            tag: record.comment,
         }
     }
-}
\ No newline at end of file
+}
+
+impl DnsProvider for CloudflareProvider {
+    fn name(&self) -> &str {
+        "cloudflare"
+    }
+
+    fn get_record<'a>(&'a self, name: &'a str, record_type: &'a str) -> ProviderFuture<'a, Option<Record>> {
+        Box::pin(async move {
+            Ok(self.client.find_dns_record(&self.zone_id, name, record_type).await?.map(Record::from))
+        })
+    }
+
+    fn list_records<'a>(&'a self, name: &'a str, record_type: &'a str) -> ProviderFuture<'a, Vec<Record>> {
+        Box::pin(async move {
+            Ok(self.client.find_dns_records(&self.zone_id, name, record_type).await?.into_iter().map(Record::from).collect())
+        })
+    }
+
+    fn supports_tags(&self) -> bool {
+        true
+    }
+
+    fn upsert_record<'a>(&'a self, record: &'a Record) -> ProviderFuture<'a, Record> {
+        Box::pin(async move {
+            let written = if record.id.is_empty() {
+                self.client.create_dns_record_from(&self.zone_id, &UpdateDnsRecordRequest {
+                    record_type: record.r#type.clone(),
+                    name: record.name.clone(),
+                    content: record.content.clone(),
+                    ttl: record.ttl,
+                    priority: None,
+                    proxied: record.proxied,
+                    comment: record.tag.clone(),
+                }).await?
+            } else {
+                self.client.update_dns_record(UpdateDnsRecordParams {
+                    zone_id: &self.zone_id,
+                    record_id: &record.id,
+                    record_type: &record.r#type,
+                    name: &record.name,
+                    content: &record.content,
+                    ttl: record.ttl,
+                    proxied: record.proxied,
+                    comment: record.tag.as_deref(),
+                }).await?
+            };
+            Ok(written.into())
+        })
+    }
+
+    fn delete_record<'a>(&'a self, record: &'a Record) -> ProviderFuture<'a, ()> {
+        Box::pin(self.client.delete_dns_record(&self.zone_id, &record.id))
+    }
+}
@@ -0,0 +1,371 @@
+// 查看与检查类子命令：validate、status、verify、zones、records、history、healthcheck
+use std::time::Duration;
+
+use serde::Serialize;
+
+use cloudflare_ddns::config::{Config, DnsRecordConfig, IpVersion, StateBackend};
+use cloudflare_ddns::error::{Error, ErrorCategory};
+use cloudflare_ddns::i18n::tr;
+use cloudflare_ddns::summary::{FailureKind, RunError};
+use cloudflare_ddns::update::{check_providers, client_from_config, connect, load_config, setting_problems, zone_provider};
+use cloudflare_ddns::{duration, history, ip_utils, notify, record_set, state};
+
+
+/// 检查配置文件能否解析、认证配置是否完整、每条记录的 IP 版本与类型是否匹配，不访问网络
+pub fn validate(config_path: &str) -> Result<(), RunError> {
+    let config = load_config(config_path)?;
+    check_providers(&config)?;
+
+    let problems = config_problems(&config);
+    if !problems.is_empty() {
+        return Err(RunError::new(FailureKind::Config, problems.join("; ")));
+    }
+    println!("{}", tr!("配置有效: {}（{} 条记录）", "Configuration is valid: {} ({} records)", config_path, config.records().count()));
+    Ok(())
+}
+
+/// 记录配置中的问题：没有记录、ip_version 无效、记录类型与 IP 版本不匹配、上行线路不存在、记录集成员、通知后端或故障转移地址无效、失败通知阈值为 0、更新次数上限无法统计
+pub fn config_problems(config: &Config) -> Vec<String> {
+    let mut problems = Vec::new();
+    if config.records().next().is_none() {
+        problems.push(tr!("未配置任何 DNS 记录", "No DNS records configured"));
+    }
+    for record in config.records() {
+        let expected = match record.get_ip_version() {
+            Ok(IpVersion::V4) => "A",
+            Ok(IpVersion::V6) => "AAAA",
+            Err(_) => {
+                problems.push(tr!("记录 {} 的 ip_version 无效: {}（应为 v4 或 v6）", "Record {} has an invalid ip_version: {} (expected v4 or v6)", record.name, record.ip_version));
+                continue;
+            }
+        };
+        if let Some(uplink) = &record.uplink
+            && !config.uplinks.iter().any(|u| &u.name == uplink)
+        {
+            problems.push(tr!("记录 {} 使用的上行线路 {} 不在 uplinks 中", "Record {} uses uplink {} which is not in uplinks", record.name, uplink));
+        }
+        if record.uplink.is_some() && !record.members.is_empty() {
+            problems.push(tr!("记录 {} 同时设置了 uplink 和 members，请在成员中指定上行线路", "Record {} sets both uplink and members; set the uplink on each member instead", record.name));
+        }
+        for (index, member) in record.members.iter().enumerate() {
+            if member.name.is_empty() || record.members[..index].iter().any(|m| m.name == member.name) {
+                problems.push(tr!("记录集 {} 的成员名称为空或重复: {:?}", "Set {} has an empty or duplicate member name: {:?}", record.name, member.name));
+            }
+            if let Some(uplink) = &member.uplink
+                && !config.uplinks.iter().any(|u| &u.name == uplink)
+            {
+                problems.push(tr!("记录集 {} 的成员 {} 使用的上行线路 {} 不在 uplinks 中", "Member {1} of set {0} uses uplink {2} which is not in uplinks", record.name, member.name, uplink));
+            }
+            if let Some(ip) = member.ip
+                && ip.is_ipv4() != (expected == "A")
+            {
+                problems.push(tr!("记录集 {} 的成员 {} 的地址 {} 与记录类型 {} 不匹配", "Member {1} of set {0} has address {2} which does not match record type {3}", record.name, member.name, ip, expected));
+            }
+        }
+        for name in &record.notify {
+            if !notify::BACKEND_NAMES.contains(&name.as_str()) {
+                problems.push(tr!("记录 {} 的 notify 中的 {} 不是通知后端（可用: {}）", "Record {} has {} in notify, which is not a notification backend (one of: {})", record.name, name, notify::BACKEND_NAMES.join(", ")));
+            } else if !notify::is_configured(&config.notifications, name) {
+                problems.push(tr!("记录 {} 的 notify 中的 {} 未在 notifications 中配置", "Record {} has {} in notify, which is not configured in notifications", record.name, name));
+            }
+        }
+        if let Some(failover) = &record.failover {
+            if !record.members.is_empty() {
+                problems.push(tr!("记录 {} 同时设置了 members 和 failover", "Record {} sets both members and failover", record.name));
+            }
+            for ip in failover.primary.iter().chain([&failover.backup]) {
+                if ip.is_ipv4() != (expected == "A") {
+                    problems.push(tr!("记录 {} 的故障转移地址 {} 与记录类型 {} 不匹配", "Record {} has failover address {} which does not match record type {}", record.name, ip, expected));
+                }
+            }
+        }
+        if record.r#type != expected {
+            problems.push(tr!("记录 {} 的类型 {} 与 ip_version {} 不匹配（应为 {}）", "Record {} has type {} which does not match ip_version {} (expected {})", record.name, record.r#type, record.ip_version, expected));
+        }
+    }
+    problems.extend(setting_problems(config));
+    problems
+}
+
+/// `status` 中单条记录的状态
+#[derive(Debug, Serialize)]
+struct RecordStatus<'a> {
+    name: &'a str,
+    #[serde(rename = "type")]
+    record_type: &'a str,
+    /// 服务商处的记录内容，记录不存在时为空
+    content: Option<&'a str>,
+    detected_ip: Option<&'a str>,
+    /// in_sync、out_of_sync 或 missing
+    state: &'static str,
+}
+
+/// 检测当前 IP 并读取配置中每条记录在服务商处的内容，显示是否一致
+pub async fn status(config_path: &str, json: bool) -> Result<(), RunError> {
+    let config = load_config(config_path)?;
+    let (ips, _) = ip_utils::detect_for(&config).await?;
+
+    for zone in config.zones() {
+        if zone.dns_records.is_empty() {
+            continue;
+        }
+        let provider = zone_provider(&config, &zone).await?;
+        for record_config in &zone.dns_records {
+            let content = match record_config.members.is_empty() {
+                true => provider.get_record(&record_config.name, &record_config.r#type).await?.map(|r| r.content),
+                false => record_set::current_content(&*provider, record_config).await?,
+            };
+            print_status(record_config, content.as_deref(), &ips, json);
+        }
+    }
+    Ok(())
+}
+
+/// 输出单条记录的状态
+fn print_status(record_config: &DnsRecordConfig, content: Option<&str>, ips: &ip_utils::DetectedIps, json: bool) {
+    let detected_ip = ips.for_record(record_config);
+    let detected_ip = detected_ip.as_deref();
+    let status = RecordStatus {
+        name: &record_config.name,
+        record_type: &record_config.r#type,
+        content,
+        detected_ip,
+        state: match content {
+            None => "missing",
+            Some(content) if Some(content) == detected_ip => "in_sync",
+            Some(_) => "out_of_sync",
+        },
+    };
+    if json {
+        println!("{}", serde_json::to_string(&status).unwrap_or_default());
+        return;
+    }
+    let state = match status.state {
+        "missing" => tr!("不存在", "missing"),
+        "in_sync" => tr!("已同步", "in sync"),
+        _ => tr!("待更新", "out of sync"),
+    };
+    println!(
+        "{}  {}  {}  {}  {}",
+        status.name,
+        status.record_type,
+        status.content.unwrap_or("-"),
+        status.detected_ip.unwrap_or("-"),
+        state
+    );
+}
+
+/// 默认的 DNS over HTTPS 解析服务（JSON 格式）
+pub const DEFAULT_RESOLVER: &str = "https://cloudflare-dns.com/dns-query";
+
+/// `verify` 中单条记录的结果
+#[derive(Debug, Serialize)]
+struct RecordVerification<'a> {
+    name: &'a str,
+    #[serde(rename = "type")]
+    record_type: &'a str,
+    /// 公共 DNS 返回的地址
+    resolved: Vec<String>,
+    detected_ip: Option<&'a str>,
+    /// match、mismatch、not_found 或 proxied（代理记录解析到 CloudFlare 的地址，无法比较）
+    state: &'static str,
+}
+
+/// 通过公共 DNS 解析每条记录并与当前检测到的 IP 比较，不访问 CloudFlare API；全部一致时返回 0，否则返回 1
+pub async fn verify(config_path: &str, resolver: &str, json: bool) -> Result<u8, RunError> {
+    let config = load_config(config_path)?;
+    let (ips, _) = ip_utils::detect_for(&config).await?;
+    let client = cloudflare_ddns::tls::client_builder()
+        .timeout(config.schedule.timeout())
+        .build()
+        .map_err(Error::from)?;
+
+    let mut all_match = true;
+    for record_config in config.records() {
+        let resolved = resolve(&client, resolver, &record_config.name, &record_config.r#type).await?;
+        let detected_ip = ips.for_record(record_config);
+        let detected_ip = detected_ip.as_deref();
+        let state = match detected_ip {
+            _ if record_config.proxied => "proxied",
+            _ if resolved.is_empty() => "not_found",
+            // 轮询记录集的每个成员地址都要解析到
+            Some(ip) if ip.split(", ").all(|ip| resolved.iter().any(|r| r == ip)) => "match",
+            _ => "mismatch",
+        };
+        all_match &= matches!(state, "match" | "proxied");
+        let verification = RecordVerification {
+            name: &record_config.name,
+            record_type: &record_config.r#type,
+            resolved,
+            detected_ip,
+            state,
+        };
+        if json {
+            println!("{}", serde_json::to_string(&verification).unwrap_or_default());
+            continue;
+        }
+        let state = match verification.state {
+            "match" => tr!("一致", "match"),
+            "mismatch" => tr!("不一致", "mismatch"),
+            "not_found" => tr!("无法解析", "not found"),
+            _ => tr!("已代理，跳过", "proxied, skipped"),
+        };
+        let resolved = if verification.resolved.is_empty() { "-".to_string() } else { verification.resolved.join(",") };
+        println!(
+            "{}  {}  {}  {}  {}",
+            verification.name,
+            verification.record_type,
+            resolved,
+            verification.detected_ip.unwrap_or("-"),
+            state
+        );
+    }
+    Ok(if all_match { 0 } else { 1 })
+}
+
+/// 通过 DNS over HTTPS (JSON) 查询记录，返回与记录类型相同的答案
+async fn resolve(client: &reqwest::Client, resolver: &str, name: &str, record_type: &str) -> Result<Vec<String>, Error> {
+    let response = client.get(resolver)
+        .query(&[("name", name), ("type", record_type)])
+        .header(reqwest::header::ACCEPT, "application/dns-json")
+        .send()
+        .await?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(Error::provider(ErrorCategory::from_status(status), tr!("DNS 查询 {} 失败，状态码 {}", "DNS query for {} failed with status {}", name, status)));
+    }
+    let body: serde_json::Value = response.json().await?;
+    Ok(answers(&body, record_type))
+}
+
+/// DNS JSON 响应中指定类型的答案（跳过 CNAME 等中间记录）
+fn answers(body: &serde_json::Value, record_type: &str) -> Vec<String> {
+    let type_code = match record_type {
+        "A" => 1,
+        "AAAA" => 28,
+        _ => return Vec::new(),
+    };
+    body["Answer"].as_array().into_iter().flatten()
+        .filter(|answer| answer["type"].as_u64() == Some(type_code))
+        .filter_map(|answer| answer["data"].as_str().map(str::to_string))
+        .collect()
+}
+
+/// 列出凭据可访问的区域
+pub async fn zones(config_path: &str, json: bool) -> Result<(), RunError> {
+    let config = load_config(config_path)?;
+    let client = client_from_config(&config)?;
+    let zones = client.list_zones().await?;
+
+    for zone in &zones {
+        if json {
+            println!("{}", serde_json::to_string(zone).unwrap_or_default());
+        } else {
+            println!("{}  {}  {}", zone.name, zone.id, zone.status);
+        }
+    }
+    Ok(())
+}
+
+/// 列出配置的区域中的所有 DNS 记录
+pub async fn records(config_path: &str, json: bool) -> Result<(), RunError> {
+    let config = load_config(config_path)?;
+    let (client, zone_id) = connect(&config).await?;
+    let records = client.list_dns_records(&zone_id).await?;
+
+    for record in &records {
+        if json {
+            println!("{}", serde_json::to_string(record).unwrap_or_default());
+        } else {
+            let proxied = if record.proxied { tr!("代理", "proxied") } else { tr!("仅 DNS", "DNS only") };
+            println!("{}  {}  {}  ttl={}  {}", record.r#type, record.name, record.content, record.ttl, proxied);
+        }
+    }
+    Ok(())
+}
+
+/// 显示 IP 变化历史以及每条记录的平均变化间隔
+pub fn history(config_path: &str, filter: &history::HistoryFilter, json: bool) -> Result<(), RunError> {
+    let config = load_config(config_path)?;
+    let path = match config.schedule.state_backend {
+        StateBackend::Json => config.history.file.clone(),
+        StateBackend::Sqlite => config.schedule.state_file.clone(),
+    };
+    let path = path.unwrap_or_default();
+    let entries = state::open(&config).query_history(filter)
+        .map_err(|e| RunError::new(FailureKind::Config, tr!("无法读取历史文件: {}", "Failed to read history file: {}", e)))?;
+
+    if json {
+        for entry in &entries {
+            println!("{}", serde_json::to_string(entry).unwrap_or_default());
+        }
+        return Ok(());
+    }
+
+    if entries.is_empty() {
+        println!("{}", tr!("没有 IP 变化记录 ({})", "No IP changes recorded ({})", path));
+        return Ok(());
+    }
+    for entry in &entries {
+        println!(
+            "{}  {}  {} -> {}",
+            entry.timestamp,
+            entry.record,
+            entry.old_ip.as_deref().unwrap_or("-"),
+            entry.new_ip
+        );
+    }
+    println!();
+    for (record, count, average) in history::average_intervals(&entries) {
+        match average {
+            Some(average) => println!(
+                "{}",
+                tr!("{}: {} 次变化，平均间隔 {}", "{}: {} changes, average interval {}",
+                    record, count, duration::format_duration(Duration::from_secs(average.as_secs())))
+            ),
+            None => println!("{}", tr!("{}: {} 次变化", "{}: {} changes", record, count)),
+        }
+    }
+    Ok(())
+}
+
+/// 读取定时模式写入的状态文件，最近一次执行成功且距今不超过 `max_age` 时返回 0，否则返回 1
+pub fn healthcheck(config_path: &str, max_age: Duration) -> u8 {
+    let result = load_config(config_path)
+        .map_err(|e| e.to_string())
+        .and_then(|config| {
+            state::open(&config).run_stats()
+                .map_err(|e| tr!("无法读取状态文件: {}", "Failed to read state file: {}", e))?
+                .ok_or_else(|| tr!("尚无执行记录", "No run recorded yet"))
+        })
+        .and_then(|state| state.check(max_age, chrono::Local::now()));
+    match result {
+        Ok(age) => {
+            println!("{}", tr!("健康：最近一次执行在 {} 前成功", "Healthy: last run succeeded {} ago", duration::format_duration(Duration::from_secs(age.as_secs()))));
+            0
+        }
+        Err(reason) => {
+            println!("{}", tr!("不健康：{}", "Unhealthy: {}", reason));
+            1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn answers_skip_other_record_types() {
+        let body = serde_json::json!({
+            "Status": 0,
+            "Answer": [
+                { "name": "home.example.com.", "type": 5, "data": "edge.example.net." },
+                { "name": "edge.example.net.", "type": 1, "data": "203.0.113.7" }
+            ]
+        });
+        assert_eq!(answers(&body, "A"), vec!["203.0.113.7".to_string()]);
+        assert!(answers(&body, "AAAA").is_empty());
+        assert!(answers(&serde_json::json!({ "Status": 3 }), "A").is_empty());
+    }
+}
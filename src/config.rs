@@ -1,23 +1,806 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::net::IpAddr;
+use std::time::Duration;
+
+use crate::i18n::tr;
+use crate::notify::EventKind;
+
+/// 网络请求的默认超时时间
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 失败记录的默认首次重试等待时间
+pub const DEFAULT_RETRY_BACKOFF: Duration = Duration::from_secs(30);
+/// 失败记录默认的最大快速重试次数
+pub const DEFAULT_RETRY_MAX_ATTEMPTS: u32 = 5;
+
+/// 故障转移默认的切换阈值：主地址连续检查失败多少次后切换到备用地址，恢复后连续成功多少次切回
+pub const DEFAULT_FAILOVER_THRESHOLD: u32 = 3;
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Config {
-    pub cloudflare: CloudflareConfig,
+    /// 主区域（CloudFlare），只使用 `zones` 时可以省略
+    pub cloudflare: Option<CloudflareConfig>,
+    #[serde(default)]
     pub dns_records: Vec<DnsRecordConfig>,
+    /// 其他区域，每个区域可以使用不同的 DNS 服务商
+    #[serde(default)]
+    pub zones: Vec<ZoneConfig>,
+    #[serde(default)]
+    pub ip_detection: IpDetectionConfig,
+    /// 多条上行线路（多 WAN），记录通过 `uplink` 选择使用哪条线路检测到的地址
+    #[serde(default)]
+    pub uplinks: Vec<UplinkConfig>,
+    #[serde(default)]
+    pub schedule: ScheduleConfig,
+    #[serde(default)]
+    pub notifications: NotificationsConfig,
+    pub mqtt: Option<MqttConfig>,
+    #[serde(default)]
+    pub logging: LoggingConfig,
+    pub metrics: Option<MetricsConfig>,
+    pub http: Option<HttpConfig>,
+    #[serde(default)]
+    pub history: HistoryConfig,
+    /// 同一名称同时配置了 A 与 AAAA 记录时的更新策略
+    #[serde(default)]
+    pub dual_stack: DualStackPolicy,
+    /// 记录被外部修改（与上一次发布的内容不一致）时的处理
+    #[serde(default)]
+    pub drift: DriftPolicy,
+    /// 每条记录的更新次数上限，超过时停止更新该记录
+    #[serde(default)]
+    pub update_limit: UpdateLimitConfig,
+}
+
+/// 每条记录在一段时间内的更新次数上限，用于 IP 来回变化或检测异常时保护记录与 API 配额
+///
+/// 次数从 IP 变化历史中统计，需要开启 `history.enabled`；未设置的上限不检查。
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default)]
+pub struct UpdateLimitConfig {
+    /// 最近一小时内的最大更新次数
+    pub per_hour: Option<u32>,
+    /// 最近一天内的最大更新次数
+    pub per_day: Option<u32>,
+}
+
+/// 双栈名称（同一区域中同名的 A 与 AAAA 记录）的更新策略
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DualStackPolicy {
+    /// 两条记录各自更新，一条失败不影响另一条
+    #[default]
+    Independent,
+    /// 两条记录都能更新时才写入；后写入的一条失败时撤销先写入的一条，撤销失败时报告记录不一致
+    Atomic,
+}
+
+/// 记录内容与上一次发布的内容不一致（被外部修改或删除）时的处理
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DriftPolicy {
+    /// 不检查，按当前 IP 照常更新
+    #[default]
+    Off,
+    /// 改回当前 IP，并发送 drift 通知
+    Restore,
+    /// 不修改记录，只发送 drift 通知
+    Notify,
+}
+
+/// IP 变化历史
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct HistoryConfig {
+    /// 是否记录历史，默认开启
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// 历史文件路径，默认为配置文件所在目录下的 history.jsonl
+    pub file: Option<String>,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self { enabled: true, file: None }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// 运行指标输出（仅定时模式）
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct MetricsConfig {
+    /// node_exporter textfile collector 的 .prom 文件路径，每次执行后更新
+    pub textfile: Option<String>,
+}
+
+/// 定时模式内置的状态 HTTP 服务
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct HttpConfig {
+    /// 监听地址，例如 127.0.0.1:8080
+    pub listen: String,
+    /// `POST /update` 所需的令牌（`Authorization: Bearer <token>`），为空时不提供远程更新
+    pub token: Option<String>,
+    /// dyndns2 协议的 `/nic/update` 接收端，供路由器内置的自定义 DDNS 客户端推送 IP，为空时不提供
+    pub dyndns: Option<DyndnsReceiverConfig>,
+}
+
+/// `/nic/update` 接收端的 HTTP Basic 认证
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct DyndnsReceiverConfig {
+    pub username: String,
+    pub password: String,
+}
+
+/// 日志输出设置
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct LoggingConfig {
+    /// 日志文件路径，为空时输出到终端
+    pub file: Option<String>,
+    /// 日志格式，默认为文本
+    #[serde(default)]
+    pub format: LogFormat,
+    /// 日志文件轮转方式，默认按天
+    #[serde(default)]
+    pub rotation: LogRotation,
+    /// 按大小轮转时单个文件的最大大小 (MB)，默认 10
+    #[serde(default = "default_log_max_size_mb")]
+    pub max_size_mb: u64,
+    /// 最多保留的日志文件数量（包括当前文件），默认 7
+    #[serde(default = "default_log_max_files")]
+    pub max_files: usize,
+    /// 同时发送到 syslog (RFC 5424)
+    pub syslog: Option<SyslogConfig>,
+    /// 同时发送到 systemd journald
+    #[serde(default)]
+    pub journald: bool,
+    /// 同时把警告、错误与记录变化写入 Windows 事件日志（仅 Windows，服务模式下始终启用）
+    #[serde(default)]
+    pub eventlog: bool,
+}
+
+/// syslog 输出设置
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct SyslogConfig {
+    /// 远程 syslog 服务器地址 (UDP)，如 "192.168.1.2:514"，为空时发送到本机 /dev/log
+    pub server: Option<String>,
+    /// syslog facility，默认 "daemon"
+    #[serde(default = "default_syslog_facility")]
+    pub facility: String,
+}
+
+fn default_syslog_facility() -> String {
+    "daemon".to_string()
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            file: None,
+            format: LogFormat::default(),
+            rotation: LogRotation::default(),
+            max_size_mb: default_log_max_size_mb(),
+            max_files: default_log_max_files(),
+            syslog: None,
+            journald: false,
+            eventlog: false,
+        }
+    }
+}
+
+/// 日志输出格式
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// 便于阅读的文本
+    #[default]
+    Text,
+    /// 每条日志一个 JSON 对象，便于 Loki、Elasticsearch 等系统按字段查询
+    Json,
+}
+
+/// 日志文件轮转方式
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum LogRotation {
+    Hourly,
+    #[default]
+    Daily,
+    /// 超过 `max_size_mb` 时轮转
+    Size,
+    /// 不轮转
+    Never,
+}
+
+fn default_log_max_size_mb() -> u64 {
+    10
+}
+
+fn default_log_max_files() -> usize {
+    7
+}
+
+/// MQTT 状态发布（仅定时模式）
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct MqttConfig {
+    pub host: String,
+    /// 为空时按是否启用 TLS 使用 1883 或 8883
+    pub port: Option<u16>,
+    #[serde(default = "default_mqtt_client_id")]
+    pub client_id: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    /// 是否使用 TLS 连接
+    #[serde(default)]
+    pub tls: bool,
+    /// 自定义 CA 证书 (PEM) 路径，为空时使用内置的根证书
+    pub ca_file: Option<String>,
+    /// 主题前缀，默认 "cloudflare_ddns"
+    #[serde(default = "default_mqtt_topic_prefix")]
+    pub topic_prefix: String,
+    /// 发布消息的 QoS (0-2)，默认 1
+    #[serde(default = "default_mqtt_qos")]
+    pub qos: u8,
+}
+
+fn default_mqtt_client_id() -> String {
+    "cloudflare-ddns".to_string()
+}
+
+fn default_mqtt_topic_prefix() -> String {
+    "cloudflare_ddns".to_string()
+}
+
+fn default_mqtt_qos() -> u8 {
+    1
+}
+
+/// 公网 IP 检测服务，服务需要以纯文本返回请求方的地址
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct IpDetectionConfig {
+    /// IPv4 检测地址，默认为 https://4.ipw.cn
+    pub ipv4_url: Option<String>,
+    /// IPv6 检测地址，默认为 https://6.ipw.cn
+    pub ipv6_url: Option<String>,
+}
+
+impl IpDetectionConfig {
+    pub fn url(&self, version: IpVersion) -> &str {
+        match version {
+            IpVersion::V4 => self.ipv4_url.as_deref().unwrap_or(crate::ip_utils::DEFAULT_IPV4_URL),
+            IpVersion::V6 => self.ipv6_url.as_deref().unwrap_or(crate::ip_utils::DEFAULT_IPV6_URL),
+        }
+    }
+}
+
+/// 一条上行线路：通过绑定的网络接口或源地址检测该线路的公网 IP
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct UplinkConfig {
+    /// 线路名称，记录的 `uplink` 引用它
+    pub name: String,
+    /// IPv4 检测地址，默认与 `ip_detection` 相同
+    pub ipv4_url: Option<String>,
+    /// IPv6 检测地址，默认与 `ip_detection` 相同
+    pub ipv6_url: Option<String>,
+    /// 检测请求绑定的网络接口，如 "ppp0"（仅 Linux 与 macOS）
+    pub interface: Option<String>,
+    /// 检测请求使用的本地源地址，用于按源地址选路的路由器
+    pub local_address: Option<IpAddr>,
+}
+
+/// 定时运行与网络请求相关的设置，时间字段均支持 "90s"、"5m"、"1h30m" 或纯数字秒数
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct ScheduleConfig {
+    /// 定时运行间隔，命令行 `--interval` 优先
+    #[serde(default, with = "crate::duration::option")]
+    pub interval: Option<Duration>,
+    /// 单个网络请求（IP 检测、API 调用）的超时时间
+    #[serde(default, with = "crate::duration::option")]
+    pub timeout: Option<Duration>,
+    /// 两级调度：每个 interval 只检测 IP，完整的 API 核对按此间隔进行（IP 变化时立即核对）
+    #[serde(default, with = "crate::duration::option")]
+    pub reconcile_interval: Option<Duration>,
+    /// 定时模式下记录更新失败后的首次重试等待时间，之后每次翻倍，不超过 interval
+    #[serde(default, with = "crate::duration::option")]
+    pub retry_backoff: Option<Duration>,
+    /// 定时模式下失败记录的最大快速重试次数，用尽后发送 retries_exhausted 通知，之后按 interval 重试
+    pub retry_max_attempts: Option<u32>,
+    /// 定时模式下收到 SIGUSR2 时把内部状态写入此文件，未设置时打印到标准错误
+    pub state_dump_file: Option<String>,
+    /// 定时模式监听的 Unix 控制套接字，供 `ctl` 子命令使用，未设置时不监听（仅 Unix）
+    pub control_socket: Option<String>,
+    /// 保存持久化状态（运行统计、上一次的 IP、各记录最近写入的内容）的文件，healthcheck 从中读取运行统计，
+    /// 默认为配置文件所在目录下的 state.json（SQLite 后端为 state.db）
+    pub state_file: Option<String>,
+    /// 持久化状态的存储后端
+    #[serde(default)]
+    pub state_backend: StateBackend,
+}
+
+/// 持久化状态的存储后端
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum StateBackend {
+    /// 状态写入 `state_file` 的 JSON 文件，历史追加到 `history.file`
+    #[default]
+    Json,
+    /// 状态与历史都写入 `state_file` 的 SQLite 数据库，需要启用 sqlite 功能
+    Sqlite,
+}
+
+impl ScheduleConfig {
+    pub fn timeout(&self) -> Duration {
+        self.timeout.unwrap_or(DEFAULT_TIMEOUT)
+    }
+
+    pub fn retry_backoff(&self) -> Duration {
+        self.retry_backoff.unwrap_or(DEFAULT_RETRY_BACKOFF)
+    }
+
+    pub fn retry_max_attempts(&self) -> u32 {
+        self.retry_max_attempts.unwrap_or(DEFAULT_RETRY_MAX_ATTEMPTS)
+    }
+}
+
+/// 通知设置，每个后端均为可选
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct NotificationsConfig {
+    pub webhook: Option<WebhookConfig>,
+    pub telegram: Option<TelegramConfig>,
+    pub slack: Option<SlackConfig>,
+    pub email: Option<EmailConfig>,
+    pub gotify: Option<GotifyConfig>,
+    pub bark: Option<BarkConfig>,
+    pub pushover: Option<PushoverConfig>,
+    pub serverchan: Option<ServerChanConfig>,
+    pub dingtalk: Option<DingTalkConfig>,
+    pub wecom: Option<WeComConfig>,
+    pub matrix: Option<MatrixConfig>,
+    pub hooks: Option<CommandHooksConfig>,
+    #[serde(default)]
+    pub templates: NotificationTemplates,
+    #[serde(default)]
+    pub retry: NotificationRetryConfig,
+}
+
+/// 通知发送失败后的重试设置
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct NotificationRetryConfig {
+    /// 首次重试的等待时间，之后每次翻倍，最长 1 小时，默认 10s
+    #[serde(default, with = "crate::duration::option")]
+    pub backoff: Option<Duration>,
+    /// 包括首次发送在内的最大尝试次数，默认 10
+    #[serde(default = "default_notify_max_attempts")]
+    pub max_attempts: u32,
+    /// 单次运行模式退出前等待重试的最长时间，默认 30s
+    #[serde(default, with = "crate::duration::option")]
+    pub flush_timeout: Option<Duration>,
+}
+
+impl Default for NotificationRetryConfig {
+    fn default() -> Self {
+        Self {
+            backoff: None,
+            max_attempts: default_notify_max_attempts(),
+            flush_timeout: None,
+        }
+    }
+}
+
+impl NotificationRetryConfig {
+    pub fn backoff(&self) -> Duration {
+        self.backoff.unwrap_or(Duration::from_secs(10))
+    }
+
+    pub fn flush_timeout(&self) -> Duration {
+        self.flush_timeout.unwrap_or(Duration::from_secs(30))
+    }
+}
+
+fn default_notify_max_attempts() -> u32 {
+    10
+}
+
+/// 每个通知后端的发送策略，直接写在后端的配置中
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct NotifyPolicy {
+    /// 接收的事件类型：changed、failed、recovered、retries_exhausted、drift、flapping、startup，默认不含 startup
+    #[serde(default = "default_notify_events")]
+    pub events: Vec<EventKind>,
+    /// 同一记录连续失败达到该次数时才发送失败通知（每轮连续失败只发送一次），默认 1
+    #[serde(default = "default_failure_threshold")]
+    pub failure_threshold: u32,
+}
+
+impl Default for NotifyPolicy {
+    fn default() -> Self {
+        Self {
+            events: default_notify_events(),
+            failure_threshold: default_failure_threshold(),
+        }
+    }
+}
+
+fn default_notify_events() -> Vec<EventKind> {
+    vec![EventKind::Changed, EventKind::Failed, EventKind::Recovered, EventKind::RetriesExhausted, EventKind::Drift, EventKind::Flapping]
+}
+
+fn default_failure_threshold() -> u32 {
+    1
+}
+
+/// 所有通知后端共用的消息模板，按事件类型配置
+///
+/// 支持占位符 {record}、{zone}、{old_ip}、{new_ip}、{timestamp}、{error}、{result}，
+/// 未配置的事件类型使用内置格式。
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct NotificationTemplates {
+    pub changed: Option<String>,
+    pub failed: Option<String>,
+    pub recovered: Option<String>,
+    pub retries_exhausted: Option<String>,
+    pub drift: Option<String>,
+    pub flapping: Option<String>,
+    pub startup: Option<String>,
+}
+
+/// 通用 Webhook：记录更新或更新失败时向每个 URL POST 一个 JSON 事件
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct WebhookConfig {
+    pub urls: Vec<String>,
+    #[serde(flatten)]
+    pub policy: NotifyPolicy,
+}
+
+/// Telegram Bot 通知
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct TelegramConfig {
+    pub bot_token: String,
+    pub chat_id: String,
+    #[serde(flatten)]
+    pub policy: NotifyPolicy,
+}
+
+/// Slack Incoming Webhook 通知
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct SlackConfig {
+    pub webhook_url: String,
+    /// 变更事件使用的 webhook 地址，为空时使用 `webhook_url`
+    #[serde(default)]
+    pub change_webhook_url: Option<String>,
+    /// 失败事件使用的 webhook 地址，为空时使用 `webhook_url`
+    #[serde(default)]
+    pub failure_webhook_url: Option<String>,
+    /// 变更事件发送到的频道（如 "#infra"），为空时使用 webhook 的默认频道；仅旧式 webhook 支持
+    pub change_channel: Option<String>,
+    /// 失败事件发送到的频道（如 "#alerts"），为空时使用 webhook 的默认频道；仅旧式 webhook 支持
+    pub failure_channel: Option<String>,
+    #[serde(flatten)]
+    pub policy: NotifyPolicy,
+}
+
+/// SMTP 邮件通知
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct EmailConfig {
+    pub host: String,
+    /// 为空时按 tls 模式使用默认端口（starttls: 587，tls: 465，none: 25）
+    pub port: Option<u16>,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    /// 连接加密方式：starttls（默认）、tls 或 none
+    #[serde(default = "default_email_tls")]
+    pub tls: String,
+    pub from: String,
+    pub to: Vec<String>,
+    /// 邮件主题模板，支持 {record}、{zone}、{old_ip}、{new_ip}、{timestamp}、{error}、{result}
+    pub subject: Option<String>,
+    /// 邮件正文模板，占位符同上
+    pub body: Option<String>,
+    #[serde(flatten)]
+    pub policy: NotifyPolicy,
+}
+
+fn default_email_tls() -> String {
+    "starttls".to_string()
+}
+
+/// Gotify 推送通知
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct GotifyConfig {
+    /// Gotify 服务器地址，如 "https://gotify.example.com"
+    pub url: String,
+    /// 应用 token
+    pub app_token: String,
+    /// 消息优先级，默认 5
+    #[serde(default = "default_gotify_priority")]
+    pub priority: u8,
+    #[serde(flatten)]
+    pub policy: NotifyPolicy,
+}
+
+fn default_gotify_priority() -> u8 {
+    5
+}
+
+/// Bark (iOS) 推送通知
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct BarkConfig {
+    /// Bark App 中复制的推送地址，如 "https://api.day.app/your_key"
+    pub url: String,
+    /// 通知分组，默认 "CloudFlare DDNS"
+    pub group: Option<String>,
+    /// 推送铃声
+    pub sound: Option<String>,
+    #[serde(flatten)]
+    pub policy: NotifyPolicy,
+}
+
+/// Pushover 推送通知
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct PushoverConfig {
+    /// 应用 API token
+    pub app_token: String,
+    /// 用户或群组 key
+    pub user_key: String,
+    /// 消息优先级 (-2 到 2)，默认 0
+    #[serde(default)]
+    pub priority: i8,
+    /// 只推送到指定设备
+    pub device: Option<String>,
+    /// 推送铃声
+    pub sound: Option<String>,
+    /// 紧急优先级 (2) 时重复提醒的间隔秒数，默认 60
+    #[serde(default = "default_pushover_retry")]
+    pub retry: u32,
+    /// 紧急优先级 (2) 时停止重复提醒的秒数，默认 3600
+    #[serde(default = "default_pushover_expire")]
+    pub expire: u32,
+    #[serde(flatten)]
+    pub policy: NotifyPolicy,
+}
+
+fn default_pushover_retry() -> u32 {
+    60
+}
+
+fn default_pushover_expire() -> u32 {
+    3600
+}
+
+/// Server酱 (方糖) 推送通知
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ServerChanConfig {
+    /// SendKey，支持 Turbo 版 (SCT...) 与 Server酱³ (sctp...)
+    pub sendkey: String,
+    #[serde(flatten)]
+    pub policy: NotifyPolicy,
+}
+
+/// 钉钉群机器人通知
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct DingTalkConfig {
+    /// 机器人 Webhook 地址（包含 access_token）
+    pub webhook_url: String,
+    /// 加签密钥 (SEC...)，机器人启用"加签"安全设置时必填
+    pub secret: Option<String>,
+    #[serde(flatten)]
+    pub policy: NotifyPolicy,
+}
+
+/// 企业微信群机器人通知，`key` 与 `webhook_url` 二选一
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct WeComConfig {
+    /// 机器人 Webhook 地址中的 key 参数
+    pub key: Option<String>,
+    /// 完整的机器人 Webhook 地址
+    pub webhook_url: Option<String>,
+    #[serde(flatten)]
+    pub policy: NotifyPolicy,
+}
+
+/// Matrix 房间通知
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct MatrixConfig {
+    /// Matrix 服务器地址，如 "https://matrix.example.com"
+    pub homeserver: String,
+    /// 发送消息所用账号的 access token
+    pub access_token: String,
+    /// 房间 ID，如 "!abcdefg:example.com"，该账号需已加入房间
+    pub room_id: String,
+    #[serde(flatten)]
+    pub policy: NotifyPolicy,
+}
+
+/// 记录变化或更新失败时执行的命令
+///
+/// 命令通过 shell 执行，可读取环境变量 RECORD、ZONE、OLD_IP、NEW_IP、RESULT、ERROR、TIMESTAMP。
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct CommandHooksConfig {
+    pub on_change: Option<String>,
+    pub on_failure: Option<String>,
+    /// 连续失败后首次恢复成功时执行
+    pub on_recovered: Option<String>,
+    /// 定时模式下记录用尽快速重试次数仍失败时执行
+    pub on_retries_exhausted: Option<String>,
+    /// 检测到记录被外部修改时执行
+    pub on_drift: Option<String>,
+    /// 记录更新过于频繁、超过 `update_limit` 而停止更新时执行
+    pub on_flapping: Option<String>,
+    /// 命令的最长执行时间，默认 60s
+    #[serde(default, with = "crate::duration::option")]
+    pub timeout: Option<Duration>,
+    #[serde(flatten)]
+    pub policy: NotifyPolicy,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct CloudflareConfig {
+    #[serde(flatten)]
+    pub credentials: CloudflareCredentials,
+    #[serde(rename = "zone_name")]
+    pub zone_name: String,
+}
+
+/// CloudFlare 的认证信息
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct CloudflareCredentials {
     #[serde(rename = "auth_type")]
     pub auth_type: String,  // 临时使用 String，稍后转换
     #[serde(rename = "auth_email")]
     pub auth_email: Option<String>,
     #[serde(rename = "auth_key")]
     pub auth_key: Option<String>,
+    /// 未设置 auth_key 时从该环境变量读取全局 API Key
+    #[serde(default)]
+    pub auth_key_env: Option<String>,
     #[serde(rename = "api_token")]
     pub api_token: Option<String>,
-    #[serde(rename = "zone_name")]
+    /// 未设置 api_token 时从该环境变量读取令牌，同一份配置在不同部署中可以指向不同的令牌
+    #[serde(default)]
+    pub api_token_env: Option<String>,
+    /// 未设置 api_token 与 api_token_env 时从 systemd 凭据读取令牌，值为凭据名称（`LoadCredential=` 中的名称）
+    #[serde(default)]
+    pub api_token_credential: Option<String>,
+    /// 以上均未设置时从系统密钥环读取令牌，值为密钥环中的服务名（账户名固定为 api_token），需要启用 keyring 功能
+    #[serde(default)]
+    pub api_token_keyring: Option<String>,
+    /// API 地址，默认为 https://api.cloudflare.com/client/v4
+    pub endpoint: Option<String>,
+}
+
+/// `zones` 中的一个区域
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ZoneConfig {
     pub zone_name: String,
+    pub provider: ProviderConfig,
+    #[serde(default)]
+    pub dns_records: Vec<DnsRecordConfig>,
+}
+
+/// 区域使用的 DNS 服务商及其凭据，`type` 字段选择服务商
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ProviderConfig {
+    Cloudflare(CloudflareCredentials),
+    Aliyun(AliyunConfig),
+    Dnspod(DnspodConfig),
+    Route53(Route53Config),
+    Huawei(HuaweiConfig),
+    Duckdns(DuckDnsConfig),
+    Dyndns2(Dyndns2Config),
+    Desec(DesecConfig),
+    Dynv6(Dynv6Config),
+    Porkbun(PorkbunConfig),
+}
+
+impl ProviderConfig {
+    /// 配置中的服务商名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            ProviderConfig::Cloudflare(_) => "cloudflare",
+            ProviderConfig::Aliyun(_) => "aliyun",
+            ProviderConfig::Dnspod(_) => "dnspod",
+            ProviderConfig::Route53(_) => "route53",
+            ProviderConfig::Huawei(_) => "huawei",
+            ProviderConfig::Duckdns(_) => "duckdns",
+            ProviderConfig::Dyndns2(_) => "dyndns2",
+            ProviderConfig::Desec(_) => "desec",
+            ProviderConfig::Dynv6(_) => "dynv6",
+            ProviderConfig::Porkbun(_) => "porkbun",
+        }
+    }
+
+    /// 该服务商是否已在编译时启用
+    pub fn enabled(&self) -> bool {
+        match self {
+            ProviderConfig::Cloudflare(_) => true,
+            ProviderConfig::Aliyun(_) => cfg!(feature = "aliyun"),
+            ProviderConfig::Dnspod(_) => cfg!(feature = "dnspod"),
+            ProviderConfig::Route53(_) => cfg!(feature = "route53"),
+            ProviderConfig::Huawei(_) => cfg!(feature = "huawei"),
+            ProviderConfig::Duckdns(_) | ProviderConfig::Dyndns2(_) => cfg!(feature = "dyndns"),
+            ProviderConfig::Desec(_) => cfg!(feature = "desec"),
+            ProviderConfig::Dynv6(_) => cfg!(feature = "dynv6"),
+            ProviderConfig::Porkbun(_) => cfg!(feature = "porkbun"),
+        }
+    }
+}
+
+/// 阿里云云解析 DNS
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct AliyunConfig {
+    pub access_key_id: String,
+    pub access_key_secret: String,
+    /// API 地址，默认为 alidns.aliyuncs.com
+    pub endpoint: Option<String>,
+}
+
+/// DNSPod（腾讯云 DNS），使用在 DNSPod 控制台创建的 API Token
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct DnspodConfig {
+    /// Token 的 ID
+    pub token_id: String,
+    pub token: String,
+}
+
+/// AWS Route 53，IAM 用户需要 route53:ListHostedZonesByName、ListResourceRecordSets 与 ChangeResourceRecordSets 权限
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Route53Config {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    /// 托管区域 ID，为空时按区域名称查找
+    pub hosted_zone_id: Option<String>,
+}
+
+/// 华为云 DNS，使用 IAM 用户的访问密钥 (AK/SK)
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct HuaweiConfig {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    /// API 地址，默认为 dns.myhuaweicloud.com
+    pub endpoint: Option<String>,
+}
+
+/// DuckDNS，区域名为 duckdns.org
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct DuckDnsConfig {
+    pub token: String,
+}
+
+/// 使用 dyndns2 协议的服务（Dyn、No-IP、Dynu 等）
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Dyndns2Config {
+    /// 服务地址，如 https://dynupdate.no-ip.com，更新请求发送到其下的 /nic/update
+    pub server: String,
+    pub username: String,
+    pub password: String,
+}
+
+/// deSEC (desec.io)，使用在 deSEC 控制台创建的 Token
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct DesecConfig {
+    pub token: String,
+}
+
+/// dynv6，使用 HTTP Token
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Dynv6Config {
+    pub token: String,
+    /// 更新由前缀展开的 AAAA 记录时，写入区域的前缀长度
+    #[serde(default = "default_dynv6_prefix_length")]
+    pub prefix_length: u8,
+}
+
+fn default_dynv6_prefix_length() -> u8 {
+    64
+}
+
+/// Porkbun，需要在域名管理中为该域名开启 API Access
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct PorkbunConfig {
+    pub api_key: String,
+    pub secret_api_key: String,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -29,10 +812,298 @@ pub struct DnsRecordConfig {
     pub proxied: bool,
     #[serde(rename = "ip_version")]
     pub ip_version: String,  // 临时使用 String，稍后转换
+    /// 使用哪条上行线路检测到的地址，为空时使用默认检测
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uplink: Option<String>,
+    /// 不为空时该记录是轮询记录集：为每个成员维护一条同名记录，不在其中的成员的记录被删除
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub members: Vec<SetMemberConfig>,
+    /// 设置后对主地址做健康检查，主地址不可用时记录指向备用地址
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failover: Option<FailoverConfig>,
+    /// 与该记录一起更新的其他名称（如根域名同时更新 `www`），可以是完整名称、相对于区域的名称或表示根域名的 `@`；
+    /// 加载配置时展开为设置相同的记录，展开后这里保存完整名称
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub also_update: Vec<String>,
+    /// 接收该记录通知的后端（`notifications` 中的配置项名称，如 "slack"），为空时发给所有后端
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notify: Vec<String>,
+    /// 成功发布新地址后的冷却时间，期间不再更新该记录（强制更新除外），用于吸收重新拨号时地址的短暂变化
+    #[serde(default, with = "crate::duration::option", skip_serializing_if = "Option::is_none")]
+    pub cooldown: Option<Duration>,
+    /// 由 `also_update` 展开的记录所属的原记录名称，加载配置时设置
+    #[serde(skip)]
+    pub alias_of: Option<String>,
+}
+
+/// 两个地址之间的故障转移：每次执行时检查主地址，连续失败 `fail_after` 次后切换到备用地址，
+/// 主地址连续成功 `recover_after` 次后切回，避免在不稳定时来回切换
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct FailoverConfig {
+    /// 主地址，为空时为检测到的地址
+    pub primary: Option<IpAddr>,
+    /// 主地址不可用时记录指向的备用地址
+    pub backup: IpAddr,
+    /// 健康检查方式
+    #[serde(default)]
+    pub check: HealthCheck,
+    /// 检查的端口
+    pub port: u16,
+    /// HTTP 检查请求的路径，默认 "/"
+    pub path: Option<String>,
+    /// 单次检查的超时时间，默认与 `schedule.timeout` 相同
+    #[serde(default, with = "crate::duration::option")]
+    pub timeout: Option<Duration>,
+    pub fail_after: Option<u32>,
+    pub recover_after: Option<u32>,
+}
+
+/// 故障转移的健康检查方式
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthCheck {
+    /// 能建立 TCP 连接即为可用
+    #[default]
+    Tcp,
+    /// HTTP 请求返回 2xx 或 3xx 即为可用
+    Http,
+}
+
+impl FailoverConfig {
+    pub fn fail_after(&self) -> u32 {
+        self.fail_after.unwrap_or(DEFAULT_FAILOVER_THRESHOLD).max(1)
+    }
+
+    pub fn recover_after(&self) -> u32 {
+        self.recover_after.unwrap_or(DEFAULT_FAILOVER_THRESHOLD).max(1)
+    }
+}
+
+/// 轮询记录集的一个成员：地址为固定的 `ip`，或 `uplink` 检测到的地址，两者都未设置时使用默认检测
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct SetMemberConfig {
+    /// 成员名称，写入记录的标签以识别本程序管理的记录
+    pub name: String,
+    pub uplink: Option<String>,
+    /// 固定地址，如另一台主机的地址
+    pub ip: Option<IpAddr>,
+}
+
+/// 展开一个区域中记录的 `also_update`
+fn expand_aliases(zone: &str, records: &mut Vec<DnsRecordConfig>) -> Result<(), String> {
+    let mut expanded: Vec<DnsRecordConfig> = Vec::with_capacity(records.len());
+    let mut alias_keys: Vec<(String, String)> = Vec::new();
+    for mut record in std::mem::take(records) {
+        for alias in &mut record.also_update {
+            *alias = match alias.as_str() {
+                "@" => zone.to_string(),
+                name if name == zone || name.ends_with(&format!(".{}", zone)) => name.to_string(),
+                name => format!("{}.{}", name, zone),
+            };
+        }
+        let aliases: Vec<_> = record.also_update.iter()
+            .map(|alias| DnsRecordConfig { name: alias.clone(), also_update: Vec::new(), alias_of: Some(record.name.clone()), ..record.clone() })
+            .collect();
+        for (index, record) in std::iter::once(record).chain(aliases).enumerate() {
+            let key = (record.name.clone(), record.r#type.clone());
+            // 展开前就重复的记录不在这里检查
+            let clash = match index {
+                0 => alias_keys.contains(&key),
+                _ => expanded.iter().any(|r| r.name == record.name && r.r#type == record.r#type),
+            };
+            if clash {
+                return Err(tr!("记录 {}（{}）配置了多次，检查 also_update", "Record {} ({}) is configured more than once, check also_update", record.name, record.r#type));
+            }
+            if index > 0 {
+                alias_keys.push(key);
+            }
+            expanded.push(record);
+        }
+    }
+    *records = expanded;
+    Ok(())
+}
+
+/// 去掉配置文件中的 `//` 与 `/* */` 注释（字符串内除外），注释替换为空格以保留错误的行列号
+pub fn strip_comments(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    let mut in_string = false;
+    while let Some(c) = chars.next() {
+        if in_string {
+            out.push(c);
+            match c {
+                '\\' => out.extend(chars.next()),
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match (c, chars.peek()) {
+            ('"', _) => {
+                in_string = true;
+                out.push(c);
+            }
+            ('/', Some('/')) => {
+                out.push(' ');
+                while let Some(&next) = chars.peek() {
+                    if next == '\n' {
+                        break;
+                    }
+                    out.push(' ');
+                    chars.next();
+                }
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                out.push_str("  ");
+                let mut prev = ' ';
+                for next in chars.by_ref() {
+                    out.push(if next == '\n' { '\n' } else { ' ' });
+                    if prev == '*' && next == '/' {
+                        break;
+                    }
+                    prev = next;
+                }
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+impl Config {
+    /// 配置中的令牌、密钥、密码以及包含令牌的推送地址，需要从日志中隐去
+    pub fn secrets(&self) -> Vec<&str> {
+        let notifications = &self.notifications;
+        let mut secrets: Vec<&str> = Vec::new();
+        if let Some(cloudflare) = &self.cloudflare {
+            secrets.extend(cloudflare.credentials.secrets());
+        }
+        for zone in &self.zones {
+            match &zone.provider {
+                ProviderConfig::Cloudflare(credentials) => secrets.extend(credentials.secrets()),
+                ProviderConfig::Aliyun(aliyun) => secrets.push(&aliyun.access_key_secret),
+                ProviderConfig::Dnspod(dnspod) => secrets.push(&dnspod.token),
+                ProviderConfig::Route53(route53) => secrets.push(&route53.secret_access_key),
+                ProviderConfig::Huawei(huawei) => secrets.push(&huawei.secret_access_key),
+                ProviderConfig::Duckdns(duckdns) => secrets.push(&duckdns.token),
+                ProviderConfig::Dyndns2(dyndns2) => secrets.push(&dyndns2.password),
+                ProviderConfig::Desec(desec) => secrets.push(&desec.token),
+                ProviderConfig::Dynv6(dynv6) => secrets.push(&dynv6.token),
+                ProviderConfig::Porkbun(porkbun) => secrets.push(&porkbun.secret_api_key),
+            }
+        }
+        secrets.extend(self.mqtt.as_ref().and_then(|m| m.password.as_deref()));
+        if let Some(http) = &self.http {
+            secrets.extend(http.token.as_deref());
+            secrets.extend(http.dyndns.as_ref().map(|d| d.password.as_str()));
+        }
+        if let Some(webhook) = &notifications.webhook {
+            secrets.extend(webhook.urls.iter().map(String::as_str));
+        }
+        secrets.extend(notifications.telegram.as_ref().map(|t| t.bot_token.as_str()));
+        if let Some(slack) = &notifications.slack {
+            secrets.push(slack.webhook_url.as_str());
+            secrets.extend(slack.change_webhook_url.as_deref());
+            secrets.extend(slack.failure_webhook_url.as_deref());
+        }
+        secrets.extend(notifications.email.as_ref().and_then(|e| e.password.as_deref()));
+        secrets.extend(notifications.gotify.as_ref().map(|g| g.app_token.as_str()));
+        secrets.extend(notifications.bark.as_ref().map(|b| b.url.as_str()));
+        if let Some(pushover) = &notifications.pushover {
+            secrets.extend([pushover.app_token.as_str(), pushover.user_key.as_str()]);
+        }
+        secrets.extend(notifications.serverchan.as_ref().map(|s| s.sendkey.as_str()));
+        if let Some(dingtalk) = &notifications.dingtalk {
+            secrets.push(&dingtalk.webhook_url);
+            secrets.extend(dingtalk.secret.as_deref());
+        }
+        if let Some(wecom) = &notifications.wecom {
+            secrets.extend(wecom.key.as_deref());
+            secrets.extend(wecom.webhook_url.as_deref());
+        }
+        secrets.extend(notifications.matrix.as_ref().map(|m| m.access_token.as_str()));
+        secrets
+    }
+
+    /// 所有区域：`cloudflare` 段与 `dns_records` 组成的主区域在前，之后是 `zones` 中的区域
+    pub fn zones(&self) -> Vec<ZoneConfig> {
+        let primary = self.cloudflare.as_ref().map(|cloudflare| ZoneConfig {
+            zone_name: cloudflare.zone_name.clone(),
+            provider: ProviderConfig::Cloudflare(cloudflare.credentials.clone()),
+            dns_records: self.dns_records.clone(),
+        });
+        primary.into_iter().chain(self.zones.iter().cloned()).collect()
+    }
+
+    /// 所有区域中的记录
+    pub fn records(&self) -> impl Iterator<Item = &DnsRecordConfig> {
+        self.dns_records.iter().chain(self.zones.iter().flat_map(|zone| &zone.dns_records))
+    }
+
+    /// 记录的名称与类型是否在 `only` 中（为空时选中所有记录），双栈名称的 A 与 AAAA 记录分别选中；
+    /// `also_update` 展开的记录随原记录一起选中
+    pub fn is_selected(&self, record: &DnsRecordConfig, only: Option<&HashSet<(String, String)>>) -> bool {
+        only.is_none_or(|only| {
+            let selected = |name: &String| only.contains(&(name.clone(), record.r#type.clone()));
+            selected(&record.name) || record.alias_of.as_ref().is_some_and(selected)
+        })
+    }
+
+    /// 把各记录的 `also_update` 展开为紧跟在原记录后的独立记录，展开的名称与其他记录（类型相同）重复时返回错误
+    pub fn expand_aliases(&mut self) -> Result<(), String> {
+        if let Some(cloudflare) = &self.cloudflare {
+            expand_aliases(&cloudflare.zone_name, &mut self.dns_records)?;
+        }
+        for zone in &mut self.zones {
+            expand_aliases(&zone.zone_name, &mut zone.dns_records)?;
+        }
+        Ok(())
+    }
+
+    /// 主区域的名称，只配置了 `zones` 时为第一个区域的名称，用于启动通知等不针对单条记录的场合
+    pub fn zone_name(&self) -> &str {
+        self.cloudflare.as_ref().map(|cloudflare| cloudflare.zone_name.as_str())
+            .or(self.zones.first().map(|zone| zone.zone_name.as_str()))
+            .unwrap_or_default()
+    }
+
+    /// 记录名称所属的区域：名称等于区域名或以 `.区域名` 结尾，有多个时取最长的区域名
+    pub fn zone_for(&self, name: &str) -> Option<ZoneConfig> {
+        self.zones().into_iter()
+            .filter(|zone| name == zone.zone_name || name.ends_with(&format!(".{}", zone.zone_name)))
+            .max_by_key(|zone| zone.zone_name.len())
+    }
+
+    /// 配置中使用默认检测的记录（及记录集成员）用到的 IP 版本（去重），即默认检测需要检测的版本
+    pub fn ip_versions(&self) -> Result<Vec<IpVersion>, &'static str> {
+        let mut versions = Vec::new();
+        for record in self.records() {
+            let version = record.get_ip_version()?;
+            if record.detection_sources().contains(&None) && !versions.contains(&version) {
+                versions.push(version);
+            }
+        }
+        Ok(versions)
+    }
+
+    /// 使用给定上行线路的记录（及记录集成员）用到的 IP 版本（去重），ip_version 无效的记录忽略
+    pub fn uplink_versions(&self, uplink: &str) -> Vec<IpVersion> {
+        let mut versions = Vec::new();
+        for record in self.records().filter(|record| record.detection_sources().contains(&Some(uplink))) {
+            if let Ok(version) = record.get_ip_version()
+                && !versions.contains(&version)
+            {
+                versions.push(version);
+            }
+        }
+        versions
+    }
 }
 
 // 定义辅助函数来转换字符串到枚举
-impl CloudflareConfig {
+impl CloudflareCredentials {
     pub fn get_auth_type(&self) -> Result<AuthType, &'static str> {
         match self.auth_type.as_str() {
             "token" => Ok(AuthType::Token),
@@ -40,6 +1111,10 @@ impl CloudflareConfig {
             _ => Err("Invalid auth type"),
         }
     }
+
+    fn secrets(&self) -> impl Iterator<Item = &str> {
+        self.auth_key.as_deref().into_iter().chain(self.api_token.as_deref())
+    }
 }
 
 impl DnsRecordConfig {
@@ -50,6 +1125,17 @@ impl DnsRecordConfig {
             _ => Err("Invalid IP version"),
         }
     }
+
+    /// 记录的地址来自哪些检测：每项为上行线路名称，`None` 为默认检测；固定地址的记录集成员不需要检测
+    pub fn detection_sources(&self) -> Vec<Option<&str>> {
+        if self.members.is_empty() {
+            return vec![self.uplink.as_deref()];
+        }
+        self.members.iter()
+            .filter(|member| member.ip.is_none())
+            .map(|member| member.uplink.as_deref())
+            .collect()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -58,7 +1144,7 @@ pub enum AuthType {
     Token,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum IpVersion {
     V4,
     V6,
@@ -0,0 +1,187 @@
+// `config edit`：在编辑器中修改配置的副本，校验通过后才替换原文件；`config encrypt` / `config decrypt`：加密与解密配置文件
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use cloudflare_ddns::i18n::tr;
+use cloudflare_ddns::{encryption, state};
+use cloudflare_ddns::summary::{FailureKind, RunError};
+use cloudflare_ddns::update::{check_providers, load_config};
+
+use crate::commands;
+
+/// `config` 子命令的操作
+#[derive(Debug, clap::Subcommand)]
+pub enum ConfigAction {
+    /// 用 $VISUAL / $EDITOR 编辑配置文件，保存后校验，无效时不会覆盖原文件
+    Edit,
+    /// 校验配置文件后用 age 加密，密钥来自 CLOUDFLARE_DDNS_CONFIG_KEY_FILE、CLOUDFLARE_DDNS_CONFIG_KEY 或系统密钥环
+    Encrypt {
+        /// 输出文件，默认为配置文件名加 .age
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// 解密配置文件，默认输出到标准输出
+    Decrypt {
+        /// 输出文件
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+}
+
+pub fn run(action: &ConfigAction, config_path: &str) -> Result<(), RunError> {
+    match action {
+        ConfigAction::Edit => edit(config_path),
+        ConfigAction::Encrypt { output } => encrypt(config_path, output.as_deref()),
+        ConfigAction::Decrypt { output } => decrypt(config_path, output.as_deref()),
+    }
+}
+
+fn encrypt(config_path: &str, output: Option<&Path>) -> Result<(), RunError> {
+    let config_error = |e: String| RunError::new(FailureKind::Config, e);
+    let content = std::fs::read(config_path)
+        .map_err(|e| config_error(tr!("无法读取配置文件 {}: {}", "Failed to read config file {}: {}", config_path, e)))?;
+    if encryption::is_encrypted(&content) {
+        return Err(config_error(tr!("配置文件 {} 已经加密", "Config file {} is already encrypted", config_path)));
+    }
+    // 加密后不便再检查内容，先确认配置有效
+    load_config(config_path)?;
+    let plaintext = String::from_utf8_lossy(&content);
+    let ciphertext = encryption::encrypt(&plaintext)?;
+
+    let output = output.map(Path::to_path_buf).unwrap_or_else(|| PathBuf::from(format!("{}.age", config_path)));
+    state::write_atomic_private(&output, ciphertext.as_bytes())
+        .map_err(|e| config_error(tr!("无法写入 {}: {}", "Failed to write {}: {}", output.display(), e)))?;
+    println!("{}", tr!("已写入 {}，确认可以用 -c 加载后即可删除明文的 {}", "Wrote {}; once it loads with -c, the plaintext {} can be deleted", output.display(), config_path));
+    Ok(())
+}
+
+fn decrypt(config_path: &str, output: Option<&Path>) -> Result<(), RunError> {
+    let config_error = |e: String| RunError::new(FailureKind::Config, e);
+    let content = std::fs::read(config_path)
+        .map_err(|e| config_error(tr!("无法读取配置文件 {}: {}", "Failed to read config file {}: {}", config_path, e)))?;
+    if !encryption::is_encrypted(&content) {
+        return Err(config_error(tr!("配置文件 {} 没有加密", "Config file {} is not encrypted", config_path)));
+    }
+    let plaintext = encryption::decrypt(&content)?;
+    match output {
+        Some(output) => {
+            state::write_atomic_private(output, plaintext.as_bytes())
+                .map_err(|e| config_error(tr!("无法写入 {}: {}", "Failed to write {}: {}", output.display(), e)))?;
+            println!("{}", tr!("已写入 {}", "Wrote {}", output.display()));
+        }
+        None => print!("{}", plaintext),
+    }
+    Ok(())
+}
+
+fn edit(config_path: &str) -> Result<(), RunError> {
+    let config_error = |e: String| RunError::new(FailureKind::Config, e);
+    let path = Path::new(config_path);
+    let original = std::fs::read_to_string(path)
+        .map_err(|e| config_error(tr!("无法读取配置文件 {}: {}", "Failed to read config file {}: {}", config_path, e)))?;
+    if encryption::is_encrypted(original.as_bytes()) {
+        return Err(config_error(tr!(
+            "配置文件 {} 已加密，请先用 config decrypt 解密，编辑后再用 config encrypt 加密",
+            "Config file {} is encrypted; decrypt it with config decrypt, edit it, then encrypt it again with config encrypt",
+            config_path
+        )));
+    }
+    // 副本放在同一目录下，替换时是同一文件系统内的重命名；复制会保留原文件的权限
+    let draft = draft_path(path);
+    std::fs::copy(path, &draft)
+        .map_err(|e| config_error(tr!("无法创建副本 {}: {}", "Failed to create the working copy {}: {}", draft.display(), e)))?;
+
+    let result = edit_until_valid(&draft);
+    let outcome = match result {
+        Ok(true) => {
+            let edited = std::fs::read_to_string(&draft).unwrap_or_default();
+            if edited == original {
+                println!("{}", tr!("配置文件没有变化", "Configuration unchanged"));
+                Ok(())
+            } else {
+                std::fs::rename(&draft, path)
+                    .map(|()| println!("{}", tr!("已保存 {}", "Saved {}", config_path)))
+                    .map_err(|e| config_error(tr!("无法保存配置文件 {}: {}", "Failed to save config file {}: {}", config_path, e)))
+            }
+        }
+        Ok(false) => {
+            println!("{}", tr!("已放弃修改，{} 保持不变", "Changes discarded, {} left untouched", config_path));
+            Ok(())
+        }
+        Err(e) => Err(e),
+    };
+    let _ = std::fs::remove_file(&draft);
+    outcome
+}
+
+/// 反复打开编辑器直到副本通过校验（返回 true），或用户放弃（返回 false）
+fn edit_until_valid(draft: &Path) -> Result<bool, RunError> {
+    let draft_path = draft.to_string_lossy();
+    loop {
+        open_editor(draft)?;
+        let problems = match load_config(&draft_path) {
+            Ok(config) => {
+                let mut problems = commands::config_problems(&config);
+                if let Err(e) = check_providers(&config) {
+                    problems.insert(0, e.message().to_string());
+                }
+                problems
+            }
+            Err(e) => vec![e.to_string()],
+        };
+        if problems.is_empty() {
+            return Ok(true);
+        }
+        println!("{}", tr!("配置无效:", "The configuration is invalid:"));
+        for problem in &problems {
+            println!("  {}", problem);
+        }
+        print!("{}", tr!("重新编辑？[Y/n] ", "Edit again? [Y/n] "));
+        let _ = io::stdout().flush();
+        let mut answer = String::new();
+        io::stdin().lock().read_line(&mut answer)
+            .map_err(|e| RunError::new(FailureKind::Config, tr!("无法读取输入: {}", "Failed to read input: {}", e)))?;
+        if matches!(answer.trim().to_ascii_lowercase().as_str(), "n" | "no") {
+            return Ok(false);
+        }
+    }
+}
+
+fn open_editor(file: &Path) -> Result<(), RunError> {
+    let variable = std::env::var("VISUAL").or_else(|_| std::env::var("EDITOR")).unwrap_or_default();
+    let (program, arguments) = editor_command(&variable);
+    let status = std::process::Command::new(&program).args(&arguments).arg(file).status()
+        .map_err(|e| RunError::new(FailureKind::Config, tr!("无法启动编辑器 {}: {}", "Failed to start editor {}: {}", program, e)))?;
+    if !status.success() {
+        return Err(RunError::new(FailureKind::Config, tr!("编辑器 {} 异常退出: {}", "Editor {} exited with {}", program, status)));
+    }
+    Ok(())
+}
+
+/// 解析 `$EDITOR`（如 `code --wait`）为程序与参数，未设置时 Windows 使用 notepad，其他系统使用 vi
+fn editor_command(variable: &str) -> (String, Vec<String>) {
+    let mut parts = variable.split_whitespace().map(str::to_string);
+    match parts.next() {
+        Some(program) => (program, parts.collect()),
+        None if cfg!(windows) => ("notepad".to_string(), Vec::new()),
+        None => ("vi".to_string(), Vec::new()),
+    }
+}
+
+/// 与配置文件同目录的副本，保留扩展名以便编辑器识别格式
+fn draft_path(path: &Path) -> PathBuf {
+    let name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+    path.with_file_name(format!(".{}.edit.json", name.trim_end_matches(".json")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn editor_command_splits_arguments() {
+        assert_eq!(editor_command("code --wait"), ("code".to_string(), vec!["--wait".to_string()]));
+        assert_eq!(editor_command("nano").1, Vec::<String>::new());
+        assert_eq!(draft_path(Path::new("/etc/ddns/config.json")), PathBuf::from("/etc/ddns/.config.edit.json"));
+    }
+}
@@ -0,0 +1,211 @@
+// 定时模式的 Unix 控制套接字与 `ctl` 子命令
+//
+// 客户端每次连接发送一行命令（run、reload、status、pause、resume），服务端回复一行 JSON 后关闭连接：
+// `{"ok": true, "message": "..."}`，`status` 的回复另有 `status` 字段，内容与 SIGUSR2 状态转储相同。
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::Mutex;
+use tracing::{error, info, warn};
+
+use cloudflare_ddns::i18n::tr;
+use cloudflare_ddns::summary::{FailureKind, RunError};
+use cloudflare_ddns::update::load_config;
+
+use crate::DaemonState;
+
+/// `ctl` 子命令的操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::Subcommand)]
+pub enum CtlAction {
+    /// 立即执行一次完整核对
+    Run,
+    /// 检查配置文件并按新配置立即执行一次完整核对
+    Reload,
+    /// 以 JSON 输出定时模式的内部状态
+    Status,
+    /// 暂停定时执行，直到 resume
+    Pause,
+    /// 恢复定时执行
+    Resume,
+}
+
+impl CtlAction {
+    fn name(self) -> &'static str {
+        match self {
+            CtlAction::Run => "run",
+            CtlAction::Reload => "reload",
+            CtlAction::Status => "status",
+            CtlAction::Pause => "pause",
+            CtlAction::Resume => "resume",
+        }
+    }
+
+    fn parse(name: &str) -> Option<Self> {
+        [CtlAction::Run, CtlAction::Reload, CtlAction::Status, CtlAction::Pause, CtlAction::Resume]
+            .into_iter()
+            .find(|action| action.name() == name)
+    }
+}
+
+/// 控制套接字的回复
+#[derive(Debug, Serialize, Deserialize)]
+struct Reply {
+    ok: bool,
+    #[serde(default)]
+    message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    status: Option<serde_json::Value>,
+}
+
+impl Reply {
+    fn ok(message: String) -> Self {
+        Self { ok: true, message, status: None }
+    }
+
+    fn error(message: String) -> Self {
+        Self { ok: false, message, status: None }
+    }
+}
+
+/// 在后台监听控制套接字，已存在的旧套接字文件会被替换，监听失败时只记录错误
+pub fn spawn(path: &str, config_path: &str, state: Arc<Mutex<DaemonState>>) {
+    use std::os::unix::fs::FileTypeExt;
+
+    if std::fs::symlink_metadata(path).is_ok_and(|meta| meta.file_type().is_socket()) {
+        let _ = std::fs::remove_file(path);
+    }
+    // 控制套接字可以暂停更新，只允许属主访问；在创建时就限制权限，bind 之后再修改会留下其他用户可以连接的间隙
+    let umask = unsafe { libc::umask(0o177) };
+    let listener = UnixListener::bind(path);
+    unsafe { libc::umask(umask) };
+    let listener = match listener {
+        Ok(listener) => listener,
+        Err(e) => {
+            error!(path = %path, error = %e, "{}", tr!("无法监听控制套接字", "Failed to listen on the control socket"));
+            return;
+        }
+    };
+    info!(path = %path, "{}", tr!("控制套接字已启动", "Control socket listening"));
+
+    let config_path = config_path.to_string();
+    tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    tokio::spawn(serve(stream, config_path.clone(), state.clone()));
+                }
+                // 单次接受失败（如文件描述符暂时耗尽）不停止监听，稍等后继续
+                Err(e) => {
+                    warn!(error = %e, "{}", tr!("控制套接字接受连接失败", "Control socket failed to accept a connection"));
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                }
+            }
+        }
+    });
+}
+
+async fn serve(stream: UnixStream, config_path: String, state: Arc<Mutex<DaemonState>>) {
+    let (reader, mut writer) = stream.into_split();
+    let mut line = String::new();
+    if BufReader::new(reader).read_line(&mut line).await.is_err() {
+        return;
+    }
+    let reply = match CtlAction::parse(line.trim()) {
+        Some(action) => handle(action, &config_path, &state).await,
+        None => Reply::error(tr!("未知的命令: {}", "Unknown command: {}", line.trim())),
+    };
+    let reply = serde_json::to_string(&reply).unwrap_or_default() + "\n";
+    let _ = writer.write_all(reply.as_bytes()).await;
+}
+
+async fn handle(action: CtlAction, config_path: &str, state: &Mutex<DaemonState>) -> Reply {
+    info!(command = action.name(), "{}", tr!("收到控制命令", "Control command received"));
+    let mut state = state.lock().await;
+    match action {
+        CtlAction::Status => Reply { status: Some(state.dump()), ..Reply::ok(String::new()) },
+        CtlAction::Pause => {
+            state.paused = true;
+            Reply::ok(tr!("已暂停定时执行", "Scheduled runs paused"))
+        }
+        CtlAction::Resume => {
+            state.paused = false;
+            state.wake.notify_one();
+            Reply::ok(tr!("已恢复定时执行", "Scheduled runs resumed"))
+        }
+        CtlAction::Run | CtlAction::Reload => {
+            if let CtlAction::Reload = action
+                && let Err(e) = load_config(config_path)
+            {
+                return Reply::error(tr!("配置无效，未重新加载: {}", "Invalid configuration, not reloaded: {}", e));
+            }
+            if state.paused {
+                return Reply::error(tr!("定时执行已暂停，请先 resume", "Scheduled runs are paused, resume first"));
+            }
+            // 下一次执行按完整核对处理
+            state.last_reconcile = None;
+            state.wake.notify_one();
+            match action {
+                CtlAction::Reload => Reply::ok(tr!("配置已重新加载，正在执行完整核对", "Configuration reloaded, reconciling all records now")),
+                _ => Reply::ok(tr!("正在执行完整核对", "Reconciling all records now")),
+            }
+        }
+    }
+}
+
+/// `ctl` 子命令：向运行中的定时模式发送命令并输出回复，命令失败时返回 1
+pub async fn ctl(config_path: &str, action: CtlAction) -> Result<u8, RunError> {
+    let config = load_config(config_path)?;
+    let path = config.schedule.control_socket.ok_or_else(|| RunError::new(
+        FailureKind::Config,
+        tr!("配置中没有设置 schedule.control_socket", "schedule.control_socket is not set in the config"),
+    ))?;
+    let reply = send(Path::new(&path), action).await.map_err(|e| RunError::new(
+        FailureKind::Config,
+        tr!("无法连接控制套接字 {}（定时模式是否在运行？）: {}", "Failed to connect to control socket {} (is the scheduled mode running?): {}", path, e),
+    ))?;
+
+    match (&reply.status, reply.ok) {
+        (Some(status), _) => println!("{}", serde_json::to_string_pretty(status).unwrap_or_default()),
+        (None, true) => println!("{}", reply.message),
+        (None, false) => eprintln!("{}", reply.message),
+    }
+    Ok(if reply.ok { 0 } else { 1 })
+}
+
+async fn send(path: &Path, action: CtlAction) -> std::io::Result<Reply> {
+    let mut stream = UnixStream::connect(path).await?;
+    stream.write_all(format!("{}\n", action.name()).as_bytes()).await?;
+    let mut line = String::new();
+    BufReader::new(stream).read_line(&mut line).await?;
+    serde_json::from_str(&line).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn commands_change_daemon_state() {
+        let path = std::env::temp_dir().join(format!("cloudflare_ddns_ctl_{}.sock", std::process::id()));
+        let state = Arc::new(Mutex::new(DaemonState::default()));
+        spawn(path.to_str().unwrap(), "missing.json", state.clone());
+
+        let reply = send(&path, CtlAction::Pause).await.unwrap();
+        assert!(reply.ok);
+        assert!(state.lock().await.paused);
+        // 暂停时不能手动执行
+        assert!(!send(&path, CtlAction::Run).await.unwrap().ok);
+        let status = send(&path, CtlAction::Status).await.unwrap().status.unwrap();
+        assert_eq!(status["paused"], true);
+
+        assert!(send(&path, CtlAction::Resume).await.unwrap().ok);
+        assert!(send(&path, CtlAction::Run).await.unwrap().ok);
+        // 配置文件不存在，reload 失败
+        assert!(!send(&path, CtlAction::Reload).await.unwrap().ok);
+        let _ = std::fs::remove_file(&path);
+    }
+}
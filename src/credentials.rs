@@ -0,0 +1,184 @@
+//! 配置文件之外的凭据来源，在创建客户端时读取，读到的值登记到日志脱敏
+//!
+//! 按以下顺序取第一个配置了的来源：
+//!
+//! - `cloudflare.api_token`: 配置文件中的明文令牌
+//! - `cloudflare.api_token_env`: 运行时从该名称的环境变量读取
+//! - `cloudflare.api_token_credential`: systemd 通过 `LoadCredential` 提供的凭据（`$CREDENTIALS_DIRECTORY` 下的文件）
+//! - `cloudflare.api_token_keyring`: 系统密钥环（Secret Service、macOS 钥匙串、Windows 凭据管理器）
+//!
+//! 全局 API Key 同样可以用 `cloudflare.auth_key_env` 从环境变量读取。只在需要时读取，`validate`、`install-service` 等不访问 API 的命令在服务环境之外也能使用同一份配置。
+use std::path::PathBuf;
+
+use crate::config::CloudflareCredentials;
+use crate::error::{Error, Result};
+use crate::i18n::tr;
+use crate::logging;
+
+/// 密钥环中保存 API 令牌使用的账户名
+pub const KEYRING_USER: &str = "api_token";
+
+/// 按配置的来源读取 API 令牌，均未配置时返回 `None`
+pub fn api_token(credentials: &CloudflareCredentials) -> Result<Option<String>> {
+    let token = if let Some(token) = &credentials.api_token {
+        token.clone()
+    } else if let Some(variable) = &credentials.api_token_env {
+        read_env(variable)?
+    } else if let Some(name) = &credentials.api_token_credential {
+        read_credential(name)?
+    } else if let Some(service) = &credentials.api_token_keyring {
+        keyring_get(service)?
+    } else {
+        return Ok(None);
+    };
+    logging::redact::register([token.as_str()]);
+    Ok(Some(token))
+}
+
+/// 按配置的来源读取全局 API Key，均未配置时返回 `None`
+pub fn auth_key(credentials: &CloudflareCredentials) -> Result<Option<String>> {
+    let key = if let Some(key) = &credentials.auth_key {
+        key.clone()
+    } else if let Some(variable) = &credentials.auth_key_env {
+        read_env(variable)?
+    } else {
+        return Ok(None);
+    };
+    logging::redact::register([key.as_str()]);
+    Ok(Some(key))
+}
+
+/// 读取环境变量中的凭据，未设置或为空时报告配置错误
+pub fn read_env(variable: &str) -> Result<String> {
+    match std::env::var(variable) {
+        Ok(value) if !value.trim().is_empty() => Ok(value.trim().to_string()),
+        Ok(_) => Err(Error::config(tr!("环境变量 {} 为空", "Environment variable {} is empty", variable))),
+        Err(e) => Err(Error::config(tr!("无法读取环境变量 {}: {}", "Failed to read environment variable {}: {}", variable, e))),
+    }
+}
+
+/// 读取 systemd 提供的凭据文件，去掉末尾的换行
+pub fn read_credential(name: &str) -> Result<String> {
+    if name.is_empty() || name.contains(['/', '\\']) || name == "." || name == ".." {
+        return Err(Error::config(tr!("凭据名称 {:?} 无效", "Invalid credential name {:?}", name)));
+    }
+    let dir = std::env::var_os("CREDENTIALS_DIRECTORY").ok_or_else(|| Error::config(tr!(
+        "配置了 systemd 凭据 {}，但未设置 $CREDENTIALS_DIRECTORY（需要在服务单元中用 LoadCredential 提供）",
+        "systemd credential {} is configured but $CREDENTIALS_DIRECTORY is not set (provide it with LoadCredential in the service unit)",
+        name
+    )))?;
+    let path = PathBuf::from(dir).join(name);
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| Error::config(tr!("无法读取 systemd 凭据 {}: {}", "Failed to read systemd credential {}: {}", path.display(), e)))?;
+    Ok(content.trim_end_matches(['\n', '\r']).to_string())
+}
+
+/// 从系统密钥环读取服务名下的 API 令牌
+#[cfg(feature = "keyring")]
+pub fn keyring_get(service: &str) -> Result<String> {
+    let value = keyring_lookup(service, KEYRING_USER)?.ok_or_else(|| Error::config(tr!(
+        "系统密钥环的服务 {} 下没有 API 令牌", "No API token in the OS keyring under service {}", service
+    )))?;
+    keyring_token(service, &value)
+}
+
+/// 去掉密钥环中令牌首尾的空白与换行；仍含有不能放入请求头的字符时报告配置错误，而不是在创建客户端时 panic
+#[cfg(any(feature = "keyring", test))]
+fn keyring_token(service: &str, value: &str) -> Result<String> {
+    let token = value.trim();
+    if token.is_empty() || reqwest::header::HeaderValue::from_str(token).is_err() {
+        return Err(Error::config(tr!(
+            "系统密钥环的服务 {} 下的 API 令牌为空或含有无效字符", "The API token in the OS keyring under service {} is empty or contains invalid characters", service
+        )));
+    }
+    Ok(token.to_string())
+}
+
+/// 读取系统密钥环中服务名与账户名下保存的值，不存在时返回 `None`
+#[cfg(feature = "keyring")]
+pub fn keyring_lookup(service: &str, account: &str) -> Result<Option<String>> {
+    let (service, account) = (service.to_string(), account.to_string());
+    match keyring_call(move || keyring::Entry::new(&service, &account)?.get_password()) {
+        Ok(value) => Ok(Some(value)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(Error::config(tr!("无法读取系统密钥环: {}", "Failed to read the OS keyring: {}", e))),
+    }
+}
+
+/// 把 API 令牌保存到系统密钥环的服务名下，替换已有的值
+#[cfg(feature = "keyring")]
+pub fn keyring_set(service: &str, token: &str) -> Result<()> {
+    let (service, token) = (service.to_string(), token.to_string());
+    keyring_call(move || keyring::Entry::new(&service, KEYRING_USER)?.set_password(&token))
+        .map_err(|e| Error::config(tr!("无法把 API 令牌写入系统密钥环: {}", "Failed to store the API token in the OS keyring: {}", e)))
+}
+
+/// Secret Service 后端会自行驱动异步的 D-Bus 连接，不能在 tokio 运行时的线程中阻塞调用，因此在独立线程中访问密钥环
+#[cfg(feature = "keyring")]
+fn keyring_call<T: Send + 'static>(call: impl FnOnce() -> keyring::Result<T> + Send + 'static) -> keyring::Result<T> {
+    std::thread::spawn(call)
+        .join()
+        .unwrap_or_else(|_| Err(keyring::Error::PlatformFailure("keyring thread panicked".into())))
+}
+
+#[cfg(not(feature = "keyring"))]
+pub fn keyring_get(_service: &str) -> Result<String> {
+    Err(keyring_disabled())
+}
+
+#[cfg(not(feature = "keyring"))]
+pub fn keyring_set(_service: &str, _token: &str) -> Result<()> {
+    Err(keyring_disabled())
+}
+
+#[cfg(not(feature = "keyring"))]
+fn keyring_disabled() -> Error {
+    Error::config(tr!("编译时未启用 keyring 功能，无法使用系统密钥环", "The keyring feature was not enabled at build time, the OS keyring is unavailable"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn credentials(value: serde_json::Value) -> CloudflareCredentials {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn token_sources_are_tried_in_order() {
+        // 已有明文令牌时不访问密钥环，未启用 keyring 功能也不会出错
+        let plain = credentials(serde_json::json!({ "auth_type": "token", "api_token": "plain-token-value", "api_token_keyring": "cf-ddns" }));
+        assert_eq!(api_token(&plain).unwrap().as_deref(), Some("plain-token-value"));
+
+        // SAFETY: 测试中没有其他线程读写该环境变量
+        unsafe { std::env::set_var("CLOUDFLARE_DDNS_TEST_TOKEN", "env-token-value\n") };
+        let env = credentials(serde_json::json!({ "auth_type": "token", "api_token_env": "CLOUDFLARE_DDNS_TEST_TOKEN", "api_token_credential": "cf_token" }));
+        let token = api_token(&env);
+        unsafe { std::env::remove_var("CLOUDFLARE_DDNS_TEST_TOKEN") };
+        assert_eq!(token.unwrap().as_deref(), Some("env-token-value"));
+        // 指定的环境变量未设置时报错，而不是退回到其他来源
+        assert!(api_token(&env).is_err());
+
+        let dir = std::env::temp_dir().join(format!("cloudflare_ddns_credentials_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("cf_token"), "credential-token-value\n").unwrap();
+        // SAFETY: 测试中没有其他线程读写 CREDENTIALS_DIRECTORY
+        unsafe { std::env::set_var("CREDENTIALS_DIRECTORY", &dir) };
+        let systemd = credentials(serde_json::json!({ "auth_type": "token", "api_token_credential": "cf_token" }));
+        let token = api_token(&systemd);
+        let escape = read_credential("../cf_token");
+        unsafe { std::env::remove_var("CREDENTIALS_DIRECTORY") };
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(token.unwrap().as_deref(), Some("credential-token-value"));
+        assert!(escape.is_err());
+        assert!(api_token(&credentials(serde_json::json!({ "auth_type": "token" }))).unwrap().is_none());
+    }
+
+    #[test]
+    fn keyring_token_is_trimmed_and_checked() {
+        assert_eq!(keyring_token("cf-ddns", " keyring-token-value\r\n").unwrap(), "keyring-token-value");
+        assert!(keyring_token("cf-ddns", "keyring\u{7}token").is_err());
+        assert!(keyring_token("cf-ddns", "\n").is_err());
+    }
+}
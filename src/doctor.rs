@@ -0,0 +1,246 @@
+// `doctor` 子命令：逐项检查配置、网络、IP 检测、凭据、区域与记录，只读不写
+use std::time::Duration;
+
+use cloudflare_ddns::config::{Config, IpDetectionConfig, IpVersion, UplinkConfig};
+use cloudflare_ddns::i18n::tr;
+use cloudflare_ddns::ip_utils;
+use cloudflare_ddns::update::{check_providers, client_from_config, load_config};
+
+use crate::commands;
+
+/// CloudFlare API 的主机名，用于检查 DNS 解析
+const API_HOST: &str = "api.cloudflare.com";
+
+/// 单项检查的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Outcome {
+    Pass,
+    /// 不影响运行，但值得注意
+    Warn,
+    Fail,
+    /// 依赖的前一项检查失败，未执行
+    Skip,
+}
+
+/// 单项检查：名称、结果说明，以及失败或警告时的处理建议
+#[derive(Debug)]
+struct Check {
+    outcome: Outcome,
+    name: String,
+    detail: String,
+    hint: Option<String>,
+}
+
+impl Check {
+    fn pass(name: String, detail: String) -> Self {
+        Self { outcome: Outcome::Pass, name, detail, hint: None }
+    }
+
+    fn fail(name: String, detail: String, hint: String) -> Self {
+        Self { outcome: Outcome::Fail, name, detail, hint: Some(hint) }
+    }
+
+    fn skip(name: String) -> Self {
+        let detail = tr!("前一项检查失败，已跳过", "Skipped because an earlier check failed");
+        Self { outcome: Outcome::Skip, name, detail, hint: None }
+    }
+
+    fn render(&self) -> String {
+        let tag = match self.outcome {
+            Outcome::Pass => "[ OK ]",
+            Outcome::Warn => "[WARN]",
+            Outcome::Fail => "[FAIL]",
+            Outcome::Skip => "[SKIP]",
+        };
+        let mut line = format!("{} {}: {}", tag, self.name, self.detail);
+        if let Some(hint) = &self.hint {
+            line.push_str(&format!("\n       {}", tr!("建议: {}", "Hint: {}", hint)));
+        }
+        line
+    }
+}
+
+/// 依次执行所有检查并打印报告，全部通过（允许警告）时返回 0，否则返回 1
+pub async fn doctor(config_path: &str) -> u8 {
+    let checks = run_checks(config_path).await;
+    for check in &checks {
+        println!("{}", check.render());
+    }
+    let failed = checks.iter().filter(|check| check.outcome == Outcome::Fail).count();
+    println!();
+    if failed == 0 {
+        println!("{}", tr!("所有检查均已通过", "All checks passed"));
+        0
+    } else {
+        println!("{}", tr!("{} 项检查未通过", "Failed checks: {}", failed));
+        1
+    }
+}
+
+async fn run_checks(config_path: &str) -> Vec<Check> {
+    let mut checks = Vec::new();
+    let name = tr!("配置文件", "Configuration");
+    let config = match load_config(config_path) {
+        Ok(config) => config,
+        Err(e) => {
+            let hint = tr!("修正配置文件，或用 generate-config 生成示例配置", "Fix the configuration file, or start from generate-config");
+            checks.push(Check::fail(name, tr!("{}: {}", "{}: {}", config_path, e), hint));
+            return checks;
+        }
+    };
+    let mut problems = commands::config_problems(&config);
+    if let Err(e) = check_providers(&config) {
+        problems.insert(0, e.message().to_string());
+    }
+    if !problems.is_empty() {
+        let hint = tr!("按提示修改配置文件中的对应项", "Update the listed settings in the configuration file");
+        checks.push(Check::fail(name, problems.join("; "), hint));
+        return checks;
+    }
+    checks.push(Check::pass(name, tr!("{}（{} 条记录）", "{} ({} records)", config_path, config.records().count())));
+
+    let timeout = config.schedule.timeout();
+    let dns = check_dns(timeout).await;
+    let reachable = dns.outcome == Outcome::Pass;
+    checks.push(dns);
+    for version in config.ip_versions().unwrap_or_default() {
+        checks.push(check_detection(version, &config.ip_detection, timeout).await);
+    }
+    for uplink in &config.uplinks {
+        let versions = config.uplink_versions(&uplink.name);
+        if !versions.is_empty() {
+            checks.push(check_uplink(uplink, &versions, &config.ip_detection, timeout).await);
+        }
+    }
+    checks.extend(check_api(&config, reachable).await);
+    checks
+}
+
+/// 能否解析 CloudFlare API 的地址
+async fn check_dns(timeout: Duration) -> Check {
+    let name = tr!("DNS 解析 {}", "DNS lookup of {}", API_HOST);
+    match tokio::time::timeout(timeout, tokio::net::lookup_host((API_HOST, 443))).await {
+        Ok(Ok(addresses)) => {
+            let addresses: Vec<String> = addresses.map(|address| address.ip().to_string()).collect();
+            Check::pass(name, addresses.join(", "))
+        }
+        Ok(Err(e)) => Check::fail(name, e.to_string(), tr!("检查网络连接与系统的 DNS 服务器设置", "Check the network connection and the system DNS resolver")),
+        Err(_) => Check::fail(name, tr!("超时", "timed out"), tr!("检查网络连接与系统的 DNS 服务器设置", "Check the network connection and the system DNS resolver")),
+    }
+}
+
+/// 能否从检测服务取得该版本的公网 IP
+async fn check_detection(version: IpVersion, detection: &IpDetectionConfig, timeout: Duration) -> Check {
+    let name = match version {
+        IpVersion::V4 => tr!("IPv4 检测", "IPv4 detection"),
+        IpVersion::V6 => tr!("IPv6 检测", "IPv6 detection"),
+    };
+    match ip_utils::detect_ips(&[version], detection, timeout).await {
+        Ok((ips, times)) => {
+            let elapsed = times.get(version).unwrap_or_default();
+            Check::pass(name, format!("{} ({} ms)", ips.get(version).unwrap_or("-"), elapsed.as_millis()))
+        }
+        Err(e) => {
+            let hint = match version {
+                IpVersion::V4 => tr!("检查网络连接，或用 --ip 直接指定地址", "Check the network connection, or pass the address with --ip"),
+                IpVersion::V6 => tr!(
+                    "确认本机有可用的 IPv6 连接，或用 --ipv4-only 只处理 IPv4 记录",
+                    "Make sure this host has IPv6 connectivity, or use --ipv4-only to process IPv4 records only"
+                ),
+            };
+            Check::fail(name, e.to_string(), hint)
+        }
+    }
+}
+
+/// 能否通过上行线路绑定的接口或源地址取得公网 IP
+async fn check_uplink(uplink: &UplinkConfig, versions: &[IpVersion], detection: &IpDetectionConfig, timeout: Duration) -> Check {
+    let name = tr!("上行线路 {}", "Uplink {}", uplink.name);
+    match ip_utils::detect_uplink(uplink, versions, detection, timeout).await {
+        Ok((ips, _)) => {
+            let detected: Vec<&str> = versions.iter().filter_map(|version| ips.get(*version)).collect();
+            Check::pass(name, detected.join(", "))
+        }
+        Err(e) => Check::fail(name, e.to_string(), tr!(
+            "确认该线路在线，并检查 interface 或 local_address 的设置",
+            "Make sure the uplink is up, and check its interface or local_address setting"
+        )),
+    }
+}
+
+/// 依次检查 cloudflare 段的凭据、区域与记录，前一项失败或 API 地址无法解析时后续项跳过，没有该段时不检查
+async fn check_api(config: &Config, reachable: bool) -> Vec<Check> {
+    let credentials = tr!("API 凭据", "API credentials");
+    let Some(cloudflare) = &config.cloudflare else {
+        return Vec::new();
+    };
+    let zone = tr!("区域 {}", "Zone {}", cloudflare.zone_name);
+    let records = tr!("DNS 记录", "DNS records");
+    let Some(client) = client_from_config(config).ok().filter(|_| reachable) else {
+        return vec![Check::skip(credentials), Check::skip(zone), Check::skip(records)];
+    };
+
+    let mut checks = Vec::new();
+    match client.verify_credentials().await {
+        Ok(detail) => checks.push(Check::pass(credentials, detail)),
+        Err(e) => {
+            let hint = tr!(
+                "确认令牌未过期、未被撤销，且具有 Zone.DNS 编辑权限",
+                "Make sure the token is active, not revoked, and has Zone.DNS edit permission"
+            );
+            checks.extend([Check::fail(credentials, e.to_string(), hint), Check::skip(zone), Check::skip(records)]);
+            return checks;
+        }
+    }
+
+    let zone_id = match client.get_zone_id(&cloudflare.zone_name).await {
+        Ok(zone_id) => {
+            checks.push(Check::pass(zone, zone_id.clone()));
+            zone_id
+        }
+        Err(e) => {
+            let hint = tr!(
+                "确认 zone_name 拼写正确，且令牌的区域资源包含该区域（可用 zones 子命令查看）",
+                "Check the spelling of zone_name and that the token's zone resources include it (see the zones subcommand)"
+            );
+            checks.extend([Check::fail(zone, e.to_string(), hint), Check::skip(records)]);
+            return checks;
+        }
+    };
+
+    let existing = match client.list_dns_records(&zone_id).await {
+        Ok(existing) => existing,
+        Err(e) => {
+            let hint = tr!("确认令牌具有 Zone.DNS 读取权限", "Make sure the token has Zone.DNS read permission");
+            checks.push(Check::fail(records, e.to_string(), hint));
+            return checks;
+        }
+    };
+    for record in &config.dns_records {
+        let name = format!("{} {}", record.r#type, record.name);
+        match existing.iter().find(|r| r.name == record.name && r.r#type == record.r#type) {
+            Some(found) => checks.push(Check::pass(name, found.content.clone())),
+            None => checks.push(Check {
+                outcome: Outcome::Warn,
+                name,
+                detail: tr!("记录不存在", "Record does not exist"),
+                hint: Some(tr!("首次运行时会自动创建", "It will be created on the first run")),
+            }),
+        }
+    }
+    checks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn failed_check_renders_hint() {
+        let check = Check::fail("IPv6".to_string(), "timeout".to_string(), "use --ipv4-only".to_string());
+        let rendered = check.render();
+        assert!(rendered.starts_with("[FAIL] IPv6: timeout\n"));
+        assert!(rendered.ends_with("use --ipv4-only"));
+        assert_eq!(Check::pass("a".to_string(), "b".to_string()).render(), "[ OK ] a: b");
+    }
+}
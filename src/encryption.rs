@@ -0,0 +1,197 @@
+//! 用 age 加密的配置文件，便于通过 Git 或网盘同步配置而不暴露其中的令牌
+//!
+//! 加载配置时按文件内容（而不是扩展名）识别加密文件，二进制与 ASCII armor 格式均可。密钥按以下顺序查找，
+//! 值为 `AGE-SECRET-KEY-` 开头的 age 身份（可以是 age-keygen 生成的完整密钥文件）时按身份解密，否则视为口令：
+//!
+//! - 环境变量 `CLOUDFLARE_DDNS_CONFIG_KEY_FILE` 指向的文件，可以指向 systemd 凭据
+//! - 环境变量 `CLOUDFLARE_DDNS_CONFIG_KEY`
+//! - 系统密钥环中服务 `cloudflare_ddns`、账户 `config_key` 下的值（需要启用 keyring 功能）
+//!
+//! 需要启用 `encrypted-config` 功能。
+use crate::error::{Error, Result};
+use crate::i18n::tr;
+
+/// 保存密钥文件路径的环境变量
+pub const KEY_FILE_ENV: &str = "CLOUDFLARE_DDNS_CONFIG_KEY_FILE";
+/// 保存密钥的环境变量
+pub const KEY_ENV: &str = "CLOUDFLARE_DDNS_CONFIG_KEY";
+/// 系统密钥环中保存密钥的服务名与账户名
+pub const KEYRING_SERVICE: &str = "cloudflare_ddns";
+pub const KEYRING_ACCOUNT: &str = "config_key";
+
+/// 二进制格式的文件头
+const BINARY_HEADER: &[u8] = b"age-encryption.org/";
+/// ASCII armor 格式的文件头
+const ARMOR_HEADER: &[u8] = b"-----BEGIN AGE ENCRYPTED FILE-----";
+
+/// 内容是否为 age 加密文件
+pub fn is_encrypted(content: &[u8]) -> bool {
+    let content = content.trim_ascii_start();
+    content.starts_with(BINARY_HEADER) || content.starts_with(ARMOR_HEADER)
+}
+
+/// 读取解密用的密钥，返回密钥及其来源（用于错误信息）
+#[cfg(feature = "encrypted-config")]
+fn key() -> Result<(String, String)> {
+    if let Some(path) = std::env::var_os(KEY_FILE_ENV) {
+        let path = std::path::PathBuf::from(path);
+        let key = std::fs::read_to_string(&path)
+            .map_err(|e| Error::config(tr!("无法读取配置密钥文件 {}: {}", "Failed to read config key file {}: {}", path.display(), e)))?;
+        return Ok((key, path.display().to_string()));
+    }
+    if let Ok(key) = std::env::var(KEY_ENV) {
+        return Ok((key, format!("${}", KEY_ENV)));
+    }
+    #[cfg(feature = "keyring")]
+    if let Some(key) = crate::credentials::keyring_lookup(KEYRING_SERVICE, KEYRING_ACCOUNT)? {
+        return Ok((key, tr!("系统密钥环", "OS keyring")));
+    }
+    Err(Error::config(tr!(
+        "配置文件已加密，但没有找到密钥：请设置 {} 或 {}",
+        "The config file is encrypted but no key was found: set {} or {}",
+        KEY_FILE_ENV,
+        KEY_ENV
+    )))
+}
+
+#[cfg(feature = "encrypted-config")]
+mod age_impl {
+    use std::io::{Read, Write};
+    use std::sync::Mutex;
+
+    use age::armor::{ArmoredReader, ArmoredWriter, Format};
+    use age::secrecy::SecretString;
+
+    use super::*;
+
+    /// 最近一次解密的密文与明文；定时模式每次执行都重新加载配置，口令的 scrypt 在路由器上可能需要数秒
+    static LAST: Mutex<Option<(Vec<u8>, String)>> = Mutex::new(None);
+
+    /// 密钥：age 身份或口令
+    enum Key {
+        Identities(age::IdentityFile<age::NoCallbacks>),
+        Passphrase(SecretString),
+    }
+
+    fn parse(key: &str) -> Result<Key> {
+        if key.lines().any(|line| line.trim().starts_with("AGE-SECRET-KEY-")) {
+            let identities = age::IdentityFile::from_buffer(key.as_bytes())
+                .map_err(|e| Error::config(tr!("age 身份无效: {}", "Invalid age identity: {}", e)))?;
+            return Ok(Key::Identities(identities));
+        }
+        let passphrase = key.trim_end_matches(['\n', '\r']);
+        if passphrase.is_empty() {
+            return Err(Error::config(tr!("配置密钥为空", "The config key is empty")));
+        }
+        Ok(Key::Passphrase(SecretString::from(passphrase.to_string())))
+    }
+
+    pub fn decrypt(content: &[u8]) -> Result<String> {
+        let mut last = LAST.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((ciphertext, plaintext)) = last.as_ref()
+            && ciphertext == content
+        {
+            return Ok(plaintext.clone());
+        }
+
+        let (key, source) = key()?;
+        let failed = |e: &dyn std::fmt::Display| Error::config(tr!(
+            "无法用 {} 中的密钥解密配置文件: {}", "Failed to decrypt the config file with the key from {}: {}", source, e
+        ));
+        let decryptor = age::Decryptor::new_buffered(ArmoredReader::new(content)).map_err(|e| failed(&e))?;
+        let mut reader = match parse(&key)? {
+            Key::Identities(file) => {
+                let identities = file.into_identities().map_err(|e| failed(&e))?;
+                decryptor.decrypt(identities.iter().map(|identity| identity.as_ref() as &dyn age::Identity))
+            }
+            Key::Passphrase(passphrase) => {
+                let identity = age::scrypt::Identity::new(passphrase);
+                decryptor.decrypt(std::iter::once(&identity as &dyn age::Identity))
+            }
+        }
+        .map_err(|e| failed(&e))?;
+        let mut plaintext = String::new();
+        reader.read_to_string(&mut plaintext).map_err(|e| failed(&e))?;
+
+        *last = Some((content.to_vec(), plaintext.clone()));
+        Ok(plaintext)
+    }
+
+    pub fn encrypt(plaintext: &str) -> Result<String> {
+        let (key, _) = key()?;
+        let encryptor = match parse(&key)? {
+            Key::Identities(file) => {
+                let recipients = file.to_recipients()
+                    .map_err(|e| Error::config(tr!("age 身份无效: {}", "Invalid age identity: {}", e)))?;
+                age::Encryptor::with_recipients(recipients.iter().map(|recipient| recipient.as_ref() as &dyn age::Recipient))
+                    .map_err(|e| Error::config(tr!("无法加密配置文件: {}", "Failed to encrypt the config file: {}", e)))?
+            }
+            Key::Passphrase(passphrase) => age::Encryptor::with_user_passphrase(passphrase),
+        };
+        let write = || -> std::io::Result<Vec<u8>> {
+            let mut ciphertext = Vec::new();
+            let mut writer = encryptor.wrap_output(ArmoredWriter::wrap_output(&mut ciphertext, Format::AsciiArmor)?)?;
+            writer.write_all(plaintext.as_bytes())?;
+            writer.finish()?.finish()?;
+            Ok(ciphertext)
+        };
+        let ciphertext = write().map_err(|e| Error::config(tr!("无法加密配置文件: {}", "Failed to encrypt the config file: {}", e)))?;
+        Ok(String::from_utf8(ciphertext).expect("armored output is ASCII"))
+    }
+}
+
+/// 解密配置文件内容
+#[cfg(feature = "encrypted-config")]
+pub fn decrypt(content: &[u8]) -> Result<String> {
+    age_impl::decrypt(content)
+}
+
+/// 用当前的密钥加密配置文件内容，输出 ASCII armor 格式
+#[cfg(feature = "encrypted-config")]
+pub fn encrypt(plaintext: &str) -> Result<String> {
+    age_impl::encrypt(plaintext)
+}
+
+#[cfg(not(feature = "encrypted-config"))]
+pub fn decrypt(_content: &[u8]) -> Result<String> {
+    Err(disabled())
+}
+
+#[cfg(not(feature = "encrypted-config"))]
+pub fn encrypt(_plaintext: &str) -> Result<String> {
+    Err(disabled())
+}
+
+#[cfg(not(feature = "encrypted-config"))]
+fn disabled() -> Error {
+    Error::config(tr!(
+        "编译时未启用 encrypted-config 功能，无法读写加密的配置文件",
+        "The encrypted-config feature was not enabled at build time, encrypted config files are unsupported"
+    ))
+}
+
+#[cfg(all(test, feature = "encrypted-config"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identity_key_round_trips() {
+        let identity = age::x25519::Identity::generate();
+        let key = age::secrecy::ExposeSecret::expose_secret(&identity.to_string()).to_string();
+        let dir = std::env::temp_dir().join(format!("cloudflare_ddns_age_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("key.txt"), format!("# created: test\n{}\n", key)).unwrap();
+        // SAFETY: 测试中没有其他线程读写该环境变量
+        unsafe { std::env::set_var(KEY_FILE_ENV, dir.join("key.txt")) };
+        let ciphertext = encrypt("{ \"dns_records\": [] }");
+        let plaintext = ciphertext.as_ref().map(|c| decrypt(c.as_bytes()));
+        unsafe { std::env::remove_var(KEY_FILE_ENV) };
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let plaintext = plaintext.unwrap();
+        let ciphertext = ciphertext.unwrap();
+        assert!(is_encrypted(ciphertext.as_bytes()));
+        assert!(!is_encrypted(b"{ \"dns_records\": [] }"));
+        assert_eq!(plaintext.unwrap(), "{ \"dns_records\": [] }");
+    }
+}
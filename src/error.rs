@@ -0,0 +1,205 @@
+// 库的统一错误类型：按失败阶段区分配置、检测、服务商、通知与调度错误，
+// 并按类别区分网络不可达、认证失败、被限流、资源不存在与响应解析失败，
+// 以便自动化处理时能区分“断网”与“令牌过期”
+use serde::Serialize;
+use std::fmt;
+
+use crate::i18n::tr;
+
+/// 错误类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCategory {
+    /// 无法连接或请求超时
+    Network,
+    /// 凭据无效或权限不足
+    Auth,
+    /// 请求过于频繁被限流
+    RateLimited,
+    /// 区域或记录不存在
+    NotFound,
+    /// 无法解析响应
+    Parse,
+    /// 其他错误
+    Other,
+}
+
+impl ErrorCategory {
+    /// 根据 HTTP 状态码分类
+    pub fn from_status(status: reqwest::StatusCode) -> Self {
+        match status {
+            reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN => ErrorCategory::Auth,
+            reqwest::StatusCode::TOO_MANY_REQUESTS => ErrorCategory::RateLimited,
+            reqwest::StatusCode::NOT_FOUND => ErrorCategory::NotFound,
+            _ => ErrorCategory::Other,
+        }
+    }
+
+    /// 沿错误链查找可识别的错误并分类
+    pub fn of(error: &(dyn std::error::Error + 'static)) -> Self {
+        let mut current = Some(error);
+        while let Some(error) = current {
+            if let Some(error) = error.downcast_ref::<Error>() {
+                return error.category();
+            }
+            if let Some(error) = error.downcast_ref::<reqwest::Error>() {
+                if let Some(status) = error.status() {
+                    return Self::from_status(status);
+                }
+                if error.is_decode() {
+                    return ErrorCategory::Parse;
+                }
+                if error.is_timeout() || error.is_connect() || error.is_request() {
+                    return ErrorCategory::Network;
+                }
+            }
+            if error.is::<serde_json::Error>() {
+                return ErrorCategory::Parse;
+            }
+            current = error.source();
+        }
+        ErrorCategory::Other
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorCategory::Network => "network",
+            ErrorCategory::Auth => "auth",
+            ErrorCategory::RateLimited => "rate_limited",
+            ErrorCategory::NotFound => "not_found",
+            ErrorCategory::Parse => "parse",
+            ErrorCategory::Other => "other",
+        }
+    }
+
+    /// 可读的类别说明，用于通知消息
+    #[cfg_attr(not(feature = "notify-core"), allow(dead_code))]
+    pub fn describe(self) -> String {
+        match self {
+            ErrorCategory::Network => tr!("网络不可达", "Network unreachable"),
+            ErrorCategory::Auth => tr!("认证失败", "Authentication failed"),
+            ErrorCategory::RateLimited => tr!("请求被限流", "Rate limited"),
+            ErrorCategory::NotFound => tr!("资源不存在", "Not found"),
+            ErrorCategory::Parse => tr!("响应解析失败", "Invalid response"),
+            ErrorCategory::Other => tr!("其他错误", "Other error"),
+        }
+    }
+}
+
+impl fmt::Display for ErrorCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// 库的统一错误类型，按失败阶段区分
+#[derive(Debug)]
+pub enum Error {
+    /// 配置文件缺失、无法解析或内容无效
+    Config { message: String },
+    /// 无法检测公网 IP
+    Detection { category: ErrorCategory, message: String },
+    /// DNS 服务商的 API 请求或记录读写失败
+    Provider { category: ErrorCategory, message: String },
+    /// 通知发送失败或通知后端配置无效
+    Notification { message: String },
+    /// 定时任务的单次执行失败，如 panic 或被取消
+    Scheduler { message: String },
+}
+
+/// 以 [`Error`] 为错误类型的 `Result`
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+impl Error {
+    pub fn config(message: impl fmt::Display) -> Self {
+        Error::Config { message: message.to_string() }
+    }
+
+    pub fn detection(category: ErrorCategory, message: impl fmt::Display) -> Self {
+        Error::Detection { category, message: message.to_string() }
+    }
+
+    pub fn provider(category: ErrorCategory, message: impl fmt::Display) -> Self {
+        Error::Provider { category, message: message.to_string() }
+    }
+
+    pub fn notification(message: impl fmt::Display) -> Self {
+        Error::Notification { message: message.to_string() }
+    }
+
+    pub fn scheduler(message: impl fmt::Display) -> Self {
+        Error::Scheduler { message: message.to_string() }
+    }
+
+    /// 错误类别，配置、通知与调度错误为 [`ErrorCategory::Other`]
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            Error::Detection { category, .. } | Error::Provider { category, .. } => *category,
+            Error::Config { .. } | Error::Notification { .. } | Error::Scheduler { .. } => ErrorCategory::Other,
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            Error::Config { message }
+            | Error::Detection { message, .. }
+            | Error::Provider { message, .. }
+            | Error::Notification { message }
+            | Error::Scheduler { message } => message,
+        }
+    }
+
+    /// 为错误补充说明，保留原阶段与类别
+    pub fn map_message(mut self, f: impl FnOnce(&str) -> String) -> Self {
+        match &mut self {
+            Error::Config { message }
+            | Error::Detection { message, .. }
+            | Error::Provider { message, .. }
+            | Error::Notification { message }
+            | Error::Scheduler { message } => *message = f(message),
+        }
+        self
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// 服务商请求中的网络与 HTTP 错误，按错误链分类
+impl From<reqwest::Error> for Error {
+    fn from(error: reqwest::Error) -> Self {
+        Error::provider(ErrorCategory::of(&error), error)
+    }
+}
+
+/// 服务商响应无法解析
+impl From<serde_json::Error> for Error {
+    fn from(error: serde_json::Error) -> Self {
+        Error::provider(ErrorCategory::Parse, error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify() {
+        let error = Error::provider(ErrorCategory::RateLimited, "slow down");
+        let wrapped = error.map_message(|message| format!("update failed: {}", message));
+        assert_eq!(wrapped.to_string(), "update failed: slow down");
+        assert_eq!(ErrorCategory::of(&wrapped), ErrorCategory::RateLimited);
+        assert_eq!(Error::config("missing").category(), ErrorCategory::Other);
+
+        let parse = serde_json::from_str::<u32>("x").unwrap_err();
+        assert_eq!(ErrorCategory::of(&parse), ErrorCategory::Parse);
+
+        let other: Box<dyn std::error::Error + Send + Sync> = "boom".into();
+        assert_eq!(ErrorCategory::of(&*other), ErrorCategory::Other);
+    }
+}
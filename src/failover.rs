@@ -0,0 +1,192 @@
+// 基于健康检查的故障转移：设置了 `failover` 的记录在主地址不可用时指向备用地址
+//
+// 每次执行计划阶段前检查各记录的主地址（TCP 连接或 HTTP 请求），结果按记录累计：连续失败 `fail_after` 次
+// 才切换到备用地址，切换后连续成功 `recover_after` 次才切回，避免主地址时好时坏时记录来回切换。
+// 累计的次数与当前指向保存在状态存储中，单次运行模式的多次运行之间同样生效；只有执行阶段保存，
+// 只生成计划的运行（`--dry-run`、`plan`）不推进计数。
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashSet};
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use crate::config::{Config, DnsRecordConfig, FailoverConfig, HealthCheck, IpVersion};
+use crate::error::{Error, ErrorCategory, Result};
+use crate::i18n::tr;
+use crate::ip_utils::DetectedIps;
+use crate::{logging, state};
+
+/// 一条故障转移记录的健康状态
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FailoverState {
+    /// 记录当前是否指向备用地址
+    pub on_backup: bool,
+    /// 与当前指向相反的连续检查结果次数：指向主地址时为连续失败次数，指向备用地址时为连续成功次数
+    pub streak: u32,
+}
+
+impl FailoverState {
+    /// 记录一次检查结果，达到阈值时切换指向，返回是否切换
+    pub fn observe(&mut self, healthy: bool, failover: &FailoverConfig) -> bool {
+        if healthy != self.on_backup {
+            self.streak = 0;
+            return false;
+        }
+        self.streak += 1;
+        let threshold = match self.on_backup {
+            true => failover.recover_after(),
+            false => failover.fail_after(),
+        };
+        if self.streak < threshold {
+            return false;
+        }
+        self.on_backup = !self.on_backup;
+        self.streak = 0;
+        true
+    }
+}
+
+/// 状态存储中记录的索引，`also_update` 展开的记录与原记录相同，共用原记录的检查结果与指向
+pub fn state_key(record: &DnsRecordConfig) -> String {
+    format!("{}/{}", record.alias_of.as_ref().unwrap_or(&record.name), record.r#type)
+}
+
+/// 检查一次主地址是否可用
+pub async fn probe(failover: &FailoverConfig, address: IpAddr, timeout: Duration) -> Result<()> {
+    let target = SocketAddr::new(address, failover.port);
+    match failover.check {
+        HealthCheck::Tcp => {
+            tokio::time::timeout(timeout, tokio::net::TcpStream::connect(target)).await
+                .map_err(|_| Error::detection(ErrorCategory::Network, tr!("连接 {} 超时", "Connecting to {} timed out", target)))?
+                .map_err(|e| Error::detection(ErrorCategory::Network, tr!("无法连接 {}: {}", "Failed to connect to {}: {}", target, e)))?;
+        }
+        HealthCheck::Http => {
+            let url = format!("http://{}{}", target, failover.path.as_deref().unwrap_or("/"));
+            let client = crate::tls::client_builder().timeout(timeout).build()
+                .map_err(|e| Error::detection(ErrorCategory::Network, e.to_string()))?;
+            let response = client.get(&url).send().await
+                .map_err(|e| Error::detection(ErrorCategory::Network, tr!("请求 {} 失败: {}", "Request to {} failed: {}", url, e)))?;
+            let status = response.status();
+            if !(status.is_success() || status.is_redirection()) {
+                return Err(Error::detection(ErrorCategory::from_status(status), tr!("{} 返回状态码 {}", "{} returned status {}", url, status)));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 各故障转移记录本次应指向的地址与检查后的健康状态，按 [`state_key`] 索引
+#[derive(Debug, Default)]
+pub struct Targets {
+    addresses: BTreeMap<String, String>,
+    /// 本次检查后的健康状态，没有检查任何记录时为空
+    states: Option<BTreeMap<String, FailoverState>>,
+}
+
+impl Targets {
+    /// 保存本次检查后的健康状态，由执行阶段在写入记录时调用
+    pub fn save(&self, config: &Config) {
+        let Some(states) = &self.states else { return };
+        if let Err(e) = state::open(config).save_failover(states) {
+            warn!(error = %e, "{}", tr!("无法保存故障转移状态", "Failed to save failover state"));
+        }
+    }
+
+    /// 记录计划时使用的检测结果：故障转移记录的地址替换为本次选定的地址
+    pub fn ips_for<'a>(&self, record: &DnsRecordConfig, ips: &'a DetectedIps) -> Cow<'a, DetectedIps> {
+        let (Some(address), Ok(version)) = (self.addresses.get(&state_key(record)), record.get_ip_version()) else {
+            return Cow::Borrowed(ips);
+        };
+        let mut ips = ips.clone();
+        let target = match &record.uplink {
+            Some(uplink) => ips.uplinks.entry(uplink.clone()).or_default(),
+            None => &mut ips,
+        };
+        match version {
+            IpVersion::V4 => target.v4 = Some(address.clone()),
+            IpVersion::V6 => target.v6 = Some(address.clone()),
+        }
+        Cow::Owned(ips)
+    }
+}
+
+/// 检查配置中各故障转移记录的主地址，返回各记录本次应指向的地址与更新后的健康状态
+///
+/// `only` 不为空时只检查其中的记录（选中 `also_update` 展开的记录时检查其原记录）；
+/// 主地址未配置且未检测到时不检查，记录保持原来的指向。
+/// 健康状态不在这里保存，见 [`Targets::save`]。
+pub async fn check(config: &Config, ips: &DetectedIps, only: Option<&HashSet<(String, String)>>) -> Targets {
+    let records: Vec<_> = config.records()
+        .filter(|record| record.alias_of.is_none())
+        .filter(|record| config.records().any(|selected| state_key(selected) == state_key(record) && config.is_selected(selected, only)))
+        .filter_map(|record| Some((record, record.failover.as_ref()?)))
+        .collect();
+    let mut targets = Targets::default();
+    if records.is_empty() {
+        return targets;
+    }
+    let store = state::open(config);
+    let saved = store.failover().unwrap_or_else(|e| {
+        warn!(error = %e, "{}", tr!("无法读取故障转移状态，从主地址开始", "Failed to read failover state, starting from the primary address"));
+        BTreeMap::new()
+    });
+    // 不再配置故障转移的记录的状态被丢弃，本次未检查的记录保留原状态
+    let mut states: BTreeMap<_, _> = saved.into_iter()
+        .filter(|(key, _)| config.records().any(|record| record.failover.is_some() && state_key(record) == *key))
+        .collect();
+    for (record, failover) in records {
+        let key = state_key(record);
+        let state = states.entry(key.clone()).or_default();
+        let primary = failover.primary.or_else(|| ips.for_record(record)?.parse().ok());
+        match primary {
+            Some(primary) => {
+                let timeout = failover.timeout.unwrap_or(config.schedule.timeout());
+                let result = probe(failover, primary, timeout).await;
+                if let Err(e) = &result {
+                    debug!(record = %record.name, primary = %primary, error = %e, "{}", tr!("主地址健康检查失败", "Primary address health check failed"));
+                }
+                if state.observe(result.is_ok(), failover) {
+                    match state.on_backup {
+                        true => warn!(target: logging::CHANGE_TARGET, record = %record.name, primary = %primary, backup = %failover.backup, "{}", tr!("主地址不可用，切换到备用地址", "Primary address is down, failing over to the backup")),
+                        false => warn!(target: logging::CHANGE_TARGET, record = %record.name, primary = %primary, backup = %failover.backup, "{}", tr!("主地址已恢复，切回主地址", "Primary address recovered, switching back")),
+                    }
+                }
+            }
+            None => warn!(record = %record.name, "{}", tr!("未检测到主地址，跳过健康检查", "No primary address detected, skipping the health check")),
+        }
+        let address = if state.on_backup { Some(failover.backup) } else { primary };
+        if let Some(address) = address {
+            targets.addresses.insert(key, address.to_string());
+        }
+    }
+    targets.states = Some(states);
+    targets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn failover_is_damped_in_both_directions() {
+        let failover: FailoverConfig = serde_json::from_str(r#"{ "backup": "192.0.2.2", "port": 80, "fail_after": 2, "recover_after": 3 }"#).unwrap();
+        let mut state = FailoverState::default();
+
+        // 单次失败不切换，成功后重新计数
+        assert!(!state.observe(false, &failover));
+        assert!(!state.observe(true, &failover));
+        assert!(!state.observe(false, &failover));
+        assert!(state.observe(false, &failover));
+        assert!(state.on_backup);
+
+        assert!(!state.observe(true, &failover));
+        assert!(!state.observe(true, &failover));
+        assert!(!state.observe(false, &failover));
+        assert!(!state.observe(true, &failover));
+        assert!(!state.observe(true, &failover));
+        assert!(state.observe(true, &failover));
+        assert_eq!(state, FailoverState::default());
+    }
+}
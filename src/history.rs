@@ -0,0 +1,183 @@
+// IP 变化历史：每次记录更新或新建时向 JSONL 文件追加一行，供 `history` 子命令查询
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::fs::OpenOptions;
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::time::Duration;
+
+/// 一次 IP 变化
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// RFC 3339 时间
+    pub timestamp: String,
+    pub record: String,
+    pub zone: String,
+    #[serde(rename = "type")]
+    pub record_type: String,
+    /// 新建记录时为空
+    pub old_ip: Option<String>,
+    pub new_ip: String,
+}
+
+impl HistoryEntry {
+    pub fn new(record: &str, zone: &str, record_type: &str, old_ip: Option<&str>, new_ip: &str) -> Self {
+        Self {
+            timestamp: Local::now().to_rfc3339(),
+            record: record.to_string(),
+            zone: zone.to_string(),
+            record_type: record_type.to_string(),
+            old_ip: old_ip.map(str::to_string),
+            new_ip: new_ip.to_string(),
+        }
+    }
+
+    pub(crate) fn time(&self) -> Option<DateTime<Local>> {
+        DateTime::parse_from_rfc3339(&self.timestamp).ok().map(|t| t.with_timezone(&Local))
+    }
+}
+
+/// 向历史文件追加一条记录，文件或所在目录不存在时创建
+///
+/// 只追加一行并刷到磁盘，不重写已有内容；多个进程同时追加时各自的行都会保留。
+/// 断电时最多留下写了一半的最后一行，[`read`] 会跳过它，下一次追加从新的一行开始。
+pub fn append(path: &Path, entry: &HistoryEntry) -> io::Result<()> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut file = OpenOptions::new().read(true).append(true).create(true).open(path)?;
+    let mut line = Vec::new();
+    // 上一次追加中断可能留下没有换行的最后一行
+    if file.metadata()?.len() > 0 {
+        let mut last = [0u8];
+        file.seek(SeekFrom::End(-1))?;
+        file.read_exact(&mut last)?;
+        if last[0] != b'\n' {
+            line.push(b'\n');
+        }
+    }
+    line.extend_from_slice(serde_json::to_string(entry).map_err(io::Error::other)?.as_bytes());
+    line.push(b'\n');
+    file.write_all(&line)?;
+    file.sync_data()
+}
+
+/// 读取历史文件中的全部记录，忽略无法解析的行；文件不存在时返回空列表
+pub fn read(path: &Path) -> io::Result<Vec<HistoryEntry>> {
+    let file = match std::fs::File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut entries = Vec::new();
+    for line in BufReader::new(file).lines() {
+        if let Ok(entry) = serde_json::from_str(&line?) {
+            entries.push(entry);
+        }
+    }
+    Ok(entries)
+}
+
+/// `history` 子命令的筛选条件
+#[derive(Debug, Default)]
+pub struct HistoryFilter {
+    /// 记录名称的通配符模式，为空时不筛选
+    pub records: Vec<String>,
+    pub since: Option<Duration>,
+    pub limit: Option<usize>,
+}
+
+impl HistoryFilter {
+    /// 按记录名与时间筛选，`limit` 只保留最近的若干条
+    pub fn apply(&self, entries: Vec<HistoryEntry>) -> Vec<HistoryEntry> {
+        let cutoff = self.cutoff();
+        let mut entries: Vec<_> = entries.into_iter()
+            .filter(|entry| self.records.is_empty() || crate::overrides::matches_any(&self.records, &entry.record))
+            .filter(|entry| cutoff.is_none_or(|cutoff| entry.time().is_some_and(|time| time >= cutoff)))
+            .collect();
+        if let Some(limit) = self.limit {
+            let skip = entries.len().saturating_sub(limit);
+            entries.drain(..skip);
+        }
+        entries
+    }
+
+    /// `since` 对应的最早时间
+    pub fn cutoff(&self) -> Option<DateTime<Local>> {
+        self.since
+            .and_then(|since| chrono::Duration::from_std(since).ok())
+            .map(|since| Local::now() - since)
+    }
+}
+
+/// 每条记录两次变化之间的平均间隔，少于两次变化的记录不计入
+pub fn average_intervals(entries: &[HistoryEntry]) -> Vec<(String, usize, Option<Duration>)> {
+    let mut records: Vec<String> = entries.iter().map(|entry| entry.record.clone()).collect();
+    records.sort();
+    records.dedup();
+
+    records.into_iter().map(|record| {
+        let times: Vec<_> = entries.iter()
+            .filter(|entry| entry.record == record)
+            .filter_map(HistoryEntry::time)
+            .collect();
+        let average = match (times.first(), times.last()) {
+            (Some(first), Some(last)) if times.len() > 1 => (*last - *first)
+                .to_std()
+                .ok()
+                .map(|total| total / (times.len() as u32 - 1)),
+            _ => None,
+        };
+        let count = entries.iter().filter(|entry| entry.record == record).count();
+        (record, count, average)
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(record: &str, timestamp: &str) -> HistoryEntry {
+        HistoryEntry {
+            timestamp: timestamp.to_string(),
+            record: record.to_string(),
+            zone: "example.com".to_string(),
+            record_type: "A".to_string(),
+            old_ip: None,
+            new_ip: "1.2.3.4".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_filter_and_intervals() {
+        let entries = vec![
+            entry("home.example.com", "2025-01-01T00:00:00+00:00"),
+            entry("nas.example.com", "2025-01-01T12:00:00+00:00"),
+            entry("home.example.com", "2025-01-02T00:00:00+00:00"),
+            entry("home.example.com", "2025-01-04T00:00:00+00:00"),
+        ];
+
+        let filter = HistoryFilter { records: vec!["home.example.com".to_string()], limit: Some(2), ..Default::default() };
+        let filtered = filter.apply(entries.clone());
+        assert_eq!(filtered.len(), 2);
+        assert_eq!(filtered[0].timestamp, "2025-01-02T00:00:00+00:00");
+
+        let intervals = average_intervals(&entries);
+        assert_eq!(intervals[0], ("home.example.com".to_string(), 3, Some(Duration::from_secs(36 * 3600))));
+        assert_eq!(intervals[1], ("nas.example.com".to_string(), 1, None));
+    }
+
+    #[test]
+    fn append_starts_a_new_line_after_a_truncated_one() {
+        let dir = std::env::temp_dir().join(format!("cloudflare_ddns_history_{}", std::process::id()));
+        let path = dir.join("history.jsonl");
+        append(&path, &entry("home.example.com", "2025-01-01T00:00:00+00:00")).unwrap();
+        // 模拟断电留下的半行
+        OpenOptions::new().append(true).open(&path).unwrap().write_all(b"{\"timestamp\":").unwrap();
+        append(&path, &entry("nas.example.com", "2025-01-02T00:00:00+00:00")).unwrap();
+
+        let records: Vec<_> = read(&path).unwrap().into_iter().map(|entry| entry.record).collect();
+        assert_eq!(records, ["home.example.com", "nas.example.com"]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
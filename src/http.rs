@@ -0,0 +1,499 @@
+// 定时模式内置的状态 HTTP 服务，供容器编排与负载均衡探测
+//
+// - `GET /healthz`: 最近一次执行成功且距今不超过两个间隔时返回 200，否则返回 503
+// - `GET /status`: 与 SIGUSR2 状态转储相同的 JSON（最近一次执行、各记录状态等）
+// - `GET /metrics`: Prometheus 文本格式的运行指标
+// - `POST /update`: 立即核对记录，需要 `http.token`
+// - `GET /`: 网页仪表盘，数据来自 `GET /api/dashboard`
+// - `GET /nic/update`: dyndns2 协议的接收端，需要 `http.dyndns`
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::Router;
+use axum::body::Bytes;
+use axum::extract::{ConnectInfo, Query, State};
+use axum::http::{HeaderMap, StatusCode, header};
+use axum::response::{Html, IntoResponse, Json, Response};
+use axum::routing::{get, post};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+use tracing::{error, info};
+
+use cloudflare_ddns::Error;
+use cloudflare_ddns::config::{DyndnsReceiverConfig, HttpConfig, IpVersion};
+use cloudflare_ddns::i18n::tr;
+use base64::Engine;
+use cloudflare_ddns::summary::{FailureKind, RecordAction, RunError, RunSummary};
+use cloudflare_ddns::update::{load_config, log_heartbeat, reconcile_tracked};
+use cloudflare_ddns::{i18n, ip_utils, notify, overrides, state};
+
+use crate::DaemonState;
+
+#[derive(Clone)]
+struct AppState {
+    daemon: Arc<Mutex<DaemonState>>,
+    config_path: String,
+    /// `POST /update` 所需的令牌，为空时拒绝远程更新
+    token: Option<String>,
+    /// `/nic/update` 的认证，为空时不提供该接口
+    dyndns: Option<DyndnsReceiverConfig>,
+    /// 最近一次成功执行距今超过该时间时 /healthz 返回 503
+    max_age: Duration,
+}
+
+/// `POST /update` 的请求体，所有字段都可以省略
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct UpdateRequest {
+    /// 只处理名称匹配任一模式的记录，支持 `*` 与 `?` 通配符
+    records: Vec<String>,
+    /// 代替检测结果使用的地址
+    ip: Option<Ipv4Addr>,
+    ipv6: Option<Ipv6Addr>,
+    /// IP 未变化时也更新记录
+    force: bool,
+    /// 只处理请求中给出了 IP 的版本的记录，其余版本不检测
+    #[serde(skip)]
+    given_only: bool,
+}
+
+/// 在后台启动状态 HTTP 服务，监听失败时只记录错误，不影响定时任务
+pub async fn spawn(config: &HttpConfig, config_path: &str, daemon: Arc<Mutex<DaemonState>>, max_age: Duration) {
+    let listener = match tokio::net::TcpListener::bind(&config.listen).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            error!(listen = %config.listen, error = %e, "{}", tr!("无法启动状态 HTTP 服务", "Failed to start the status HTTP server"));
+            return;
+        }
+    };
+    info!(listen = %config.listen, "{}", tr!("状态 HTTP 服务已启动", "Status HTTP server started"));
+    let app = router(AppState {
+        daemon,
+        config_path: config_path.to_string(),
+        token: config.token.clone(),
+        dyndns: config.dyndns.clone(),
+        max_age,
+    });
+    tokio::spawn(async move {
+        if let Err(e) = axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>()).await {
+            error!(error = %e, "{}", tr!("状态 HTTP 服务已停止", "Status HTTP server stopped"));
+        }
+    });
+}
+
+fn router(state: AppState) -> Router {
+    Router::new()
+        .route("/healthz", get(healthz))
+        .route("/status", get(status))
+        .route("/metrics", get(metrics))
+        .route("/update", post(update))
+        .route("/", get(dashboard))
+        .route("/api/dashboard", get(dashboard_data))
+        .route("/nic/update", get(nic_update))
+        .with_state(state)
+}
+
+async fn healthz(State(app): State<AppState>) -> impl IntoResponse {
+    let daemon = app.daemon.lock().await;
+    let result = match &daemon.run_stats {
+        Some(stats) => stats.check(app.max_age, chrono::Local::now()).map(|_| ()),
+        None => Err(tr!("尚无执行记录", "No run recorded yet")),
+    };
+    match result {
+        Ok(()) => (StatusCode::OK, "ok\n".to_string()),
+        Err(reason) => (StatusCode::SERVICE_UNAVAILABLE, reason + "\n"),
+    }
+}
+
+async fn status(State(app): State<AppState>) -> Json<serde_json::Value> {
+    Json(app.daemon.lock().await.dump())
+}
+
+async fn metrics(State(app): State<AppState>) -> impl IntoResponse {
+    let body = app.daemon.lock().await.metrics.render();
+    ([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], body)
+}
+
+async fn update(State(app): State<AppState>, headers: HeaderMap, body: Bytes) -> Response {
+    let Some(token) = &app.token else {
+        return (StatusCode::FORBIDDEN, tr!("未配置 http.token，远程更新已禁用\n", "http.token is not set, remote updates are disabled\n")).into_response();
+    };
+    let authorized = headers.get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|given| constant_time_eq(given.as_bytes(), token.as_bytes()));
+    if !authorized {
+        return (StatusCode::UNAUTHORIZED, tr!("令牌无效\n", "Invalid token\n")).into_response();
+    }
+    let request: UpdateRequest = match body.is_empty() {
+        true => UpdateRequest::default(),
+        false => match serde_json::from_slice(&body) {
+            Ok(request) => request,
+            Err(e) => return (StatusCode::BAD_REQUEST, tr!("请求体无效: {}\n", "Invalid request body: {}\n", e)).into_response(),
+        },
+    };
+
+    info!(records = ?request.records, force = request.force, "{}", tr!("收到远程更新请求", "Remote update requested"));
+    match run_update(&app, &request).await {
+        Ok(summary) => {
+            let status = if summary.success { StatusCode::OK } else { StatusCode::BAD_GATEWAY };
+            (status, Json(summary)).into_response()
+        }
+        Err(e) => {
+            let status = match e.kind {
+                FailureKind::Config => StatusCode::BAD_REQUEST,
+                _ => StatusCode::BAD_GATEWAY,
+            };
+            (status, Json(RunSummary::error(&e))).into_response()
+        }
+    }
+}
+
+/// 按请求核对记录：请求中给出的 IP 不再检测，单条记录失败不影响其余记录
+async fn run_update(app: &AppState, request: &UpdateRequest) -> Result<RunSummary, RunError> {
+    let mut config = load_config(&app.config_path)?;
+    let given = |version: IpVersion| match version {
+        IpVersion::V4 => request.ip.map(|ip| ip.to_string()),
+        IpVersion::V6 => request.ipv6.map(|ip| ip.to_string()),
+    };
+    if !request.records.is_empty() {
+        overrides::retain(&mut config, |record| overrides::matches_any(&request.records, &record.name));
+        if config.records().next().is_none() {
+            return Err(RunError::new(FailureKind::Config, tr!("配置中没有与 {} 匹配的记录", "No records in the config match {}", request.records.join(", "))));
+        }
+    }
+    if request.given_only {
+        overrides::retain(&mut config, |record| record.get_ip_version().is_ok_and(|version| given(version).is_some()));
+        if config.records().next().is_none() {
+            return Err(RunError::new(FailureKind::Config, tr!("没有与给出的 IP 版本相同的记录", "No records match the IP version given")));
+        }
+    }
+
+    let versions = config.ip_versions()
+        .map_err(|e| Error::config(tr!("IP 版本无效: {}", "Invalid IP version: {}", e)))?;
+    let detect: Vec<IpVersion> = versions.iter().copied().filter(|version| given(*version).is_none()).collect();
+    let (mut ips, mut times) = ip_utils::detect_ips(&detect, &config.ip_detection, config.schedule.timeout()).await?;
+    for version in versions {
+        if let Some(ip) = given(version) {
+            match version {
+                IpVersion::V4 => ips.v4 = Some(ip),
+                IpVersion::V6 => ips.v6 = Some(ip),
+            }
+        }
+    }
+    // 请求中给出的 IP 只用于默认检测的记录，指定了上行线路的记录仍按线路检测
+    ip_utils::detect_uplinks(&config, &mut ips, &mut times).await;
+
+    // 与定时任务及使用同一份配置的其他进程串行执行，避免同时写入同一条记录
+    let _lock = crate::lock::acquire(&app.config_path).await;
+    let mut state = app.daemon.lock().await;
+    let notifiers = notify::Notifiers::from_config(&config.notifications, config.schedule.timeout())
+        .with_queue(std::mem::take(&mut state.notification_queue));
+    let result = reconcile_tracked(&config, &notifiers, &ips, &times, request.force, None, &mut state.failure_counts).await;
+    if let Ok(results) = &result {
+        state.track_retries(&config, &notifiers, results).await;
+    }
+    state.notification_queue = notifiers.into_queue();
+    let results = result?;
+    log_heartbeat(&ips, &results);
+    state.record_results(&results).await;
+    Ok(RunSummary::new(&config, &ips, &results))
+}
+
+async fn dashboard() -> Html<String> {
+    let lang = match i18n::lang() {
+        i18n::Lang::Zh => "zh",
+        i18n::Lang::En => "en",
+    };
+    Html(include_str!("http/dashboard.html").replace("{{lang}}", lang))
+}
+
+/// 仪表盘中的一条记录
+#[derive(Debug, Serialize)]
+struct DashboardRecord {
+    name: String,
+    #[serde(rename = "type")]
+    record_type: String,
+    zone: String,
+    /// 当前检测到的 IP
+    detected: Option<String>,
+    /// DNS 中的值：最近一次处理时确认或写入的值，没有时为本程序最近一次写入的值
+    dns: Option<String>,
+    /// 本程序最近一次写入记录的时间
+    updated_at: Option<String>,
+    /// 最近一次处理的结果，尚未处理时为空
+    status: Option<&'static str>,
+    error: Option<String>,
+}
+
+/// 仪表盘显示的最近历史条数
+const DASHBOARD_HISTORY: usize = 20;
+
+async fn dashboard_data(State(app): State<AppState>) -> Response {
+    let config = match load_config(&app.config_path) {
+        Ok(config) => config,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, format!("{}\n", e)).into_response(),
+    };
+    let store = state::open(&config);
+    let published = store.published().unwrap_or_default();
+    let mut history = store.history().unwrap_or_default();
+    history.reverse();
+    history.truncate(DASHBOARD_HISTORY);
+
+    let daemon = app.daemon.lock().await;
+    let ips = daemon.last_ips.clone().unwrap_or_default();
+    let records: Vec<_> = config.zones().iter()
+        .flat_map(|zone| zone.dns_records.iter().map(move |record| (zone, record)))
+        .map(|(zone, record)| {
+            let published = published.iter().find(|p| p.name == record.name && p.record_type == record.r#type);
+            let last = daemon.records.get(&crate::record_key(&record.name, &record.r#type));
+            let confirmed = last.filter(|last| last.status != "failed").and_then(|last| last.ip.clone());
+            DashboardRecord {
+                name: record.name.clone(),
+                record_type: record.r#type.clone(),
+                zone: zone.zone_name.clone(),
+                detected: ips.for_record(record),
+                dns: confirmed.or_else(|| published.map(|p| p.content.clone())),
+                updated_at: published.map(|p| p.updated_at.clone()),
+                status: last.map(|last| last.status),
+                error: last.and_then(|last| last.error.clone()),
+            }
+        })
+        .collect();
+    Json(serde_json::json!({ "ips": ips, "records": records, "history": history })).into_response()
+}
+
+/// dyndns2 协议 `/nic/update` 的查询参数
+#[derive(Debug, Deserialize)]
+struct NicUpdate {
+    /// 逗号分隔的记录名称
+    #[serde(default)]
+    hostname: String,
+    /// 逗号分隔的 IPv4 / IPv6 地址，省略时使用请求方的地址
+    myip: Option<String>,
+}
+
+/// dyndns2 协议的接收端，按协议以纯文本逐行返回每个名称的结果（`good`、`nochg`、`nohost`、`911` 等），
+/// 因冷却时间未更新的名称返回 `911`
+async fn nic_update(
+    State(app): State<AppState>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Query(query): Query<NicUpdate>,
+) -> Response {
+    let Some(dyndns) = &app.dyndns else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    if !basic_auth_matches(&headers, dyndns) {
+        return (StatusCode::UNAUTHORIZED, [(header::WWW_AUTHENTICATE, "Basic realm=\"cloudflare_ddns\"")], "badauth").into_response();
+    }
+    let hostnames: Vec<&str> = query.hostname.split(',').map(str::trim).filter(|name| !name.is_empty()).collect();
+    if hostnames.is_empty() || hostnames.iter().any(|name| name.contains(['*', '?'])) {
+        return "notfqdn".into_response();
+    }
+
+    let mut request = UpdateRequest { given_only: true, ..UpdateRequest::default() };
+    let myip = query.myip.unwrap_or_else(|| peer.ip().to_canonical().to_string());
+    for ip in myip.split(',').map(str::trim).filter(|ip| !ip.is_empty()) {
+        match ip.parse::<IpAddr>() {
+            Ok(IpAddr::V4(ip)) => request.ip = Some(ip),
+            Ok(IpAddr::V6(ip)) => request.ipv6 = Some(ip),
+            Err(_) => return (StatusCode::BAD_REQUEST, "911").into_response(),
+        }
+    }
+
+    let config = match load_config(&app.config_path) {
+        Ok(config) => config,
+        Err(e) => {
+            error!(error = %e, "{}", tr!("dyndns2 更新失败", "dyndns2 update failed"));
+            return "911".into_response();
+        }
+    };
+    let known = |name: &str| config.records().any(|record| record.name.eq_ignore_ascii_case(name));
+    request.records = hostnames.iter().filter(|name| known(name)).map(|name| name.to_string()).collect();
+    info!(hostnames = ?hostnames, ip = %myip, "{}", tr!("收到 dyndns2 更新请求", "dyndns2 update received"));
+
+    let summary = match request.records.is_empty() {
+        true => None,
+        false => match run_update(&app, &request).await {
+            Ok(summary) => Some(summary),
+            Err(e) if e.kind == FailureKind::Config => None,
+            Err(e) => {
+                error!(error = %e, "{}", tr!("dyndns2 更新失败", "dyndns2 update failed"));
+                return "911".into_response();
+            }
+        },
+    };
+    let lines: Vec<String> = hostnames.iter().map(|name| {
+        let records: Vec<_> = summary.iter()
+            .flat_map(|summary| &summary.records)
+            .filter(|record| record.name.eq_ignore_ascii_case(name) && record.action != RecordAction::Skipped)
+            .collect();
+        let ips: Vec<&str> = records.iter().filter_map(|record| record.detected_ip.as_deref()).collect();
+        if records.is_empty() {
+            "nohost".to_string()
+        } else if records.iter().any(|record| record.action == RecordAction::Failed) {
+            "911".to_string()
+        } else if records.iter().any(|record| matches!(record.action, RecordAction::Updated | RecordAction::Created)) {
+            format!("good {}", ips.join(","))
+        } else if records.iter().any(|record| record.action == RecordAction::Held) {
+            // dyndns2 没有“暂缓”的返回码，911 让客户端稍后重试，而不是以为地址已生效
+            "911".to_string()
+        } else {
+            format!("nochg {}", ips.join(","))
+        }
+    }).collect();
+    lines.join("\n").into_response()
+}
+
+/// 请求的 HTTP Basic 认证是否与配置一致
+fn basic_auth_matches(headers: &HeaderMap, dyndns: &DyndnsReceiverConfig) -> bool {
+    let expected = format!("{}:{}", dyndns.username, dyndns.password);
+    headers.get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Basic "))
+        .and_then(|encoded| base64::engine::general_purpose::STANDARD.decode(encoded.trim()).ok())
+        .is_some_and(|given| constant_time_eq(&given, expected.as_bytes()))
+}
+
+/// 比较令牌，耗时与内容无关
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cloudflare_ddns::state::RunStats;
+
+    /// 在随机端口上启动服务，返回其地址
+    async fn serve(daemon: Arc<Mutex<DaemonState>>, config_path: &str, token: Option<&str>) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let state = AppState {
+            daemon,
+            config_path: config_path.to_string(),
+            token: token.map(str::to_string),
+            dyndns: Some(DyndnsReceiverConfig { username: "router".to_string(), password: "secret".to_string() }),
+            max_age: Duration::from_secs(60),
+        };
+        let app = router(state).into_make_service_with_connect_info::<SocketAddr>();
+        tokio::spawn(axum::serve(listener, app).into_future());
+        url
+    }
+
+    /// 在临时目录中写入只有两条 A 记录的配置，返回目录与配置文件路径
+    fn write_config(name: &str) -> (std::path::PathBuf, String) {
+        let dir = std::env::temp_dir().join(format!("cloudflare_ddns_{}_{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let config_path = dir.join("config.json");
+        let config = serde_json::json!({
+            "cloudflare": { "auth_type": "token", "api_token": "test-token", "zone_name": "example.com" },
+            "dns_records": [
+                { "name": "home.example.com", "type": "A", "ttl": 1, "proxied": false, "ip_version": "v4" },
+                { "name": "nas.example.com", "type": "A", "ttl": 1, "proxied": false, "ip_version": "v4" },
+            ],
+        });
+        std::fs::write(&config_path, config.to_string()).unwrap();
+        let config_path = config_path.to_str().unwrap().to_string();
+        (dir, config_path)
+    }
+
+    #[tokio::test]
+    async fn healthz_reflects_last_run() {
+        let daemon = Arc::new(Mutex::new(DaemonState::default()));
+        let url = serve(daemon.clone(), "missing.json", None).await;
+        let client = reqwest::Client::new();
+
+        let response = client.get(format!("{}/healthz", url)).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        let now = chrono::Local::now();
+        daemon.lock().await.run_stats = Some(RunStats::new(now, Some(now), None));
+        let response = client.get(format!("{}/healthz", url)).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let status: serde_json::Value = client.get(format!("{}/status", url)).send().await.unwrap().json().await.unwrap();
+        assert!(status["records"].is_object());
+        let metrics = client.get(format!("{}/metrics", url)).send().await.unwrap().text().await.unwrap();
+        assert!(metrics.contains("cloudflare_ddns_runs_total"));
+    }
+
+    #[tokio::test]
+    async fn update_requires_token() {
+        let client = reqwest::Client::new();
+        let url = serve(Arc::default(), "missing.json", None).await;
+        let response = client.post(format!("{}/update", url)).bearer_auth("secret").send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let url = serve(Arc::default(), "missing.json", Some("secret")).await;
+        let response = client.post(format!("{}/update", url)).bearer_auth("wrong").send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = client.post(format!("{}/update", url)).bearer_auth("secret").body("{\"unknown\": 1}").send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        // 配置文件不存在
+        let response = client.post(format!("{}/update", url)).bearer_auth("secret").send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn dashboard_compares_detected_and_dns_values() {
+        let (dir, config_path) = write_config("dashboard");
+
+        let mut daemon = DaemonState {
+            last_ips: Some(ip_utils::DetectedIps { v4: Some("203.0.113.10".to_string()), ..Default::default() }),
+            ..DaemonState::default()
+        };
+        daemon.records.insert(crate::record_key("home.example.com", "A"), crate::RecordState {
+            ip: Some("203.0.113.10".to_string()),
+            status: "unchanged",
+            error: None,
+            at: chrono::Local::now().to_rfc3339(),
+        });
+        // 同名的 AAAA 记录的结果不覆盖 A 记录的结果
+        daemon.records.insert(crate::record_key("home.example.com", "AAAA"), crate::RecordState {
+            ip: None,
+            status: "failed",
+            error: Some("timeout".to_string()),
+            at: chrono::Local::now().to_rfc3339(),
+        });
+        let url = serve(Arc::new(Mutex::new(daemon)), &config_path, None).await;
+
+        let client = reqwest::Client::new();
+        let page = client.get(&url).send().await.unwrap().text().await.unwrap();
+        let data: serde_json::Value = client.get(format!("{}/api/dashboard", url)).send().await.unwrap().json().await.unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert!(page.contains("api/dashboard"));
+        assert_eq!(data["ips"]["v4"], "203.0.113.10");
+        assert_eq!(data["records"][0]["dns"], "203.0.113.10");
+        assert_eq!(data["records"][0]["status"], "unchanged");
+        assert_eq!(data["records"][1]["detected"], "203.0.113.10");
+        assert!(data["records"][1]["dns"].is_null());
+        assert_eq!(data["history"], serde_json::json!([]));
+    }
+
+    #[tokio::test]
+    async fn nic_update_checks_auth_and_hostnames() {
+        let (dir, config_path) = write_config("nic_update");
+        let url = serve(Arc::default(), &config_path, None).await;
+        let client = reqwest::Client::new();
+        let nic_update = |query: &str, password: &str| client
+            .get(format!("{}/nic/update?{}", url, query))
+            .basic_auth("router", Some(password))
+            .send();
+
+        let response = nic_update("hostname=home.example.com&myip=203.0.113.10", "wrong").await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(response.text().await.unwrap(), "badauth");
+        let response = nic_update("hostname=", "secret").await.unwrap();
+        assert_eq!(response.text().await.unwrap(), "notfqdn");
+        let response = nic_update("hostname=other.example.com&myip=203.0.113.10", "secret").await.unwrap();
+        assert_eq!(response.text().await.unwrap(), "nohost");
+        // 只给出 IPv6 时没有可更新的 A 记录
+        let response = nic_update("hostname=home.example.com&myip=2001:db8::1", "secret").await.unwrap();
+        assert_eq!(response.text().await.unwrap(), "nohost");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,54 @@
+// 面向用户的输出语言：中文或英文，由 --lang 指定或根据 LC_ALL / LC_MESSAGES / LANG 自动检测
+use std::sync::OnceLock;
+
+/// 输出语言
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum Lang {
+    #[default]
+    Zh,
+    En,
+}
+
+static LANG: OnceLock<Lang> = OnceLock::new();
+
+/// 设置输出语言，未指定时根据环境变量检测
+pub fn init(lang: Option<Lang>) {
+    let _ = LANG.set(lang.unwrap_or_else(detect));
+}
+
+/// 当前输出语言，未初始化时为中文
+pub fn lang() -> Lang {
+    LANG.get().copied().unwrap_or_default()
+}
+
+/// 按 POSIX 的优先级读取 locale，非中文的 locale 使用英文；未设置或为 C/POSIX 时保持中文
+fn detect() -> Lang {
+    let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.is_empty());
+    match locale.as_deref() {
+        None | Some("C") | Some("POSIX") => Lang::Zh,
+        Some(locale) if locale.to_ascii_lowercase().starts_with("zh") => Lang::Zh,
+        Some(locale) if locale.starts_with("C.") => Lang::Zh,
+        Some(_) => Lang::En,
+    }
+}
+
+/// 按当前语言选择中文或英文的格式化字符串，参数两种语言共用
+///
+/// ```ignore
+/// tr!("记录 {} 处理失败", "Failed to process record {}", name)
+/// ```
+#[macro_export]
+#[doc(hidden)]
+macro_rules! tr {
+    ($zh:literal, $en:literal $(, $arg:expr)* $(,)?) => {
+        match $crate::i18n::lang() {
+            $crate::i18n::Lang::Zh => format!($zh $(, $arg)*),
+            $crate::i18n::Lang::En => format!($en $(, $arg)*),
+        }
+    };
+}
+#[doc(inline)]
+pub use tr;
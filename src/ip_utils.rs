@@ -1,46 +1,257 @@
-use std::time::Duration;
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
 
-/// 获取当前公网 IPv4 地址
-pub async fn get_external_ipv4() -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-    let client = reqwest::Client::builder()
-        .timeout(Duration::from_secs(10))
-        .build()?;
-    
-    let response = client.get("https://4.ipw.cn").send().await?;
-    
-    if response.status().is_success() {
-        let ip = response.text().await?.trim().to_string();
-        Ok(ip)
-    } else {
-        Err(format!("获取 IPv4 地址失败: {}", response.status()).into())
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::config::{Config, DnsRecordConfig, IpDetectionConfig, IpVersion, SetMemberConfig, UplinkConfig};
+use crate::error::{Error, ErrorCategory, Result};
+use crate::i18n::tr;
+use crate::overrides;
+
+/// 一次检测得到的公网 IP 地址，未检测的版本为 None
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DetectedIps {
+    pub v4: Option<String>,
+    pub v6: Option<String>,
+    /// 各上行线路检测到的地址，按线路名称索引，检测失败的线路不在其中
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub uplinks: BTreeMap<String, DetectedIps>,
+}
+
+impl DetectedIps {
+    pub fn get(&self, version: IpVersion) -> Option<&str> {
+        match version {
+            IpVersion::V4 => self.v4.as_deref(),
+            IpVersion::V6 => self.v6.as_deref(),
+        }
+    }
+
+    /// 记录应指向的地址：指定了上行线路的记录取该线路检测到的地址
+    ///
+    /// 轮询记录集为各成员的地址，以 `, ` 连接，没有成员得到地址时为 None。
+    pub fn for_record(&self, record: &DnsRecordConfig) -> Option<String> {
+        let version = record.get_ip_version().ok()?;
+        if !record.members.is_empty() {
+            let addresses: Vec<_> = record.members.iter().filter_map(|member| self.for_member(version, member)).collect();
+            return (!addresses.is_empty()).then(|| addresses.join(", "));
+        }
+        self.for_uplink(record.uplink.as_deref(), version).map(str::to_string)
+    }
+
+    /// 记录集成员应指向的地址：固定地址，或所用检测得到的地址
+    pub fn for_member(&self, version: IpVersion, member: &SetMemberConfig) -> Option<String> {
+        match member.ip {
+            Some(ip) => Some(ip.to_string()),
+            None => self.for_uplink(member.uplink.as_deref(), version).map(str::to_string),
+        }
+    }
+
+    fn for_uplink(&self, uplink: Option<&str>, version: IpVersion) -> Option<&str> {
+        match uplink {
+            Some(uplink) => self.uplinks.get(uplink)?.get(version),
+            None => self.get(version),
+        }
     }
 }
 
-/// 获取当前公网 IPv6 地址
-pub async fn get_external_ipv6() -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-    let client = reqwest::Client::builder()
-        .timeout(Duration::from_secs(10))
-        .build()?;
-    
-    let response = client.get("https://6.ipw.cn").send().await?;
+/// 每个 IP 版本的检测耗时，未检测的版本为 None
+#[derive(Debug, Clone, Default)]
+pub struct DetectionTimes {
+    pub v4: Option<Duration>,
+    pub v6: Option<Duration>,
+    /// 各上行线路的检测耗时，按线路名称索引
+    pub uplinks: BTreeMap<String, DetectionTimes>,
+}
+
+impl DetectionTimes {
+    pub fn get(&self, version: IpVersion) -> Option<Duration> {
+        match version {
+            IpVersion::V4 => self.v4,
+            IpVersion::V6 => self.v6,
+        }
+    }
+
+    /// 记录所用地址的检测耗时
+    pub fn for_record(&self, record: &DnsRecordConfig) -> Option<Duration> {
+        let version = record.get_ip_version().ok()?;
+        record.detection_sources().into_iter()
+            .filter_map(|source| match source {
+                Some(uplink) => self.uplinks.get(uplink)?.get(version),
+                None => self.get(version),
+            })
+            .max()
+    }
+}
+
+/// 默认的 IPv4 检测服务
+pub const DEFAULT_IPV4_URL: &str = "https://4.ipw.cn";
+
+/// 默认的 IPv6 检测服务
+pub const DEFAULT_IPV6_URL: &str = "https://6.ipw.cn";
+
+/// 检测给定版本集合的公网 IP，每个版本只请求一次，同时返回各版本的检测耗时
+///
+/// 命令行通过 `--ip` / `--ip6` 指定了地址的版本不会检测，耗时记为 0。
+pub async fn detect_ips(versions: &[IpVersion], detection: &IpDetectionConfig, timeout: Duration) -> Result<(DetectedIps, DetectionTimes)> {
+    let mut ips = DetectedIps::default();
+    let mut times = DetectionTimes::default();
+    if versions.contains(&IpVersion::V4) {
+        let started = Instant::now();
+        ips.v4 = Some(match overrides::ip(IpVersion::V4) {
+            Some(ip) => ip,
+            None => get_external_ip(IpVersion::V4, detection.url(IpVersion::V4), timeout).await?,
+        });
+        times.v4 = Some(started.elapsed());
+    }
+    if versions.contains(&IpVersion::V6) {
+        let started = Instant::now();
+        ips.v6 = Some(match overrides::ip(IpVersion::V6) {
+            Some(ip) => ip,
+            None => get_external_ip(IpVersion::V6, detection.url(IpVersion::V6), timeout).await?,
+        });
+        times.v6 = Some(started.elapsed());
+    }
+    Ok((ips, times))
+}
+
+/// 检测配置中所有记录用到的地址：未指定上行线路的记录使用默认检测，其余按各自的上行线路检测
+///
+/// 默认检测失败时返回错误；某条上行线路检测失败时只记录警告，该线路的记录在计划阶段失败，不影响其他线路。
+pub async fn detect_for(config: &Config) -> Result<(DetectedIps, DetectionTimes)> {
+    let versions = config.ip_versions()
+        .map_err(|e| Error::config(tr!("IP 版本无效: {}", "Invalid IP version: {}", e)))?;
+    let (mut ips, mut times) = detect_ips(&versions, &config.ip_detection, config.schedule.timeout()).await?;
+    detect_uplinks(config, &mut ips, &mut times).await;
+    Ok((ips, times))
+}
+
+/// 检测每条用到的上行线路的地址，结果写入 `ips.uplinks` 与 `times.uplinks`
+pub async fn detect_uplinks(config: &Config, ips: &mut DetectedIps, times: &mut DetectionTimes) {
+    for uplink in &config.uplinks {
+        let versions = config.uplink_versions(&uplink.name);
+        if versions.is_empty() {
+            continue;
+        }
+        match detect_uplink(uplink, &versions, &config.ip_detection, config.schedule.timeout()).await {
+            Ok((uplink_ips, uplink_times)) => {
+                ips.uplinks.insert(uplink.name.clone(), uplink_ips);
+                times.uplinks.insert(uplink.name.clone(), uplink_times);
+            }
+            Err(e) => warn!(uplink = %uplink.name, error = %e, "{}", tr!("上行线路的 IP 检测失败，跳过其中的记录", "IP detection failed on uplink, skipping its records")),
+        }
+    }
+}
+
+/// 通过上行线路绑定的接口或源地址检测公网 IP，未配置检测地址的版本使用 `ip_detection` 中的地址
+pub async fn detect_uplink(uplink: &UplinkConfig, versions: &[IpVersion], detection: &IpDetectionConfig, timeout: Duration) -> Result<(DetectedIps, DetectionTimes)> {
+    let client = uplink_client(uplink, timeout)?;
+    let mut ips = DetectedIps::default();
+    let mut times = DetectionTimes::default();
+    for &version in versions {
+        let url = match version {
+            IpVersion::V4 => uplink.ipv4_url.as_deref(),
+            IpVersion::V6 => uplink.ipv6_url.as_deref(),
+        };
+        let started = Instant::now();
+        let ip = fetch_ip(&client, version, url.unwrap_or_else(|| detection.url(version))).await?;
+        match version {
+            IpVersion::V4 => (ips.v4, times.v4) = (Some(ip), Some(started.elapsed())),
+            IpVersion::V6 => (ips.v6, times.v6) = (Some(ip), Some(started.elapsed())),
+        }
+    }
+    Ok((ips, times))
+}
+
+/// 检测请求绑定到上行线路的网络接口与源地址
+fn uplink_client(uplink: &UplinkConfig, timeout: Duration) -> Result<reqwest::Client> {
+    let mut builder = crate::tls::client_builder()
+        .timeout(timeout)
+        .local_address(uplink.local_address);
+    if let Some(interface) = &uplink.interface {
+        #[cfg(any(target_os = "linux", target_os = "android", target_os = "macos"))]
+        {
+            builder = builder.interface(interface);
+        }
+        #[cfg(not(any(target_os = "linux", target_os = "android", target_os = "macos")))]
+        return Err(Error::config(tr!(
+            "上行线路 {} 的 interface（{}）只在 Linux 与 macOS 上受支持，请改用 local_address",
+            "Uplink {} sets interface {}, which is only supported on Linux and macOS, use local_address instead",
+            uplink.name,
+            interface
+        )));
+    }
+    builder.build().map_err(detection_error)
+}
+
+/// 从默认的检测服务获取当前公网 IPv4 地址
+pub async fn get_external_ipv4(timeout: Duration) -> Result<String> {
+    get_external_ip(IpVersion::V4, DEFAULT_IPV4_URL, timeout).await
+}
+
+/// 从默认的检测服务获取当前公网 IPv6 地址
+pub async fn get_external_ipv6(timeout: Duration) -> Result<String> {
+    get_external_ip(IpVersion::V6, DEFAULT_IPV6_URL, timeout).await
+}
+
+/// 从以纯文本返回地址的检测服务获取当前公网 IP
+pub async fn get_external_ip(version: IpVersion, url: &str, timeout: Duration) -> Result<String> {
+    let client = crate::tls::client_builder()
+        .timeout(timeout)
+        .build()
+        .map_err(detection_error)?;
+    fetch_ip(&client, version, url).await
+}
+
+async fn fetch_ip(client: &reqwest::Client, version: IpVersion, url: &str) -> Result<String> {
+    let response = client.get(url).send().await.map_err(detection_error)?;
     
     if response.status().is_success() {
-        let ip = response.text().await?.trim().to_string();
+        let ip = response.text().await.map_err(detection_error)?.trim().to_string();
         Ok(ip)
     } else {
-        Err(format!("获取 IPv6 地址失败: {}", response.status()).into())
+        let category = ErrorCategory::from_status(response.status());
+        Err(Error::detection(category, match version {
+            IpVersion::V4 => tr!("获取 IPv4 地址失败: {}", "Failed to get IPv4 address: {}", response.status()),
+            IpVersion::V6 => tr!("获取 IPv6 地址失败: {}", "Failed to get IPv6 address: {}", response.status()),
+        }))
     }
 }
 
+/// 检测请求的网络与 HTTP 错误
+fn detection_error(error: reqwest::Error) -> Error {
+    Error::detection(ErrorCategory::of(&error), error)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[tokio::test]
     async fn test_get_external_ipv4() {
-        let result = get_external_ipv4().await;
+        let result = get_external_ipv4(Duration::from_secs(10)).await;
         assert!(result.is_ok());
         let ip = result.unwrap();
         println!("Current IPv4: {}", ip);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn records_use_their_uplink_address() {
+        let config: Config = serde_json::from_str(r#"{
+            "uplinks": [{ "name": "isp1", "interface": "ppp0" }, { "name": "isp2", "local_address": "192.168.2.2" }],
+            "dns_records": [
+                { "name": "home.example.com", "type": "A", "ttl": 1, "proxied": false, "ip_version": "v4" },
+                { "name": "isp1.example.com", "type": "A", "ttl": 1, "proxied": false, "ip_version": "v4", "uplink": "isp1" },
+                { "name": "isp2.example.com", "type": "AAAA", "ttl": 1, "proxied": false, "ip_version": "v6", "uplink": "isp2" }
+            ]
+        }"#).unwrap();
+        assert_eq!(config.ip_versions().unwrap(), [IpVersion::V4]);
+        assert_eq!(config.uplink_versions("isp2"), [IpVersion::V6]);
+
+        // isp2 检测失败，不在结果中
+        let isp1 = DetectedIps { v4: Some("198.51.100.1".to_string()), ..Default::default() };
+        let ips = DetectedIps { v4: Some("203.0.113.1".to_string()), uplinks: BTreeMap::from([("isp1".to_string(), isp1)]), ..Default::default() };
+        let detected: Vec<_> = config.records().map(|record| ips.for_record(record)).collect();
+        assert_eq!(detected, [Some("203.0.113.1".to_string()), Some("198.51.100.1".to_string()), None]);
+    }
+}
@@ -0,0 +1,58 @@
+//! CloudFlare DDNS：检测公网 IP 并更新 CloudFlare 上的 DNS 记录
+//!
+//! 命令行程序 `cloudflare_ddns` 建立在这个库之上，也可以把更新流程嵌入到其他服务中：
+//!
+//! ```no_run
+//! # async fn example() -> Result<(), cloudflare_ddns::Error> {
+//! let config = cloudflare_ddns::load_config("config.json")?;
+//! let (ips, times) = cloudflare_ddns::ip_utils::detect_for(&config).await?;
+//! for result in cloudflare_ddns::reconcile(&config, &ips, &times, false).await? {
+//!     println!("{}: {:?}", result.name, result.outcome);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! - [`config`]：配置文件的结构
+//! - [`encryption`]：用 age 加密的配置文件
+//! - [`credentials`]：配置文件之外的凭据来源（systemd 凭据、系统密钥环）
+//! - [`builder`]：在代码中构建配置与更新器（[`DdnsUpdater`]），不需要配置文件
+//! - [`cloudflare`]：CloudFlare API 客户端
+//! - [`provider`]：DNS 服务商接口，更新流程通过它读写记录
+//! - [`record_set`]：轮询记录集，用标签识别并维护同名的多条记录
+//! - [`ip_utils`]：公网 IP 检测
+//! - [`failover`]：基于健康检查的故障转移
+//! - [`update`]：核对与更新记录的流程
+//! - [`error`]：统一的错误类型 [`Error`] 与错误类别
+//! - [`summary`]：单次运行的结构化结果与退出码
+//! - [`state`]：持久化状态（上一次的 IP、记录最近发布的内容、历史与运行统计）的存储接口
+//! - `blocking`：检测与更新流程的同步版本，需要启用 `blocking` 功能
+
+#[cfg(feature = "blocking")]
+pub mod blocking;
+pub mod builder;
+pub mod cloudflare;
+pub mod config;
+pub mod credentials;
+pub mod duration;
+pub mod encryption;
+pub mod error;
+pub mod failover;
+pub mod history;
+pub mod i18n;
+pub mod ip_utils;
+pub mod logging;
+pub mod notify;
+pub mod overrides;
+pub mod provider;
+pub mod record_set;
+pub mod state;
+pub mod summary;
+pub mod tls;
+pub mod update;
+
+pub use builder::{ConfigBuilder, DdnsUpdater};
+pub use cloudflare::CloudflareClient;
+pub use config::Config;
+pub use error::Error;
+pub use update::{RecordOutcome, RecordResult, load_config, reconcile, run, run_once};
@@ -0,0 +1,83 @@
+// 按配置文件加锁：使用同一份配置的多个进程（重叠的 cron 调用、定时模式与手动运行）依次写入记录，不会交错读写
+//
+// 锁文件位于数据目录（见 `default_data_file`）下，以配置文件名加 `.lock` 命名，使用操作系统的咨询锁，
+// 进程退出（包括崩溃）时自动释放。锁文件本身不会被删除，删除会让等待中的进程锁住一个已经不存在的文件。
+use std::fs::{File, OpenOptions, TryLockError};
+use std::path::{Path, PathBuf};
+
+use tracing::{info, warn};
+
+use cloudflare_ddns::i18n::tr;
+use cloudflare_ddns::update::default_data_file;
+
+/// 持有期间其他进程无法对同一份配置执行更新，离开作用域时释放
+#[derive(Debug)]
+pub struct RunLock {
+    _file: File,
+}
+
+/// 配置文件对应的锁文件路径
+pub fn lock_path(config_path: &str) -> PathBuf {
+    let name = Path::new(config_path).file_name().map_or_else(|| "config".into(), |name| name.to_string_lossy());
+    PathBuf::from(default_data_file(config_path, &format!("{}.lock", name)))
+}
+
+/// 获取配置文件的运行锁，已被其他进程持有时等待其释放
+///
+/// 无法创建锁文件（如目录只读）时只给出警告并返回 `None`，不阻止本次运行。
+pub async fn acquire(config_path: &str) -> Option<RunLock> {
+    let path = lock_path(config_path);
+    let file = match OpenOptions::new().create(true).truncate(false).write(true).open(&path) {
+        Ok(file) => file,
+        Err(e) => {
+            warn!(path = %path.display(), error = %e, "{}", tr!("无法创建锁文件，本次运行不加锁", "Failed to create lock file, running without a lock"));
+            return None;
+        }
+    };
+    match file.try_lock() {
+        Ok(()) => return Some(RunLock { _file: file }),
+        Err(TryLockError::WouldBlock) => {}
+        Err(TryLockError::Error(e)) => {
+            warn!(path = %path.display(), error = %e, "{}", tr!("无法加锁，本次运行不加锁", "Failed to take the lock, running without it"));
+            return None;
+        }
+    }
+
+    info!(path = %path.display(), "{}", tr!("另一个进程正在使用同一份配置更新记录，等待其完成", "Another process is updating records with the same config, waiting for it to finish"));
+    let waited = tokio::task::spawn_blocking(move || file.lock().map(|()| file)).await;
+    match waited {
+        Ok(Ok(file)) => Some(RunLock { _file: file }),
+        Ok(Err(e)) => {
+            warn!(path = %path.display(), error = %e, "{}", tr!("无法加锁，本次运行不加锁", "Failed to take the lock, running without it"));
+            None
+        }
+        Err(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn second_run_waits_for_the_first() {
+        let dir = std::env::temp_dir().join(format!("cloudflare_ddns_lock_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let config_path = dir.join("config.json").to_string_lossy().into_owned();
+        assert_eq!(lock_path(&config_path), dir.join("config.json.lock"));
+
+        let first = acquire(&config_path).await.unwrap();
+        let second = tokio::spawn({
+            let config_path = config_path.clone();
+            async move { acquire(&config_path).await }
+        });
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert!(!second.is_finished());
+
+        drop(first);
+        let second = tokio::time::timeout(Duration::from_secs(5), second).await.unwrap().unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+        assert!(second.is_some());
+    }
+}
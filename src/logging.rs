@@ -0,0 +1,236 @@
+// 日志初始化：所有输出通过 tracing 记录，级别由命令行参数或 RUST_LOG 控制，默认 info
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::EnvFilter;
+
+use crate::config::{LogFormat, LogRotation, LoggingConfig};
+use crate::i18n::tr;
+
+mod color;
+#[cfg(windows)]
+mod eventlog;
+pub mod redact;
+mod syslog;
+
+/// 记录变化使用的日志 target，安静模式下仍会输出
+pub const CHANGE_TARGET: &str = "ddns::change";
+
+/// 每次执行结束的汇总行使用的日志 target，安静模式下仍会输出
+pub const HEARTBEAT_TARGET: &str = "ddns::heartbeat";
+
+/// 记录无需更新使用的日志 target，彩色输出时以暗色显示
+pub const NOOP_TARGET: &str = "ddns::noop";
+
+/// 命令行可指定的日志级别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    fn as_str(self) -> &'static str {
+        match self {
+            LogLevel::Error => "error",
+            LogLevel::Warn => "warn",
+            LogLevel::Info => "info",
+            LogLevel::Debug => "debug",
+            LogLevel::Trace => "trace",
+        }
+    }
+}
+
+/// 初始化全局日志订阅者
+///
+/// 优先级：`level` > 安静模式 > RUST_LOG > info。
+/// 安静模式只输出警告、错误、记录变化以及每次执行的汇总行。
+/// 配置了日志文件时写入文件，返回的 guard 需要保持到程序退出，以便写完缓冲的日志。
+/// 启用 syslog、journald 或 Windows 事件日志时，日志会同时发送到这些系统日志。
+/// `stderr` 为 true 时终端日志输出到标准错误，以便标准输出只包含程序结果。
+/// 已登记的密钥在写出前被替换，见 [`redact`]。
+/// `color` 为 true、未设置 NO_COLOR 且输出到终端时使用彩色输出。
+pub fn init(level: Option<LogLevel>, quiet: bool, stderr: bool, color: bool, config: &LoggingConfig) -> io::Result<Option<WorkerGuard>> {
+    let filter = match level {
+        Some(level) => EnvFilter::new(level.as_str()),
+        None if quiet => EnvFilter::new(format!("warn,{}=info,{}=info", CHANGE_TARGET, HEARTBEAT_TARGET)),
+        None => EnvFilter::try_from_default_env()
+            .unwrap_or_else(|_| EnvFilter::new("info")),
+    };
+
+    let (writer, ansi, guard) = match &config.file {
+        Some(path) => {
+            let file = file_writer(Path::new(path), config)
+                .map_err(|e| io::Error::new(e.kind(), tr!("无法打开日志文件 {}: {}", "Failed to open log file {}: {}", path, e)))?;
+            let (writer, guard) = tracing_appender::non_blocking(file);
+            (BoxMakeWriter::new(writer), false, Some(guard))
+        }
+        None if stderr => (BoxMakeWriter::new(io::stderr), io::stderr().is_terminal(), None),
+        None => (BoxMakeWriter::new(io::stdout), io::stdout().is_terminal(), None),
+    };
+    // 约定见 https://no-color.org：NO_COLOR 非空时禁用颜色
+    let no_color_env = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+    let ansi = ansi && color && !no_color_env;
+
+    let layer = tracing_subscriber::fmt::layer()
+        .with_writer(redact::RedactingMakeWriter::new(writer));
+    let layer = match config.format {
+        LogFormat::Text => layer
+            .event_format(color::Highlight::new(tracing_subscriber::fmt::format().with_target(false)))
+            .with_ansi(ansi)
+            .boxed(),
+        // 字段直接放在顶层，便于按 record、ip 等字段查询
+        LogFormat::Json => layer.json().flatten_event(true).boxed(),
+    };
+
+    let syslog = config.syslog.as_ref()
+        .map(syslog::SyslogLayer::new)
+        .transpose()
+        .map_err(|e| io::Error::new(e.kind(), tr!("无法连接 syslog: {}", "Failed to connect to syslog: {}", e)))?;
+    let journald = if config.journald {
+        Some(tracing_journald::layer()
+            .map_err(|e| io::Error::new(e.kind(), tr!("无法连接 journald: {}", "Failed to connect to journald: {}", e)))?)
+    } else {
+        None
+    };
+
+    #[cfg(windows)]
+    let eventlog = if config.eventlog {
+        Some(eventlog::EventLogLayer::new()
+            .map_err(|e| io::Error::new(e.kind(), tr!("无法打开 Windows 事件日志: {}", "Failed to open the Windows event log: {}", e)))?)
+    } else {
+        None
+    };
+    #[cfg(not(windows))]
+    if config.eventlog {
+        return Err(io::Error::new(io::ErrorKind::Unsupported, tr!("Windows 事件日志仅在 Windows 上可用", "The Windows event log is only available on Windows")));
+    }
+
+    let registry = tracing_subscriber::registry()
+        .with(filter)
+        .with(layer)
+        .with(syslog)
+        .with(journald);
+    #[cfg(windows)]
+    let registry = registry.with(eventlog);
+    registry.init();
+    Ok(guard)
+}
+
+/// 按配置的轮转方式创建日志文件写入器
+fn file_writer(path: &Path, config: &LoggingConfig) -> io::Result<Box<dyn Write + Send>> {
+    let max_files = config.max_files.max(1);
+    if config.rotation == LogRotation::Size {
+        let max_size = config.max_size_mb.max(1) * 1024 * 1024;
+        return Ok(Box::new(SizeRollingWriter::new(path.to_path_buf(), max_size, max_files)?));
+    }
+
+    let rotation = match config.rotation {
+        LogRotation::Hourly => Rotation::HOURLY,
+        LogRotation::Daily => Rotation::DAILY,
+        _ => Rotation::NEVER,
+    };
+    let directory = path.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    fs::create_dir_all(directory)?;
+    let file_name = path.file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, tr!("无效的日志文件路径: {}", "Invalid log file path: {}", path.display())))?;
+    let appender = RollingFileAppender::builder()
+        .rotation(rotation)
+        .filename_prefix(file_name.to_string_lossy())
+        .max_log_files(max_files)
+        .build(directory)
+        .map_err(io::Error::other)?;
+    Ok(Box::new(appender))
+}
+
+/// 按大小轮转的日志文件：超过上限时依次重命名为 `.1`、`.2`……，超出保留数量的旧文件被删除
+struct SizeRollingWriter {
+    path: PathBuf,
+    max_size: u64,
+    max_files: usize,
+    file: File,
+    size: u64,
+}
+
+impl SizeRollingWriter {
+    fn new(path: PathBuf, max_size: u64, max_files: usize) -> io::Result<Self> {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+        Ok(Self { path, max_size, max_files, file, size })
+    }
+
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(format!(".{}", index));
+        PathBuf::from(name)
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        let oldest = self.max_files - 1;
+        if oldest == 0 {
+            fs::remove_file(&self.path)?;
+        } else {
+            let _ = fs::remove_file(self.rotated_path(oldest));
+            for index in (1..oldest).rev() {
+                let from = self.rotated_path(index);
+                if from.exists() {
+                    fs::rename(&from, self.rotated_path(index + 1))?;
+                }
+            }
+            fs::rename(&self.path, self.rotated_path(1))?;
+        }
+        self.file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+impl Write for SizeRollingWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.size > 0 && self.size + buf.len() as u64 > self.max_size {
+            self.rotate()?;
+        }
+        let written = self.file.write(buf)?;
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_size_rolling_writer() {
+        let dir = std::env::temp_dir().join(format!("cloudflare_ddns_log_test_{}", std::process::id()));
+        let path = dir.join("ddns.log");
+        let mut writer = SizeRollingWriter::new(path.clone(), 10, 3).unwrap();
+
+        for line in ["aaaaaaaa\n", "bbbbbbbb\n", "cccccccc\n", "dddddddd\n"] {
+            writer.write_all(line.as_bytes()).unwrap();
+        }
+        writer.flush().unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "dddddddd\n");
+        assert_eq!(fs::read_to_string(writer.rotated_path(1)).unwrap(), "cccccccc\n");
+        assert_eq!(fs::read_to_string(writer.rotated_path(2)).unwrap(), "bbbbbbbb\n");
+        assert!(!writer.rotated_path(3).exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,54 @@
+// 终端彩色输出：记录变化为绿色，失败为红色，无变化为暗色
+use std::fmt;
+
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields};
+use tracing_subscriber::registry::LookupSpan;
+
+use super::{CHANGE_TARGET, NOOP_TARGET};
+
+const GREEN: &str = "32";
+const RED: &str = "31";
+const DIM: &str = "2";
+
+/// 按事件类别为整行着色，其余事件保持原格式
+pub struct Highlight<F> {
+    inner: F,
+}
+
+impl<F> Highlight<F> {
+    pub fn new(inner: F) -> Self {
+        Self { inner }
+    }
+}
+
+fn style(event: &Event<'_>) -> Option<&'static str> {
+    let metadata = event.metadata();
+    if *metadata.level() == Level::ERROR {
+        Some(RED)
+    } else if metadata.target() == CHANGE_TARGET {
+        Some(GREEN)
+    } else if metadata.target() == NOOP_TARGET {
+        Some(DIM)
+    } else {
+        None
+    }
+}
+
+impl<S, N, F> FormatEvent<S, N> for Highlight<F>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+    F: FormatEvent<S, N>,
+{
+    fn format_event(&self, ctx: &FmtContext<'_, S, N>, mut writer: Writer<'_>, event: &Event<'_>) -> fmt::Result {
+        let Some(style) = style(event).filter(|_| writer.has_ansi_escapes()) else {
+            return self.inner.format_event(ctx, writer, event);
+        };
+        // 先以无颜色格式输出，再整体着色，避免原有的颜色重置打断整行颜色
+        let mut line = String::new();
+        self.inner.format_event(ctx, Writer::new(&mut line), event)?;
+        writeln!(writer, "\x1b[{}m{}\x1b[0m", style, line.trim_end_matches('\n'))
+    }
+}
@@ -0,0 +1,121 @@
+// 把警告、错误与记录变化写入 Windows 事件日志（应用程序日志）
+use std::io;
+
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::Context;
+use windows_sys::Win32::System::EventLog::{
+    DeregisterEventSource, RegisterEventSourceW, ReportEventW, EVENTLOG_ERROR_TYPE, EVENTLOG_INFORMATION_TYPE,
+    EVENTLOG_WARNING_TYPE,
+};
+
+use super::syslog::MessageVisitor;
+
+/// 事件来源名称，显示在事件查看器的"来源"一列
+const SOURCE_NAME: &str = "CloudFlare DDNS";
+
+/// 记录被更新的事件 ID
+pub const EVENT_UPDATED: u32 = 1001;
+/// 记录被新建的事件 ID
+pub const EVENT_CREATED: u32 = 1002;
+/// 警告的基础事件 ID，加上错误类别的偏移（见 [`category_offset`]）
+pub const EVENT_WARNING: u32 = 2000;
+/// 错误的基础事件 ID，加上错误类别的偏移
+pub const EVENT_ERROR: u32 = 3000;
+
+/// 事件日志没有按级别过滤的订阅者，只写入警告、错误与记录变化，避免每次执行都产生事件
+pub struct EventLogLayer {
+    /// RegisterEventSourceW 返回的句柄，以整数保存以便在线程间共享
+    handle: isize,
+}
+
+impl EventLogLayer {
+    pub fn new() -> io::Result<Self> {
+        let source = wide(SOURCE_NAME);
+        let handle = unsafe { RegisterEventSourceW(std::ptr::null(), source.as_ptr()) };
+        if handle.is_null() {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Self { handle: handle as isize })
+    }
+}
+
+impl Drop for EventLogLayer {
+    fn drop(&mut self) {
+        unsafe { DeregisterEventSource(self.handle as _) };
+    }
+}
+
+impl<S: Subscriber> tracing_subscriber::Layer<S> for EventLogLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        if !matches!(*metadata.level(), Level::ERROR | Level::WARN)
+            && !(*metadata.level() == Level::INFO && metadata.target() == super::CHANGE_TARGET)
+        {
+            return;
+        }
+        let mut visitor = EventVisitor::default();
+        event.record(&mut visitor);
+        let offset = category_offset(visitor.category.as_deref());
+        let (event_type, event_id) = match *metadata.level() {
+            Level::ERROR => (EVENTLOG_ERROR_TYPE, EVENT_ERROR + offset),
+            Level::WARN => (EVENTLOG_WARNING_TYPE, EVENT_WARNING + offset),
+            _ if visitor.updated => (EVENTLOG_INFORMATION_TYPE, EVENT_UPDATED),
+            _ => (EVENTLOG_INFORMATION_TYPE, EVENT_CREATED),
+        };
+
+        let message = wide(&super::redact::redact(&format!("{}{}", visitor.inner.message, visitor.inner.fields)));
+        let strings = [message.as_ptr()];
+        // 写入失败无处报告，直接忽略
+        unsafe {
+            ReportEventW(self.handle as _, event_type, 0, event_id, std::ptr::null_mut(), 1, 0, strings.as_ptr(), std::ptr::null());
+        }
+    }
+}
+
+/// 错误类别在警告与错误事件 ID 中的偏移，未分类为 0
+fn category_offset(category: Option<&str>) -> u32 {
+    match category {
+        Some("network") => 1,
+        Some("auth") => 2,
+        Some("rate_limited") => 3,
+        Some("not_found") => 4,
+        Some("parse") => 5,
+        _ => 0,
+    }
+}
+
+/// 在 `MessageVisitor` 之外记下选择事件 ID 所需的字段：错误类别，以及记录变化是否带有旧地址
+#[derive(Default)]
+struct EventVisitor {
+    inner: MessageVisitor,
+    category: Option<String>,
+    updated: bool,
+}
+
+impl Visit for EventVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.note(field, value.to_string());
+        self.inner.record_str(field, value);
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.note(field, format!("{:?}", value));
+        self.inner.record_debug(field, value);
+    }
+}
+
+impl EventVisitor {
+    fn note(&mut self, field: &Field, value: String) {
+        match field.name() {
+            "category" => self.category = Some(value),
+            "old_ip" => self.updated = true,
+            _ => {}
+        }
+    }
+}
+
+/// 以 NUL 结尾的 UTF-16 字符串
+fn wide(text: &str) -> Vec<u16> {
+    text.encode_utf16().chain(std::iter::once(0)).collect()
+}
@@ -0,0 +1,88 @@
+// 日志脱敏：配置中的令牌、密钥、密码等在写出日志前被替换为 "***"
+use std::borrow::Cow;
+use std::io::{self, Write};
+use std::sync::RwLock;
+
+use tracing_subscriber::fmt::MakeWriter;
+
+/// 替换后的占位符
+const MASK: &str = "***";
+
+/// 过短的值替换后会误伤正常日志，不登记
+const MIN_SECRET_LEN: usize = 4;
+
+static SECRETS: RwLock<Vec<String>> = RwLock::new(Vec::new());
+
+/// 登记需要从日志中隐去的值，重复登记会被忽略
+pub fn register<'a>(secrets: impl IntoIterator<Item = &'a str>) {
+    let mut registered = SECRETS.write().unwrap_or_else(|e| e.into_inner());
+    for secret in secrets {
+        if secret.len() >= MIN_SECRET_LEN && !registered.iter().any(|s| s == secret) {
+            registered.push(secret.to_string());
+        }
+    }
+    // 先替换较长的值，避免其中包含的较短值先被替换后长值无法匹配
+    registered.sort_by_key(|s| std::cmp::Reverse(s.len()));
+}
+
+/// 把文本中所有已登记的值替换为 "***"
+pub fn redact(text: &str) -> Cow<'_, str> {
+    let secrets = SECRETS.read().unwrap_or_else(|e| e.into_inner());
+    let mut text = Cow::Borrowed(text);
+    for secret in secrets.iter() {
+        if text.contains(secret.as_str()) {
+            text = Cow::Owned(text.replace(secret.as_str(), MASK));
+        }
+    }
+    text
+}
+
+/// 在写出前脱敏的 MakeWriter 包装
+pub struct RedactingMakeWriter<M> {
+    inner: M,
+}
+
+impl<M> RedactingMakeWriter<M> {
+    pub fn new(inner: M) -> Self {
+        Self { inner }
+    }
+}
+
+impl<'a, M: MakeWriter<'a>> MakeWriter<'a> for RedactingMakeWriter<M> {
+    type Writer = RedactingWriter<M::Writer>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        RedactingWriter { inner: self.inner.make_writer() }
+    }
+}
+
+/// fmt 层每条日志只调用一次 write，因此可以按每次写入的内容整体替换
+pub struct RedactingWriter<W> {
+    inner: W,
+}
+
+impl<W: Write> Write for RedactingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match std::str::from_utf8(buf) {
+            Ok(text) => self.inner.write_all(redact(text).as_bytes())?,
+            Err(_) => self.inner.write_all(buf)?,
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact() {
+        register(["abc", "secret-token-123", "secret-token"]);
+        assert_eq!(redact("token=secret-token-123 abc"), "token=*** abc");
+        assert_eq!(redact("bearer secret-token"), "bearer ***");
+    }
+}
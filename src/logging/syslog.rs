@@ -0,0 +1,160 @@
+// 以 RFC 5424 格式把日志发送到本机 syslog (/dev/log) 或远程 syslog 服务器 (UDP)
+use std::fmt::Write as _;
+use std::io;
+use std::net::UdpSocket;
+#[cfg(unix)]
+use std::os::unix::net::UnixDatagram;
+
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::Context;
+
+use crate::config::SyslogConfig;
+use crate::i18n::tr;
+
+/// 本机 syslog 套接字路径
+#[cfg(unix)]
+const LOCAL_SYSLOG_SOCKET: &str = "/dev/log";
+
+/// RFC 5424 中的 APP-NAME
+const APP_NAME: &str = "cloudflare_ddns";
+
+enum Transport {
+    #[cfg(unix)]
+    Unix(UnixDatagram),
+    Udp(UdpSocket),
+}
+
+impl Transport {
+    fn send(&self, message: &[u8]) -> io::Result<usize> {
+        match self {
+            #[cfg(unix)]
+            Transport::Unix(socket) => socket.send(message),
+            Transport::Udp(socket) => socket.send(message),
+        }
+    }
+}
+
+/// 把每条日志作为一条 syslog 消息发送的 tracing 层
+pub struct SyslogLayer {
+    transport: Transport,
+    facility: u8,
+    hostname: String,
+    pid: u32,
+}
+
+impl SyslogLayer {
+    pub fn new(config: &SyslogConfig) -> io::Result<Self> {
+        let facility = facility_code(&config.facility).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, tr!("无效的 syslog facility: {}", "Invalid syslog facility: {}", config.facility))
+        })?;
+
+        let transport = match &config.server {
+            Some(server) => {
+                let socket = UdpSocket::bind(if server.starts_with('[') { "[::]:0" } else { "0.0.0.0:0" })?;
+                socket.connect(server)?;
+                Transport::Udp(socket)
+            }
+            #[cfg(unix)]
+            None => {
+                let socket = UnixDatagram::unbound()?;
+                socket.connect(LOCAL_SYSLOG_SOCKET)?;
+                Transport::Unix(socket)
+            }
+            #[cfg(not(unix))]
+            None => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    tr!("当前平台没有本机 syslog，请配置 syslog.server", "No local syslog on this platform, configure syslog.server"),
+                ));
+            }
+        };
+
+        let hostname = hostname::get()
+            .ok()
+            .and_then(|name| name.into_string().ok())
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| "-".to_string());
+
+        Ok(Self { transport, facility, hostname, pid: std::process::id() })
+    }
+}
+
+impl<S: Subscriber> tracing_subscriber::Layer<S> for SyslogLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+
+        let severity = match *event.metadata().level() {
+            Level::ERROR => 3,
+            Level::WARN => 4,
+            Level::INFO => 6,
+            Level::DEBUG | Level::TRACE => 7,
+        };
+        // <PRI>VERSION TIMESTAMP HOSTNAME APP-NAME PROCID MSGID STRUCTURED-DATA MSG
+        let line = format!(
+            "<{}>1 {} {} {} {} - - {}{}",
+            self.facility * 8 + severity,
+            chrono::Local::now().to_rfc3339_opts(chrono::SecondsFormat::Micros, false),
+            self.hostname,
+            APP_NAME,
+            self.pid,
+            visitor.message,
+            visitor.fields,
+        );
+        // 日志系统本身的发送失败无处报告，直接忽略
+        let _ = self.transport.send(super::redact::redact(&line).as_bytes());
+    }
+}
+
+/// 收集日志消息与字段，字段以 `key=value` 形式追加在消息之后
+#[derive(Default)]
+pub(super) struct MessageVisitor {
+    pub(super) message: String,
+    pub(super) fields: String,
+}
+
+impl Visit for MessageVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message.push_str(value);
+        } else {
+            let _ = write!(self.fields, " {}={}", field.name(), value);
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.message, "{:?}", value);
+        } else {
+            let _ = write!(self.fields, " {}={:?}", field.name(), value);
+        }
+    }
+}
+
+/// syslog facility 名称对应的编号
+fn facility_code(name: &str) -> Option<u8> {
+    Some(match name {
+        "kern" => 0,
+        "user" => 1,
+        "mail" => 2,
+        "daemon" => 3,
+        "auth" => 4,
+        "syslog" => 5,
+        "lpr" => 6,
+        "news" => 7,
+        "uucp" => 8,
+        "cron" => 9,
+        "authpriv" => 10,
+        "ftp" => 11,
+        "local0" => 16,
+        "local1" => 17,
+        "local2" => 18,
+        "local3" => 19,
+        "local4" => 20,
+        "local5" => 21,
+        "local6" => 22,
+        "local7" => 23,
+        _ => return None,
+    })
+}
@@ -1,216 +1,941 @@
-use clap::Parser;
-use config::Config;
-use cloudflare::UpdateDnsRecordParams;
+use clap::{Parser, Subcommand};
+use chrono::{DateTime, Local};
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use tracing::{debug, error, info, warn};
 
-mod ip_utils;
-mod cloudflare;
-mod config;
+use cloudflare_ddns::Error;
+use cloudflare_ddns::config::{self, Config};
+use cloudflare_ddns::i18n::{self, tr};
+use cloudflare_ddns::update::{
+    load_config, log_heartbeat, plan_record, process_record, reconcile_tracked, record_history, run_once, zone_provider,
+    RecordChange, RecordOutcome,
+};
+use cloudflare_ddns::{duration, history, ip_utils, logging, notify, overrides, state as state_store, summary};
+
+mod auth;
+mod batch;
+mod commands;
+mod config_edit;
+#[cfg(unix)]
+mod control;
+mod doctor;
+mod lock;
+#[cfg(feature = "http-server")]
+mod http;
+mod manpage;
+mod metrics;
+#[cfg(feature = "mqtt")]
+mod mqtt;
+mod plan;
+mod retry;
+mod sample;
 mod scheduler;
+mod service;
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// 配置文件路径
-    #[arg(short, long, default_value = "config.json")]
+    #[arg(short, long, default_value = "config.json", global = true)]
     config: String,
     
     /// 强制更新，即使 IP 没有变化
-    #[arg(short, long)]
+    #[arg(short, long, global = true)]
     force: bool,
     
     /// 只检查 IP，不更新 DNS 记录
-    #[arg(long)]
+    #[arg(long, global = true)]
     check_only: bool,
+
+    /// 试运行：检测 IP 并读取现有记录，打印将要执行的变更，但不写入任何记录
+    #[arg(long, conflicts_with = "check_only", global = true)]
+    dry_run: bool,
     
-    /// 定时运行模式，指定检查间隔（秒）
-    #[arg(short, long)]
-    interval: Option<u64>,
+    /// 定时运行模式，指定检查间隔（如 90s、5m、1h30m，纯数字按秒处理）
+    #[arg(short, long, value_parser = duration::parse_duration, global = true)]
+    interval: Option<Duration>,
+
+    /// 单次运行的总时长上限（检测 IP 与所有 API 请求），超过后中止并以退出码 9 退出；定时模式下不生效
+    #[arg(long, value_parser = duration::parse_duration, global = true)]
+    timeout: Option<Duration>,
+
+    /// 两级调度：定时模式下每个间隔只检测 IP，完整核对 DNS 记录按此间隔进行（IP 变化时立即核对）
+    #[arg(long, value_parser = duration::parse_duration, global = true)]
+    reconcile_interval: Option<Duration>,
+
+    /// 日志级别，优先于 -v/-q 与 RUST_LOG
+    #[arg(long, value_enum, conflicts_with_all = ["verbose", "quiet"], global = true)]
+    log_level: Option<logging::LogLevel>,
+
+    /// 输出更详细的日志，-v 为 debug，-vv 为 trace
+    #[arg(short, long, action = clap::ArgAction::Count, conflicts_with = "quiet", global = true)]
+    verbose: u8,
+
+    /// 安静模式，只输出记录变化、错误与每次执行的汇总行
+    #[arg(short, long, global = true)]
+    quiet: bool,
+
+    /// 日志文件路径，覆盖配置文件中的 logging.file
+    #[arg(long, global = true)]
+    log_file: Option<String>,
+
+    /// 日志格式，覆盖配置文件中的 logging.format
+    #[arg(long, value_enum, global = true)]
+    log_format: Option<config::LogFormat>,
+
+    /// 单次运行结果的输出格式，json 时在标准输出打印结构化结果，日志改为输出到标准错误
+    #[arg(long, value_enum, default_value_t = summary::OutputFormat::Text, global = true)]
+    output: summary::OutputFormat,
+
+    /// 禁用彩色输出（也可设置 NO_COLOR 环境变量），输出不是终端时始终不使用颜色
+    #[arg(long, global = true)]
+    no_color: bool,
+
+    /// 只处理名称匹配的记录，可重复指定，支持 `*`、`?` 通配符（如 '*.home.example.com'）
+    #[arg(long = "record", value_name = "NAME", global = true)]
+    records: Vec<String>,
+
+    /// 只处理 IPv4 (A) 记录，不检测 IPv6
+    #[arg(long, conflicts_with = "ipv6_only", global = true)]
+    ipv4_only: bool,
+
+    /// 只处理 IPv6 (AAAA) 记录，不检测 IPv4
+    #[arg(long, global = true)]
+    ipv6_only: bool,
+
+    /// 不检测公网 IPv4，直接使用指定的地址（检测服务不可用时手动恢复）
+    #[arg(long, value_name = "IPV4", conflicts_with = "ipv6_only", global = true)]
+    ip: Option<std::net::Ipv4Addr>,
+
+    /// 不检测公网 IPv6，直接使用指定的地址
+    #[arg(long, value_name = "IPV6", conflicts_with = "ipv4_only", global = true)]
+    ip6: Option<std::net::Ipv6Addr>,
+
+    /// 本次运行所有记录使用的 TTL（秒，1 为自动），覆盖配置文件
+    #[arg(long, global = true)]
+    ttl: Option<u32>,
+
+    /// 本次运行所有记录是否启用 CloudFlare 代理（true/false），覆盖配置文件
+    #[arg(long, value_name = "BOOL", global = true)]
+    proxied: Option<bool>,
+
+    /// 输出语言，未指定时根据 LC_ALL / LC_MESSAGES / LANG 自动检测
+    #[arg(long, value_enum, global = true)]
+    lang: Option<i18n::Lang>,
+}
+
+// 子命令，未指定时按 `--interval` 选择 `run` 或 `once`，与旧版命令行保持兼容
+#[derive(Subcommand)]
+enum Command {
+    /// 定时运行，间隔取 --interval 或配置中的 schedule.interval
+    Run,
+    /// 单次运行：检测 IP 并更新所有记录
+    Once,
+    /// 只检测 IP，不访问 CloudFlare API（等同于 once --check-only）
+    Check,
+    /// 计算并显示每条记录将要执行的变更，不写入记录；有变更时退出码为 10
+    Plan {
+        /// 把计划保存到文件，供 apply 执行
+        #[arg(long, value_name = "FILE")]
+        out: Option<std::path::PathBuf>,
+    },
+    /// 执行 plan --out 保存的计划；未指定计划文件时重新计算计划，确认后执行
+    Apply {
+        /// plan --out 保存的计划文件
+        #[arg(value_name = "PLAN")]
+        plan_file: Option<std::path::PathBuf>,
+        /// 不询问确认，直接执行
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// 从标准输入逐行读取 JSON 记录操作（name、type、content，可选 ttl、proxied）并应用，结果以 JSON Lines 输出
+    Batch,
+    /// 把一条记录更新为指定的 IP（未指定时检测），使用记录所属区域的服务商与凭据，记录不必在 dns_records 中
+    Update {
+        /// 记录名称，如 vpn.example.com
+        name: String,
+        /// 记录指向的地址，IPv4 更新 A 记录、IPv6 更新 AAAA 记录；未指定时检测公网 IP
+        #[arg(value_name = "IP")]
+        address: Option<std::net::IpAddr>,
+        /// 未指定地址时检测公网 IPv6 并更新 AAAA 记录
+        #[arg(short = '6', long, conflicts_with = "address")]
+        ipv6: bool,
+    },
+    /// 检查配置文件，不访问网络
+    Validate,
+    /// 显示每条记录在 CloudFlare 上的内容与当前 IP 是否一致
+    Status {
+        /// 以 JSON Lines 格式输出
+        #[arg(long)]
+        json: bool,
+    },
+    /// 通过公共 DNS 解析每条记录，与当前 IP 比较，不访问 CloudFlare API；全部一致时退出码为 0，否则为 1
+    Verify {
+        /// DNS over HTTPS 解析服务地址（JSON 格式）
+        #[arg(long, default_value = commands::DEFAULT_RESOLVER)]
+        resolver: String,
+        /// 以 JSON Lines 格式输出
+        #[arg(long)]
+        json: bool,
+    },
+    /// 配置文件管理
+    Config {
+        #[command(subcommand)]
+        action: config_edit::ConfigAction,
+    },
+    /// 凭据管理
+    Auth {
+        #[command(subcommand)]
+        action: auth::AuthAction,
+    },
+    /// 逐项检查配置、网络、IP 检测、凭据、区域与记录并给出建议，不做任何修改；全部通过时退出码为 0，否则为 1
+    Doctor,
+    /// 列出凭据可访问的区域
+    Zones {
+        /// 以 JSON Lines 格式输出
+        #[arg(long)]
+        json: bool,
+    },
+    /// 列出配置的区域中的所有 DNS 记录
+    Records {
+        /// 以 JSON Lines 格式输出
+        #[arg(long)]
+        json: bool,
+    },
+    /// 输出 shell 补全脚本，如 `completions bash > /etc/bash_completion.d/cloudflare_ddns`
+    Completions {
+        /// 目标 shell
+        shell: clap_complete::Shell,
+    },
+    /// 输出带注释的完整示例配置，如 `generate-config > config.json`
+    GenerateConfig,
+    /// 生成 man 页（含配置项与退出码），供打包使用；未指定 --out-dir 时输出主页面到标准输出
+    #[command(hide = true)]
+    Mangen {
+        /// 写入主页面与每个子命令页面的目录
+        #[arg(long)]
+        out_dir: Option<std::path::PathBuf>,
+    },
+    /// 检查定时模式最近一次执行是否成功且足够新，健康时退出码为 0，否则为 1（用于 Docker HEALTHCHECK）
+    Healthcheck {
+        /// 最近一次执行距今的最长时间，默认为间隔的两倍（未配置间隔时为 10 分钟）
+        #[arg(long, value_parser = duration::parse_duration)]
+        max_age: Option<Duration>,
+    },
+    /// 生成加固的 systemd 服务单元（可选 timer），配置文件通过 LoadCredential 传入
+    InstallService(service::SystemdOptions),
+    /// 生成 macOS launchd plist，可选安装到 LaunchAgents 或 LaunchDaemons
+    InstallLaunchd(service::LaunchdOptions),
+    /// 管理 Windows 服务：install、uninstall、start、stop
+    #[cfg(windows)]
+    Service {
+        #[command(subcommand)]
+        action: service::ServiceAction,
+    },
+    /// 向运行中的定时模式发送控制命令（需要配置 schedule.control_socket，仅 Unix）
+    #[cfg(unix)]
+    Ctl {
+        #[command(subcommand)]
+        action: control::CtlAction,
+    },
+    /// 查看记录的 IP 变化历史
+    History {
+        /// 只显示最近一段时间内的变化（如 7d、24h）
+        #[arg(long, value_parser = duration::parse_duration)]
+        since: Option<Duration>,
+
+        /// 最多显示最近的多少条
+        #[arg(long)]
+        limit: Option<usize>,
+
+        /// 以 JSON Lines 格式输出
+        #[arg(long)]
+        json: bool,
+    },
 }
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    env_logger::init();
-    
-    let args = Args::parse();
-    println!("程序启动");
-    println!("参数解析完成: {:?}", args.config);
+async fn main() -> Result<std::process::ExitCode, Box<dyn std::error::Error + Send + Sync>> {
+    // Windows 服务模式下定时任务在 SCM 的服务线程中运行，参数需要在整个进程生命周期内有效
+    let args: &'static Args = Box::leak(Box::new(Args::parse()));
+
+    // 补全脚本直接输出到标准输出，不加载配置也不初始化日志
+    if let Some(Command::Completions { shell }) = args.command {
+        let mut command = <Args as clap::CommandFactory>::command();
+        let name = command.get_name().to_string();
+        let mut script = Vec::new();
+        clap_complete::generate(shell, &mut command, name, &mut script);
+        // 管道提前关闭（如 `| head`）时不视为错误
+        let _ = std::io::Write::write_all(&mut std::io::stdout(), &script);
+        return Ok(std::process::ExitCode::SUCCESS);
+    }
+    i18n::init(args.lang);
+    overrides::init(overrides::Overrides {
+        records: args.records.clone(),
+        ip_version: match (args.ipv4_only, args.ipv6_only) {
+            (true, _) => Some(config::IpVersion::V4),
+            (_, true) => Some(config::IpVersion::V6),
+            _ => None,
+        },
+        ipv4: args.ip,
+        ipv6: args.ip6,
+        ttl: args.ttl,
+        proxied: args.proxied,
+    });
+    if let Some(Command::GenerateConfig) = args.command {
+        let _ = std::io::Write::write_all(&mut std::io::stdout(), sample::render().as_bytes());
+        return Ok(std::process::ExitCode::SUCCESS);
+    }
+    if let Some(Command::Mangen { out_dir }) = &args.command {
+        manpage::generate(<Args as clap::CommandFactory>::command(), out_dir.as_deref())
+            .map_err(|e| tr!("无法生成 man 页: {}", "Failed to generate man pages: {}", e))?;
+        return Ok(std::process::ExitCode::SUCCESS);
+    }
+    let log_level = args.log_level.or(match args.verbose {
+        0 => None,
+        1 => Some(logging::LogLevel::Debug),
+        _ => Some(logging::LogLevel::Trace),
+    });
+    // 日志在加载配置之前初始化，这里读取的配置只用于日志与调度参数，配置错误留给后续加载时报告
+    let file_config = load_config(&args.config).ok();
+    let mut logging_config = file_config.as_ref().map(|c| c.logging.clone()).unwrap_or_default();
+    if let Some(log_file) = &args.log_file {
+        logging_config.file = Some(log_file.clone());
+    }
+    if let Some(log_format) = args.log_format {
+        logging_config.format = log_format;
+    }
+    // 作为 Windows 服务运行时没有控制台，日志同时写入事件日志
+    #[cfg(windows)]
+    if matches!(args.command, Some(Command::Service { action: service::ServiceAction::Run })) {
+        logging_config.eventlog = true;
+    }
+    // 结构化结果输出到标准输出时，日志改为输出到标准错误
+    let log_to_stderr = args.output == summary::OutputFormat::Json || matches!(args.command, Some(Command::Batch));
+    let _log_guard = logging::init(log_level, args.quiet, log_to_stderr, !args.no_color, &logging_config)
+        .map_err(|e| tr!("无法初始化日志: {}", "Failed to initialize logging: {}", e))?;
+    debug!(config = %args.config, "{}", tr!("程序启动", "Starting"));
+
+    // 命令行未指定间隔时，使用配置文件中的 schedule.interval 与 schedule.reconcile_interval
+    let schedule = file_config.as_ref().map(|c| c.schedule.clone()).unwrap_or_default();
+    let interval = args.interval.or(schedule.interval);
+    let reconcile_interval = args.reconcile_interval.or(schedule.reconcile_interval);
+
+    let result = match (&args.command, interval) {
+        (None | Some(Command::Run), Some(interval)) => {
+            run_daemon(args, interval, reconcile_interval, file_config).await;
+            return Ok(std::process::ExitCode::SUCCESS);
+        }
+        (Some(Command::Run), None) => Err(summary::RunError::new(
+            summary::FailureKind::Config,
+            tr!("run 需要通过 --interval 或配置中的 schedule.interval 指定间隔", "run requires an interval via --interval or schedule.interval in the config"),
+        )),
+        // 单次运行模式，退出码反映运行结果
+        (None | Some(Command::Once), _) => {
+            run_ddns_update(&args.config, args.force, args.check_only, args.dry_run, args.output, args.timeout).await
+        }
+        (Some(Command::Check), _) => run_ddns_update(&args.config, args.force, true, false, args.output, args.timeout).await,
+        (Some(Command::Plan { out }), _) => plan::plan(&args.config, out.as_deref(), args.force, args.output).await,
+        (Some(Command::Apply { plan_file, yes }), _) => {
+            plan::apply(&args.config, plan_file.as_deref(), *yes, args.force, args.output).await
+        }
+        (Some(Command::Batch), _) => batch::run(&args.config, args.dry_run).await,
+        (Some(Command::Update { name, address, ipv6 }), _) => run_adhoc_update(&args.config, name, *address, *ipv6, args.force, args.dry_run).await,
+        (Some(Command::Validate), _) => commands::validate(&args.config).map(|()| 0),
+        (Some(Command::Status { json }), _) => commands::status(&args.config, *json).await.map(|()| 0),
+        (Some(Command::Verify { resolver, json }), _) => commands::verify(&args.config, resolver, *json).await,
+        (Some(Command::Config { action }), _) => config_edit::run(action, &args.config).map(|()| 0),
+        (Some(Command::Auth { action }), _) => auth::run(action, &args.config).await.map(|()| 0),
+        (Some(Command::Doctor), _) => Ok(doctor::doctor(&args.config).await),
+        (Some(Command::Zones { json }), _) => commands::zones(&args.config, *json).await.map(|()| 0),
+        (Some(Command::Records { json }), _) => commands::records(&args.config, *json).await.map(|()| 0),
+        #[cfg(windows)]
+        (Some(Command::Service { action: service::ServiceAction::Run }), interval) => match interval {
+            Some(interval) => service::run_windows_service(run_daemon(args, interval, reconcile_interval, file_config)),
+            None => Err(summary::RunError::new(
+                summary::FailureKind::Config,
+                tr!("服务需要通过 --interval 或配置中的 schedule.interval 指定间隔", "The service requires an interval via --interval or schedule.interval in the config"),
+            )),
+        }
+        .map(|()| 0),
+        #[cfg(windows)]
+        (Some(Command::Service { action }), _) => {
+            service::manage_windows_service(action, &args.config, args.interval, args.reconcile_interval).map(|()| 0)
+        }
+        (Some(Command::Healthcheck { max_age }), interval) => {
+            let max_age = max_age.or(interval.map(|interval| interval * 2)).unwrap_or(Duration::from_secs(600));
+            Ok(commands::healthcheck(&args.config, max_age))
+        }
+        (Some(Command::InstallService(options)), _) => {
+            service::install_systemd(options, &args.config, args.interval, args.reconcile_interval).map(|()| 0)
+        }
+        (Some(Command::InstallLaunchd(options)), _) => {
+            service::install_launchd(options, &args.config, args.interval, args.reconcile_interval).map(|()| 0)
+        }
+        (Some(Command::Completions { .. } | Command::GenerateConfig | Command::Mangen { .. }), _) => unreachable!(),
+        #[cfg(unix)]
+        (Some(Command::Ctl { action }), _) => control::ctl(&args.config, *action).await,
+        (Some(Command::History { since, limit, json }), _) => {
+            let filter = history::HistoryFilter { records: args.records.clone(), since: *since, limit: *limit };
+            commands::history(&args.config, &filter, *json).map(|()| 0)
+        }
+    };
+
+    let code = match result {
+        Ok(code) => code,
+        Err(e) => {
+            error!(error = %e, category = %e.category, "{}", tr!("运行失败", "Run failed"));
+            e.exit_code()
+        }
+    };
+    Ok(std::process::ExitCode::from(code))
+}
+
+/// 定时运行，直到进程退出
+async fn run_daemon(args: &Args, interval: Duration, reconcile_interval: Option<Duration>, file_config: Option<Config>) {
+    info!(interval = %duration::format_duration(interval), "{}", tr!("以定时模式启动 CloudFlare DDNS", "Starting CloudFlare DDNS in scheduled mode"));
+    if let Some(reconcile_interval) = reconcile_interval {
+        info!(
+            interval = %duration::format_duration(interval),
+            reconcile_interval = %duration::format_duration(reconcile_interval),
+            "{}", tr!("两级调度已启用：每个间隔只检测 IP，按核对间隔完整核对 DNS 记录", "Two-tier scheduling enabled: detect IP every interval, fully reconcile DNS records every reconcile interval")
+        );
+    }
     
-    // 如果指定了定时运行间隔，则以定时模式运行
-    if let Some(interval) = args.interval {
-        println!("以定时模式启动 CloudFlare DDNS，间隔 {} 秒", interval);
-        
-        // 创建一个闭包，用于执行 DDNS 更新逻辑
-        let config_path = args.config.clone();
-        let force_update = args.force;
-        let check_only = args.check_only;
-        
-        scheduler::run_with_schedule(interval, move || {
-            let config_path = config_path.clone();
-            let force_update = force_update;
-            let check_only = check_only;
-            
-            async move {
-                run_ddns_update(&config_path, force_update, check_only).await
+    // 创建一个闭包，用于执行 DDNS 更新逻辑
+    let config_path = args.config.clone();
+    let force_update = args.force;
+    let check_only = args.check_only;
+    let dry_run = args.dry_run;
+    let state = Arc::new(Mutex::new(DaemonState {
+        started_at: Some(Local::now()),
+        interval,
+        reconcile_interval,
+        ..DaemonState::default()
+    }));
+
+    if let Some(config) = file_config {
+        spawn_state_dump(state.clone(), config.schedule.state_dump_file.clone());
+        #[cfg(unix)]
+        if let Some(path) = &config.schedule.control_socket {
+            control::spawn(path, &config_path, state.clone());
+        }
+
+        // 启动状态 HTTP 服务
+        #[cfg(feature = "http-server")]
+        if let Some(http_config) = &config.http {
+            http::spawn(http_config, &config_path, state.clone(), interval * 2).await;
+        }
+        #[cfg(not(feature = "http-server"))]
+        if config.http.is_some() {
+            warn!("{}", tr!("编译时未启用 http-server 功能，状态 HTTP 服务配置已忽略", "Built without the http-server feature, status HTTP server ignored"));
+        }
+
+        let mut state = state.lock().await;
+
+        // 连接 MQTT 并发布在线状态
+        #[cfg(feature = "mqtt")]
+        if let Some(mqtt_config) = &config.mqtt {
+            match mqtt::MqttPublisher::connect(mqtt_config) {
+                Ok(mqtt) => state.mqtt = Some(mqtt),
+                Err(e) => error!(error = %e, "{}", tr!("MQTT 配置无效，已禁用", "Invalid MQTT configuration, MQTT disabled")),
             }
-        }).await;
-    } else {
-        // 单次运行模式
-        run_ddns_update(&args.config, args.force, args.check_only).await?;
+        }
+        #[cfg(not(feature = "mqtt"))]
+        if config.mqtt.is_some() {
+            warn!("{}", tr!("编译时未启用 mqtt 功能，MQTT 配置已忽略", "Built without the mqtt feature, MQTT settings ignored"));
+        }
+
+        #[cfg(feature = "metrics")]
+        {
+            state.metrics_textfile = config.metrics.as_ref().and_then(|m| m.textfile.clone());
+        }
+        #[cfg(not(feature = "metrics"))]
+        if config.metrics.as_ref().is_some_and(|m| m.textfile.is_some()) {
+            warn!("{}", tr!("编译时未启用 metrics 功能，指标文件配置已忽略", "Built without the metrics feature, metrics textfile ignored"));
+        }
+        state.store = Some(state_store::open(&config));
+
+        // 发送启动通知（仅发送给订阅了 startup 事件的后端）
+        let notifiers = notify::Notifiers::from_config(&config.notifications, config.schedule.timeout());
+        notifiers.notify(&notify::NotificationEvent::startup(config.zone_name())).await;
+        state.notification_queue = notifiers.into_queue();
     }
     
-    Ok(())
+    let hooks = console_hooks().wake_on(state.lock().await.wake.clone());
+    scheduler::run_with_schedule(interval, hooks, move || {
+        let config_path = config_path.clone();
+        let state = state.clone();
+        
+        async move {
+            if check_only || dry_run {
+                run_ddns_update(&config_path, force_update, check_only, dry_run, summary::OutputFormat::Text, None).await
+                    .map_err(Error::scheduler)?;
+                return Ok(scheduler::NextRun::Interval);
+            }
+            let started = Instant::now();
+            let result = run_scheduled_tick(&config_path, force_update, reconcile_interval, &state).await;
+            finish_tick(&state, result.as_ref().err().map(|e| e.to_string()), started.elapsed()).await;
+            result
+        }
+    }).await;
 }
 
-async fn run_ddns_update(config_path: &str, force: bool, check_only: bool) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    println!("准备加载配置文件: {}", config_path);
-    // 从配置文件加载配置
-    let config = load_config(config_path)?;
-    
-    // 在 check_only 模式下，我们只获取外部 IP，不进行 API 调用
-    if check_only {
-        println!("仅检查模式 - 正在获取配置中的记录的外部 IP 地址...");
-        
-        for record_config in &config.dns_records {
-            let ip_version = record_config.get_ip_version()
-                .map_err(|e| format!("IP 版本无效: {}", e))?;
-            let current_ip = match ip_version {
-                config::IpVersion::V4 => ip_utils::get_external_ipv4().await?,
-                config::IpVersion::V6 => ip_utils::get_external_ipv6().await?,
+/// 在控制台输出每次执行的开始、成功与失败
+fn console_hooks() -> scheduler::ScheduleHooks {
+    scheduler::ScheduleHooks::new()
+        .on_start(|info| async move {
+            debug!(
+                execution = info.execution,
+                started_at = %info.started_at.format("%Y-%m-%d %H:%M:%S"),
+                "{}", tr!("定时任务开始执行", "Scheduled run started")
+            );
+        })
+        .on_success(|info| async move {
+            debug!(
+                execution = info.execution,
+                duration = format_args!("{:.2}s", info.elapsed.as_secs_f64()),
+                "{}", tr!("定时任务执行成功", "Scheduled run succeeded")
+            );
+        })
+        .on_failure(|info| async move {
+            error!(
+                execution = info.execution,
+                duration = format_args!("{:.2}s", info.elapsed.as_secs_f64()),
+                panicked = info.panicked,
+                error = %info.error,
+                "{}", tr!("定时任务执行失败", "Scheduled run failed")
+            );
+        })
+}
+
+/// 定时模式在多次执行之间保留的状态
+#[derive(Default)]
+struct DaemonState {
+    /// 上一次核对时使用的 IP
+    last_ips: Option<ip_utils::DetectedIps>,
+    /// 上一次完整核对的时间
+    last_reconcile: Option<Instant>,
+    /// 失败后等待重试的记录
+    retry_queue: retry::RetryQueue,
+    /// 因冷却时间未更新的记录（名称、类型）及冷却结束的时间，到期后提前执行
+    held: BTreeMap<(String, String), Instant>,
+    /// 每条记录的连续失败次数，用于通知策略
+    failure_counts: notify::FailureCounts,
+    /// 发送失败、等待重试的通知
+    notification_queue: notify::NotificationQueue,
+    /// MQTT 状态发布，启动时根据配置连接
+    #[cfg(feature = "mqtt")]
+    mqtt: Option<mqtt::MqttPublisher>,
+    /// 累计的运行指标
+    metrics: metrics::Metrics,
+    /// 每次执行后写入指标的 textfile 路径
+    #[cfg(feature = "metrics")]
+    metrics_textfile: Option<String>,
+    /// 持久化状态，每次执行后写入运行统计供 healthcheck 读取
+    store: Option<Box<dyn state_store::StateStore>>,
+    /// 最近一次成功执行的时间
+    last_success: Option<DateTime<Local>>,
+    /// 最近一次执行的统计，供状态 HTTP 服务的 /healthz 使用
+    run_stats: Option<state_store::RunStats>,
+    /// 以下字段仅用于 SIGUSR2 状态转储
+    started_at: Option<DateTime<Local>>,
+    interval: Duration,
+    reconcile_interval: Option<Duration>,
+    /// 每条记录最近一次的处理结果，按 [`record_key`] 索引
+    records: BTreeMap<String, RecordState>,
+    /// 最近一次执行失败的时间与错误
+    last_error: Option<(DateTime<Local>, String)>,
+    /// 通过控制套接字暂停定时执行
+    paused: bool,
+    /// 通知调度器立即开始下一次执行
+    wake: Arc<tokio::sync::Notify>,
+}
+
+/// 状态转储中记录的索引 `名称/类型`，双栈名称的 A 与 AAAA 记录分开
+fn record_key(name: &str, record_type: &str) -> String {
+    format!("{}/{}", name, record_type)
+}
+
+/// 状态转储中单条记录最近一次的处理结果
+#[derive(Debug, serde::Serialize)]
+struct RecordState {
+    ip: Option<String>,
+    status: &'static str,
+    error: Option<String>,
+    /// RFC 3339 格式的处理时间
+    at: String,
+}
+
+impl DaemonState {
+    /// 保存每条记录的处理结果并更新指标，启用 MQTT 时同时发布
+    async fn record_results(&mut self, results: &[cloudflare_ddns::update::RecordResult]) {
+        self.metrics.record_results(results);
+        let now = Instant::now();
+        for result in results {
+            let key = (result.name.clone(), result.record_type.clone());
+            match result.outcome.as_ref().ok().and_then(RecordOutcome::held_for) {
+                Some(remaining) => self.held.insert(key, now + remaining),
+                None => self.held.remove(&key),
+            };
+            let (status, error) = match &result.outcome {
+                Ok(RecordOutcome::Unchanged) => ("unchanged", None),
+                Ok(RecordOutcome::Updated { .. }) => ("updated", None),
+                Ok(RecordOutcome::Created { .. }) => ("created", None),
+                Ok(RecordOutcome::Held { .. }) => ("held", None),
+                Err(e) => ("failed", Some(e.message.clone())),
             };
-            
-            println!("外部 IP 地址 {} ({}): {}", record_config.name, record_config.ip_version, current_ip);
+            self.records.insert(record_key(&result.name, &result.record_type), RecordState { ip: result.ip.clone(), status, error, at: Local::now().to_rfc3339() });
+        }
+
+        #[cfg(feature = "mqtt")]
+        if let Some(mqtt) = &self.mqtt {
+            for result in results {
+                mqtt.publish_record(result).await;
+            }
         }
-        
-        println!("仅检查模式完成 - 未更新任何 DNS 记录.");
-        return Ok(());
     }
-    
-    // 创建 CloudFlare 客户端 (仅在非 check_only 模式下)
-    let auth_type = config.cloudflare.get_auth_type()
-        .map_err(|e| format!("认证类型无效: {}", e))?;
-    let cf_client = match auth_type {
-        config::AuthType::EmailKey => {
-            let email = config.cloudflare.auth_email
-                .as_ref()
-                .ok_or("使用邮箱+密钥认证时，邮箱是必需的")?;
-            let key = config.cloudflare.auth_key
-                .as_ref()
-                .ok_or("使用邮箱+密钥认证时，密钥是必需的")?;
-            cloudflare::CloudflareClient::new(email.clone(), key.clone())
-        },
-        config::AuthType::Token => {
-            let token = config.cloudflare.api_token
-                .as_ref()
-                .ok_or("使用令牌认证时，API 令牌是必需的")?;
-            cloudflare::CloudflareClient::new_with_token(token.clone())
+
+    /// 按本次的结果更新重试队列，记录用尽快速重试次数时发送 retries_exhausted 通知
+    async fn track_retries(&mut self, config: &Config, notifiers: &notify::Notifiers, results: &[cloudflare_ddns::update::RecordResult]) {
+        let policy = retry::RetryPolicy {
+            backoff: config.schedule.retry_backoff(),
+            max_attempts: config.schedule.retry_max_attempts(),
+            interval: self.interval,
+        };
+        let now = Instant::now();
+        for result in results {
+            let Err(e) = &result.outcome else {
+                self.retry_queue.record_success(&result.name, &result.record_type);
+                continue;
+            };
+            if self.retry_queue.record_failure(&result.name, &result.record_type, policy, now) != retry::RetryStatus::Exhausted {
+                continue;
+            }
+            error!(
+                record = %result.name,
+                r#type = %result.record_type,
+                attempts = policy.max_attempts,
+                "{}", tr!("记录多次重试后仍失败，改为按正常间隔重试", "Record still failing after retries, falling back to the normal interval")
+            );
+            let is_result = |record: &config::DnsRecordConfig| record.name == result.name && record.r#type == result.record_type;
+            let zone = config.zones().into_iter()
+                .find(|zone| zone.dns_records.iter().any(is_result))
+                .map(|zone| zone.zone_name)
+                .unwrap_or_default();
+            let routes = config.records().find(|record| is_result(record)).map(|record| record.notify.clone()).unwrap_or_default();
+            let event = notify::NotificationEvent::retries_exhausted(
+                &result.name, &zone, result.ip.as_deref(), &e.message, e.category, policy.max_attempts + 1,
+            );
+            notifiers.notify(&event.routed_to(&routes)).await;
         }
-    };
-    
-    // 获取 Zone ID - 添加更友好的错误处理
-    let zone_id = match cf_client.get_zone_id(&config.cloudflare.zone_name).await {
-        Ok(id) => {
-            println!("区域 ID: {}", id);
-            id
-        },
+    }
+
+    /// 转储为 JSON，供排查长时间运行的实例
+    fn dump(&self) -> serde_json::Value {
+        #[cfg(feature = "mqtt")]
+        let mqtt_connected = self.mqtt.is_some();
+        #[cfg(not(feature = "mqtt"))]
+        let mqtt_connected = false;
+        serde_json::json!({
+            "started_at": self.started_at.map(|t| t.to_rfc3339()),
+            "interval": duration::format_duration(self.interval),
+            "reconcile_interval": self.reconcile_interval.map(duration::format_duration),
+            "ips": {
+                "v4": self.last_ips.as_ref().and_then(|ips| ips.v4.clone()),
+                "v6": self.last_ips.as_ref().and_then(|ips| ips.v6.clone()),
+            },
+            "last_reconcile_secs_ago": self.last_reconcile.map(|t| t.elapsed().as_secs()),
+            "records": self.records,
+            "retry_queue": self.retry_queue.dump(Instant::now()),
+            "paused": self.paused,
+            "pending_notifications": self.notification_queue.len(),
+            "mqtt_connected": mqtt_connected,
+            "scheduler": self.metrics.stats(),
+            "last_success": self.last_success.map(|t| t.to_rfc3339()),
+            "last_error": self.last_error.as_ref().map(|(at, error)| serde_json::json!({
+                "at": at.to_rfc3339(),
+                "error": error,
+            })),
+        })
+    }
+}
+
+/// 收到 SIGUSR2 时转储定时模式的内部状态，`file` 为空时打印到标准错误
+#[cfg(unix)]
+fn spawn_state_dump(state: Arc<Mutex<DaemonState>>, file: Option<String>) {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut signals = match signal(SignalKind::user_defined2()) {
+        Ok(signals) => signals,
         Err(e) => {
-            return Err(format!("无法获取区域 ID。请检查您的 API 凭据和域名。错误: {}", e).into());
+            warn!(error = %e, "{}", tr!("无法监听 SIGUSR2，状态转储不可用", "Failed to listen for SIGUSR2, state dump disabled"));
+            return;
         }
     };
-    
-    // 处理每个 DNS 记录
-    for record_config in &config.dns_records {
-        println!("正在处理记录: {}", record_config.name);
-        
-        let ip_version = record_config.get_ip_version()
-            .map_err(|e| format!("IP 版本无效: {}", e))?;
-        let current_ip = match ip_version {
-            config::IpVersion::V4 => ip_utils::get_external_ipv4().await?,
-            config::IpVersion::V6 => ip_utils::get_external_ipv6().await?,
-        };
-        
-        println!("当前外部 IP: {}", current_ip);
-        
-        // 获取现有的 DNS 记录 - 添加更友好的错误处理
-        match cf_client.get_dns_record_id(&zone_id, &record_config.name).await {
-            Ok(record_id) => {
-                let existing_record = match cf_client.get_dns_record(&zone_id, &record_id).await {
-                    Ok(record) => record,
-                    Err(e) => {
-                        return Err(format!("无法获取 DNS 记录详情。请检查您的 API 凭据。错误: {}", e).into());
-                    }
-                };
-                
-                // 检查 IP 是否发生变化，或者是否强制更新
-                if existing_record.content != current_ip || force {
-                    println!("IP 已更改或强制更新请求.正在更新 DNS 记录...");
-                    
-                    let updated_record = match cf_client
-                        .update_dns_record(
-                            UpdateDnsRecordParams {
-                                zone_id: &zone_id,
-                                record_id: &record_id,
-                                record_type: &record_config.r#type,
-                                name: &record_config.name,
-                                content: &current_ip,
-                                ttl: record_config.ttl,
-                                proxied: record_config.proxied,
-                            }
-                        )
-                        .await {
-                            Ok(record) => record,
-                            Err(e) => {
-                                return Err(format!("无法更新 DNS 记录。请检查您的 API 凭据和权限。错误: {}", e).into());
-                            }
-                        };
-                    
-                    println!(
-                        "DNS 记录更新成功！新 IP: {}",
-                        updated_record.content
-                    );
-                } else {
-                    println!("IP 未更改.无需更新.");
-                }
-            }
-            Err(_) => {
-                // 如果记录不存在，创建新的记录
-                println!("DNS 记录不存在，正在创建新记录...");
-                
-                let new_record = match cf_client
-                    .create_dns_record(
-                        &zone_id,
-                        &record_config.r#type,
-                        &record_config.name,
-                        &current_ip,
-                        record_config.ttl,
-                        record_config.proxied,
-                    )
-                    .await {
-                        Ok(record) => record,
-                        Err(e) => {
-                            return Err(format!("无法创建 DNS 记录。请检查您的 API 凭据和权限。错误: {}", e).into());
-                        }
-                    };
-                
-                println!("新的 DNS 记录已创建: {}", new_record.content);
+    tokio::spawn(async move {
+        while signals.recv().await.is_some() {
+            let dump = serde_json::to_string_pretty(&state.lock().await.dump()).unwrap_or_default();
+            match &file {
+                Some(path) => match state_store::write_atomic(std::path::Path::new(path), (dump + "\n").as_bytes()) {
+                    Ok(()) => info!(path = %path, "{}", tr!("已写入状态转储", "State dump written")),
+                    Err(e) => warn!(path = %path, error = %e, "{}", tr!("无法写入状态转储", "Failed to write state dump")),
+                },
+                None => eprintln!("{}", dump),
             }
         }
+    });
+}
+
+#[cfg(not(unix))]
+fn spawn_state_dump(_state: Arc<Mutex<DaemonState>>, _file: Option<String>) {}
+
+/// 定时模式单次执行结束后更新运行指标与状态文件，配置了 textfile 时写入文件
+async fn finish_tick(state: &Mutex<DaemonState>, error: Option<String>, elapsed: Duration) {
+    let mut state = state.lock().await;
+    let now = Local::now();
+    state.metrics.record_run(error.is_none(), elapsed);
+    match &error {
+        Some(error) => state.last_error = Some((now, error.clone())),
+        None => state.last_success = Some(now),
+    }
+    let stats = state_store::RunStats::new(now, state.last_success, error);
+    if let Some(store) = &state.store
+        && let Err(e) = store.save_run_stats(&stats)
+    {
+        warn!(error = %e, "{}", tr!("无法写入状态文件", "Failed to write state file"));
+    }
+    state.run_stats = Some(stats);
+    #[cfg(feature = "metrics")]
+    if let Some(path) = &state.metrics_textfile
+        && let Err(e) = metrics::write_textfile(std::path::Path::new(path), &state.metrics.render())
+    {
+        warn!(path = %path, error = %e, "{}", tr!("无法写入指标文件", "Failed to write metrics textfile"));
     }
-    
-    Ok(())
 }
 
-fn load_config(config_path: &str) -> Result<Config, Box<dyn std::error::Error + Send + Sync>> {
-    let content = std::fs::read_to_string(config_path)?;
-    println!("正在加载配置文件: {}", config_path);
-    println!("配置文件内容: {}", content);
-    
-    let config: Config = match serde_json::from_str(&content) {
-        Ok(config) => config,
-        Err(e) => {
-            eprintln!("JSON 解析错误: {}", e);
-            eprintln!("错误位置: 行 {}, 列 {}", e.line(), e.column());
-            return Err(Box::new(e));
+/// 定时模式的单次执行
+///
+/// 先检测 IP；若启用了两级调度，仅在 IP 变化或到达核对间隔时调用 API。
+/// 有记录失败时，按重试队列中每条记录的退避时间提前执行，只重试到期的记录。
+async fn run_scheduled_tick(
+    config_path: &str,
+    force: bool,
+    reconcile_interval: Option<Duration>,
+    state: &Mutex<DaemonState>,
+) -> Result<scheduler::NextRun, Error> {
+    if state.lock().await.paused {
+        info!("{}", tr!("定时执行已暂停，跳过本次执行", "Scheduled runs are paused, skipping"));
+        return Ok(scheduler::NextRun::Interval);
+    }
+    let _lock = lock::acquire(config_path).await;
+    let config = load_config(config_path)?;
+    let (ips, times) = ip_utils::detect_for(&config).await?;
+
+    let mut state = state.lock().await;
+    #[cfg(feature = "mqtt")]
+    if let Some(mqtt) = &state.mqtt {
+        mqtt.publish_ips(&ips).await;
+    }
+
+    // 先重试上一次未能发送的通知
+    let notifiers = notify::Notifiers::from_config(&config.notifications, config.schedule.timeout())
+        .with_queue(std::mem::take(&mut state.notification_queue));
+    notifiers.retry_pending().await;
+
+    let changed = state.last_ips.as_ref() != Some(&ips);
+    let ip_changed = changed && state.last_ips.is_some();
+    state.metrics.record_ips(&ips, ip_changed);
+    let due = match reconcile_interval {
+        Some(reconcile_interval) => state.last_reconcile
+            .is_none_or(|last| last.elapsed() >= reconcile_interval),
+        None => true,
+    };
+    let now = Instant::now();
+    let mut retry_due: HashSet<(String, String)> = state.retry_queue.due(now).into_iter().collect();
+    retry_due.extend(state.held.iter().filter(|(_, until)| **until <= now).map(|(key, _)| key.clone()));
+
+    if !changed && !due && retry_due.is_empty() {
+        debug!("{}", tr!("IP 未变化，跳过本次核对", "IP unchanged, skipping reconciliation"));
+        log_heartbeat(&ips, &[]);
+        state.notification_queue = notifiers.into_queue();
+        return Ok(next_retry(&state));
+    }
+
+    // IP 未变化且未到核对时间时，只重试到期的失败记录与冷却结束的记录
+    let only = if !changed && !due {
+        info!(count = retry_due.len(), "{}", tr!("重试失败或冷却结束的记录", "Retrying failed records and records whose cooldown ended"));
+        Some(retry_due)
+    } else {
+        if changed && reconcile_interval.is_some() {
+            info!("{}", tr!("检测到 IP 变化，立即执行完整核对", "IP change detected, reconciling all records now"));
         }
+        None
+    };
+
+    let result = reconcile_tracked(&config, &notifiers, &ips, &times, force, only.as_ref(), &mut state.failure_counts).await;
+    if let Ok(results) = &result {
+        state.track_retries(&config, &notifiers, results).await;
+    }
+    state.notification_queue = notifiers.into_queue();
+    let results = result?;
+    log_heartbeat(&ips, &results);
+    state.record_results(&results).await;
+
+    if let Some(store) = &state.store
+        && let Err(e) = store.save_last_ips(&ips)
+    {
+        warn!(error = %e, "{}", tr!("无法写入状态文件", "Failed to write state file"));
+    }
+    state.last_ips = Some(ips);
+    if only.is_none() {
+        state.last_reconcile = Some(Instant::now());
+    }
+
+    let next_run = next_retry(&state);
+    let failed: Vec<&str> = results.iter().filter(|result| result.outcome.is_err()).map(|result| result.name.as_str()).collect();
+    if let scheduler::NextRun::After(backoff) = next_run
+        && !failed.is_empty()
+    {
+        warn!(
+            count = failed.len(),
+            records = ?failed,
+            backoff = %duration::format_duration(backoff),
+            "{}", tr!("部分记录处理失败，将提前重试", "Some records failed, retrying early")
+        );
+    }
+    Ok(next_run)
+}
+
+/// 重试队列中有待快速重试的记录、或有记录因冷却时间未更新时，在最早的一条到期时提前执行
+fn next_retry(state: &DaemonState) -> scheduler::NextRun {
+    let now = Instant::now();
+    let held = state.held.values().map(|until| until.saturating_duration_since(now)).min();
+    match state.retry_queue.next_retry(now).into_iter().chain(held).min() {
+        Some(delay) => scheduler::NextRun::After(delay),
+        None => scheduler::NextRun::Interval,
+    }
+}
+
+/// 单次运行：检测 IP 并核对所有记录，`output` 为 json 时在结束后打印结构化结果
+///
+/// 试运行时以文本输出将要执行的变更。返回进程退出码，见 [`summary::RunSummary::exit_code`]。
+async fn run_ddns_update(
+    config_path: &str,
+    force: bool,
+    check_only: bool,
+    dry_run: bool,
+    output: summary::OutputFormat,
+    timeout: Option<Duration>,
+) -> Result<u8, summary::RunError> {
+    let run = async {
+        // 只读的检查与试运行不需要等待其他进程
+        let _lock = match check_only || dry_run {
+            true => None,
+            false => lock::acquire(config_path).await,
+        };
+        run_once(config_path, force, check_only, dry_run).await
+    };
+    let result = match timeout {
+        // 超时后丢弃未完成的请求，已经完成的记录更新不会回滚
+        Some(timeout) => tokio::time::timeout(timeout, run).await.unwrap_or_else(|_| Err(summary::RunError::new(
+            summary::FailureKind::Timeout,
+            tr!("运行超过 {}，已中止", "Run exceeded {} and was aborted", duration::format_duration(timeout)),
+        ))),
+        None => run.await,
     };
-    Ok(config)
+    match (&result, output) {
+        (Ok(summary), summary::OutputFormat::Json) => summary.print(),
+        (Err(e), summary::OutputFormat::Json) => summary::RunSummary::error(e).print(),
+        (Ok(summary), summary::OutputFormat::Text) if dry_run => summary.print_plan(),
+        _ => {}
+    }
+
+    let summary = result?;
+    summary.log_failures();
+    Ok(summary.exit_code())
+}
+
+/// 把不在记录列表中的一条记录更新为指定或检测到的 IP；已有记录保留原来的 TTL 与代理设置（`--ttl` / `--proxied` 优先）
+async fn run_adhoc_update(
+    config_path: &str,
+    name: &str,
+    ip: Option<std::net::IpAddr>,
+    ipv6: bool,
+    force: bool,
+    dry_run: bool,
+) -> Result<u8, summary::RunError> {
+    use summary::{FailureKind, RunError};
+
+    let _lock = match dry_run {
+        true => None,
+        false => lock::acquire(config_path).await,
+    };
+    let config = load_config(config_path)?;
+    let ips = match ip {
+        Some(std::net::IpAddr::V4(ip)) => ip_utils::DetectedIps { v4: Some(ip.to_string()), ..Default::default() },
+        Some(std::net::IpAddr::V6(ip)) => ip_utils::DetectedIps { v6: Some(ip.to_string()), ..Default::default() },
+        None => {
+            let version = if ipv6 { config::IpVersion::V6 } else { config::IpVersion::V4 };
+            ip_utils::detect_ips(&[version], &config.ip_detection, config.schedule.timeout()).await?.0
+        }
+    };
+    let (record_type, ip_version) = if ips.v6.is_some() { ("AAAA", "v6") } else { ("A", "v4") };
+
+    let zone = config.zone_for(name).ok_or_else(|| RunError::new(
+        FailureKind::Config,
+        tr!("记录 {} 不属于配置中的任何区域", "Record {} does not belong to any configured zone", name),
+    ))?;
+    let provider = zone_provider(&config, &zone).await?;
+    let existing = provider.get_record(name, record_type).await?;
+    let existing = existing.as_ref();
+    let record_config = config::DnsRecordConfig {
+        name: name.to_string(),
+        r#type: record_type.to_string(),
+        ttl: overrides::ttl().or(existing.map(|r| r.ttl)).unwrap_or(1),
+        proxied: overrides::proxied().or(existing.map(|r| r.proxied)).unwrap_or(false),
+        ip_version: ip_version.to_string(),
+        uplink: None,
+        members: Vec::new(),
+        failover: None,
+        also_update: Vec::new(),
+        notify: Vec::new(),
+        cooldown: None,
+        alias_of: None,
+    };
+
+    if dry_run {
+        let (current_ip, change) = plan_record(&*provider, &record_config, &ips, force).await?;
+        return Ok(match change {
+            // 临时记录没有冷却时间，不会被暂缓
+            RecordChange::Noop | RecordChange::Skip | RecordChange::Hold { .. } => {
+                println!("= {} {} {}", name, record_type, current_ip);
+                0
+            }
+            RecordChange::Update { old_ip, .. } | RecordChange::Set { previous: Some(old_ip), .. } => {
+                println!("~ {} {} {} -> {}", name, record_type, old_ip, current_ip);
+                summary::EXIT_UPDATED
+            }
+            RecordChange::Create | RecordChange::Set { previous: None, .. } => {
+                println!("+ {} {} {}", name, record_type, current_ip);
+                summary::EXIT_UPDATED
+            }
+        });
+    }
+
+    let result = process_record(&*provider, &record_config, &ips, force).await;
+    record_history(&config, &zone.zone_name, &record_config, &result);
+    match result? {
+        RecordOutcome::Unchanged | RecordOutcome::Held { .. } => {
+            info!(record = %name, "{}", tr!("记录已指向该地址，无需更新", "Record already points at this address, nothing to update"));
+            Ok(0)
+        }
+        RecordOutcome::Updated { .. } | RecordOutcome::Created { .. } => Ok(summary::EXIT_UPDATED),
+    }
 }
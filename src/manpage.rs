@@ -0,0 +1,92 @@
+// man 页生成：命令行部分由 clap_mangen 渲染，另外补充配置项与退出码两节
+use std::io::{self, Write};
+use std::path::Path;
+
+use clap_mangen::Man;
+
+use cloudflare_ddns::i18n::tr;
+use cloudflare_ddns::summary;
+
+use crate::sample;
+
+/// 未指定 `out_dir` 时把主 man 页输出到标准输出；否则在该目录下写入主页面与每个子命令的页面
+pub fn generate(command: clap::Command, out_dir: Option<&Path>) -> io::Result<()> {
+    let mut command = command.disable_help_subcommand(true);
+    command.build();
+    let page = render(command.clone())?;
+    let Some(out_dir) = out_dir else {
+        // 管道提前关闭（如 `| head`）时不视为错误
+        let _ = io::stdout().write_all(&page);
+        return Ok(());
+    };
+
+    std::fs::create_dir_all(out_dir)?;
+    std::fs::write(out_dir.join(Man::new(command.clone()).get_filename()), &page)?;
+    let mut pending: Vec<clap::Command> = command.get_subcommands().cloned().collect();
+    while let Some(subcommand) = pending.pop() {
+        if subcommand.is_hide_set() {
+            continue;
+        }
+        pending.extend(subcommand.get_subcommands().cloned());
+        Man::new(subcommand).generate_to(out_dir)?;
+    }
+    Ok(())
+}
+
+/// 主 man 页：在子命令之后加入 CONFIGURATION 与 EXIT STATUS 两节
+fn render(command: clap::Command) -> io::Result<Vec<u8>> {
+    let has_version = command.get_version().is_some();
+    let man = Man::new(command);
+    let mut out = Vec::new();
+    man.render_title(&mut out)?;
+    man.render_name_section(&mut out)?;
+    man.render_synopsis_section(&mut out)?;
+    man.render_description_section(&mut out)?;
+    man.render_options_section(&mut out)?;
+    man.render_subcommands_section(&mut out)?;
+
+    writeln!(out, ".SH CONFIGURATION")?;
+    writeln!(out, "{}", escape(&tr!(
+        "配置文件为 JSON 格式，支持 // 与 /* */ 注释，默认读取当前目录下的 config.json，可用 -c 指定。generate-config 子命令输出带注释的完整示例。",
+        "The configuration file is JSON with // and /* */ comments allowed, read from config.json in the current directory unless -c is given. The generate-config subcommand prints a fully commented example."
+    )))?;
+    for (path, description) in sample::fields() {
+        writeln!(out, ".TP\n\\fB{}\\fR\n{}", escape(&path), escape(&description))?;
+    }
+
+    writeln!(out, ".SH \"EXIT STATUS\"")?;
+    for (code, description) in summary::exit_codes() {
+        writeln!(out, ".TP\n\\fB{}\\fR\n{}", code, escape(&description))?;
+    }
+
+    if has_version {
+        man.render_version_section(&mut out)?;
+    }
+    Ok(out)
+}
+
+/// 转义 roff 中的反斜杠与连字符，行首的 `.` 与 `'` 不被当作控制行
+fn escape(text: &str) -> String {
+    let escaped = text.replace('\\', "\\e").replace('-', "\\-").replace('\n', " ");
+    if escaped.starts_with('.') || escaped.starts_with('\'') {
+        format!("\\&{}", escaped)
+    } else {
+        escaped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn page_includes_config_fields_and_exit_codes() {
+        let command = clap::Command::new("cloudflare_ddns").subcommand(clap::Command::new("once"));
+        let page = String::from_utf8(render(command).unwrap()).unwrap();
+        assert!(page.contains(".SH CONFIGURATION\n"));
+        assert!(page.contains("\\fBcloudflare.api_token\\fR\n"));
+        assert!(page.contains(".SH \"EXIT STATUS\"\n"));
+        assert!(page.contains(".TP\n\\fB10\\fR\n"));
+        assert_eq!(escape(".hidden --flag"), "\\&.hidden \\-\\-flag");
+    }
+}
@@ -0,0 +1,204 @@
+// 运行指标：以 Prometheus 文本格式输出，可写入 node_exporter 的 textfile collector 目录
+use std::collections::BTreeMap;
+#[cfg(feature = "metrics")]
+use std::fmt::Write as _;
+#[cfg(feature = "metrics")]
+use std::io;
+#[cfg(feature = "metrics")]
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use cloudflare_ddns::ip_utils::DetectedIps;
+use cloudflare_ddns::update::{RecordOutcome, RecordResult, RecordTiming};
+
+/// 单条记录的指标
+#[derive(Debug, Default, Clone)]
+struct RecordMetrics {
+    updates: u64,
+    failures: u64,
+    up: bool,
+    /// 最近一次处理的耗时
+    timing: RecordTiming,
+}
+
+/// 定时模式累计的运行指标
+#[derive(Debug, Default)]
+pub struct Metrics {
+    runs: u64,
+    run_failures: u64,
+    ip_changes: u64,
+    last_run: Option<SystemTime>,
+    last_success: Option<SystemTime>,
+    last_duration: Duration,
+    ips: DetectedIps,
+    records: BTreeMap<String, RecordMetrics>,
+}
+
+impl Metrics {
+    /// 记录一次执行的结果与耗时
+    pub fn record_run(&mut self, success: bool, elapsed: Duration) {
+        let now = SystemTime::now();
+        self.runs += 1;
+        self.last_run = Some(now);
+        self.last_duration = elapsed;
+        if success {
+            self.last_success = Some(now);
+        } else {
+            self.run_failures += 1;
+        }
+    }
+
+    /// 记录本次检测到的公网 IP，`changed` 为 true 时计入 IP 变化次数
+    pub fn record_ips(&mut self, ips: &DetectedIps, changed: bool) {
+        if changed {
+            self.ip_changes += 1;
+        }
+        self.ips = ips.clone();
+    }
+
+    /// 记录每条记录的处理结果
+    pub fn record_results(&mut self, results: &[RecordResult]) {
+        for result in results {
+            let record = self.records.entry(result.name.clone()).or_default();
+            record.timing = result.timing;
+            match &result.outcome {
+                Ok(RecordOutcome::Unchanged | RecordOutcome::Held { .. }) => record.up = true,
+                Ok(RecordOutcome::Updated { .. } | RecordOutcome::Created { .. }) => {
+                    record.updates += 1;
+                    record.up = true;
+                }
+                Err(_) => {
+                    record.failures += 1;
+                    record.up = false;
+                }
+            }
+        }
+    }
+
+    /// 执行次数、失败次数与最近执行时间等调度统计，用于状态转储
+    pub fn stats(&self) -> serde_json::Value {
+        let time = |time: Option<SystemTime>| time.map(|time| chrono::DateTime::<chrono::Local>::from(time).to_rfc3339());
+        serde_json::json!({
+            "runs": self.runs,
+            "run_failures": self.run_failures,
+            "ip_changes": self.ip_changes,
+            "last_run": time(self.last_run),
+            "last_success": time(self.last_success),
+            "last_duration_secs": self.last_duration.as_secs_f64(),
+        })
+    }
+
+    /// 以 Prometheus 文本格式输出所有指标
+    #[cfg(feature = "metrics")]
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+
+        metric(&mut out, "cloudflare_ddns_runs_total", "counter", "Total number of scheduled runs.");
+        let _ = writeln!(out, "cloudflare_ddns_runs_total {}", self.runs);
+        metric(&mut out, "cloudflare_ddns_run_failures_total", "counter", "Total number of failed scheduled runs.");
+        let _ = writeln!(out, "cloudflare_ddns_run_failures_total {}", self.run_failures);
+        metric(&mut out, "cloudflare_ddns_ip_changes_total", "counter", "Total number of detected public IP changes.");
+        let _ = writeln!(out, "cloudflare_ddns_ip_changes_total {}", self.ip_changes);
+
+        metric(&mut out, "cloudflare_ddns_last_run_timestamp_seconds", "gauge", "Unix time of the last run.");
+        let _ = writeln!(out, "cloudflare_ddns_last_run_timestamp_seconds {}", unix_seconds(self.last_run));
+        metric(&mut out, "cloudflare_ddns_last_success_timestamp_seconds", "gauge", "Unix time of the last successful run.");
+        let _ = writeln!(out, "cloudflare_ddns_last_success_timestamp_seconds {}", unix_seconds(self.last_success));
+        metric(&mut out, "cloudflare_ddns_last_run_duration_seconds", "gauge", "Duration of the last run in seconds.");
+        let _ = writeln!(out, "cloudflare_ddns_last_run_duration_seconds {:.3}", self.last_duration.as_secs_f64());
+
+        metric(&mut out, "cloudflare_ddns_public_ip_info", "gauge", "Currently detected public IP address.");
+        for (version, ip) in [("v4", &self.ips.v4), ("v6", &self.ips.v6)] {
+            if let Some(ip) = ip {
+                let _ = writeln!(out, "cloudflare_ddns_public_ip_info{{version=\"{}\",ip=\"{}\"}} 1", version, escape(ip));
+            }
+        }
+
+        metric(&mut out, "cloudflare_ddns_record_up", "gauge", "Whether the last update of the record succeeded.");
+        for (name, record) in &self.records {
+            let _ = writeln!(out, "cloudflare_ddns_record_up{{record=\"{}\"}} {}", escape(name), u8::from(record.up));
+        }
+        metric(&mut out, "cloudflare_ddns_record_updates_total", "counter", "Total number of DNS record updates.");
+        for (name, record) in &self.records {
+            let _ = writeln!(out, "cloudflare_ddns_record_updates_total{{record=\"{}\"}} {}", escape(name), record.updates);
+        }
+        metric(&mut out, "cloudflare_ddns_record_failures_total", "counter", "Total number of failed DNS record updates.");
+        for (name, record) in &self.records {
+            let _ = writeln!(out, "cloudflare_ddns_record_failures_total{{record=\"{}\"}} {}", escape(name), record.failures);
+        }
+        metric(&mut out, "cloudflare_ddns_record_duration_seconds", "gauge", "Time spent on the record in the last run, by phase.");
+        for (name, record) in &self.records {
+            let timing = &record.timing;
+            for (phase, elapsed) in [("detection", timing.detection), ("api", timing.api), ("total", timing.total())] {
+                let _ = writeln!(out, "cloudflare_ddns_record_duration_seconds{{record=\"{}\",phase=\"{}\"}} {:.3}", escape(name), phase, elapsed.as_secs_f64());
+            }
+        }
+
+        out
+    }
+}
+
+#[cfg(feature = "metrics")]
+/// 写入 textfile collector 使用的 .prom 文件
+///
+/// 先写入同目录下的临时文件再重命名，避免 node_exporter 读到写了一半的文件。
+pub fn write_textfile(path: &Path, content: &str) -> io::Result<()> {
+    cloudflare_ddns::state::write_atomic(path, content.as_bytes())
+}
+
+#[cfg(feature = "metrics")]
+fn metric(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+#[cfg(feature = "metrics")]
+fn unix_seconds(time: Option<SystemTime>) -> u64 {
+    time.and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_secs())
+}
+
+#[cfg(feature = "metrics")]
+/// 转义标签值中的反斜杠、双引号与换行
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[cfg(all(test, feature = "metrics"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let mut metrics = Metrics::default();
+        metrics.record_ips(&DetectedIps { v4: Some("1.2.3.4".to_string()), ..Default::default() }, true);
+        metrics.record_results(&[
+            RecordResult {
+                name: "home.example.com".to_string(),
+                record_type: "A".to_string(),
+                ip: Some("1.2.3.4".to_string()),
+                outcome: Ok(RecordOutcome::Updated { old_ip: "5.6.7.8".to_string(), new_ip: "1.2.3.4".to_string() }),
+                timing: RecordTiming { detection: Duration::from_millis(200), api: Duration::from_millis(300) },
+            },
+            RecordResult {
+                name: "nas.example.com".to_string(),
+                record_type: "A".to_string(),
+                ip: Some("1.2.3.4".to_string()),
+                outcome: Err(cloudflare_ddns::update::RecordError { message: "timeout".to_string(), category: cloudflare_ddns::error::ErrorCategory::Network }),
+                timing: RecordTiming::default(),
+            },
+        ]);
+        metrics.record_run(false, Duration::from_millis(1500));
+
+        let out = metrics.render();
+        assert!(out.contains("cloudflare_ddns_runs_total 1\n"));
+        assert!(out.contains("cloudflare_ddns_run_failures_total 1\n"));
+        assert!(out.contains("cloudflare_ddns_ip_changes_total 1\n"));
+        assert!(out.contains("cloudflare_ddns_public_ip_info{version=\"v4\",ip=\"1.2.3.4\"} 1\n"));
+        assert!(out.contains("cloudflare_ddns_record_up{record=\"home.example.com\"} 1\n"));
+        assert!(out.contains("cloudflare_ddns_record_up{record=\"nas.example.com\"} 0\n"));
+        assert!(out.contains("cloudflare_ddns_record_failures_total{record=\"nas.example.com\"} 1\n"));
+        assert!(out.contains("cloudflare_ddns_last_run_duration_seconds 1.500\n"));
+        assert!(out.contains("cloudflare_ddns_record_duration_seconds{record=\"home.example.com\",phase=\"total\"} 0.500\n"));
+    }
+}
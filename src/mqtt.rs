@@ -0,0 +1,136 @@
+// 通过 MQTT 发布当前公网 IP、各记录状态与在线状态，便于 Home Assistant 等自动化系统使用
+use chrono::Local;
+use rumqttc::{AsyncClient, Event, LastWill, MqttOptions, Packet, QoS, TlsConfiguration, Transport};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::warn;
+
+use cloudflare_ddns::Error;
+use cloudflare_ddns::config::MqttConfig;
+use cloudflare_ddns::ip_utils::DetectedIps;
+use cloudflare_ddns::i18n::tr;
+use cloudflare_ddns::update::{RecordOutcome, RecordResult};
+
+/// 与 MQTT 服务器保持长连接的发布者
+///
+/// 主题（均为 retained）：
+/// - `{prefix}/availability`: "online"（每次连接后发布）/ "offline"（遗嘱消息）
+/// - `{prefix}/ip/v4`、`{prefix}/ip/v6`: 当前公网 IP
+/// - `{prefix}/records/{name}/{type}`: 记录状态 JSON
+pub struct MqttPublisher {
+    client: AsyncClient,
+    prefix: String,
+    qos: QoS,
+}
+
+impl MqttPublisher {
+    /// 根据配置连接服务器，连接在后台任务中维护并自动重连
+    pub fn connect(config: &MqttConfig) -> Result<Self, Error> {
+        let qos = match config.qos {
+            0 => QoS::AtMostOnce,
+            1 => QoS::AtLeastOnce,
+            2 => QoS::ExactlyOnce,
+            other => return Err(Error::config(tr!("无效的 QoS: {}", "Invalid QoS: {}", other))),
+        };
+        let prefix = config.topic_prefix.trim_end_matches('/').to_string();
+        let port = config.port.unwrap_or(if config.tls { 8883 } else { 1883 });
+
+        let mut options = MqttOptions::new(&config.client_id, &config.host, port);
+        options.set_keep_alive(Duration::from_secs(30));
+        let availability = format!("{}/availability", prefix);
+        options.set_last_will(LastWill::new(&availability, "offline", qos, true));
+        if let (Some(username), Some(password)) = (&config.username, &config.password) {
+            options.set_credentials(username, password);
+        }
+        if config.tls {
+            options.set_transport(Transport::tls_with_config(TlsConfiguration::Rustls(Arc::new(
+                tls_config(config.ca_file.as_deref())?,
+            ))));
+        }
+
+        let (client, mut eventloop) = AsyncClient::new(options, 64);
+        let online = client.clone();
+        tokio::spawn(async move {
+            loop {
+                match eventloop.poll().await {
+                    // 每次连接（包括重连）后重新发布在线状态，覆盖断线时服务器发布的遗嘱消息
+                    Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                        if let Err(e) = online.try_publish(&availability, qos, true, "online") {
+                            warn!(topic = %availability, error = %e, "{}", tr!("MQTT 发布失败", "MQTT publish failed"));
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(e) => {
+                        warn!(error = %e, "{}", tr!("MQTT 连接错误，5 秒后重连", "MQTT connection error, reconnecting in 5 seconds"));
+                        tokio::time::sleep(Duration::from_secs(5)).await;
+                    }
+                }
+            }
+        });
+
+        Ok(Self { client, prefix, qos })
+    }
+
+    /// 发布当前检测到的公网 IP
+    pub async fn publish_ips(&self, ips: &DetectedIps) {
+        if let Some(v4) = &ips.v4 {
+            self.publish("ip/v4", v4.clone());
+        }
+        if let Some(v6) = &ips.v6 {
+            self.publish("ip/v6", v6.clone());
+        }
+    }
+
+    /// 发布单条记录的处理结果
+    pub async fn publish_record(&self, result: &RecordResult) {
+        let status = match &result.outcome {
+            Ok(RecordOutcome::Unchanged) => "unchanged",
+            Ok(RecordOutcome::Updated { .. }) => "updated",
+            Ok(RecordOutcome::Created { .. }) => "created",
+            Ok(RecordOutcome::Held { .. }) => "held",
+            Err(_) => "failed",
+        };
+        let payload = serde_json::json!({
+            "status": status,
+            "ip": result.ip,
+            "error": result.outcome.as_ref().err().map(|e| &e.message),
+            "error_category": result.outcome.as_ref().err().map(|e| e.category),
+            "timestamp": Local::now().to_rfc3339(),
+        });
+        self.publish(&format!("records/{}/{}", result.name, result.record_type), payload.to_string());
+    }
+
+    /// 以 retained 方式发布消息，请求队列已满时丢弃并输出错误
+    fn publish(&self, topic: &str, payload: String) {
+        let topic = format!("{}/{}", self.prefix, topic);
+        if let Err(e) = self.client.try_publish(&topic, self.qos, true, payload) {
+            warn!(topic = %topic, error = %e, "{}", tr!("MQTT 发布失败", "MQTT publish failed"));
+        }
+    }
+}
+
+/// 使用与 reqwest 相同的 aws-lc-rs 加密后端构造 TLS 配置
+fn tls_config(ca_file: Option<&str>) -> Result<rustls::ClientConfig, Error> {
+    use rustls::pki_types::{CertificateDer, pem::PemObject};
+
+    let mut roots = rustls::RootCertStore::empty();
+    match ca_file {
+        Some(path) => {
+            for cert in CertificateDer::pem_file_iter(path).map_err(|e| ca_error(path, e))? {
+                roots.add(cert.map_err(|e| ca_error(path, e))?).map_err(|e| ca_error(path, e))?;
+            }
+        }
+        None => roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned()),
+    }
+
+    let provider = Arc::new(rustls::crypto::aws_lc_rs::default_provider());
+    Ok(rustls::ClientConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .map_err(Error::config)?
+        .with_root_certificates(roots)
+        .with_no_client_auth())
+}
+
+fn ca_error(path: &str, error: impl std::fmt::Display) -> Error {
+    Error::config(tr!("无法加载 CA 证书 {}: {}", "Failed to load CA certificate {}: {}", path, error))
+}
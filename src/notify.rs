@@ -0,0 +1,790 @@
+// IP 变化与更新失败时的通知
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::{error, info, warn};
+
+use crate::config::{NotificationRetryConfig, NotificationTemplates, NotificationsConfig, NotifyPolicy};
+use crate::error::ErrorCategory;
+use crate::i18n::tr;
+
+/// 重试队列的最大长度，超出时丢弃最早的通知
+const MAX_PENDING_NOTIFICATIONS: usize = 1000;
+
+/// 重试退避时间的上限
+const MAX_RETRY_BACKOFF: Duration = Duration::from_secs(3600);
+
+/// 记录的 `notify` 中可以使用的通知后端名称，与 `notifications` 中的配置项同名
+pub const BACKEND_NAMES: &[&str] = &["webhook", "telegram", "slack", "email", "gotify", "bark", "pushover", "serverchan", "dingtalk", "wecom", "matrix", "hooks"];
+
+#[cfg(feature = "notify-bark")]
+mod bark;
+#[cfg(feature = "notify-command")]
+mod command;
+#[cfg(feature = "notify-dingtalk")]
+mod dingtalk;
+#[cfg(feature = "notify-email")]
+mod email;
+#[cfg(feature = "notify-gotify")]
+mod gotify;
+#[cfg(feature = "notify-matrix")]
+mod matrix;
+#[cfg(feature = "notify-pushover")]
+mod pushover;
+#[cfg(feature = "notify-serverchan")]
+mod serverchan;
+#[cfg(feature = "notify-slack")]
+mod slack;
+#[cfg(feature = "notify-telegram")]
+mod telegram;
+#[cfg(feature = "notify-webhook")]
+mod webhook;
+#[cfg(feature = "notify-wecom")]
+mod wecom;
+
+pub type NotifyFuture<'a> = Pin<Box<dyn Future<Output = crate::error::Result<()>> + Send + 'a>>;
+
+/// 通知事件类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    /// 记录已更新或新建
+    Changed,
+    /// 记录更新失败
+    Failed,
+    /// 记录在连续失败后再次处理成功
+    Recovered,
+    /// 定时模式下记录用尽了快速重试次数仍失败，之后按正常间隔重试
+    RetriesExhausted,
+    /// 记录内容与上一次发布的内容不一致（被外部修改或删除）
+    Drift,
+    /// 记录更新过于频繁，超过了 `update_limit`，已停止更新
+    Flapping,
+    /// 定时模式启动
+    Startup,
+}
+
+/// 发送给各通知后端的事件
+#[derive(Debug, Clone, Serialize)]
+pub struct NotificationEvent {
+    pub event: EventKind,
+    pub record: String,
+    pub zone: String,
+    pub old_ip: Option<String>,
+    pub new_ip: Option<String>,
+    /// RFC 3339 格式的本地时间
+    pub timestamp: String,
+    /// "success" 或 "failure"
+    pub result: &'static str,
+    pub error: Option<String>,
+    /// 失败事件的错误类别，如 network、auth、rate_limited
+    pub error_category: Option<ErrorCategory>,
+    /// 失败事件为当前连续失败次数，恢复事件为恢复前的连续失败次数
+    pub consecutive_failures: u32,
+    /// 用户自定义的消息模板，为空时使用内置格式
+    #[serde(skip)]
+    pub template: Option<String>,
+    /// 只发给这些通知后端（`notifications` 中的配置项名称），为空时发给所有后端
+    #[serde(skip)]
+    pub routes: Vec<String>,
+}
+
+impl NotificationEvent {
+    /// 记录更新（或新建）成功
+    pub fn changed(record: &str, zone: &str, old_ip: Option<&str>, new_ip: &str) -> Self {
+        Self {
+            event: EventKind::Changed,
+            record: record.to_string(),
+            zone: zone.to_string(),
+            old_ip: old_ip.map(str::to_string),
+            new_ip: Some(new_ip.to_string()),
+            timestamp: Local::now().to_rfc3339(),
+            result: "success",
+            error: None,
+            error_category: None,
+            consecutive_failures: 0,
+            template: None,
+            routes: Vec::new(),
+        }
+    }
+
+    /// 记录更新失败，`new_ip` 为本次尝试发布的地址（如果已检测到）
+    pub fn failed(record: &str, zone: &str, new_ip: Option<&str>, error: &str, category: ErrorCategory, consecutive_failures: u32) -> Self {
+        Self {
+            event: EventKind::Failed,
+            record: record.to_string(),
+            zone: zone.to_string(),
+            old_ip: None,
+            new_ip: new_ip.map(str::to_string),
+            timestamp: Local::now().to_rfc3339(),
+            result: "failure",
+            error: Some(error.to_string()),
+            error_category: Some(category),
+            consecutive_failures,
+            template: None,
+            routes: Vec::new(),
+        }
+    }
+
+    /// 记录在连续失败 `previous_failures` 次后处理成功
+    pub fn recovered(record: &str, zone: &str, new_ip: Option<&str>, previous_failures: u32) -> Self {
+        Self {
+            event: EventKind::Recovered,
+            record: record.to_string(),
+            zone: zone.to_string(),
+            old_ip: None,
+            new_ip: new_ip.map(str::to_string),
+            timestamp: Local::now().to_rfc3339(),
+            result: "success",
+            error: None,
+            error_category: None,
+            consecutive_failures: previous_failures,
+            template: None,
+            routes: Vec::new(),
+        }
+    }
+
+    /// 记录快速重试 `attempts` 次后仍失败
+    pub fn retries_exhausted(record: &str, zone: &str, new_ip: Option<&str>, error: &str, category: ErrorCategory, attempts: u32) -> Self {
+        Self {
+            event: EventKind::RetriesExhausted,
+            ..Self::failed(record, zone, new_ip, error, category, attempts)
+        }
+    }
+
+    /// 记录被外部修改为 `found`（已删除时为空），`expected` 为应有的内容，`restored` 表示是否已改回
+    pub fn drift(record: &str, zone: &str, found: Option<&str>, expected: &str, restored: bool) -> Self {
+        Self {
+            event: EventKind::Drift,
+            record: record.to_string(),
+            zone: zone.to_string(),
+            old_ip: found.map(str::to_string),
+            new_ip: Some(expected.to_string()),
+            timestamp: Local::now().to_rfc3339(),
+            result: if restored { "success" } else { "failure" },
+            error: None,
+            error_category: None,
+            consecutive_failures: 0,
+            template: None,
+            routes: Vec::new(),
+        }
+    }
+
+    /// 记录更新过于频繁而停止更新，`detail` 说明超过的上限，`new_ip` 为未发布的 IP
+    pub fn flapping(record: &str, zone: &str, new_ip: Option<&str>, detail: &str) -> Self {
+        Self {
+            event: EventKind::Flapping,
+            record: record.to_string(),
+            zone: zone.to_string(),
+            old_ip: None,
+            new_ip: new_ip.map(str::to_string),
+            timestamp: Local::now().to_rfc3339(),
+            result: "failure",
+            error: Some(detail.to_string()),
+            error_category: None,
+            consecutive_failures: 0,
+            template: None,
+            routes: Vec::new(),
+        }
+    }
+
+    /// 只发给给定名称的通知后端，为空时发给所有后端
+    pub fn routed_to(mut self, notifiers: &[String]) -> Self {
+        self.routes = notifiers.to_vec();
+        self
+    }
+
+    /// 是否发给给定的通知后端，`notifier` 为 [`Notifier::name`]
+    fn routes_to(&self, notifier: &str) -> bool {
+        // 自定义命令在配置中是 hooks
+        let key = match notifier {
+            "command" => "hooks",
+            name => name,
+        };
+        self.routes.is_empty() || self.routes.iter().any(|route| route == key)
+    }
+
+    /// 定时模式启动，`record` 为空
+    pub fn startup(zone: &str) -> Self {
+        Self {
+            event: EventKind::Startup,
+            record: String::new(),
+            zone: zone.to_string(),
+            old_ip: None,
+            new_ip: None,
+            timestamp: Local::now().to_rfc3339(),
+            result: "success",
+            error: None,
+            error_category: None,
+            consecutive_failures: 0,
+            template: None,
+            routes: Vec::new(),
+        }
+    }
+
+    /// 供推送类通知后端使用的简短标题
+    pub fn title(&self) -> String {
+        match self.event {
+            EventKind::Changed => tr!("{} 已更新", "{} updated", self.record),
+            EventKind::Failed => tr!("{} 更新失败", "{} update failed", self.record),
+            EventKind::Recovered => tr!("{} 已恢复", "{} recovered", self.record),
+            EventKind::RetriesExhausted => tr!("{} 重试后仍失败", "{} still failing after retries", self.record),
+            EventKind::Drift => tr!("{} 被外部修改", "{} changed externally", self.record),
+            EventKind::Flapping => tr!("{} 更新过于频繁", "{} flapping detected", self.record),
+            EventKind::Startup => tr!("CloudFlare DDNS 已启动", "CloudFlare DDNS started"),
+        }
+    }
+
+    /// 供聊天类通知后端使用的可读消息，配置了模板时按模板渲染
+    #[cfg_attr(not(feature = "notify-core"), allow(dead_code))]
+    pub fn message(&self) -> String {
+        if let Some(template) = &self.template {
+            return render(template, self);
+        }
+        match self.event {
+            EventKind::Changed => tr!(
+                "[CloudFlare DDNS] {} 已更新\n旧 IP: {}\n新 IP: {}\n时间: {}", "[CloudFlare DDNS] {} updated\nOld IP: {}\nNew IP: {}\nTime: {}",
+                self.record,
+                self.old_ip.clone().unwrap_or_else(|| tr!("(新建)", "(created)")),
+                self.new_ip.as_deref().unwrap_or("-"),
+                self.timestamp,
+            ),
+            EventKind::Failed => tr!(
+                "[CloudFlare DDNS] {} 更新失败\n目标 IP: {}\n错误类型: {}\n错误: {}\n时间: {}", "[CloudFlare DDNS] {} update failed\nTarget IP: {}\nError type: {}\nError: {}\nTime: {}",
+                self.record,
+                self.new_ip.as_deref().unwrap_or("-"),
+                self.error_category.unwrap_or(ErrorCategory::Other).describe(),
+                self.error.as_deref().unwrap_or("-"),
+                self.timestamp,
+            ),
+            EventKind::Recovered => tr!(
+                "[CloudFlare DDNS] {} 在连续失败 {} 次后已恢复\n当前 IP: {}\n时间: {}", "[CloudFlare DDNS] {} recovered after {} consecutive failures\nCurrent IP: {}\nTime: {}",
+                self.record,
+                self.consecutive_failures,
+                self.new_ip.as_deref().unwrap_or("-"),
+                self.timestamp,
+            ),
+            EventKind::RetriesExhausted => tr!(
+                "[CloudFlare DDNS] {} 连续失败 {} 次，已停止快速重试，之后按正常间隔重试\n目标 IP: {}\n错误类型: {}\n错误: {}\n时间: {}",
+                "[CloudFlare DDNS] {} failed {} times in a row, fast retries stopped, retrying at the normal interval\nTarget IP: {}\nError type: {}\nError: {}\nTime: {}",
+                self.record,
+                self.consecutive_failures,
+                self.new_ip.as_deref().unwrap_or("-"),
+                self.error_category.unwrap_or(ErrorCategory::Other).describe(),
+                self.error.as_deref().unwrap_or("-"),
+                self.timestamp,
+            ),
+            EventKind::Drift if self.result == "success" => tr!(
+                "[CloudFlare DDNS] {} 被外部修改为 {}，已改回 {}\n时间: {}", "[CloudFlare DDNS] {} was changed externally to {}, restored to {}\nTime: {}",
+                self.record,
+                self.old_ip.clone().unwrap_or_else(|| tr!("(已删除)", "(deleted)")),
+                self.new_ip.as_deref().unwrap_or("-"),
+                self.timestamp,
+            ),
+            EventKind::Drift => tr!(
+                "[CloudFlare DDNS] {} 被外部修改为 {}，上一次发布的是 {}，未做修改\n时间: {}", "[CloudFlare DDNS] {} was changed externally to {}, last published {}, left unchanged\nTime: {}",
+                self.record,
+                self.old_ip.clone().unwrap_or_else(|| tr!("(已删除)", "(deleted)")),
+                self.new_ip.as_deref().unwrap_or("-"),
+                self.timestamp,
+            ),
+            EventKind::Flapping => tr!(
+                "[CloudFlare DDNS] {} 更新过于频繁（{}），已停止更新\n未发布的 IP: {}\n时间: {}", "[CloudFlare DDNS] {} is flapping ({}), updates stopped\nUnpublished IP: {}\nTime: {}",
+                self.record,
+                self.error.as_deref().unwrap_or("-"),
+                self.new_ip.as_deref().unwrap_or("-"),
+                self.timestamp,
+            ),
+            EventKind::Startup => tr!(
+                "[CloudFlare DDNS] 已启动，区域: {}\n时间: {}", "[CloudFlare DDNS] started, zone: {}\nTime: {}",
+                self.zone,
+                self.timestamp,
+            ),
+        }
+    }
+
+    /// 供支持 Markdown 的机器人使用的消息，配置了模板时与 `message` 相同
+    #[cfg_attr(not(feature = "notify-core"), allow(dead_code))]
+    pub fn markdown(&self) -> String {
+        if self.template.is_some() {
+            return self.message();
+        }
+        let mut lines = vec![format!("#### {}", self.title())];
+        if !self.record.is_empty() {
+            lines.push(tr!("- 记录: {}", "- Record: {}", self.record));
+        }
+        lines.push(tr!("- 区域: {}", "- Zone: {}", self.zone));
+        if let Some(old_ip) = &self.old_ip {
+            lines.push(tr!("- 旧 IP: `{}`", "- Old IP: `{}`", old_ip));
+        }
+        if let Some(new_ip) = &self.new_ip {
+            lines.push(tr!("- 新 IP: `{}`", "- New IP: `{}`", new_ip));
+        }
+        if let Some(category) = self.error_category {
+            lines.push(tr!("- 错误类型: {}", "- Error type: {}", category.describe()));
+        }
+        if let Some(error) = &self.error {
+            lines.push(tr!("- 错误: {}", "- Error: {}", error));
+        }
+        lines.push(tr!("- 时间: {}", "- Time: {}", self.timestamp));
+        lines.join("\n")
+    }
+}
+
+/// 替换模板中的占位符：{record}、{zone}、{old_ip}、{new_ip}、{timestamp}、{error}、{category}、{result}、{failures}
+#[cfg_attr(not(feature = "notify-core"), allow(dead_code))]
+pub fn render(template: &str, event: &NotificationEvent) -> String {
+    template
+        .replace("{record}", &event.record)
+        .replace("{zone}", &event.zone)
+        .replace("{old_ip}", event.old_ip.as_deref().unwrap_or("-"))
+        .replace("{new_ip}", event.new_ip.as_deref().unwrap_or("-"))
+        .replace("{timestamp}", &event.timestamp)
+        .replace("{error}", event.error.as_deref().unwrap_or(""))
+        .replace("{category}", event.error_category.map_or("", ErrorCategory::as_str))
+        .replace("{result}", event.result)
+        .replace("{failures}", &event.consecutive_failures.to_string())
+}
+
+/// 通知后端
+pub trait Notifier: Send + Sync {
+    /// 后端名称，用于日志与记录的 `notify` 路由
+    fn name(&self) -> &str;
+
+    /// 重试队列中的标识，同一后端有多个发送目标时各目标不同，只重试发送失败的目标
+    fn target(&self) -> &str {
+        self.name()
+    }
+
+    /// 发送一条通知
+    fn notify<'a>(&'a self, event: &'a NotificationEvent) -> NotifyFuture<'a>;
+}
+
+impl NotifyPolicy {
+    /// 按策略判断是否向该后端发送事件
+    pub fn allows(&self, event: &NotificationEvent) -> bool {
+        if !self.events.contains(&event.event) {
+            return false;
+        }
+        match event.event {
+            // 每轮连续失败只在达到阈值时通知一次
+            EventKind::Failed => event.consecutive_failures == self.failure_threshold,
+            // 只有之前发送过失败通知时才发送恢复通知
+            EventKind::Recovered => event.consecutive_failures >= self.failure_threshold,
+            EventKind::Changed | EventKind::RetriesExhausted | EventKind::Drift | EventKind::Flapping | EventKind::Startup => true,
+        }
+    }
+}
+
+/// 记录在 [`FailureCounts`] 中的索引 `区域/名称/类型`，双栈名称的 A 与 AAAA 记录分别计数
+fn record_key(zone: &str, record: &str, record_type: &str) -> String {
+    format!("{}/{}/{}", zone, record, record_type)
+}
+
+/// 每条记录的连续失败次数与最近通知过的漂移、频繁更新，用于失败阈值、恢复通知与漂移、频繁更新通知去重
+///
+/// 定时模式在内存中跨多次执行保留；单次运行时保存在状态存储中，由下一次运行读取。
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct FailureCounts {
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    counts: BTreeMap<String, u32>,
+    /// 最近一次通知的漂移内容（记录被删除时为空）
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    drifts: BTreeMap<String, Option<String>>,
+    /// 已通知过更新过于频繁、仍在停止更新的记录
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    flapping: BTreeSet<String>,
+}
+
+impl FailureCounts {
+    /// 没有任何需要保留的状态
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty() && self.drifts.is_empty() && self.flapping.is_empty()
+    }
+
+    /// 记录一次失败，返回当前连续失败次数
+    pub fn record_failure(&mut self, zone: &str, record: &str, record_type: &str) -> u32 {
+        let count = self.counts.entry(record_key(zone, record, record_type)).or_insert(0);
+        *count += 1;
+        *count
+    }
+
+    /// 记录一次成功，返回此前的连续失败次数
+    pub fn record_success(&mut self, zone: &str, record: &str, record_type: &str) -> u32 {
+        self.counts.remove(&record_key(zone, record, record_type)).unwrap_or(0)
+    }
+
+    /// 记录一次漂移，与上一次通知的内容相同时返回 false，避免重复通知
+    pub fn record_drift(&mut self, zone: &str, record: &str, record_type: &str, found: Option<&str>) -> bool {
+        let found = found.map(str::to_string);
+        self.drifts.insert(record_key(zone, record, record_type), found.clone()) != Some(found)
+    }
+
+    /// 记录内容与上一次发布的一致，清除漂移状态
+    pub fn clear_drift(&mut self, zone: &str, record: &str, record_type: &str) {
+        self.drifts.remove(&record_key(zone, record, record_type));
+    }
+
+    /// 记录一次因更新过于频繁而停止更新，此前已通知过时返回 false
+    pub fn record_flapping(&mut self, zone: &str, record: &str, record_type: &str) -> bool {
+        self.flapping.insert(record_key(zone, record, record_type))
+    }
+
+    /// 记录的更新次数回到上限以内，清除频繁更新状态
+    pub fn clear_flapping(&mut self, zone: &str, record: &str, record_type: &str) {
+        self.flapping.remove(&record_key(zone, record, record_type));
+    }
+}
+
+/// 发送失败、等待重试的通知
+#[derive(Debug)]
+struct PendingNotification {
+    /// 目标后端的 [`Notifier::target`]
+    notifier: String,
+    event: NotificationEvent,
+    /// 已尝试发送的次数
+    attempts: u32,
+    next_attempt: Instant,
+}
+
+/// 发送失败的通知队列，定时模式下在多次执行之间保留
+#[derive(Debug, Default)]
+pub struct NotificationQueue {
+    items: VecDeque<PendingNotification>,
+}
+
+impl NotificationQueue {
+    /// 等待重试的通知数量
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+}
+
+/// 根据配置创建的全部通知后端
+pub struct Notifiers {
+    notifiers: Vec<(Box<dyn Notifier>, NotifyPolicy)>,
+    templates: NotificationTemplates,
+    retry: NotificationRetryConfig,
+    queue: Mutex<NotificationQueue>,
+}
+
+impl Notifiers {
+    pub fn from_config(config: &NotificationsConfig, timeout: Duration) -> Self {
+        Self {
+            notifiers: backends(config, timeout),
+            templates: config.templates.clone(),
+            retry: config.retry.clone(),
+            queue: Mutex::new(NotificationQueue::default()),
+        }
+    }
+
+    /// 接管上一次执行留下的重试队列
+    pub fn with_queue(self, queue: NotificationQueue) -> Self {
+        *self.queue.lock().unwrap() = queue;
+        self
+    }
+
+    /// 取出重试队列，供下一次执行使用
+    pub fn into_queue(self) -> NotificationQueue {
+        self.queue.into_inner().unwrap()
+    }
+
+    /// 计算第 `attempts` 次失败后的等待时间
+    fn backoff(&self, attempts: u32) -> Duration {
+        self.retry.backoff()
+            .saturating_mul(1 << attempts.saturating_sub(1).min(16))
+            .min(MAX_RETRY_BACKOFF)
+    }
+
+    /// 将发送失败的通知加入重试队列
+    fn enqueue(&self, notifier: &str, event: &NotificationEvent, attempts: u32) {
+        if attempts >= self.retry.max_attempts {
+            error!(notifier, attempts, title = %event.title(), "{}", tr!("通知重试次数已用完，放弃发送", "Notification retries exhausted, giving up"));
+            return;
+        }
+
+        let mut queue = self.queue.lock().unwrap();
+        if queue.items.len() >= MAX_PENDING_NOTIFICATIONS
+            && let Some(dropped) = queue.items.pop_front()
+        {
+            warn!(notifier = %dropped.notifier, title = %dropped.event.title(), "{}", tr!("通知重试队列已满，丢弃最早的通知", "Notification retry queue full, dropping the oldest notification"));
+        }
+        queue.items.push_back(PendingNotification {
+            notifier: notifier.to_string(),
+            event: event.clone(),
+            attempts,
+            next_attempt: Instant::now() + self.backoff(attempts),
+        });
+    }
+
+    /// 重试队列中已到期的通知
+    pub async fn retry_pending(&self) {
+        let due: VecDeque<PendingNotification> = {
+            let mut queue = self.queue.lock().unwrap();
+            let now = Instant::now();
+            let (due, waiting) = queue.items.drain(..).partition(|item| item.next_attempt <= now);
+            queue.items = waiting;
+            due
+        };
+
+        for item in due {
+            let Some((notifier, _)) = self.notifiers.iter().find(|(n, _)| n.target() == item.notifier) else {
+                warn!(notifier = %item.notifier, "{}", tr!("通知后端已不在配置中，丢弃待重试的通知", "Notifier no longer configured, dropping pending notification"));
+                continue;
+            };
+            match notifier.notify(&item.event).await {
+                Ok(()) => info!(notifier = %item.notifier, title = %item.event.title(), "{}", tr!("通知重试成功", "Notification retry succeeded")),
+                Err(e) => {
+                    warn!(notifier = %item.notifier, attempts = item.attempts + 1, error = %e, "{}", tr!("通知重试失败", "Notification retry failed"));
+                    self.enqueue(&item.notifier, &item.event, item.attempts + 1);
+                }
+            }
+        }
+    }
+
+    /// 在限定时间内反复重试，直到队列为空，用于单次运行模式退出前
+    pub async fn flush(&self, limit: Duration) {
+        let deadline = Instant::now() + limit;
+        loop {
+            let next_attempt = {
+                let queue = self.queue.lock().unwrap();
+                match queue.items.iter().map(|item| item.next_attempt).min() {
+                    Some(next_attempt) => next_attempt,
+                    None => return,
+                }
+            };
+            if next_attempt > deadline {
+                let remaining = self.queue.lock().unwrap().items.len();
+                warn!(remaining, "{}", tr!("仍有通知未能发送", "Some notifications could not be delivered"));
+                return;
+            }
+            tokio::time::sleep_until(next_attempt.into()).await;
+            self.retry_pending().await;
+        }
+    }
+
+    /// 将事件发送给所有后端，发送失败的加入重试队列，不影响 DNS 更新流程
+    pub async fn notify(&self, event: &NotificationEvent) {
+        let mut event = event.clone();
+        event.template = match event.event {
+            EventKind::Changed => self.templates.changed.clone(),
+            EventKind::Failed => self.templates.failed.clone(),
+            EventKind::Recovered => self.templates.recovered.clone(),
+            EventKind::RetriesExhausted => self.templates.retries_exhausted.clone(),
+            EventKind::Drift => self.templates.drift.clone(),
+            EventKind::Flapping => self.templates.flapping.clone(),
+            EventKind::Startup => self.templates.startup.clone(),
+        };
+
+        for (notifier, policy) in &self.notifiers {
+            if !policy.allows(&event) || !event.routes_to(notifier.name()) {
+                continue;
+            }
+            if let Err(e) = notifier.notify(&event).await {
+                warn!(notifier = notifier.target(), error = %e, "{}", tr!("通知发送失败，稍后重试", "Notification failed, will retry later"));
+                self.enqueue(notifier.target(), &event, 1);
+            }
+        }
+    }
+}
+
+/// 根据配置创建各通知后端
+// 只启用部分后端时，`timeout` 与 `notifiers` 可能用不到
+#[allow(unused_mut, unused_variables)]
+fn backends(config: &NotificationsConfig, timeout: Duration) -> Vec<(Box<dyn Notifier>, NotifyPolicy)> {
+    for name in disabled_backends(config) {
+        warn!(notifier = name, "{}", tr!("编译时未启用该通知后端，配置已忽略", "Built without this notification backend, its settings are ignored"));
+    }
+    let mut notifiers: Vec<(Box<dyn Notifier>, NotifyPolicy)> = Vec::new();
+    #[cfg(feature = "notify-webhook")]
+    if let Some(webhook) = &config.webhook {
+        for notifier in webhook::WebhookNotifier::for_each_url(webhook, timeout) {
+            notifiers.push((Box::new(notifier), webhook.policy.clone()));
+        }
+    }
+    #[cfg(feature = "notify-telegram")]
+    if let Some(telegram) = &config.telegram {
+        notifiers.push((Box::new(telegram::TelegramNotifier::new(telegram.clone(), timeout)), telegram.policy.clone()));
+    }
+    #[cfg(feature = "notify-slack")]
+    if let Some(slack) = &config.slack {
+        notifiers.push((Box::new(slack::SlackNotifier::new(slack.clone(), timeout)), slack.policy.clone()));
+    }
+    #[cfg(feature = "notify-gotify")]
+    if let Some(gotify) = &config.gotify {
+        notifiers.push((Box::new(gotify::GotifyNotifier::new(gotify.clone(), timeout)), gotify.policy.clone()));
+    }
+    #[cfg(feature = "notify-bark")]
+    if let Some(bark) = &config.bark {
+        notifiers.push((Box::new(bark::BarkNotifier::new(bark.clone(), timeout)), bark.policy.clone()));
+    }
+    #[cfg(feature = "notify-pushover")]
+    if let Some(pushover) = &config.pushover {
+        match pushover::PushoverNotifier::new(pushover.clone(), timeout) {
+            Ok(notifier) => notifiers.push((Box::new(notifier), pushover.policy.clone())),
+            Err(e) => error!(error = %e, "{}", tr!("Pushover 通知配置无效，已忽略", "Invalid Pushover notification configuration, ignored")),
+        }
+    }
+    #[cfg(feature = "notify-serverchan")]
+    if let Some(serverchan) = &config.serverchan {
+        notifiers.push((Box::new(serverchan::ServerChanNotifier::new(serverchan.clone(), timeout)), serverchan.policy.clone()));
+    }
+    #[cfg(feature = "notify-dingtalk")]
+    if let Some(dingtalk) = &config.dingtalk {
+        notifiers.push((Box::new(dingtalk::DingTalkNotifier::new(dingtalk.clone(), timeout)), dingtalk.policy.clone()));
+    }
+    #[cfg(feature = "notify-wecom")]
+    if let Some(wecom) = &config.wecom {
+        match wecom::WeComNotifier::new(wecom.clone(), timeout) {
+            Ok(notifier) => notifiers.push((Box::new(notifier), wecom.policy.clone())),
+            Err(e) => error!(error = %e, "{}", tr!("企业微信通知配置无效，已忽略", "Invalid WeCom notification configuration, ignored")),
+        }
+    }
+    #[cfg(feature = "notify-matrix")]
+    if let Some(matrix) = &config.matrix {
+        notifiers.push((Box::new(matrix::MatrixNotifier::new(matrix.clone(), timeout)), matrix.policy.clone()));
+    }
+    #[cfg(feature = "notify-command")]
+    if let Some(hooks) = &config.hooks {
+        notifiers.push((Box::new(command::CommandNotifier::new(hooks.clone())), hooks.policy.clone()));
+    }
+    #[cfg(feature = "notify-email")]
+    if let Some(email) = &config.email {
+        match email::EmailNotifier::new(email.clone(), timeout) {
+            Ok(notifier) => notifiers.push((Box::new(notifier), email.policy.clone())),
+            Err(e) => error!(error = %e, "{}", tr!("邮件通知配置无效，已忽略", "Invalid email notification configuration, ignored")),
+        }
+    }
+    notifiers
+}
+
+/// 已配置的通知后端的名称与发送策略，包括编译时未启用对应功能的后端
+pub fn policies(config: &NotificationsConfig) -> Vec<(&'static str, &NotifyPolicy)> {
+    [
+        ("webhook", config.webhook.as_ref().map(|c| &c.policy)),
+        ("telegram", config.telegram.as_ref().map(|c| &c.policy)),
+        ("slack", config.slack.as_ref().map(|c| &c.policy)),
+        ("email", config.email.as_ref().map(|c| &c.policy)),
+        ("gotify", config.gotify.as_ref().map(|c| &c.policy)),
+        ("bark", config.bark.as_ref().map(|c| &c.policy)),
+        ("pushover", config.pushover.as_ref().map(|c| &c.policy)),
+        ("serverchan", config.serverchan.as_ref().map(|c| &c.policy)),
+        ("dingtalk", config.dingtalk.as_ref().map(|c| &c.policy)),
+        ("wecom", config.wecom.as_ref().map(|c| &c.policy)),
+        ("matrix", config.matrix.as_ref().map(|c| &c.policy)),
+        ("hooks", config.hooks.as_ref().map(|c| &c.policy)),
+    ]
+    .into_iter()
+    .filter_map(|(name, policy)| Some((name, policy?)))
+    .collect()
+}
+
+/// 名称为 `name`（见 [`BACKEND_NAMES`]）的通知后端是否已配置
+pub fn is_configured(config: &NotificationsConfig, name: &str) -> bool {
+    match name {
+        "webhook" => config.webhook.is_some(),
+        "telegram" => config.telegram.is_some(),
+        "slack" => config.slack.is_some(),
+        "email" => config.email.is_some(),
+        "gotify" => config.gotify.is_some(),
+        "bark" => config.bark.is_some(),
+        "pushover" => config.pushover.is_some(),
+        "serverchan" => config.serverchan.is_some(),
+        "dingtalk" => config.dingtalk.is_some(),
+        "wecom" => config.wecom.is_some(),
+        "matrix" => config.matrix.is_some(),
+        "hooks" => config.hooks.is_some(),
+        _ => false,
+    }
+}
+
+/// 已配置、但编译时未启用对应功能的通知后端
+fn disabled_backends(config: &NotificationsConfig) -> Vec<&'static str> {
+    [
+        ("webhook", config.webhook.is_some() && !cfg!(feature = "notify-webhook")),
+        ("telegram", config.telegram.is_some() && !cfg!(feature = "notify-telegram")),
+        ("slack", config.slack.is_some() && !cfg!(feature = "notify-slack")),
+        ("gotify", config.gotify.is_some() && !cfg!(feature = "notify-gotify")),
+        ("bark", config.bark.is_some() && !cfg!(feature = "notify-bark")),
+        ("pushover", config.pushover.is_some() && !cfg!(feature = "notify-pushover")),
+        ("serverchan", config.serverchan.is_some() && !cfg!(feature = "notify-serverchan")),
+        ("dingtalk", config.dingtalk.is_some() && !cfg!(feature = "notify-dingtalk")),
+        ("wecom", config.wecom.is_some() && !cfg!(feature = "notify-wecom")),
+        ("matrix", config.matrix.is_some() && !cfg!(feature = "notify-matrix")),
+        ("hooks", config.hooks.is_some() && !cfg!(feature = "notify-command")),
+        ("email", config.email.is_some() && !cfg!(feature = "notify-email")),
+    ]
+    .into_iter()
+    .filter_map(|(name, disabled)| disabled.then_some(name))
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let event = NotificationEvent::changed("home.example.com", "example.com", Some("203.0.113.1"), "203.0.113.7");
+        assert_eq!(
+            render("{record}: {old_ip} -> {new_ip} ({result})", &event),
+            "home.example.com: 203.0.113.1 -> 203.0.113.7 (success)"
+        );
+
+        let mut event = NotificationEvent::failed("home.example.com", "example.com", None, "timeout", ErrorCategory::Network, 1);
+        assert_eq!(render("{record} {new_ip} {error} ({category})", &event), "home.example.com - timeout (network)");
+
+        event.template = Some("{zone}: {error}".to_string());
+        assert_eq!(event.message(), "example.com: timeout");
+        assert_eq!(event.markdown(), "example.com: timeout");
+    }
+
+    #[test]
+    fn test_policy_failure_threshold() {
+        let policy = NotifyPolicy { failure_threshold: 3, ..NotifyPolicy::default() };
+        let failed = |n| NotificationEvent::failed("home.example.com", "example.com", None, "timeout", ErrorCategory::Network, n);
+
+        assert!(!policy.allows(&failed(2)));
+        assert!(policy.allows(&failed(3)));
+        assert!(!policy.allows(&failed(4)));
+        assert!(!policy.allows(&NotificationEvent::recovered("home.example.com", "example.com", None, 2)));
+        assert!(policy.allows(&NotificationEvent::recovered("home.example.com", "example.com", None, 5)));
+        assert!(!policy.allows(&NotificationEvent::startup("example.com")));
+    }
+
+    #[test]
+    fn test_failure_counts_per_record_type() {
+        let mut counts = FailureCounts::default();
+        // 双栈名称的 A 记录持续失败、AAAA 记录成功时，AAAA 的成功不清除 A 的失败次数
+        assert_eq!(counts.record_failure("example.com", "home.example.com", "A"), 1);
+        assert_eq!(counts.record_success("example.com", "home.example.com", "AAAA"), 0);
+        assert_eq!(counts.record_failure("example.com", "home.example.com", "A"), 2);
+        assert_eq!(counts.record_success("example.com", "home.example.com", "AAAA"), 0);
+        assert_eq!(counts.record_failure("example.com", "home.example.com", "A"), 3);
+        assert_eq!(counts.record_success("example.com", "home.example.com", "A"), 3);
+
+        // 只有 A 记录漂移时，未漂移的 AAAA 记录不清除 A 的漂移状态，同一漂移只通知一次
+        assert!(counts.record_drift("example.com", "home.example.com", "A", Some("192.0.2.9")));
+        counts.clear_drift("example.com", "home.example.com", "AAAA");
+        assert!(!counts.record_drift("example.com", "home.example.com", "A", Some("192.0.2.9")));
+    }
+
+    #[test]
+    fn test_event_routes() {
+        let event = NotificationEvent::changed("vpn.work.example.com", "example.com", None, "203.0.113.7");
+        assert!(event.routes_to("telegram"));
+
+        let event = event.routed_to(&["slack".to_string(), "hooks".to_string()]);
+        assert!(event.routes_to("slack"));
+        assert!(event.routes_to("command"));
+        assert!(!event.routes_to("telegram"));
+    }
+}
@@ -0,0 +1,66 @@
+// Bark (iOS) 推送通知
+use serde::Deserialize;
+use std::time::Duration;
+
+use super::{NotificationEvent, Notifier, NotifyFuture};
+use crate::config::BarkConfig;
+use crate::error::Error;
+use crate::i18n::tr;
+
+#[derive(Debug, Deserialize)]
+struct BarkResponse {
+    code: i32,
+    message: String,
+}
+
+pub struct BarkNotifier {
+    client: reqwest::Client,
+    config: BarkConfig,
+}
+
+impl BarkNotifier {
+    pub fn new(config: BarkConfig, timeout: Duration) -> Self {
+        Self {
+            client: crate::tls::client_builder()
+                .timeout(timeout)
+                .build()
+                .expect("Failed to build client"),
+            config,
+        }
+    }
+}
+
+impl Notifier for BarkNotifier {
+    fn name(&self) -> &str {
+        "bark"
+    }
+
+    fn notify<'a>(&'a self, event: &'a NotificationEvent) -> NotifyFuture<'a> {
+        Box::pin(async move {
+            let mut body = serde_json::json!({
+                "title": event.title(),
+                "body": event.message(),
+                "group": self.config.group.as_deref().unwrap_or("CloudFlare DDNS"),
+            });
+            if let Some(sound) = &self.config.sound {
+                body["sound"] = serde_json::Value::String(sound.clone());
+            }
+
+            // 推送地址中包含设备 key，错误信息中去掉 URL
+            let response = self.client
+                .post(self.config.url.trim_end_matches('/'))
+                .json(&body)
+                .send()
+                .await
+                .map_err(|e| Error::notification(e.without_url()))?;
+            let status = response.status();
+            let response_text = response.text().await.map_err(Error::notification)?;
+
+            match serde_json::from_str::<BarkResponse>(&response_text) {
+                Ok(response) if response.code == 200 => Ok(()),
+                Ok(response) => Err(Error::notification(tr!("Bark 返回错误 {}: {}", "Bark returned error {}: {}", response.code, response.message))),
+                Err(_) => Err(Error::notification(tr!("无法解析 Bark 响应，状态码 {}: {}", "Failed to parse Bark response, status {}: {}", status, response_text))),
+            }
+        })
+    }
+}
@@ -0,0 +1,86 @@
+// 记录变化或更新失败时执行自定义命令
+use std::time::Duration;
+use tokio::process::Command;
+
+use super::{EventKind, NotificationEvent, Notifier, NotifyFuture};
+use crate::config::CommandHooksConfig;
+use crate::error::Error;
+use crate::i18n::tr;
+
+const DEFAULT_COMMAND_TIMEOUT: Duration = Duration::from_secs(60);
+
+pub struct CommandNotifier {
+    config: CommandHooksConfig,
+}
+
+impl CommandNotifier {
+    pub fn new(config: CommandHooksConfig) -> Self {
+        Self { config }
+    }
+}
+
+/// 通过系统 shell 执行命令
+fn shell_command(command: &str) -> Command {
+    #[cfg(windows)]
+    {
+        let mut cmd = Command::new("cmd");
+        cmd.arg("/C").arg(command);
+        cmd
+    }
+    #[cfg(not(windows))]
+    {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg(command);
+        cmd
+    }
+}
+
+impl Notifier for CommandNotifier {
+    fn name(&self) -> &str {
+        "command"
+    }
+
+    fn notify<'a>(&'a self, event: &'a NotificationEvent) -> NotifyFuture<'a> {
+        Box::pin(async move {
+            let command = match event.event {
+                EventKind::Changed => &self.config.on_change,
+                EventKind::Failed => &self.config.on_failure,
+                EventKind::Recovered => &self.config.on_recovered,
+                EventKind::RetriesExhausted => &self.config.on_retries_exhausted,
+                EventKind::Drift => &self.config.on_drift,
+                EventKind::Flapping => &self.config.on_flapping,
+                EventKind::Startup => &None,
+            };
+            let Some(command) = command else {
+                return Ok(());
+            };
+
+            let mut cmd = shell_command(command);
+            cmd.env("RECORD", &event.record)
+                .env("ZONE", &event.zone)
+                .env("OLD_IP", event.old_ip.as_deref().unwrap_or(""))
+                .env("NEW_IP", event.new_ip.as_deref().unwrap_or(""))
+                .env("RESULT", event.result)
+                .env("ERROR", event.error.as_deref().unwrap_or(""))
+                .env("TIMESTAMP", &event.timestamp)
+                .env("FAILURES", event.consecutive_failures.to_string())
+                .kill_on_drop(true);
+
+            let timeout = self.config.timeout.unwrap_or(DEFAULT_COMMAND_TIMEOUT);
+            let output = tokio::time::timeout(timeout, cmd.output())
+                .await
+                .map_err(|_| Error::notification(tr!("命令执行超时 ({}): {}", "Command timed out ({}): {}", crate::duration::format_duration(timeout), command)))?
+                .map_err(Error::notification)?;
+
+            if output.status.success() {
+                Ok(())
+            } else {
+                Err(Error::notification(tr!(
+                    "命令退出状态 {}: {}", "Command exited with {}: {}",
+                    output.status,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                )))
+            }
+        })
+    }
+}
@@ -0,0 +1,80 @@
+// 钉钉群机器人通知，发送 Markdown 消息
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use sha2::Sha256;
+use std::time::Duration;
+
+use super::{NotificationEvent, Notifier, NotifyFuture};
+use crate::config::DingTalkConfig;
+use crate::error::Error;
+use crate::i18n::tr;
+
+#[derive(Debug, Deserialize)]
+struct DingTalkResponse {
+    errcode: i32,
+    errmsg: String,
+}
+
+pub struct DingTalkNotifier {
+    client: reqwest::Client,
+    config: DingTalkConfig,
+}
+
+impl DingTalkNotifier {
+    pub fn new(config: DingTalkConfig, timeout: Duration) -> Self {
+        Self {
+            client: crate::tls::client_builder()
+                .timeout(timeout)
+                .build()
+                .expect("Failed to build client"),
+            config,
+        }
+    }
+}
+
+/// 钉钉加签：对 "timestamp\nsecret" 做 HmacSHA256 后 Base64 编码
+fn sign(timestamp: i64, secret: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .expect("HMAC can take key of any size");
+    mac.update(format!("{}\n{}", timestamp, secret).as_bytes());
+    base64::engine::general_purpose::STANDARD.encode(mac.finalize().into_bytes())
+}
+
+impl Notifier for DingTalkNotifier {
+    fn name(&self) -> &str {
+        "dingtalk"
+    }
+
+    fn notify<'a>(&'a self, event: &'a NotificationEvent) -> NotifyFuture<'a> {
+        Box::pin(async move {
+            let body = serde_json::json!({
+                "msgtype": "markdown",
+                "markdown": {
+                    "title": event.title(),
+                    "text": event.markdown(),
+                },
+            });
+
+            let mut request = self.client.post(&self.config.webhook_url);
+            if let Some(secret) = &self.config.secret {
+                let timestamp = chrono::Utc::now().timestamp_millis();
+                request = request.query(&[
+                    ("timestamp", timestamp.to_string()),
+                    ("sign", sign(timestamp, secret)),
+                ]);
+            }
+
+            // Webhook 地址中包含 access_token，错误信息中去掉 URL
+            let response = request.json(&body).send().await.map_err(|e| Error::notification(e.without_url()))?;
+            let status = response.status();
+            let response_text = response.text().await.map_err(Error::notification)?;
+
+            match serde_json::from_str::<DingTalkResponse>(&response_text) {
+                Ok(response) if response.errcode == 0 => Ok(()),
+                Ok(response) => Err(Error::notification(tr!("钉钉返回错误 {}: {}", "DingTalk returned error {}: {}", response.errcode, response.errmsg))),
+                Err(_) => Err(Error::notification(tr!("无法解析钉钉响应，状态码 {}: {}", "Failed to parse DingTalk response, status {}: {}", status, response_text))),
+            }
+        })
+    }
+}
@@ -0,0 +1,87 @@
+// SMTP 邮件通知
+use lettre::message::{Mailbox, header::ContentType};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use std::time::Duration;
+
+use super::{EventKind, NotificationEvent, Notifier, NotifyFuture, render};
+use crate::config::EmailConfig;
+use crate::error::{Error, Result};
+use crate::i18n::tr;
+
+pub struct EmailNotifier {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+    to: Vec<Mailbox>,
+    config: EmailConfig,
+}
+
+impl EmailNotifier {
+    pub fn new(config: EmailConfig, timeout: Duration) -> Result<Self> {
+        let builder = match config.tls.as_str() {
+            "starttls" => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.host).map_err(Error::notification)?,
+            "tls" => AsyncSmtpTransport::<Tokio1Executor>::relay(&config.host).map_err(Error::notification)?,
+            "none" => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&config.host),
+            other => return Err(Error::notification(tr!("无效的 tls 模式: {}", "Invalid tls mode: {}", other))),
+        };
+
+        let mut builder = builder.timeout(Some(timeout));
+        if let Some(port) = config.port {
+            builder = builder.port(port);
+        }
+        if let (Some(username), Some(password)) = (&config.username, &config.password) {
+            builder = builder.credentials(Credentials::new(username.clone(), password.clone()));
+        }
+
+        let from = config.from.parse().map_err(Error::notification)?;
+        let to = config.to
+            .iter()
+            .map(|address| address.parse())
+            .collect::<Result<Vec<Mailbox>, _>>()
+            .map_err(Error::notification)?;
+
+        Ok(Self {
+            transport: builder.build(),
+            from,
+            to,
+            config,
+        })
+    }
+}
+
+impl Notifier for EmailNotifier {
+    fn name(&self) -> &str {
+        "email"
+    }
+
+    fn notify<'a>(&'a self, event: &'a NotificationEvent) -> NotifyFuture<'a> {
+        Box::pin(async move {
+            let subject = match (&self.config.subject, event.event) {
+                (Some(subject), _) => render(subject, event),
+                (None, EventKind::Changed) => render(&tr!("[CloudFlare DDNS] {{record}} 已更新为 {{new_ip}}", "[CloudFlare DDNS] {{record}} updated to {{new_ip}}"), event),
+                (None, EventKind::Failed) => render(&tr!("[CloudFlare DDNS] {{record}} 更新失败", "[CloudFlare DDNS] {{record}} update failed"), event),
+                (None, EventKind::Recovered) => render(&tr!("[CloudFlare DDNS] {{record}} 已恢复", "[CloudFlare DDNS] {{record}} recovered"), event),
+                (None, EventKind::RetriesExhausted) => render(&tr!("[CloudFlare DDNS] {{record}} 重试后仍失败", "[CloudFlare DDNS] {{record}} still failing after retries"), event),
+                (None, EventKind::Drift) => render(&tr!("[CloudFlare DDNS] {{record}} 被外部修改", "[CloudFlare DDNS] {{record}} changed externally"), event),
+                (None, EventKind::Flapping) => render(&tr!("[CloudFlare DDNS] {{record}} 更新过于频繁", "[CloudFlare DDNS] {{record}} flapping detected"), event),
+                (None, EventKind::Startup) => tr!("[CloudFlare DDNS] 已启动", "[CloudFlare DDNS] started"),
+            };
+            let body = match &self.config.body {
+                Some(body) => render(body, event),
+                None => event.message(),
+            };
+
+            let mut message = Message::builder()
+                .from(self.from.clone())
+                .subject(subject)
+                .header(ContentType::TEXT_PLAIN);
+            for to in &self.to {
+                message = message.to(to.clone());
+            }
+
+            let message = message.body(body).map_err(Error::notification)?;
+            self.transport.send(message).await.map_err(Error::notification)?;
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,56 @@
+// Gotify 推送通知
+use std::time::Duration;
+
+use super::{NotificationEvent, Notifier, NotifyFuture};
+use crate::config::GotifyConfig;
+use crate::error::Error;
+use crate::i18n::tr;
+
+pub struct GotifyNotifier {
+    client: reqwest::Client,
+    config: GotifyConfig,
+}
+
+impl GotifyNotifier {
+    pub fn new(config: GotifyConfig, timeout: Duration) -> Self {
+        Self {
+            client: crate::tls::client_builder()
+                .timeout(timeout)
+                .build()
+                .expect("Failed to build client"),
+            config,
+        }
+    }
+}
+
+impl Notifier for GotifyNotifier {
+    fn name(&self) -> &str {
+        "gotify"
+    }
+
+    fn notify<'a>(&'a self, event: &'a NotificationEvent) -> NotifyFuture<'a> {
+        Box::pin(async move {
+            let url = format!("{}/message", self.config.url.trim_end_matches('/'));
+            let body = serde_json::json!({
+                "title": event.title(),
+                "message": event.message(),
+                "priority": self.config.priority,
+            });
+
+            let response = self.client
+                .post(&url)
+                .header("X-Gotify-Key", &self.config.app_token)
+                .json(&body)
+                .send()
+                .await?;
+
+            let status = response.status();
+            if status.is_success() {
+                Ok(())
+            } else {
+                let response_text = response.text().await.unwrap_or_default();
+                Err(Error::notification(tr!("Gotify 请求失败，状态码 {}: {}", "Gotify request failed with status {}: {}", status, response_text)))
+            }
+        })
+    }
+}
@@ -0,0 +1,87 @@
+// Matrix 房间通知：通过 client-server API 向房间发送文本消息
+use serde::Deserialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use super::{NotificationEvent, Notifier, NotifyFuture};
+use crate::config::MatrixConfig;
+use crate::error::{Error, Result};
+use crate::i18n::tr;
+
+#[derive(Debug, Deserialize)]
+struct MatrixError {
+    errcode: String,
+    error: Option<String>,
+}
+
+pub struct MatrixNotifier {
+    client: reqwest::Client,
+    config: MatrixConfig,
+    /// 事务 ID 计数器，同一 access token 下的事务 ID 不能重复
+    txn_counter: AtomicU64,
+}
+
+impl MatrixNotifier {
+    pub fn new(config: MatrixConfig, timeout: Duration) -> Self {
+        Self {
+            client: crate::tls::client_builder()
+                .timeout(timeout)
+                .build()
+                .expect("Failed to build client"),
+            config,
+            txn_counter: AtomicU64::new(0),
+        }
+    }
+
+    /// 生成发送消息接口的地址，房间 ID 中的 `!`、`:` 需要编码
+    fn send_url(&self) -> Result<reqwest::Url> {
+        let txn_id = format!(
+            "cloudflare-ddns-{}-{}",
+            chrono::Utc::now().timestamp_millis(),
+            self.txn_counter.fetch_add(1, Ordering::Relaxed)
+        );
+        let mut url = reqwest::Url::parse(&self.config.homeserver)
+            .map_err(|e| Error::notification(tr!("无效的 Matrix 服务器地址 '{}': {}", "Invalid Matrix homeserver URL '{}': {}", self.config.homeserver, e)))?;
+        url.path_segments_mut()
+            .map_err(|_| Error::notification(tr!("无效的 Matrix 服务器地址 '{}'", "Invalid Matrix homeserver URL '{}'", self.config.homeserver)))?
+            .pop_if_empty()
+            .extend(["_matrix", "client", "v3", "rooms", &self.config.room_id, "send", "m.room.message", &txn_id]);
+        Ok(url)
+    }
+}
+
+impl Notifier for MatrixNotifier {
+    fn name(&self) -> &str {
+        "matrix"
+    }
+
+    fn notify<'a>(&'a self, event: &'a NotificationEvent) -> NotifyFuture<'a> {
+        Box::pin(async move {
+            let body = serde_json::json!({
+                "msgtype": "m.text",
+                "body": format!("{}\n{}", event.title(), event.message()),
+            });
+
+            let response = self.client
+                .put(self.send_url()?)
+                .bearer_auth(&self.config.access_token)
+                .json(&body)
+                .send()
+                .await?;
+
+            let status = response.status();
+            if status.is_success() {
+                return Ok(());
+            }
+            let response_text = response.text().await.unwrap_or_default();
+            match serde_json::from_str::<MatrixError>(&response_text) {
+                Ok(error) => Err(Error::notification(tr!(
+                    "Matrix 返回错误 {}: {}", "Matrix returned error {}: {}",
+                    error.errcode,
+                    error.error.unwrap_or_else(|| status.to_string())
+                ))),
+                Err(_) => Err(Error::notification(tr!("Matrix 请求失败，状态码 {}: {}", "Matrix request failed with status {}: {}", status, response_text))),
+            }
+        })
+    }
+}
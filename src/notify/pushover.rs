@@ -0,0 +1,80 @@
+// Pushover 推送通知
+use serde::Deserialize;
+use std::time::Duration;
+
+use super::{NotificationEvent, Notifier, NotifyFuture};
+use crate::config::PushoverConfig;
+use crate::error::{Error, Result};
+use crate::i18n::tr;
+
+const PUSHOVER_API_URL: &str = "https://api.pushover.net/1/messages.json";
+
+#[derive(Debug, Deserialize)]
+struct PushoverResponse {
+    status: i32,
+    #[serde(default)]
+    errors: Vec<String>,
+}
+
+pub struct PushoverNotifier {
+    client: reqwest::Client,
+    config: PushoverConfig,
+}
+
+impl PushoverNotifier {
+    pub fn new(config: PushoverConfig, timeout: Duration) -> Result<Self> {
+        if !(-2..=2).contains(&config.priority) {
+            return Err(Error::notification(tr!("无效的 Pushover 优先级 {}，应为 -2 到 2", "Invalid Pushover priority {}, must be between -2 and 2", config.priority)));
+        }
+        Ok(Self {
+            client: crate::tls::client_builder()
+                .timeout(timeout)
+                .build()
+                .expect("Failed to build client"),
+            config,
+        })
+    }
+}
+
+impl Notifier for PushoverNotifier {
+    fn name(&self) -> &str {
+        "pushover"
+    }
+
+    fn notify<'a>(&'a self, event: &'a NotificationEvent) -> NotifyFuture<'a> {
+        Box::pin(async move {
+            let mut body = serde_json::json!({
+                "token": self.config.app_token,
+                "user": self.config.user_key,
+                "title": event.title(),
+                "message": event.message(),
+                "priority": self.config.priority,
+            });
+            // 紧急优先级需要指定重复提醒的间隔与过期时间
+            if self.config.priority == 2 {
+                body["retry"] = self.config.retry.into();
+                body["expire"] = self.config.expire.into();
+            }
+            if let Some(device) = &self.config.device {
+                body["device"] = serde_json::Value::String(device.clone());
+            }
+            if let Some(sound) = &self.config.sound {
+                body["sound"] = serde_json::Value::String(sound.clone());
+            }
+
+            let response = self.client
+                .post(PUSHOVER_API_URL)
+                .json(&body)
+                .send()
+                .await?;
+            let status = response.status();
+            let response_text = response.text().await.map_err(Error::notification)?;
+
+            match serde_json::from_str::<PushoverResponse>(&response_text) {
+                Ok(response) if response.status == 1 => Ok(()),
+                Ok(response) => Err(Error::notification(tr!("Pushover 返回错误: {}", "Pushover returned error: {}", response.errors.join("; ")))),
+                Err(_) => Err(Error::notification(tr!("无法解析 Pushover 响应，状态码 {}: {}", "Failed to parse Pushover response, status {}: {}", status, response_text))),
+            }
+        })
+    }
+}
@@ -0,0 +1,74 @@
+// Server酱 (方糖) 推送通知，消息可转发到微信
+use serde::Deserialize;
+use std::time::Duration;
+
+use super::{NotificationEvent, Notifier, NotifyFuture};
+use crate::config::ServerChanConfig;
+use crate::error::Error;
+use crate::i18n::tr;
+
+#[derive(Debug, Deserialize)]
+struct ServerChanResponse {
+    code: i32,
+    message: String,
+}
+
+pub struct ServerChanNotifier {
+    client: reqwest::Client,
+    config: ServerChanConfig,
+}
+
+impl ServerChanNotifier {
+    pub fn new(config: ServerChanConfig, timeout: Duration) -> Self {
+        Self {
+            client: crate::tls::client_builder()
+                .timeout(timeout)
+                .build()
+                .expect("Failed to build client"),
+            config,
+        }
+    }
+}
+
+/// 根据 SendKey 得到推送地址，Server酱³ 的 key 形如 sctp{uid}t...
+fn push_url(sendkey: &str) -> String {
+    if let Some(rest) = sendkey.strip_prefix("sctp") {
+        let uid: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+        if !uid.is_empty() {
+            return format!("https://{}.push.ft07.com/send/{}.send", uid, sendkey);
+        }
+    }
+    format!("https://sctapi.ftqq.com/{}.send", sendkey)
+}
+
+impl Notifier for ServerChanNotifier {
+    fn name(&self) -> &str {
+        "serverchan"
+    }
+
+    fn notify<'a>(&'a self, event: &'a NotificationEvent) -> NotifyFuture<'a> {
+        Box::pin(async move {
+            // desp 支持 Markdown，换行需要两个空格或空行
+            let body = serde_json::json!({
+                "title": event.title(),
+                "desp": event.message().replace('\n', "\n\n"),
+            });
+
+            // 推送地址中包含 SendKey，错误信息中去掉 URL
+            let response = self.client
+                .post(push_url(&self.config.sendkey))
+                .json(&body)
+                .send()
+                .await
+                .map_err(|e| Error::notification(e.without_url()))?;
+            let status = response.status();
+            let response_text = response.text().await.map_err(Error::notification)?;
+
+            match serde_json::from_str::<ServerChanResponse>(&response_text) {
+                Ok(response) if response.code == 0 => Ok(()),
+                Ok(response) => Err(Error::notification(tr!("Server酱返回错误 {}: {}", "ServerChan returned error {}: {}", response.code, response.message))),
+                Err(_) => Err(Error::notification(tr!("无法解析 Server酱响应，状态码 {}: {}", "Failed to parse ServerChan response, status {}: {}", status, response_text))),
+            }
+        })
+    }
+}
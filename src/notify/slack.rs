@@ -0,0 +1,112 @@
+// Slack Incoming Webhook 通知，使用 Block Kit 格式
+use std::time::Duration;
+
+use super::{EventKind, NotificationEvent, Notifier, NotifyFuture};
+use crate::config::SlackConfig;
+use crate::error::Error;
+use crate::i18n::tr;
+
+pub struct SlackNotifier {
+    client: reqwest::Client,
+    config: SlackConfig,
+}
+
+impl SlackNotifier {
+    pub fn new(config: SlackConfig, timeout: Duration) -> Self {
+        Self {
+            client: crate::tls::client_builder()
+                .timeout(timeout)
+                .build()
+                .expect("Failed to build client"),
+            config,
+        }
+    }
+
+    /// 按事件类型选择 webhook 地址与频道：变更与启动事件使用 `change_*`，其余使用 `failure_*`
+    ///
+    /// 现在的 Incoming Webhook 绑定在创建时选择的频道上，会忽略消息中的 `channel`，
+    /// 要分开发送需要为各频道分别创建 webhook；`channel` 只对旧式 webhook 生效。
+    fn route(&self, event: EventKind) -> (&str, &Option<String>) {
+        let (url, channel) = match event {
+            EventKind::Changed | EventKind::Startup => (&self.config.change_webhook_url, &self.config.change_channel),
+            _ => (&self.config.failure_webhook_url, &self.config.failure_channel),
+        };
+        (url.as_deref().unwrap_or(&self.config.webhook_url), channel)
+    }
+
+    /// 构造 Block Kit 消息体
+    fn payload(&self, event: &NotificationEvent) -> serde_json::Value {
+        let title = match event.event {
+            EventKind::Changed => tr!(":white_check_mark: {} 已更新", ":white_check_mark: {} updated", event.record),
+            EventKind::Failed => tr!(":x: {} 更新失败", ":x: {} update failed", event.record),
+            EventKind::Recovered => tr!(":large_green_circle: {} 已恢复", ":large_green_circle: {} recovered", event.record),
+            EventKind::RetriesExhausted => tr!(":rotating_light: {} 重试后仍失败", ":rotating_light: {} still failing after retries", event.record),
+            EventKind::Drift => tr!(":warning: {} 被外部修改", ":warning: {} changed externally", event.record),
+            EventKind::Flapping => tr!(":warning: {} 更新过于频繁", ":warning: {} flapping detected", event.record),
+            EventKind::Startup => tr!(":rocket: CloudFlare DDNS 已启动", ":rocket: CloudFlare DDNS started"),
+        };
+
+        let mut fields = Vec::new();
+        if !event.record.is_empty() {
+            fields.push(serde_json::json!({ "type": "mrkdwn", "text": tr!("*记录*\n{}", "*Record*\n{}", event.record) }));
+        }
+        fields.push(serde_json::json!({ "type": "mrkdwn", "text": tr!("*区域*\n{}", "*Zone*\n{}", event.zone) }));
+        if let Some(old_ip) = &event.old_ip {
+            fields.push(serde_json::json!({ "type": "mrkdwn", "text": tr!("*旧 IP*\n`{}`", "*Old IP*\n`{}`", old_ip) }));
+        }
+        if let Some(new_ip) = &event.new_ip {
+            fields.push(serde_json::json!({ "type": "mrkdwn", "text": tr!("*新 IP*\n`{}`", "*New IP*\n`{}`", new_ip) }));
+        }
+
+        let mut blocks = vec![
+            serde_json::json!({ "type": "header", "text": { "type": "plain_text", "text": title } }),
+            serde_json::json!({ "type": "section", "fields": fields }),
+        ];
+        if let Some(error) = &event.error {
+            blocks.push(serde_json::json!({
+                "type": "section",
+                "text": { "type": "mrkdwn", "text": tr!("*错误*\n```{}```", "*Error*\n```{}```", error) },
+            }));
+        }
+        blocks.push(serde_json::json!({
+            "type": "context",
+            "elements": [{ "type": "mrkdwn", "text": event.timestamp }],
+        }));
+
+        let mut payload = serde_json::json!({
+            "text": event.message(),
+            "blocks": blocks,
+        });
+        if let (_, Some(channel)) = self.route(event.event) {
+            payload["channel"] = serde_json::Value::String(channel.clone());
+        }
+        payload
+    }
+}
+
+impl Notifier for SlackNotifier {
+    fn name(&self) -> &str {
+        "slack"
+    }
+
+    fn notify<'a>(&'a self, event: &'a NotificationEvent) -> NotifyFuture<'a> {
+        Box::pin(async move {
+            // 错误信息中去掉 URL，避免泄露 webhook 密钥
+            let (url, _) = self.route(event.event);
+            let response = self.client
+                .post(url)
+                .json(&self.payload(event))
+                .send()
+                .await
+                .map_err(|e| Error::notification(e.without_url()))?;
+
+            let status = response.status();
+            if status.is_success() {
+                Ok(())
+            } else {
+                let response_text = response.text().await.unwrap_or_default();
+                Err(Error::notification(tr!("Slack 请求失败，状态码 {}: {}", "Slack request failed with status {}: {}", status, response_text)))
+            }
+        })
+    }
+}
@@ -0,0 +1,62 @@
+// Telegram Bot 通知：通过 sendMessage 接口发送文本消息
+use serde::Deserialize;
+use std::time::Duration;
+
+use super::{NotificationEvent, Notifier, NotifyFuture};
+use crate::config::TelegramConfig;
+use crate::error::Error;
+use crate::i18n::tr;
+
+#[derive(Debug, Deserialize)]
+struct TelegramResponse {
+    ok: bool,
+    description: Option<String>,
+}
+
+pub struct TelegramNotifier {
+    client: reqwest::Client,
+    config: TelegramConfig,
+}
+
+impl TelegramNotifier {
+    pub fn new(config: TelegramConfig, timeout: Duration) -> Self {
+        Self {
+            client: crate::tls::client_builder()
+                .timeout(timeout)
+                .build()
+                .expect("Failed to build client"),
+            config,
+        }
+    }
+}
+
+impl Notifier for TelegramNotifier {
+    fn name(&self) -> &str {
+        "telegram"
+    }
+
+    fn notify<'a>(&'a self, event: &'a NotificationEvent) -> NotifyFuture<'a> {
+        Box::pin(async move {
+            let url = format!("https://api.telegram.org/bot{}/sendMessage", self.config.bot_token);
+            let body = serde_json::json!({
+                "chat_id": self.config.chat_id,
+                "text": event.message(),
+            });
+
+            // 错误信息中去掉 URL，避免泄露 bot token
+            let response = self.client.post(&url).json(&body).send().await
+                .map_err(|e| Error::notification(e.without_url()))?;
+            let status = response.status();
+            let response_text = response.text().await.map_err(Error::notification)?;
+
+            match serde_json::from_str::<TelegramResponse>(&response_text) {
+                Ok(response) if response.ok => Ok(()),
+                Ok(response) => Err(Error::notification(tr!(
+                    "Telegram 返回错误: {}", "Telegram returned error: {}",
+                    response.description.unwrap_or_else(|| status.to_string())
+                ))),
+                Err(_) => Err(Error::notification(tr!("无法解析 Telegram 响应，状态码 {}: {}", "Failed to parse Telegram response, status {}: {}", status, response_text))),
+            }
+        })
+    }
+}
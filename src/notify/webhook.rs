@@ -0,0 +1,58 @@
+// 通用 Webhook 通知：将事件以 JSON 格式 POST 到配置的 URL
+//
+// 每个 URL 是一个单独的通知后端，发送失败时只重试失败的 URL，已经收到事件的 URL 不会收到重复的通知。
+use std::time::Duration;
+
+use super::{NotificationEvent, Notifier, NotifyFuture};
+use crate::config::WebhookConfig;
+use crate::error::Error;
+use crate::i18n::tr;
+
+pub struct WebhookNotifier {
+    client: reqwest::Client,
+    url: String,
+    /// 重试队列中的标识，按 URL 在 `urls` 中的位置区分
+    target: String,
+}
+
+impl WebhookNotifier {
+    /// 为 `urls` 中的每个 URL 创建一个后端
+    pub fn for_each_url(config: &WebhookConfig, timeout: Duration) -> Vec<Self> {
+        let client = crate::tls::client_builder()
+            .timeout(timeout)
+            .build()
+            .expect("Failed to build client");
+        config.urls.iter().enumerate().map(|(index, url)| Self {
+            client: client.clone(),
+            url: url.clone(),
+            target: format!("webhook[{}]", index),
+        }).collect()
+    }
+}
+
+impl Notifier for WebhookNotifier {
+    fn name(&self) -> &str {
+        "webhook"
+    }
+
+    fn target(&self) -> &str {
+        &self.target
+    }
+
+    fn notify<'a>(&'a self, event: &'a NotificationEvent) -> NotifyFuture<'a> {
+        Box::pin(async move {
+            // 错误信息中去掉 URL，URL 可能包含密钥
+            let response = self.client
+                .post(&self.url)
+                .json(event)
+                .send()
+                .await
+                .map_err(|e| Error::notification(e.without_url()))?;
+            if response.status().is_success() {
+                Ok(())
+            } else {
+                Err(Error::notification(tr!("Webhook 请求失败，状态码 {}", "Webhook request failed with status {}", response.status())))
+            }
+        })
+    }
+}
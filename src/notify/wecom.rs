@@ -0,0 +1,73 @@
+// 企业微信群机器人通知，发送 Markdown 消息
+use serde::Deserialize;
+use std::time::Duration;
+
+use super::{NotificationEvent, Notifier, NotifyFuture};
+use crate::config::WeComConfig;
+use crate::error::{Error, Result};
+use crate::i18n::tr;
+
+const WECOM_WEBHOOK_URL: &str = "https://qyapi.weixin.qq.com/cgi-bin/webhook/send";
+
+#[derive(Debug, Deserialize)]
+struct WeComResponse {
+    errcode: i32,
+    errmsg: String,
+}
+
+pub struct WeComNotifier {
+    client: reqwest::Client,
+    url: String,
+}
+
+impl WeComNotifier {
+    pub fn new(config: WeComConfig, timeout: Duration) -> Result<Self> {
+        // 机器人通过 URL 中的 key 鉴权
+        let url = match (config.webhook_url, config.key) {
+            (Some(url), _) => url,
+            (None, Some(key)) => format!("{}?key={}", WECOM_WEBHOOK_URL, key),
+            (None, None) => return Err(Error::notification(tr!("必须配置 key 或 webhook_url", "either key or webhook_url must be set"))),
+        };
+
+        Ok(Self {
+            client: crate::tls::client_builder()
+                .timeout(timeout)
+                .build()
+                .expect("Failed to build client"),
+            url,
+        })
+    }
+}
+
+impl Notifier for WeComNotifier {
+    fn name(&self) -> &str {
+        "wecom"
+    }
+
+    fn notify<'a>(&'a self, event: &'a NotificationEvent) -> NotifyFuture<'a> {
+        Box::pin(async move {
+            let body = serde_json::json!({
+                "msgtype": "markdown",
+                "markdown": {
+                    "content": event.markdown(),
+                },
+            });
+
+            // 地址中包含 key，错误信息中去掉 URL
+            let response = self.client
+                .post(&self.url)
+                .json(&body)
+                .send()
+                .await
+                .map_err(|e| Error::notification(e.without_url()))?;
+            let status = response.status();
+            let response_text = response.text().await.map_err(Error::notification)?;
+
+            match serde_json::from_str::<WeComResponse>(&response_text) {
+                Ok(response) if response.errcode == 0 => Ok(()),
+                Ok(response) => Err(Error::notification(tr!("企业微信返回错误 {}: {}", "WeCom returned error {}: {}", response.errcode, response.errmsg))),
+                Err(_) => Err(Error::notification(tr!("无法解析企业微信响应，状态码 {}: {}", "Failed to parse WeCom response, status {}: {}", status, response_text))),
+            }
+        })
+    }
+}
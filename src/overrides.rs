@@ -0,0 +1,146 @@
+// 命令行对配置中记录的筛选与覆盖：解析参数后设置一次，每次加载配置时应用
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::sync::OnceLock;
+
+use crate::config::{Config, DnsRecordConfig, IpVersion};
+use crate::i18n::tr;
+
+/// 命令行指定的筛选与覆盖
+#[derive(Debug, Default)]
+pub struct Overrides {
+    /// 只处理名称匹配任一模式的记录，支持 `*` 与 `?` 通配符
+    pub records: Vec<String>,
+    /// 只处理该 IP 版本的记录（`--ipv4-only` / `--ipv6-only`）
+    pub ip_version: Option<IpVersion>,
+    /// 代替检测结果使用的 IP 地址（`--ip` / `--ip6`）
+    pub ipv4: Option<Ipv4Addr>,
+    pub ipv6: Option<Ipv6Addr>,
+    /// 覆盖所有记录的 TTL 与代理设置（`--ttl` / `--proxied`）
+    pub ttl: Option<u32>,
+    pub proxied: Option<bool>,
+}
+
+static OVERRIDES: OnceLock<Overrides> = OnceLock::new();
+
+pub fn init(overrides: Overrides) {
+    let _ = OVERRIDES.set(overrides);
+}
+
+/// 按命令行参数筛选配置中的记录并覆盖 TTL 与代理设置，没有记录剩下时返回错误
+///
+/// 只检测剩余记录用到的 IP 版本，因此按 IP 版本筛选后另一版本的检测不会执行。
+pub fn apply(config: &mut Config) -> Result<(), String> {
+    let Some(overrides) = OVERRIDES.get() else {
+        return Ok(());
+    };
+    if !overrides.records.is_empty() {
+        retain(config, |record| matches_any(&overrides.records, &record.name));
+        if config.records().next().is_none() {
+            return Err(tr!("配置中没有与 --record {} 匹配的记录", "No records in the config match --record {}", overrides.records.join(", ")));
+        }
+    }
+    if let Some(version) = overrides.ip_version {
+        retain(config, |record| record.get_ip_version() == Ok(version));
+        if config.records().next().is_none() {
+            let flag = match version {
+                IpVersion::V4 => "--ipv4-only",
+                IpVersion::V6 => "--ipv6-only",
+            };
+            return Err(tr!("配置中没有符合 {} 的记录", "No records in the config match {}", flag));
+        }
+    }
+    let zones = config.zones.iter_mut().map(|zone| &mut zone.dns_records);
+    for record in std::iter::once(&mut config.dns_records).chain(zones).flatten() {
+        record.ttl = overrides.ttl.unwrap_or(record.ttl);
+        record.proxied = overrides.proxied.unwrap_or(record.proxied);
+    }
+    Ok(())
+}
+
+/// 在所有区域中只保留满足条件的记录
+pub fn retain(config: &mut Config, keep: impl Fn(&DnsRecordConfig) -> bool) {
+    config.dns_records.retain(&keep);
+    for zone in &mut config.zones {
+        zone.dns_records.retain(&keep);
+    }
+}
+
+/// 是否通过命令行覆盖了 TTL 或代理设置，此时 IP 未变化的记录也按新设置更新
+pub fn settings_overridden() -> bool {
+    OVERRIDES.get().is_some_and(|overrides| overrides.ttl.is_some() || overrides.proxied.is_some())
+}
+
+/// 名称是否符合 `--record` 筛选，未指定筛选时总是符合
+pub fn selects(name: &str) -> bool {
+    OVERRIDES.get().is_none_or(|overrides| overrides.records.is_empty() || matches_any(&overrides.records, name))
+}
+
+/// 命令行指定的 TTL
+pub fn ttl() -> Option<u32> {
+    OVERRIDES.get()?.ttl
+}
+
+/// 命令行指定的代理设置
+pub fn proxied() -> Option<bool> {
+    OVERRIDES.get()?.proxied
+}
+
+/// 命令行为该 IP 版本指定的地址，指定后不再检测
+pub fn ip(version: IpVersion) -> Option<String> {
+    let overrides = OVERRIDES.get()?;
+    match version {
+        IpVersion::V4 => overrides.ipv4.map(|ip| ip.to_string()),
+        IpVersion::V6 => overrides.ipv6.map(|ip| ip.to_string()),
+    }
+}
+
+/// 名称是否匹配任一模式，不区分大小写
+pub fn matches_any(patterns: &[String], name: &str) -> bool {
+    patterns.iter().any(|pattern| glob_match(&pattern.to_ascii_lowercase(), &name.to_ascii_lowercase()))
+}
+
+/// 通配符匹配：`*` 匹配任意长度（包括 0）的字符，`?` 匹配单个字符
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // 最近一个 `*` 的位置，以及它当前匹配到的文本位置，失配时回溯
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((star_p, star_t)) => {
+                    p = star_p + 1;
+                    t = star_t + 1;
+                    star = Some((star_p, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_match() {
+        let patterns = ["*.home.example.com".to_string(), "nas?.example.com".to_string()];
+        assert!(matches_any(&patterns, "router.home.example.com"));
+        assert!(matches_any(&patterns, "NAS1.example.com"));
+        assert!(!matches_any(&patterns, "nas10.example.com"));
+        assert!(!matches_any(&patterns, "home.example.com"));
+        assert!(matches_any(&["home.example.com".to_string()], "home.example.com"));
+        assert!(matches_any(&["*".to_string()], "anything"));
+    }
+}
@@ -0,0 +1,303 @@
+// `plan` 与 `apply` 子命令：先计算并审阅将要执行的变更，再按计划写入记录
+use std::collections::{BTreeMap, HashSet};
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+use cloudflare_ddns::config::Config;
+use cloudflare_ddns::i18n::tr;
+use cloudflare_ddns::{ip_utils, state};
+use cloudflare_ddns::record_set::SetMember;
+use cloudflare_ddns::summary::{self, FailureKind, RunError};
+use cloudflare_ddns::update::{apply_once, load_config, Pipeline, Plan, PlannedRecord, RecordChange, RecordTiming};
+
+/// 计划文件的格式版本，格式不兼容时递增
+const PLAN_VERSION: u32 = 1;
+
+/// `plan --out` 保存的计划
+#[derive(Debug, Serialize, Deserialize)]
+struct SavedPlan {
+    version: u32,
+    /// RFC 3339 时间
+    created_at: String,
+    /// 计算计划时是否指定了 --force
+    force: bool,
+    ipv4: Option<String>,
+    ipv6: Option<String>,
+    /// 各上行线路检测到的地址
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    uplinks: BTreeMap<String, ip_utils::DetectedIps>,
+    records: Vec<SavedRecord>,
+}
+
+/// 计划文件中单条记录的变更
+#[derive(Debug, Serialize, Deserialize)]
+struct SavedRecord {
+    zone: String,
+    name: String,
+    #[serde(rename = "type")]
+    record_type: String,
+    ip: Option<String>,
+    #[serde(flatten)]
+    change: SavedChange,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+enum SavedChange {
+    Noop,
+    Update { record_id: String, old_ip: String },
+    Create,
+    Skip,
+    Set { members: Vec<SetMember>, previous: Option<String> },
+    Hold { until: String },
+}
+
+impl From<&RecordChange> for SavedChange {
+    fn from(change: &RecordChange) -> Self {
+        match change {
+            RecordChange::Noop => Self::Noop,
+            RecordChange::Update { record_id, old_ip } => Self::Update { record_id: record_id.clone(), old_ip: old_ip.clone() },
+            RecordChange::Create => Self::Create,
+            RecordChange::Skip => Self::Skip,
+            RecordChange::Set { members, previous } => Self::Set { members: members.clone(), previous: previous.clone() },
+            RecordChange::Hold { until } => Self::Hold { until: until.clone() },
+        }
+    }
+}
+
+impl From<SavedChange> for RecordChange {
+    fn from(change: SavedChange) -> Self {
+        match change {
+            SavedChange::Noop => Self::Noop,
+            SavedChange::Update { record_id, old_ip } => Self::Update { record_id, old_ip },
+            SavedChange::Create => Self::Create,
+            SavedChange::Skip => Self::Skip,
+            SavedChange::Set { members, previous } => Self::Set { members, previous },
+            SavedChange::Hold { until } => Self::Hold { until },
+        }
+    }
+}
+
+impl SavedPlan {
+    /// 保存计划；有记录计划失败时返回 None，这样的计划不能执行
+    fn new(ips: &ip_utils::DetectedIps, plan: &Plan, force: bool) -> Option<Self> {
+        let records = plan.records.iter().map(|planned| Some(SavedRecord {
+            zone: planned.zone.clone(),
+            name: planned.name.clone(),
+            record_type: planned.record_type.clone(),
+            ip: planned.ip.clone(),
+            change: planned.change.as_ref().ok()?.into(),
+        })).collect::<Option<Vec<_>>>()?;
+        Some(Self {
+            version: PLAN_VERSION,
+            created_at: Local::now().to_rfc3339(),
+            force,
+            ipv4: ips.v4.clone(),
+            ipv6: ips.v6.clone(),
+            uplinks: ips.uplinks.clone(),
+            records,
+        })
+    }
+
+    fn read(path: &Path) -> Result<Self, RunError> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| RunError::new(FailureKind::Config, tr!("无法读取计划文件 {}: {}", "Failed to read plan file {}: {}", path.display(), e)))?;
+        let saved: Self = serde_json::from_str(&content)
+            .map_err(|e| RunError::new(FailureKind::Config, tr!("计划文件 {} 解析失败: {}", "Failed to parse plan file {}: {}", path.display(), e)))?;
+        if saved.version != PLAN_VERSION {
+            return Err(RunError::new(FailureKind::Config, tr!(
+                "计划文件 {} 的版本 {} 不受支持，请重新运行 plan", "Plan file {} has unsupported version {}, run plan again", path.display(), saved.version
+            )));
+        }
+        Ok(saved)
+    }
+
+    fn write(&self, path: &Path) -> Result<(), RunError> {
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| RunError::new(FailureKind::Config, tr!("无法序列化计划: {}", "Failed to serialize plan: {}", e)))?;
+        state::write_atomic(path, (content + "\n").as_bytes())
+            .map_err(|e| RunError::new(FailureKind::Config, tr!("无法写入计划文件 {}: {}", "Failed to write plan file {}: {}", path.display(), e)))
+    }
+
+    fn ips(&self) -> ip_utils::DetectedIps {
+        ip_utils::DetectedIps { v4: self.ipv4.clone(), v6: self.ipv6.clone(), uplinks: self.uplinks.clone() }
+    }
+
+    fn into_plan(self) -> Plan {
+        let records = self.records.into_iter().map(|record| PlannedRecord {
+            name: record.name,
+            record_type: record.record_type,
+            zone: record.zone,
+            ip: record.ip,
+            change: Ok(record.change.into()),
+            timing: RecordTiming::default(),
+            drift: None,
+            flapping: None,
+        }).collect();
+        // 计划文件不保存故障转移的健康状态，执行保存的计划时不推进切换计数
+        Plan { records, failover: Default::default() }
+    }
+}
+
+/// 检测 IP 并计算每条记录的变更，以差异格式输出；指定 `out` 时保存计划供 `apply` 使用
+///
+/// 有记录将被修改时退出码为 10，有记录计划失败时按错误类别，且不保存计划。
+pub async fn plan(config_path: &str, out: Option<&Path>, force: bool, output: summary::OutputFormat) -> Result<u8, RunError> {
+    let config = load_config(config_path)?;
+    let (ips, times) = detect(&config).await?;
+    let plan = Pipeline::new(&config).plan(&ips, &times, force, None).await?;
+    let run_summary = summary::RunSummary::planned(&config, &ips, &plan);
+    match output {
+        summary::OutputFormat::Json => run_summary.print(),
+        summary::OutputFormat::Text => {
+            let changes = run_summary.print_changes();
+            println!("{}", tr!("计划：{} 条记录将被修改", "Plan: {} record(s) to change", changes));
+        }
+    }
+
+    if let Some(out) = out {
+        match SavedPlan::new(&ips, &plan, force) {
+            Some(saved) => {
+                saved.write(out)?;
+                if output == summary::OutputFormat::Text {
+                    println!("{}", tr!("计划已保存到 {0}，使用 apply {0} 执行", "Plan saved to {0}, run apply {0} to execute it", out.display()));
+                }
+            }
+            None => error!(path = %out.display(), "{}", tr!("有记录计划失败，未保存计划", "Some records failed to plan, the plan was not saved")),
+        }
+    }
+    Ok(run_summary.exit_code())
+}
+
+/// 执行变更：指定计划文件时按保存的计划与其中的 IP 执行，否则重新计算计划并在确认后执行
+///
+/// 执行保存的计划前会用同样的 IP 重新读取记录，服务商处的记录在计划之后发生变化时拒绝执行。
+/// 运行锁在核对或计算计划之前获取，确保执行的变更基于其他进程不会再修改的记录。
+pub async fn apply(config_path: &str, plan_file: Option<&Path>, yes: bool, force: bool, output: summary::OutputFormat) -> Result<u8, RunError> {
+    let config = load_config(config_path)?;
+    let _lock = crate::lock::acquire(config_path).await;
+    let (ips, plan, mut pipeline) = match plan_file {
+        Some(plan_file) => {
+            let saved = SavedPlan::read(plan_file)?;
+            let ips = saved.ips();
+            let force = saved.force;
+            let plan = saved.into_plan();
+            let mut pipeline = Pipeline::new(&config);
+            check_current(&mut pipeline, &ips, &plan, force).await?;
+            (ips, plan, pipeline)
+        }
+        None => {
+            if output == summary::OutputFormat::Json && !yes {
+                return Err(RunError::new(FailureKind::Config, tr!(
+                    "--output json 时需要指定计划文件或 --yes", "--output json requires a plan file or --yes"
+                )));
+            }
+            let (ips, times) = detect(&config).await?;
+            let mut pipeline = Pipeline::new(&config);
+            let plan = pipeline.plan(&ips, &times, force, None).await?;
+            let run_summary = summary::RunSummary::planned(&config, &ips, &plan);
+            if output == summary::OutputFormat::Text {
+                run_summary.print_changes();
+            }
+            if let Some(category) = run_summary.records.iter().find_map(|record| record.error_category) {
+                return Err(RunError {
+                    kind: FailureKind::Api,
+                    category,
+                    message: tr!("有记录计划失败，未执行任何变更", "Some records failed to plan, nothing was applied"),
+                });
+            }
+            if !plan.has_changes() {
+                if output == summary::OutputFormat::Text {
+                    println!("{}", tr!("所有记录均无变化，无需执行", "No changes, nothing to apply"));
+                }
+                return Ok(0);
+            }
+            if !yes && !confirm()? {
+                println!("{}", tr!("已取消，未写入任何变更", "Cancelled, nothing was written"));
+                return Ok(0);
+            }
+            (ips, plan, pipeline)
+        }
+    };
+
+    let results = apply_once(&mut pipeline, &plan).await?;
+    let run_summary = summary::RunSummary::new(&config, &ips, &results);
+    if output == summary::OutputFormat::Json {
+        run_summary.print();
+    }
+    run_summary.log_failures();
+    Ok(run_summary.exit_code())
+}
+
+async fn detect(config: &Config) -> Result<(ip_utils::DetectedIps, ip_utils::DetectionTimes), RunError> {
+    Ok(ip_utils::detect_for(config).await?)
+}
+
+/// 用保存的 IP 重新计划计划中要处理的记录，与保存的变更不一致时说明计划已过期
+async fn check_current(pipeline: &mut Pipeline<'_>, ips: &ip_utils::DetectedIps, saved: &Plan, force: bool) -> Result<(), RunError> {
+    let selected: HashSet<(String, String)> = saved.records.iter()
+        .filter(|planned| !matches!(planned.change, Ok(RecordChange::Skip)))
+        .map(|planned| (planned.name.clone(), planned.record_type.clone()))
+        .collect();
+    let current = pipeline.plan(ips, &ip_utils::DetectionTimes::default(), force, Some(&selected)).await?;
+    if let Some((name, e)) = current.records.iter().find_map(|now| Some((&now.name, now.change.as_ref().err()?))) {
+        return Err(RunError {
+            kind: FailureKind::Api,
+            category: e.category,
+            message: tr!("无法读取记录 {}: {}", "Failed to read record {}: {}", name, e.message),
+        });
+    }
+    let stale: Vec<&str> = saved.records.iter()
+        .filter(|planned| selected.contains(&(planned.name.clone(), planned.record_type.clone())))
+        .filter(|planned| current.get(&planned.name, &planned.record_type).is_none_or(|now| now.zone != planned.zone || now.change.as_ref().ok() != planned.change.as_ref().ok()))
+        .map(|planned| planned.name.as_str())
+        .collect();
+    if !stale.is_empty() {
+        return Err(RunError::new(FailureKind::Config, tr!(
+            "计划已过期，以下记录在计划之后发生了变化，请重新运行 plan: {}", "The plan is stale, these records changed after it was made, run plan again: {}", stale.join(", ")
+        )));
+    }
+    Ok(())
+}
+
+fn confirm() -> Result<bool, RunError> {
+    print!("{}", tr!("执行以上变更？[y/N] ", "Apply these changes? [y/N] "));
+    let _ = io::stdout().flush();
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)
+        .map_err(|e| RunError::new(FailureKind::Config, tr!("无法读取输入: {}", "Failed to read input: {}", e)))?;
+    Ok(matches!(answer.trim().to_ascii_lowercase().as_str(), "y" | "yes"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn saved_plan_round_trips() {
+        let plan = Plan {
+            records: vec![PlannedRecord {
+                name: "home.example.com".to_string(),
+                record_type: "A".to_string(),
+                zone: "example.com".to_string(),
+                ip: Some("1.2.3.4".to_string()),
+                change: Ok(RecordChange::Update { record_id: "id".to_string(), old_ip: "5.6.7.8".to_string() }),
+                timing: RecordTiming::default(),
+                drift: None,
+                flapping: None,
+            }],
+            failover: Default::default(),
+        };
+        let ips = ip_utils::DetectedIps { v4: Some("1.2.3.4".to_string()), ..Default::default() };
+        let json = serde_json::to_string(&SavedPlan::new(&ips, &plan, false).unwrap()).unwrap();
+        assert!(json.contains(r#""action":"update""#));
+
+        let saved: SavedPlan = serde_json::from_str(&json).unwrap();
+        assert_eq!(saved.ips(), ips);
+        assert_eq!(saved.into_plan().records[0].change.as_ref().ok(), plan.records[0].change.as_ref().ok());
+    }
+}
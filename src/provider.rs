@@ -0,0 +1,80 @@
+// DNS 服务商抽象：更新流程只通过该接口读写记录，CloudFlare 的实现在 cloudflare.rs，其他服务商在子模块中
+use std::future::Future;
+use std::pin::Pin;
+
+#[cfg(feature = "aliyun")]
+pub mod aliyun;
+#[cfg(feature = "desec")]
+pub mod desec;
+#[cfg(feature = "dnspod")]
+pub mod dnspod;
+#[cfg(feature = "dyndns")]
+pub mod dyndns;
+#[cfg(feature = "dynv6")]
+pub mod dynv6;
+#[cfg(feature = "huawei")]
+pub mod huawei;
+#[cfg(feature = "porkbun")]
+pub mod porkbun;
+#[cfg(feature = "route53")]
+pub mod route53;
+#[cfg(any(feature = "aliyun", feature = "huawei", feature = "route53"))]
+mod sign;
+
+pub type ProviderFuture<'a, T> = Pin<Box<dyn Future<Output = crate::error::Result<T>> + Send + 'a>>;
+
+/// 服务商中的一条 DNS 记录
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    /// 服务商分配的记录 ID，尚未创建的记录为空
+    pub id: String,
+    pub name: String,
+    pub r#type: String,
+    pub content: String,
+    pub ttl: u32,
+    /// 是否经服务商代理（CloudFlare 的橙色云朵），不支持代理的服务商忽略该项
+    pub proxied: bool,
+    /// 标签，用于识别本程序管理的记录集成员；CloudFlare 保存在记录的备注中，不支持标签的服务商为空
+    pub tag: Option<String>,
+}
+
+/// DNS 服务商，实例绑定到一个区域
+pub trait DnsProvider: Send + Sync {
+    /// 服务商名称，用于日志
+    fn name(&self) -> &str;
+
+    /// 查询名称与类型都匹配的记录，不存在时返回 `None`
+    fn get_record<'a>(&'a self, name: &'a str, record_type: &'a str) -> ProviderFuture<'a, Option<Record>>;
+
+    /// 查询名称与类型都匹配的所有记录（如轮询记录集），默认只返回 [`get_record`](Self::get_record) 找到的一条
+    fn list_records<'a>(&'a self, name: &'a str, record_type: &'a str) -> ProviderFuture<'a, Vec<Record>> {
+        Box::pin(async move { Ok(self.get_record(name, record_type).await?.into_iter().collect()) })
+    }
+
+    /// 是否能读写记录的标签，管理记录集需要服务商支持标签
+    fn supports_tags(&self) -> bool {
+        false
+    }
+
+    /// 写入记录：`record.id` 为空时新建，否则更新该 ID 的记录；返回写入后的记录
+    fn upsert_record<'a>(&'a self, record: &'a Record) -> ProviderFuture<'a, Record>;
+
+    /// 删除记录
+    fn delete_record<'a>(&'a self, record: &'a Record) -> ProviderFuture<'a, ()>;
+}
+
+/// 完整名称在区域中的主机记录，区域本身为 `@`
+#[cfg_attr(not(any(feature = "aliyun", feature = "dnspod", feature = "desec", feature = "dynv6", feature = "porkbun")), allow(dead_code))]
+fn relative_name(name: &str, zone: &str) -> String {
+    match name.strip_suffix(zone) {
+        Some("") => "@".to_string(),
+        Some(prefix) if prefix.ends_with('.') => prefix.trim_end_matches('.').to_string(),
+        _ => name.to_string(),
+    }
+}
+
+/// 主机记录对应的完整名称
+#[cfg_attr(not(any(feature = "aliyun", feature = "dnspod", feature = "desec", feature = "dynv6", feature = "porkbun")), allow(dead_code))]
+fn absolute_name(host: &str, zone: &str) -> String {
+    if host == "@" { zone.to_string() } else { format!("{}.{}", host, zone) }
+}
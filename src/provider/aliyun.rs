@@ -0,0 +1,201 @@
+// 阿里云云解析 DNS：使用 AccessKey 的 ACS3-HMAC-SHA256 签名调用 RPC 风格的 API
+use serde::Deserialize;
+use std::time::Duration;
+
+use super::sign;
+use super::{DnsProvider, ProviderFuture, Record, absolute_name, relative_name};
+use crate::config::AliyunConfig;
+use crate::error::{Error, ErrorCategory, Result};
+use crate::i18n::tr;
+
+const DEFAULT_ENDPOINT: &str = "alidns.aliyuncs.com";
+const API_VERSION: &str = "2015-01-09";
+/// TTL 为 1（自动）时使用的值，也是免费版允许的最小值
+const DEFAULT_TTL: u32 = 600;
+
+pub struct AliyunProvider {
+    client: reqwest::Client,
+    access_key_id: String,
+    access_key_secret: String,
+    endpoint: String,
+    zone_name: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ErrorResponse {
+    code: String,
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct DescribeResponse {
+    domain_records: DomainRecords,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct DomainRecords {
+    record: Vec<DomainRecord>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DomainRecord {
+    #[serde(rename = "RecordId")]
+    record_id: String,
+    #[serde(rename = "RR")]
+    rr: String,
+    #[serde(rename = "Type")]
+    r#type: String,
+    #[serde(rename = "Value")]
+    value: String,
+    #[serde(rename = "TTL")]
+    ttl: u32,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct RecordIdResponse {
+    record_id: String,
+}
+
+impl AliyunProvider {
+    pub fn new(config: &AliyunConfig, zone_name: &str, timeout: Duration) -> Self {
+        Self {
+            client: crate::tls::client_builder()
+                .timeout(timeout)
+                .build()
+                .expect("Failed to build client"),
+            access_key_id: config.access_key_id.clone(),
+            access_key_secret: config.access_key_secret.clone(),
+            endpoint: config.endpoint.clone().unwrap_or_else(|| DEFAULT_ENDPOINT.to_string()),
+            zone_name: zone_name.to_string(),
+        }
+    }
+
+    /// 调用一个 API，返回响应正文
+    async fn call(&self, action: &str, params: &[(&str, &str)]) -> Result<String> {
+        let query = sign::canonical_query(params);
+        let date = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+        let nonce = sign::nonce();
+        let payload_hash = sign::sha256_hex(b"");
+        let headers = [
+            ("host", self.endpoint.as_str()),
+            ("x-acs-action", action),
+            ("x-acs-content-sha256", payload_hash.as_str()),
+            ("x-acs-date", date.as_str()),
+            ("x-acs-signature-nonce", nonce.as_str()),
+            ("x-acs-version", API_VERSION),
+        ];
+        let authorization = authorization(&self.access_key_id, &self.access_key_secret, &query, &headers);
+
+        let mut request = self.client.get(format!("https://{}/?{}", self.endpoint, query));
+        for (name, value) in headers.iter().filter(|(name, _)| *name != "host") {
+            request = request.header(*name, *value);
+        }
+        let response = request.header("Authorization", authorization).send().await?;
+        let status = response.status();
+        let text = response.text().await?;
+        if status.is_success() {
+            return Ok(text);
+        }
+        let error: ErrorResponse = serde_json::from_str(&text)
+            .map_err(|_| Error::provider(ErrorCategory::from_status(status), tr!("阿里云 API 请求失败，状态码 {}: {}", "Aliyun API request failed with status {}: {}", status, text)))?;
+        Err(Error::provider(classify(status, &error.code), tr!("阿里云 API 错误 {}: {}", "Aliyun API error {}: {}", error.code, error.message)))
+    }
+}
+
+/// ACS3-HMAC-SHA256 签名的 Authorization 头；`headers` 须按名称排序，请求正文为空
+fn authorization(access_key_id: &str, access_key_secret: &str, query: &str, headers: &[(&str, &str)]) -> String {
+    let canonical_headers: String = headers.iter().map(|(name, value)| format!("{}:{}\n", name, value.trim())).collect();
+    let signed_headers = headers.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(";");
+    let canonical_request = format!("GET\n/\n{}\n{}\n{}\n{}", query, canonical_headers, signed_headers, sign::sha256_hex(b""));
+    let string_to_sign = format!("ACS3-HMAC-SHA256\n{}", sign::sha256_hex(canonical_request.as_bytes()));
+    let signature = sign::hex(&sign::hmac_sha256(access_key_secret.as_bytes(), string_to_sign.as_bytes()));
+    format!("ACS3-HMAC-SHA256 Credential={},SignedHeaders={},Signature={}", access_key_id, signed_headers, signature)
+}
+
+/// 按错误码分类，无法识别时按状态码
+fn classify(status: reqwest::StatusCode, code: &str) -> ErrorCategory {
+    if code.starts_with("InvalidAccessKeyId") || code.starts_with("SignatureDoesNotMatch") || code.starts_with("Forbidden") {
+        ErrorCategory::Auth
+    } else if code.starts_with("Throttling") {
+        ErrorCategory::RateLimited
+    } else if code.starts_with("InvalidDomainName") || code == "DomainRecordNotBelongToUser" {
+        ErrorCategory::NotFound
+    } else {
+        ErrorCategory::from_status(status)
+    }
+}
+
+impl DnsProvider for AliyunProvider {
+    fn name(&self) -> &str {
+        "aliyun"
+    }
+
+    fn get_record<'a>(&'a self, name: &'a str, record_type: &'a str) -> ProviderFuture<'a, Option<Record>> {
+        Box::pin(async move {
+            let text = self.call("DescribeSubDomainRecords", &[("SubDomain", name), ("Type", record_type), ("DomainName", &self.zone_name)]).await?;
+            let response: DescribeResponse = serde_json::from_str(&text)?;
+            Ok(response.domain_records.record.into_iter()
+                .find(|record| record.r#type == record_type)
+                .map(|record| Record {
+                    id: record.record_id,
+                    name: absolute_name(&record.rr, &self.zone_name),
+                    r#type: record.r#type,
+                    content: record.value,
+                    ttl: record.ttl,
+                    proxied: false,
+                    tag: None,
+                }))
+        })
+    }
+
+    fn upsert_record<'a>(&'a self, record: &'a Record) -> ProviderFuture<'a, Record> {
+        Box::pin(async move {
+            let rr = relative_name(&record.name, &self.zone_name);
+            let ttl = if record.ttl <= 1 { DEFAULT_TTL } else { record.ttl }.to_string();
+            let mut params = vec![("RR", rr.as_str()), ("Type", record.r#type.as_str()), ("Value", record.content.as_str()), ("TTL", ttl.as_str())];
+            let record_id = if record.id.is_empty() {
+                params.push(("DomainName", &self.zone_name));
+                let text = self.call("AddDomainRecord", &params).await?;
+                serde_json::from_str::<RecordIdResponse>(&text)?.record_id
+            } else {
+                params.push(("RecordId", &record.id));
+                match self.call("UpdateDomainRecord", &params).await {
+                    Ok(text) => serde_json::from_str::<RecordIdResponse>(&text)?.record_id,
+                    // 强制更新时内容未变，阿里云拒绝与现有记录完全相同的修改
+                    Err(e) if e.to_string().contains("DomainRecordDuplicate") => record.id.clone(),
+                    Err(e) => return Err(e),
+                }
+            };
+            Ok(Record { id: record_id, proxied: false, ..record.clone() })
+        })
+    }
+
+    fn delete_record<'a>(&'a self, record: &'a Record) -> ProviderFuture<'a, ()> {
+        Box::pin(async move {
+            self.call("DeleteDomainRecord", &[("RecordId", &record.id)]).await?;
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_names_and_signature() {
+        assert_eq!(relative_name("home.example.cn", "example.cn"), "home");
+        assert_eq!(relative_name("example.cn", "example.cn"), "@");
+        assert_eq!(relative_name("myexample.cn", "example.cn"), "myexample.cn");
+        assert_eq!(absolute_name("@", "example.cn"), "example.cn");
+
+        let headers = [("host", DEFAULT_ENDPOINT), ("x-acs-action", "DescribeSubDomainRecords")];
+        let authorization = authorization("id", "secret", "SubDomain=home.example.cn", &headers);
+        assert!(authorization.starts_with("ACS3-HMAC-SHA256 Credential=id,SignedHeaders=host;x-acs-action,Signature="));
+        assert_eq!(authorization.rsplit('=').next().unwrap().len(), 64);
+    }
+}
@@ -0,0 +1,136 @@
+// deSEC：使用 Token 调用 desec.io 的 RRset API
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use super::{DnsProvider, ProviderFuture, Record, absolute_name, relative_name};
+use crate::config::DesecConfig;
+use crate::error::{Error, ErrorCategory, Result};
+use crate::i18n::tr;
+
+const API_BASE: &str = "https://desec.io/api/v1";
+/// TTL 为 1（自动）时使用的值，也是 deSEC 默认允许的最小值
+const DEFAULT_TTL: u32 = 3600;
+
+pub struct DesecProvider {
+    client: reqwest::Client,
+    token: String,
+    zone_name: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct RrSet {
+    subname: String,
+    r#type: String,
+    ttl: u32,
+    records: Vec<String>,
+}
+
+impl DesecProvider {
+    pub fn new(config: &DesecConfig, zone_name: &str, timeout: Duration) -> Self {
+        Self {
+            client: crate::tls::client_builder()
+                .timeout(timeout)
+                .build()
+                .expect("Failed to build client"),
+            token: config.token.clone(),
+            zone_name: zone_name.to_string(),
+        }
+    }
+
+    /// 单个 RRset 的地址，区域本身的子名称在 URL 中写作 `@`
+    fn rrset_url(&self, name: &str, record_type: &str) -> String {
+        format!("{}/domains/{}/rrsets/{}/{}/", API_BASE, self.zone_name, relative_name(name, &self.zone_name), record_type)
+    }
+
+    /// 发送请求，404 时返回 `None`
+    async fn send(&self, request: reqwest::RequestBuilder) -> Result<Option<RrSet>> {
+        let response = request.header("Authorization", format!("Token {}", self.token)).send().await?;
+        let status = response.status();
+        if status == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let text = response.text().await?;
+        if !status.is_success() {
+            return Err(Error::provider(ErrorCategory::from_status(status), tr!("deSEC API 请求失败，状态码 {}: {}", "deSEC API request failed with status {}: {}", status, text)));
+        }
+        Ok(Some(serde_json::from_str(&text)?))
+    }
+
+    fn record(&self, rrset: RrSet) -> Record {
+        let host = if rrset.subname.is_empty() { "@" } else { rrset.subname.as_str() };
+        Record {
+            id: absolute_name(host, &self.zone_name),
+            name: absolute_name(host, &self.zone_name),
+            r#type: rrset.r#type,
+            content: rrset.records.into_iter().next().unwrap_or_default(),
+            ttl: rrset.ttl,
+            proxied: false,
+            tag: None,
+        }
+    }
+}
+
+impl DnsProvider for DesecProvider {
+    fn name(&self) -> &str {
+        "desec"
+    }
+
+    fn get_record<'a>(&'a self, name: &'a str, record_type: &'a str) -> ProviderFuture<'a, Option<Record>> {
+        Box::pin(async move {
+            let rrset = self.send(self.client.get(self.rrset_url(name, record_type))).await?;
+            Ok(rrset.map(|rrset| self.record(rrset)))
+        })
+    }
+
+    fn upsert_record<'a>(&'a self, record: &'a Record) -> ProviderFuture<'a, Record> {
+        Box::pin(async move {
+            let subname = match relative_name(&record.name, &self.zone_name) {
+                host if host == "@" => String::new(),
+                host => host,
+            };
+            let rrset = RrSet {
+                subname,
+                r#type: record.r#type.clone(),
+                ttl: if record.ttl <= 1 { DEFAULT_TTL } else { record.ttl },
+                records: vec![record.content.clone()],
+            };
+            let request = if record.id.is_empty() {
+                self.client.post(format!("{}/domains/{}/rrsets/", API_BASE, self.zone_name))
+            } else {
+                self.client.patch(self.rrset_url(&record.name, &record.r#type))
+            };
+            let written = self.send(request.json(&rrset)).await?
+                .ok_or_else(|| Error::provider(ErrorCategory::NotFound, tr!("deSEC 中没有区域 {}", "Zone {} not found in deSEC", self.zone_name)))?;
+            Ok(self.record(written))
+        })
+    }
+
+    fn delete_record<'a>(&'a self, record: &'a Record) -> ProviderFuture<'a, ()> {
+        Box::pin(async move {
+            let response = self.client.delete(self.rrset_url(&record.name, &record.r#type))
+                .header("Authorization", format!("Token {}", self.token))
+                .send()
+                .await?;
+            let status = response.status();
+            if !status.is_success() {
+                return Err(Error::provider(ErrorCategory::from_status(status), tr!("deSEC API 请求失败，状态码 {}", "deSEC API request failed with status {}", status)));
+            }
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rrset_urls() {
+        let config = DesecConfig { token: "token".to_string() };
+        let provider = DesecProvider::new(&config, "example.dedyn.io", Duration::from_secs(1));
+        assert_eq!(provider.rrset_url("example.dedyn.io", "AAAA"), "https://desec.io/api/v1/domains/example.dedyn.io/rrsets/@/AAAA/");
+        assert_eq!(provider.rrset_url("nas.example.dedyn.io", "A"), "https://desec.io/api/v1/domains/example.dedyn.io/rrsets/nas/A/");
+        let rrset: RrSet = serde_json::from_str(r#"{"subname":"","type":"A","ttl":3600,"records":["1.2.3.4"],"touched":"2025-01-01T00:00:00Z"}"#).unwrap();
+        assert_eq!(provider.record(rrset).name, "example.dedyn.io");
+    }
+}
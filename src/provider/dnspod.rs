@@ -0,0 +1,164 @@
+// DNSPod（腾讯云 DNS）：使用 DNSPod Token 调用 dnsapi.cn 的 API
+use serde::Deserialize;
+use serde_json::Value;
+use std::time::Duration;
+
+use super::{DnsProvider, ProviderFuture, Record, absolute_name, relative_name};
+use crate::config::DnspodConfig;
+use crate::error::{Error, ErrorCategory, Result};
+use crate::i18n::tr;
+
+const API_BASE: &str = "https://dnsapi.cn";
+/// TTL 为 1（自动）时使用的值，也是免费套餐允许的最小值
+const DEFAULT_TTL: u32 = 600;
+/// 默认线路
+const DEFAULT_LINE: &str = "默认";
+
+pub struct DnspodProvider {
+    client: reqwest::Client,
+    login_token: String,
+    zone_name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Status {
+    code: String,
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Response {
+    status: Status,
+    #[serde(default)]
+    records: Vec<Value>,
+    record: Option<Value>,
+}
+
+impl DnspodProvider {
+    pub fn new(config: &DnspodConfig, zone_name: &str, timeout: Duration) -> Self {
+        Self {
+            client: crate::tls::client_builder()
+                .timeout(timeout)
+                // DNSPod 要求带有程序名与版本的 User-Agent，否则可能封禁
+                .user_agent(concat!("cloudflare_ddns/", env!("CARGO_PKG_VERSION")))
+                .build()
+                .expect("Failed to build client"),
+            login_token: format!("{},{}", config.token_id, config.token),
+            zone_name: zone_name.to_string(),
+        }
+    }
+
+    /// 调用一个 API；`allow` 中的状态码（如列表为空）与成功一样返回响应
+    async fn call(&self, action: &str, params: &[(&str, &str)], allow: &[&str]) -> Result<Response> {
+        let mut form = vec![("login_token", self.login_token.as_str()), ("format", "json"), ("domain", self.zone_name.as_str())];
+        form.extend_from_slice(params);
+        let response = self.client.post(format!("{}/{}", API_BASE, action)).form(&form).send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(Error::provider(ErrorCategory::from_status(status), tr!("DNSPod API 请求失败，状态码 {}", "DNSPod API request failed with status {}", status)));
+        }
+        let response: Response = response.json().await?;
+        if response.status.code == "1" || allow.contains(&response.status.code.as_str()) {
+            return Ok(response);
+        }
+        Err(Error::provider(
+            classify(&response.status.code),
+            tr!("DNSPod API 错误 {}: {}", "DNSPod API error {}: {}", response.status.code, response.status.message),
+        ))
+    }
+
+    fn record(&self, value: &Value) -> Option<Record> {
+        Some(Record {
+            id: text(value.get("id")?),
+            name: absolute_name(value.get("name")?.as_str()?, &self.zone_name),
+            r#type: text(value.get("type")?),
+            content: text(value.get("value")?),
+            ttl: text(value.get("ttl")?).parse().ok()?,
+            proxied: false,
+            tag: None,
+        })
+    }
+}
+
+/// DNSPod 的数字字段有时以字符串返回，有时以数字返回
+fn text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// 按状态码分类
+fn classify(code: &str) -> ErrorCategory {
+    match code {
+        // 登录失败、Token 无效或已禁用
+        "-1" | "-7" | "85" => ErrorCategory::Auth,
+        // 登录失败次数过多被暂时封禁、API 调用过于频繁
+        "-8" | "-99" => ErrorCategory::RateLimited,
+        // 域名不存在或不属于该账号、记录不存在
+        "6" | "8" | "13" => ErrorCategory::NotFound,
+        _ => ErrorCategory::Other,
+    }
+}
+
+impl DnsProvider for DnspodProvider {
+    fn name(&self) -> &str {
+        "dnspod"
+    }
+
+    fn get_record<'a>(&'a self, name: &'a str, record_type: &'a str) -> ProviderFuture<'a, Option<Record>> {
+        Box::pin(async move {
+            let sub_domain = relative_name(name, &self.zone_name);
+            // 10：记录列表为空
+            let response = self.call("Record.List", &[("sub_domain", &sub_domain), ("record_type", record_type)], &["10"]).await?;
+            Ok(response.records.iter().filter_map(|value| self.record(value)).find(|record| record.r#type == record_type))
+        })
+    }
+
+    fn upsert_record<'a>(&'a self, record: &'a Record) -> ProviderFuture<'a, Record> {
+        Box::pin(async move {
+            let sub_domain = relative_name(&record.name, &self.zone_name);
+            let ttl = if record.ttl <= 1 { DEFAULT_TTL } else { record.ttl }.to_string();
+            let mut params = vec![
+                ("sub_domain", sub_domain.as_str()),
+                ("record_type", record.r#type.as_str()),
+                ("record_line", DEFAULT_LINE),
+                ("value", record.content.as_str()),
+                ("ttl", ttl.as_str()),
+            ];
+            let action = if record.id.is_empty() {
+                "Record.Create"
+            } else {
+                params.push(("record_id", &record.id));
+                "Record.Modify"
+            };
+            let response = self.call(action, &params, &[]).await?;
+            let id = response.record.as_ref().and_then(|r| r.get("id")).map(text).unwrap_or_else(|| record.id.clone());
+            Ok(Record { id, proxied: false, ..record.clone() })
+        })
+    }
+
+    fn delete_record<'a>(&'a self, record: &'a Record) -> ProviderFuture<'a, ()> {
+        Box::pin(async move {
+            self.call("Record.Remove", &[("record_id", &record.id)], &[]).await?;
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_records() {
+        let config = DnspodConfig { token_id: "13490".to_string(), token: "6b5976c68aba5b14a0558b77c17c3932".to_string() };
+        let provider = DnspodProvider::new(&config, "example.com", Duration::from_secs(1));
+        let response: Response = serde_json::from_str(r#"{"status":{"code":"1","message":"ok"},"records":[{"id":"16894439","name":"home","type":"A","value":"1.2.3.4","ttl":"600","line":"默认"}]}"#).unwrap();
+        let record = provider.record(&response.records[0]).unwrap();
+        assert_eq!((record.id.as_str(), record.name.as_str(), record.ttl), ("16894439", "home.example.com", 600));
+
+        let modified: Response = serde_json::from_str(r#"{"status":{"code":"1","message":"ok"},"record":{"id":16894439,"name":"home","value":"5.6.7.8"}}"#).unwrap();
+        assert_eq!(modified.record.as_ref().and_then(|r| r.get("id")).map(text).as_deref(), Some("16894439"));
+    }
+}
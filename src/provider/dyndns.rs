@@ -0,0 +1,167 @@
+// DuckDNS 与 dyndns2 协议：只能通过带凭据的 GET 请求写入地址，没有读取记录的 API，
+// 当前地址通过系统解析器查询，查询不到时视为记录不存在
+use std::time::Duration;
+
+use super::{DnsProvider, ProviderFuture, Record};
+use crate::config::{DuckDnsConfig, Dyndns2Config};
+use crate::error::{Error, ErrorCategory, Result};
+use crate::i18n::tr;
+
+const DUCKDNS_URL: &str = "https://www.duckdns.org/update";
+const DUCKDNS_SUFFIX: &str = ".duckdns.org";
+
+fn client(timeout: Duration) -> reqwest::Client {
+    crate::tls::client_builder()
+        .timeout(timeout)
+        // dyndns2 要求带有程序名与版本的 User-Agent
+        .user_agent(concat!("cloudflare_ddns/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .expect("Failed to build client")
+}
+
+/// 通过系统解析器查询名称当前的地址，只取与记录类型对应的 IP 版本
+async fn resolve(name: &str, record_type: &str) -> Option<Record> {
+    let addresses = tokio::net::lookup_host((name, 0)).await.ok()?;
+    let ip = addresses.map(|address| address.ip()).find(|ip| match record_type {
+        "A" => ip.is_ipv4(),
+        "AAAA" => ip.is_ipv6(),
+        _ => false,
+    })?;
+    Some(Record {
+        // 没有记录 ID，以名称标识已存在的记录
+        id: name.to_string(),
+        name: name.to_string(),
+        r#type: record_type.to_string(),
+        content: ip.to_string(),
+        ttl: 1,
+        proxied: false,
+        tag: None,
+    })
+}
+
+/// 更新请求的响应正文
+async fn send(request: reqwest::RequestBuilder) -> Result<String> {
+    let response = request.send().await?;
+    let status = response.status();
+    let text = response.text().await?;
+    if !status.is_success() {
+        return Err(Error::provider(ErrorCategory::from_status(status), tr!("更新请求失败，状态码 {}: {}", "Update request failed with status {}: {}", status, text.trim())));
+    }
+    Ok(text.trim().to_string())
+}
+
+/// DuckDNS，记录名称为 `<子域名>.duckdns.org`
+pub struct DuckDnsProvider {
+    client: reqwest::Client,
+    token: String,
+}
+
+impl DuckDnsProvider {
+    pub fn new(config: &DuckDnsConfig, timeout: Duration) -> Self {
+        Self { client: client(timeout), token: config.token.clone() }
+    }
+}
+
+impl DnsProvider for DuckDnsProvider {
+    fn name(&self) -> &str {
+        "duckdns"
+    }
+
+    fn get_record<'a>(&'a self, name: &'a str, record_type: &'a str) -> ProviderFuture<'a, Option<Record>> {
+        Box::pin(async move { Ok(resolve(name, record_type).await) })
+    }
+
+    fn upsert_record<'a>(&'a self, record: &'a Record) -> ProviderFuture<'a, Record> {
+        Box::pin(async move {
+            let domain = record.name.strip_suffix(DUCKDNS_SUFFIX).unwrap_or(&record.name);
+            let ip_param = if record.r#type == "AAAA" { "ipv6" } else { "ip" };
+            let request = self.client.get(DUCKDNS_URL)
+                .query(&[("domains", domain), ("token", &self.token), (ip_param, &record.content)]);
+            match send(request).await?.as_str() {
+                "OK" => Ok(Record { id: record.name.clone(), ttl: 1, proxied: false, ..record.clone() }),
+                // DuckDNS 对令牌错误与域名不存在都只返回 KO
+                _ => Err(Error::provider(ErrorCategory::Auth, tr!("DuckDNS 拒绝了更新，请检查 token 与域名", "DuckDNS rejected the update, check the token and domain"))),
+            }
+        })
+    }
+
+    fn delete_record<'a>(&'a self, record: &'a Record) -> ProviderFuture<'a, ()> {
+        Box::pin(async move {
+            let domain = record.name.strip_suffix(DUCKDNS_SUFFIX).unwrap_or(&record.name);
+            let request = self.client.get(DUCKDNS_URL).query(&[("domains", domain), ("token", &self.token), ("clear", "true")]);
+            match send(request).await?.as_str() {
+                "OK" => Ok(()),
+                _ => Err(Error::provider(ErrorCategory::Other, tr!("DuckDNS 拒绝了清除请求", "DuckDNS rejected the clear request"))),
+            }
+        })
+    }
+}
+
+/// dyndns2 协议（Dyn、No-IP、Dynu 等）
+pub struct Dyndns2Provider {
+    client: reqwest::Client,
+    server: String,
+    username: String,
+    password: String,
+}
+
+impl Dyndns2Provider {
+    pub fn new(config: &Dyndns2Config, timeout: Duration) -> Self {
+        Self {
+            client: client(timeout),
+            server: config.server.trim_end_matches('/').to_string(),
+            username: config.username.clone(),
+            password: config.password.clone(),
+        }
+    }
+}
+
+/// 按 dyndns2 的返回码判断结果：good 与 nochg 表示成功
+fn dyndns2_result(body: &str) -> Result<()> {
+    let code = body.split_whitespace().next().unwrap_or_default();
+    let category = match code {
+        "good" | "nochg" => return Ok(()),
+        "badauth" | "!donator" | "badagent" => ErrorCategory::Auth,
+        "nohost" | "notfqdn" | "numhost" => ErrorCategory::NotFound,
+        "abuse" => ErrorCategory::RateLimited,
+        _ => ErrorCategory::Other,
+    };
+    Err(Error::provider(category, tr!("dyndns2 服务返回 {}", "The dyndns2 server returned {}", body)))
+}
+
+impl DnsProvider for Dyndns2Provider {
+    fn name(&self) -> &str {
+        "dyndns2"
+    }
+
+    fn get_record<'a>(&'a self, name: &'a str, record_type: &'a str) -> ProviderFuture<'a, Option<Record>> {
+        Box::pin(async move { Ok(resolve(name, record_type).await) })
+    }
+
+    fn upsert_record<'a>(&'a self, record: &'a Record) -> ProviderFuture<'a, Record> {
+        Box::pin(async move {
+            let request = self.client.get(format!("{}/nic/update", self.server))
+                .basic_auth(&self.username, Some(&self.password))
+                .query(&[("hostname", &record.name), ("myip", &record.content)]);
+            dyndns2_result(&send(request).await?)?;
+            Ok(Record { id: record.name.clone(), ttl: 1, proxied: false, ..record.clone() })
+        })
+    }
+
+    fn delete_record<'a>(&'a self, _record: &'a Record) -> ProviderFuture<'a, ()> {
+        Box::pin(async { Err(Error::provider(ErrorCategory::Other, tr!("dyndns2 协议不支持删除记录", "The dyndns2 protocol cannot delete records"))) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dyndns2_return_codes() {
+        assert!(dyndns2_result("good 1.2.3.4").is_ok());
+        assert!(dyndns2_result("nochg 1.2.3.4").is_ok());
+        assert_eq!(dyndns2_result("badauth").unwrap_err().category(), ErrorCategory::Auth);
+        assert_eq!(dyndns2_result("nohost").unwrap_err().category(), ErrorCategory::NotFound);
+    }
+}
@@ -0,0 +1,198 @@
+// dynv6：使用 HTTP Token 调用 REST API
+//
+// 区域本身的 A / AAAA 记录保存在区域的 ipv4address / ipv6prefix 中；区域内只写了接口标识
+// （如 `::1`）的 AAAA 记录由区域前缀展开，更新这类记录时改写区域前缀，同前缀的记录一起跟随变化。
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::net::Ipv6Addr;
+use std::time::Duration;
+
+use super::{DnsProvider, ProviderFuture, Record, absolute_name, relative_name};
+use crate::config::Dynv6Config;
+use crate::error::{Error, ErrorCategory, Result};
+use crate::i18n::tr;
+
+const API_BASE: &str = "https://dynv6.com/api/v2";
+/// 区域本身的记录使用的 ID
+const ZONE_RECORD_ID: &str = "@";
+
+pub struct Dynv6Provider {
+    client: reqwest::Client,
+    token: String,
+    prefix_length: u8,
+    zone: Zone,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Zone {
+    id: u64,
+    name: String,
+    #[serde(default)]
+    ipv4address: String,
+    #[serde(default)]
+    ipv6prefix: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ZoneRecord {
+    #[serde(default, skip_serializing)]
+    id: u64,
+    r#type: String,
+    name: String,
+    data: String,
+    /// 接口标识按区域前缀展开后的地址
+    #[serde(default, skip_serializing)]
+    expanded_data: Option<String>,
+}
+
+impl ZoneRecord {
+    /// 是否只写了接口标识、由区域前缀展开
+    fn uses_prefix(&self) -> bool {
+        self.r#type == "AAAA" && self.expanded_data.as_deref().is_some_and(|expanded| expanded != self.data)
+    }
+}
+
+impl Dynv6Provider {
+    /// 创建客户端并按名称读取区域
+    pub async fn connect(config: &Dynv6Config, zone_name: &str, timeout: Duration) -> Result<Self> {
+        let client = crate::tls::client_builder()
+            .timeout(timeout)
+            .build()
+            .expect("Failed to build client");
+        let mut provider = Self {
+            client,
+            token: config.token.clone(),
+            prefix_length: config.prefix_length,
+            zone: Zone { id: 0, name: zone_name.to_string(), ipv4address: String::new(), ipv6prefix: String::new() },
+        };
+        let text = provider.call(reqwest::Method::GET, &format!("/zones/by-name/{}", zone_name), None).await?;
+        provider.zone = serde_json::from_str(&text)?;
+        Ok(provider)
+    }
+
+    async fn call(&self, method: reqwest::Method, path: &str, body: Option<serde_json::Value>) -> Result<String> {
+        let mut request = self.client.request(method, format!("{}{}", API_BASE, path)).bearer_auth(&self.token);
+        if let Some(body) = body {
+            request = request.json(&body);
+        }
+        let response = request.send().await?;
+        let status = response.status();
+        let text = response.text().await?;
+        if !status.is_success() {
+            return Err(Error::provider(ErrorCategory::from_status(status), tr!("dynv6 API 请求失败，状态码 {}: {}", "dynv6 API request failed with status {}: {}", status, text)));
+        }
+        Ok(text)
+    }
+
+    async fn records(&self) -> Result<Vec<ZoneRecord>> {
+        let text = self.call(reqwest::Method::GET, &format!("/zones/{}/records", self.zone.id), None).await?;
+        Ok(serde_json::from_str(&text)?)
+    }
+
+    /// 改写区域本身的地址：A 写入 ipv4address，AAAA 写入 ipv6prefix
+    async fn update_zone(&self, record_type: &str, content: &str) -> Result<()> {
+        let body = match record_type {
+            "A" => json!({ "ipv4address": content }),
+            _ => json!({ "ipv6prefix": content }),
+        };
+        self.call(reqwest::Method::PATCH, &format!("/zones/{}", self.zone.id), Some(body)).await?;
+        Ok(())
+    }
+
+    fn record(&self, id: String, name: &str, r#type: &str, content: String) -> Record {
+        Record { id, name: absolute_name(name, &self.zone.name), r#type: r#type.to_string(), content, ttl: 1, proxied: false, tag: None }
+    }
+}
+
+/// 地址所在网络的前缀（保留前 `length` 位）
+fn network(address: &str, length: u8) -> Option<String> {
+    let address: Ipv6Addr = address.parse().ok()?;
+    let mask = u128::MAX.checked_shl(128 - u32::from(length.min(128))).unwrap_or(0);
+    Some(Ipv6Addr::from(u128::from(address) & mask).to_string())
+}
+
+impl DnsProvider for Dynv6Provider {
+    fn name(&self) -> &str {
+        "dynv6"
+    }
+
+    fn get_record<'a>(&'a self, name: &'a str, record_type: &'a str) -> ProviderFuture<'a, Option<Record>> {
+        Box::pin(async move {
+            let host = relative_name(name, &self.zone.name);
+            if host == "@" {
+                let content = match record_type {
+                    "A" => &self.zone.ipv4address,
+                    "AAAA" => &self.zone.ipv6prefix,
+                    _ => return Ok(None),
+                };
+                return Ok((!content.is_empty()).then(|| self.record(ZONE_RECORD_ID.to_string(), "@", record_type, content.clone())));
+            }
+            Ok(self.records().await?.into_iter()
+                .find(|record| record.name == host && record.r#type == record_type)
+                .map(|record| {
+                    let content = record.expanded_data.clone().unwrap_or_else(|| record.data.clone());
+                    self.record(record.id.to_string(), &record.name, &record.r#type, content)
+                }))
+        })
+    }
+
+    fn upsert_record<'a>(&'a self, record: &'a Record) -> ProviderFuture<'a, Record> {
+        Box::pin(async move {
+            let host = relative_name(&record.name, &self.zone.name);
+            if record.id == ZONE_RECORD_ID || host == "@" {
+                self.update_zone(&record.r#type, &record.content).await?;
+                return Ok(Record { id: ZONE_RECORD_ID.to_string(), ttl: 1, proxied: false, ..record.clone() });
+            }
+
+            let existing = self.records().await?.into_iter().find(|r| !record.id.is_empty() && r.id.to_string() == record.id);
+            if let Some(existing) = existing.as_ref().filter(|existing| existing.uses_prefix()) {
+                // 由前缀展开的记录：改写区域前缀，记录本身保持接口标识不变
+                let prefix = network(&record.content, self.prefix_length)
+                    .ok_or_else(|| Error::provider(ErrorCategory::Other, tr!("无效的 IPv6 地址: {}", "Invalid IPv6 address: {}", record.content)))?;
+                self.update_zone("AAAA", &prefix).await?;
+                return Ok(Record { id: existing.id.to_string(), ttl: 1, proxied: false, ..record.clone() });
+            }
+
+            let body = serde_json::to_value(ZoneRecord {
+                id: 0,
+                r#type: record.r#type.clone(),
+                name: host,
+                data: record.content.clone(),
+                expanded_data: None,
+            })?;
+            let text = match existing {
+                Some(existing) => self.call(reqwest::Method::PATCH, &format!("/zones/{}/records/{}", self.zone.id, existing.id), Some(body)).await?,
+                None => self.call(reqwest::Method::POST, &format!("/zones/{}/records", self.zone.id), Some(body)).await?,
+            };
+            let written: ZoneRecord = serde_json::from_str(&text)?;
+            Ok(Record { id: written.id.to_string(), ttl: 1, proxied: false, ..record.clone() })
+        })
+    }
+
+    fn delete_record<'a>(&'a self, record: &'a Record) -> ProviderFuture<'a, ()> {
+        Box::pin(async move {
+            if record.id == ZONE_RECORD_ID {
+                return Err(Error::provider(ErrorCategory::Other, tr!("不能删除区域本身的地址", "The zone's own address cannot be deleted")));
+            }
+            self.call(reqwest::Method::DELETE, &format!("/zones/{}/records/{}", self.zone.id, record.id), None).await?;
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefix_records() {
+        assert_eq!(network("2001:db8:1:2:aaaa:bbbb:cccc:dddd", 64).as_deref(), Some("2001:db8:1:2::"));
+        assert_eq!(network("2001:db8:1:2::1", 56).as_deref(), Some("2001:db8:1::"));
+        let record: ZoneRecord = serde_json::from_str(r#"{"id":7,"type":"AAAA","name":"nas","data":"::1","expandedData":"2001:db8::1","zoneID":1}"#).unwrap();
+        assert!(record.uses_prefix());
+        let record: ZoneRecord = serde_json::from_str(r#"{"id":8,"type":"AAAA","name":"vpn","data":"2001:db8::2","expandedData":"2001:db8::2"}"#).unwrap();
+        assert!(!record.uses_prefix());
+    }
+}
@@ -0,0 +1,203 @@
+// 华为云 DNS：使用 AK/SK 的 SDK-HMAC-SHA256 签名调用记录集 (recordsets) API
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use super::sign;
+use super::{DnsProvider, ProviderFuture, Record};
+use crate::config::HuaweiConfig;
+use crate::error::{Error, ErrorCategory, Result};
+use crate::i18n::tr;
+
+const DEFAULT_ENDPOINT: &str = "dns.myhuaweicloud.com";
+/// TTL 为 1（自动）时使用的值
+const DEFAULT_TTL: u32 = 300;
+
+pub struct HuaweiProvider {
+    client: reqwest::Client,
+    access_key_id: String,
+    secret_access_key: String,
+    endpoint: String,
+    zone_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ZonesResponse {
+    zones: Vec<Zone>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Zone {
+    id: String,
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RecordSetsResponse {
+    recordsets: Vec<RecordSet>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct RecordSet {
+    #[serde(default, skip_serializing)]
+    id: String,
+    name: String,
+    r#type: String,
+    ttl: u32,
+    records: Vec<String>,
+}
+
+/// 错误响应：DNS 服务返回 code/message，API 网关返回 error_code/error_msg
+#[derive(Debug, Deserialize)]
+struct ErrorResponse {
+    #[serde(alias = "error_code")]
+    code: String,
+    #[serde(alias = "error_msg")]
+    message: String,
+}
+
+impl RecordSet {
+    fn into_record(self) -> Record {
+        Record {
+            id: self.id,
+            name: self.name.trim_end_matches('.').to_string(),
+            r#type: self.r#type,
+            content: self.records.into_iter().next().unwrap_or_default(),
+            ttl: self.ttl,
+            proxied: false,
+            tag: None,
+        }
+    }
+}
+
+impl HuaweiProvider {
+    /// 创建客户端并按区域名称查找公网区域的 ID
+    pub async fn connect(config: &HuaweiConfig, zone_name: &str, timeout: Duration) -> Result<Self> {
+        let mut provider = Self {
+            client: crate::tls::client_builder()
+                .timeout(timeout)
+                .build()
+                .expect("Failed to build client"),
+            access_key_id: config.access_key_id.clone(),
+            secret_access_key: config.secret_access_key.clone(),
+            endpoint: config.endpoint.clone().unwrap_or_else(|| DEFAULT_ENDPOINT.to_string()),
+            zone_id: String::new(),
+        };
+        let fqdn = format!("{}.", zone_name.trim_end_matches('.'));
+        let text = provider.call("GET", "/v2/zones", &[("name", &fqdn), ("type", "public")], "").await?;
+        let zones: ZonesResponse = serde_json::from_str(&text)?;
+        provider.zone_id = zones.zones.into_iter().find(|zone| zone.name == fqdn).map(|zone| zone.id)
+            .ok_or_else(|| Error::provider(ErrorCategory::NotFound, tr!("华为云 DNS 中没有公网区域 {}", "No public zone {} found in Huawei Cloud DNS", zone_name)))?;
+        Ok(provider)
+    }
+
+    /// 调用一个 API，返回响应正文
+    async fn call(&self, method: &str, path: &str, query: &[(&str, &str)], body: &str) -> Result<String> {
+        let query = sign::canonical_query(query);
+        let date = chrono::Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+        let authorization = self.authorization(method, path, &query, &date, body);
+
+        let mut url = format!("https://{}{}", self.endpoint, path);
+        if !query.is_empty() {
+            url = format!("{}?{}", url, query);
+        }
+        let response = self.client.request(reqwest::Method::from_bytes(method.as_bytes()).map_err(|e| Error::provider(ErrorCategory::Other, e))?, url)
+            .header("X-Sdk-Date", date)
+            .header("Authorization", authorization)
+            .header("Content-Type", "application/json")
+            .body(body.to_string())
+            .send()
+            .await?;
+        let status = response.status();
+        let text = response.text().await?;
+        if status.is_success() {
+            return Ok(text);
+        }
+        let category = ErrorCategory::from_status(status);
+        Err(match serde_json::from_str::<ErrorResponse>(&text) {
+            Ok(error) => Error::provider(category, tr!("华为云 DNS API 错误 {}: {}", "Huawei Cloud DNS API error {}: {}", error.code, error.message)),
+            Err(_) => Error::provider(category, tr!("华为云 DNS API 请求失败，状态码 {}: {}", "Huawei Cloud DNS API request failed with status {}: {}", status, text)),
+        })
+    }
+
+    /// SDK-HMAC-SHA256 签名的 Authorization 头，签名的头部为 host 与 x-sdk-date
+    fn authorization(&self, method: &str, path: &str, query: &str, date: &str, body: &str) -> String {
+        // 规范 URI 必须以 / 结尾
+        let path = if path.ends_with('/') { path.to_string() } else { format!("{}/", path) };
+        let canonical_request = format!(
+            "{}\n{}\n{}\nhost:{}\nx-sdk-date:{}\n\nhost;x-sdk-date\n{}",
+            method, path, query, self.endpoint, date, sign::sha256_hex(body.as_bytes()),
+        );
+        let string_to_sign = format!("SDK-HMAC-SHA256\n{}\n{}", date, sign::sha256_hex(canonical_request.as_bytes()));
+        let signature = sign::hex(&sign::hmac_sha256(self.secret_access_key.as_bytes(), string_to_sign.as_bytes()));
+        format!("SDK-HMAC-SHA256 Access={}, SignedHeaders=host;x-sdk-date, Signature={}", self.access_key_id, signature)
+    }
+}
+
+impl DnsProvider for HuaweiProvider {
+    fn name(&self) -> &str {
+        "huawei"
+    }
+
+    fn get_record<'a>(&'a self, name: &'a str, record_type: &'a str) -> ProviderFuture<'a, Option<Record>> {
+        Box::pin(async move {
+            let fqdn = format!("{}.", name);
+            let path = format!("/v2/zones/{}/recordsets", self.zone_id);
+            let text = self.call("GET", &path, &[("name", &fqdn), ("type", record_type)], "").await?;
+            let response: RecordSetsResponse = serde_json::from_str(&text)?;
+            // name 参数是模糊匹配，需要再按完整名称筛选
+            Ok(response.recordsets.into_iter()
+                .find(|set| set.name == fqdn && set.r#type == record_type)
+                .map(RecordSet::into_record))
+        })
+    }
+
+    fn upsert_record<'a>(&'a self, record: &'a Record) -> ProviderFuture<'a, Record> {
+        Box::pin(async move {
+            let body = serde_json::to_string(&RecordSet {
+                id: String::new(),
+                name: format!("{}.", record.name),
+                r#type: record.r#type.clone(),
+                ttl: if record.ttl <= 1 { DEFAULT_TTL } else { record.ttl },
+                records: vec![record.content.clone()],
+            })?;
+            let text = if record.id.is_empty() {
+                self.call("POST", &format!("/v2/zones/{}/recordsets", self.zone_id), &[], &body).await?
+            } else {
+                self.call("PUT", &format!("/v2/zones/{}/recordsets/{}", self.zone_id, record.id), &[], &body).await?
+            };
+            let written: RecordSet = serde_json::from_str(&text)?;
+            Ok(written.into_record())
+        })
+    }
+
+    fn delete_record<'a>(&'a self, record: &'a Record) -> ProviderFuture<'a, ()> {
+        Box::pin(async move {
+            self.call("DELETE", &format!("/v2/zones/{}/recordsets/{}", self.zone_id, record.id), &[], "").await?;
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signature_and_record_sets() {
+        let provider = HuaweiProvider {
+            client: reqwest::Client::new(),
+            access_key_id: "AK".to_string(),
+            secret_access_key: "SK".to_string(),
+            endpoint: DEFAULT_ENDPOINT.to_string(),
+            zone_id: String::new(),
+        };
+        let authorization = provider.authorization("GET", "/v2/zones", "name=example.com.", "20250101T000000Z", "");
+        assert!(authorization.starts_with("SDK-HMAC-SHA256 Access=AK, SignedHeaders=host;x-sdk-date, Signature="));
+
+        let response: RecordSetsResponse = serde_json::from_str(r#"{"recordsets":[{"id":"2c9eb155","name":"home.example.com.","type":"A","ttl":300,"records":["1.2.3.4"],"status":"ACTIVE"}]}"#).unwrap();
+        let record = response.recordsets.into_iter().next().unwrap().into_record();
+        assert_eq!((record.name.as_str(), record.content.as_str()), ("home.example.com", "1.2.3.4"));
+        let error: ErrorResponse = serde_json::from_str(r#"{"error_code":"APIGW.0301","error_msg":"Incorrect IAM authentication information"}"#).unwrap();
+        assert_eq!(error.code, "APIGW.0301");
+    }
+}
@@ -0,0 +1,166 @@
+// Porkbun：使用 API Key 与 Secret API Key 调用 JSON API，凭据放在每个 POST 请求的正文中
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::time::Duration;
+
+use super::{DnsProvider, ProviderFuture, Record, absolute_name, relative_name};
+use crate::config::PorkbunConfig;
+use crate::error::{Error, ErrorCategory, Result};
+use crate::i18n::tr;
+
+const API_BASE: &str = "https://api.porkbun.com/api/json/v3";
+/// TTL 为 1（自动）时使用的值，也是 Porkbun 允许的最小值
+const DEFAULT_TTL: u32 = 600;
+
+pub struct PorkbunProvider {
+    client: reqwest::Client,
+    api_key: String,
+    secret_api_key: String,
+    zone_name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Response {
+    status: String,
+    #[serde(default)]
+    message: String,
+    #[serde(default)]
+    records: Vec<PorkbunRecord>,
+    /// 创建记录时返回的 ID
+    id: Option<Value>,
+}
+
+/// Porkbun 返回的记录，数字字段以字符串表示
+#[derive(Debug, Deserialize)]
+struct PorkbunRecord {
+    id: String,
+    name: String,
+    r#type: String,
+    content: String,
+    ttl: String,
+}
+
+/// 子域名，区域本身为空字符串
+fn subdomain(name: &str, zone_name: &str) -> String {
+    match relative_name(name, zone_name) {
+        host if host == "@" => String::new(),
+        host => host,
+    }
+}
+
+impl PorkbunProvider {
+    pub fn new(config: &PorkbunConfig, zone_name: &str, timeout: Duration) -> Self {
+        Self {
+            client: crate::tls::client_builder()
+                .timeout(timeout)
+                .build()
+                .expect("Failed to build client"),
+            api_key: config.api_key.clone(),
+            secret_api_key: config.secret_api_key.clone(),
+            zone_name: zone_name.to_string(),
+        }
+    }
+
+    /// 调用一个 API，`body` 中的字段与凭据一起发送
+    async fn call(&self, path: &str, body: Value) -> Result<Response> {
+        let mut body = body;
+        body["apikey"] = json!(self.api_key);
+        body["secretapikey"] = json!(self.secret_api_key);
+        let response = self.client.post(format!("{}{}", API_BASE, path)).json(&body).send().await?;
+        let status = response.status();
+        let text = response.text().await?;
+        // 出错时同样返回 JSON，HTTP 状态码多为 400
+        match serde_json::from_str::<Response>(&text) {
+            Ok(response) if response.status == "SUCCESS" => Ok(response),
+            Ok(response) => Err(Error::provider(classify(status, &response.message), tr!("Porkbun API 错误: {}", "Porkbun API error: {}", response.message))),
+            Err(_) => Err(Error::provider(ErrorCategory::from_status(status), tr!("Porkbun API 请求失败，状态码 {}: {}", "Porkbun API request failed with status {}: {}", status, text))),
+        }
+    }
+
+    fn record(&self, record: PorkbunRecord) -> Record {
+        let host = subdomain(&record.name, &self.zone_name);
+        Record {
+            id: record.id,
+            name: absolute_name(if host.is_empty() { "@" } else { &host }, &self.zone_name),
+            r#type: record.r#type,
+            content: record.content,
+            ttl: record.ttl.parse().unwrap_or(DEFAULT_TTL),
+            proxied: false,
+            tag: None,
+        }
+    }
+}
+
+/// 按错误消息细分类别：Porkbun 对凭据错误与未开启 API 访问都返回 400
+fn classify(status: reqwest::StatusCode, message: &str) -> ErrorCategory {
+    let message = message.to_ascii_lowercase();
+    if message.contains("api key") || message.contains("api access") {
+        ErrorCategory::Auth
+    } else {
+        ErrorCategory::from_status(status)
+    }
+}
+
+impl DnsProvider for PorkbunProvider {
+    fn name(&self) -> &str {
+        "porkbun"
+    }
+
+    fn get_record<'a>(&'a self, name: &'a str, record_type: &'a str) -> ProviderFuture<'a, Option<Record>> {
+        Box::pin(async move {
+            let path = format!("/dns/retrieveByNameType/{}/{}/{}", self.zone_name, record_type, subdomain(name, &self.zone_name));
+            let response = self.call(&path, json!({})).await?;
+            Ok(response.records.into_iter().next().map(|record| self.record(record)))
+        })
+    }
+
+    fn upsert_record<'a>(&'a self, record: &'a Record) -> ProviderFuture<'a, Record> {
+        Box::pin(async move {
+            let body = json!({
+                "name": subdomain(&record.name, &self.zone_name),
+                "type": record.r#type,
+                "content": record.content,
+                "ttl": (if record.ttl <= 1 { DEFAULT_TTL } else { record.ttl }).to_string(),
+            });
+            let id = if record.id.is_empty() {
+                let response = self.call(&format!("/dns/create/{}", self.zone_name), body).await?;
+                match response.id {
+                    Some(Value::String(id)) => id,
+                    Some(id) => id.to_string(),
+                    None => String::new(),
+                }
+            } else {
+                self.call(&format!("/dns/edit/{}/{}", self.zone_name, record.id), body).await?;
+                record.id.clone()
+            };
+            Ok(Record { id, ttl: if record.ttl <= 1 { DEFAULT_TTL } else { record.ttl }, proxied: false, ..record.clone() })
+        })
+    }
+
+    fn delete_record<'a>(&'a self, record: &'a Record) -> ProviderFuture<'a, ()> {
+        Box::pin(async move {
+            self.call(&format!("/dns/delete/{}/{}", self.zone_name, record.id), json!({})).await?;
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_and_errors() {
+        let config = PorkbunConfig { api_key: "pk1_".to_string(), secret_api_key: "sk1_".to_string() };
+        let provider = PorkbunProvider::new(&config, "example.com", Duration::from_secs(1));
+        assert_eq!(subdomain("example.com", "example.com"), "");
+        assert_eq!(subdomain("home.example.com", "example.com"), "home");
+
+        let response: Response = serde_json::from_str(r#"{"status":"SUCCESS","records":[{"id":"106926659","name":"home.example.com","type":"A","content":"1.2.3.4","ttl":"600","prio":"0","notes":""}]}"#).unwrap();
+        let record = provider.record(response.records.into_iter().next().unwrap());
+        assert_eq!((record.id.as_str(), record.name.as_str(), record.ttl), ("106926659", "home.example.com", 600));
+
+        let error: Response = serde_json::from_str(r#"{"status":"ERROR","message":"Invalid API key. (002)"}"#).unwrap();
+        assert_eq!(classify(reqwest::StatusCode::BAD_REQUEST, &error.message), ErrorCategory::Auth);
+    }
+}
@@ -0,0 +1,196 @@
+// AWS Route 53：使用 SigV4 签名调用 REST API，写入通过 ChangeResourceRecordSets 完成
+use std::time::Duration;
+
+use super::sign;
+use super::{DnsProvider, ProviderFuture, Record};
+use crate::config::Route53Config;
+use crate::error::{Error, ErrorCategory, Result};
+use crate::i18n::tr;
+
+const HOST: &str = "route53.amazonaws.com";
+const API_VERSION: &str = "2013-04-01";
+/// Route 53 是全局服务，签名固定使用 us-east-1
+const REGION: &str = "us-east-1";
+const SERVICE: &str = "route53";
+/// TTL 为 1（自动）时使用的值
+const DEFAULT_TTL: u32 = 300;
+
+pub struct Route53Provider {
+    client: reqwest::Client,
+    access_key_id: String,
+    secret_access_key: String,
+    hosted_zone_id: String,
+}
+
+impl Route53Provider {
+    /// 创建客户端，未配置 `hosted_zone_id` 时按区域名称查找托管区域
+    pub async fn connect(config: &Route53Config, zone_name: &str, timeout: Duration) -> Result<Self> {
+        let mut provider = Self {
+            client: crate::tls::client_builder()
+                .timeout(timeout)
+                .build()
+                .expect("Failed to build client"),
+            access_key_id: config.access_key_id.clone(),
+            secret_access_key: config.secret_access_key.clone(),
+            hosted_zone_id: config.hosted_zone_id.clone().unwrap_or_default(),
+        };
+        if provider.hosted_zone_id.is_empty() {
+            provider.hosted_zone_id = provider.find_hosted_zone(zone_name).await?;
+        }
+        Ok(provider)
+    }
+
+    async fn find_hosted_zone(&self, zone_name: &str) -> Result<String> {
+        let xml = self.call("GET", "hostedzonesbyname", &[("dnsname", zone_name), ("maxitems", "1")], "").await?;
+        let zone = elements(&xml, "HostedZone").into_iter()
+            .find(|zone| element(zone, "Name").is_some_and(|name| name.trim_end_matches('.') == zone_name.trim_end_matches('.')));
+        let id = zone.and_then(|zone| element(zone, "Id"))
+            .ok_or_else(|| Error::provider(ErrorCategory::NotFound, tr!("Route 53 中没有区域 {} 的托管区域", "No Route 53 hosted zone found for {}", zone_name)))?;
+        Ok(id.trim_start_matches("/hostedzone/").to_string())
+    }
+
+    /// 调用一个 API，`path` 为 API 版本之后的路径，返回响应正文
+    async fn call(&self, method: &str, path: &str, query: &[(&str, &str)], body: &str) -> Result<String> {
+        let path = format!("/{}/{}", API_VERSION, path);
+        let query = sign::canonical_query(query);
+        let now = chrono::Utc::now();
+        let request = sign::SigV4Request { method, path: &path, query: &query, host: HOST, body };
+        let authorization = request.authorization(&self.access_key_id, &self.secret_access_key, REGION, SERVICE, now);
+
+        let mut url = format!("https://{}{}", HOST, path);
+        if !query.is_empty() {
+            url = format!("{}?{}", url, query);
+        }
+        let method = reqwest::Method::from_bytes(method.as_bytes()).map_err(|e| Error::provider(ErrorCategory::Other, e))?;
+        let response = self.client.request(method, url)
+            .header("x-amz-date", now.format(sign::AMZ_DATE_FORMAT).to_string())
+            .header("Authorization", authorization)
+            .header("Content-Type", "application/xml")
+            .body(body.to_string())
+            .send()
+            .await?;
+        let status = response.status();
+        let text = response.text().await?;
+        if status.is_success() {
+            return Ok(text);
+        }
+        let code = element(&text, "Code").unwrap_or_default();
+        let message = element(&text, "Message").unwrap_or_default();
+        Err(Error::provider(classify(status, &code), tr!("Route 53 API 错误 {} {}: {}", "Route 53 API error {} {}: {}", status, code, message)))
+    }
+
+    /// 提交一个变更：`action` 为 UPSERT 或 DELETE
+    async fn change(&self, action: &str, record: &Record) -> Result<()> {
+        let ttl = if record.ttl <= 1 { DEFAULT_TTL } else { record.ttl };
+        let body = format!(
+            concat!(
+                r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+                r#"<ChangeResourceRecordSetsRequest xmlns="https://route53.amazonaws.com/doc/2013-04-01/"><ChangeBatch><Changes><Change>"#,
+                "<Action>{}</Action><ResourceRecordSet><Name>{}</Name><Type>{}</Type><TTL>{}</TTL>",
+                "<ResourceRecords><ResourceRecord><Value>{}</Value></ResourceRecord></ResourceRecords>",
+                "</ResourceRecordSet></Change></Changes></ChangeBatch></ChangeResourceRecordSetsRequest>",
+            ),
+            action, escape(&record.name), escape(&record.r#type), ttl, escape(&record.content),
+        );
+        self.call("POST", &format!("hostedzone/{}/rrset/", self.hosted_zone_id), &[], &body).await?;
+        Ok(())
+    }
+}
+
+/// 按错误码分类，无法识别时按状态码
+fn classify(status: reqwest::StatusCode, code: &str) -> ErrorCategory {
+    match code {
+        "InvalidClientTokenId" | "SignatureDoesNotMatch" | "AccessDenied" | "ExpiredToken" => ErrorCategory::Auth,
+        "Throttling" | "PriorRequestNotComplete" => ErrorCategory::RateLimited,
+        "NoSuchHostedZone" => ErrorCategory::NotFound,
+        _ => ErrorCategory::from_status(status),
+    }
+}
+
+/// 第一个名为 `name` 的元素的内容
+fn element(xml: &str, name: &str) -> Option<String> {
+    elements(xml, name).first().map(|text| unescape(text))
+}
+
+/// 所有名为 `name` 的元素的内容（不解码），Route 53 的响应没有属性与自闭合的同名元素
+fn elements<'a>(xml: &'a str, name: &str) -> Vec<&'a str> {
+    let (open, close) = (format!("<{}>", name), format!("</{}>", name));
+    let mut found = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find(&open) {
+        let after = &rest[start + open.len()..];
+        let Some(end) = after.find(&close) else { break };
+        found.push(&after[..end]);
+        rest = &after[end + close.len()..];
+    }
+    found
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<").replace("&gt;", ">").replace("&quot;", "\"").replace("&apos;", "'").replace("&amp;", "&")
+}
+
+/// Route 53 返回的名称以点结尾，通配符 `*` 转义为 `\052`
+fn normalize_name(name: &str) -> String {
+    name.trim_end_matches('.').replace("\\052", "*")
+}
+
+impl DnsProvider for Route53Provider {
+    fn name(&self) -> &str {
+        "route53"
+    }
+
+    fn get_record<'a>(&'a self, name: &'a str, record_type: &'a str) -> ProviderFuture<'a, Option<Record>> {
+        Box::pin(async move {
+            let path = format!("hostedzone/{}/rrset", self.hosted_zone_id);
+            let xml = self.call("GET", &path, &[("name", name), ("type", record_type), ("maxitems", "1")], "").await?;
+            // 列表从给定名称开始，第一项不一定是要找的记录
+            Ok(elements(&xml, "ResourceRecordSet").into_iter()
+                .filter_map(|set| {
+                    let found = normalize_name(&element(set, "Name")?);
+                    let r#type = element(set, "Type")?;
+                    (found == name && r#type == record_type).then(|| Record {
+                        // Route 53 没有记录 ID，以名称标识已存在的记录
+                        id: found.clone(),
+                        name: found,
+                        r#type,
+                        content: element(set, "Value").unwrap_or_default(),
+                        ttl: element(set, "TTL").and_then(|ttl| ttl.parse().ok()).unwrap_or_default(),
+                        proxied: false,
+                        tag: None,
+                    })
+                })
+                .next())
+        })
+    }
+
+    fn upsert_record<'a>(&'a self, record: &'a Record) -> ProviderFuture<'a, Record> {
+        Box::pin(async move {
+            self.change("UPSERT", record).await?;
+            Ok(Record { id: record.name.clone(), proxied: false, ..record.clone() })
+        })
+    }
+
+    fn delete_record<'a>(&'a self, record: &'a Record) -> ProviderFuture<'a, ()> {
+        Box::pin(self.change("DELETE", record))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_record_sets() {
+        let xml = r#"<ListResourceRecordSetsResponse><ResourceRecordSets><ResourceRecordSet><Name>\052.example.com.</Name><Type>A</Type><TTL>300</TTL><ResourceRecords><ResourceRecord><Value>1.2.3.4</Value></ResourceRecord></ResourceRecords></ResourceRecordSet></ResourceRecordSets></ListResourceRecordSetsResponse>"#;
+        let sets = elements(xml, "ResourceRecordSet");
+        assert_eq!(sets.len(), 1);
+        assert_eq!(normalize_name(&element(sets[0], "Name").unwrap()), "*.example.com");
+        assert_eq!(element(sets[0], "Value").as_deref(), Some("1.2.3.4"));
+        assert_eq!(element("<Message>a &amp; b</Message>", "Message").as_deref(), Some("a & b"));
+    }
+}
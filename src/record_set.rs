@@ -0,0 +1,232 @@
+// 轮询记录集：一条配置的记录对应多条同名的 A/AAAA 记录，每个成员一条
+//
+// 成员记录的标签为 `cloudflare_ddns:member=<成员名称>`，只有带这种标签的记录由本程序管理：
+// 缺少的成员会被创建，地址变化的成员会被更新，不在配置中的成员会被删除，没有标签的同名记录保持不变。
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+
+use crate::config::DnsRecordConfig;
+use crate::error::{Error, ErrorCategory, Result};
+use crate::i18n::tr;
+use crate::ip_utils::DetectedIps;
+use crate::logging;
+use crate::provider::{DnsProvider, Record};
+use crate::update::RecordOutcome;
+
+/// 成员记录标签的前缀
+pub const TAG_PREFIX: &str = "cloudflare_ddns:member=";
+
+/// 成员记录的标签
+pub fn member_tag(member: &str) -> String {
+    format!("{}{}", TAG_PREFIX, member)
+}
+
+/// 记录标签对应的成员名称，不由本程序管理的记录为 `None`
+pub fn tagged_member(record: &Record) -> Option<&str> {
+    record.tag.as_deref()?.strip_prefix(TAG_PREFIX)
+}
+
+/// 记录集中一个成员需要的变更
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SetMember {
+    pub member: String,
+    /// 服务商处已有记录的 ID，需要新建时为空
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub record_id: Option<String>,
+    /// 已有记录的内容，需要新建时为空
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub old_ip: Option<String>,
+    /// 成员应指向的地址，为空时删除该成员的记录
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub new_ip: Option<String>,
+}
+
+/// 比较服务商处的记录与各成员应指向的地址（没有地址的成员保留已有记录），返回需要的变更
+///
+/// `force` 为真时地址未变的成员也会重新写入；同一成员有多条记录时多余的记录被删除。
+pub fn diff(existing: &[Record], desired: &[(String, Option<String>)], force: bool) -> Vec<SetMember> {
+    let mut changes = Vec::new();
+    let mut kept: Vec<&str> = Vec::new();
+    for (member, ip) in desired {
+        let current = existing.iter().find(|record| tagged_member(record) == Some(member.as_str()));
+        if let Some(record) = current {
+            kept.push(&record.id);
+        }
+        let Some(ip) = ip else { continue };
+        if current.is_some_and(|record| record.content == *ip) && !force {
+            continue;
+        }
+        changes.push(SetMember {
+            member: member.clone(),
+            record_id: current.map(|record| record.id.clone()),
+            old_ip: current.map(|record| record.content.clone()),
+            new_ip: Some(ip.clone()),
+        });
+    }
+    for record in existing {
+        if let Some(member) = tagged_member(record)
+            && !kept.contains(&record.id.as_str())
+        {
+            changes.push(SetMember {
+                member: member.to_string(),
+                record_id: Some(record.id.clone()),
+                old_ip: Some(record.content.clone()),
+                new_ip: None,
+            });
+        }
+    }
+    changes
+}
+
+/// 本程序管理的成员记录的内容，以 `, ` 连接，没有成员记录时为 `None`
+fn managed_content(existing: &[Record]) -> Option<String> {
+    let contents: Vec<_> = existing.iter().filter(|record| tagged_member(record).is_some()).map(|record| record.content.as_str()).collect();
+    (!contents.is_empty()).then(|| contents.join(", "))
+}
+
+/// 读取服务商处本程序管理的成员记录的内容，以 `, ` 连接
+pub async fn current_content(provider: &dyn DnsProvider, record_config: &DnsRecordConfig) -> Result<Option<String>> {
+    let existing = provider.list_records(&record_config.name, &record_config.r#type).await?;
+    Ok(managed_content(&existing))
+}
+
+/// 只读取现有记录，计算记录集需要的变更，返回各成员地址（以 `, ` 连接）、原有成员记录的内容与变更，没有变更时变更为空
+pub async fn plan(
+    provider: &dyn DnsProvider,
+    record_config: &DnsRecordConfig,
+    ips: &DetectedIps,
+    force: bool,
+) -> Result<(String, Option<String>, Vec<SetMember>)> {
+    if !provider.supports_tags() {
+        return Err(Error::config(tr!(
+            "记录 {} 是轮询记录集，但 {} 不支持记录标签",
+            "Record {} is a round-robin set but {} does not support record tags",
+            record_config.name, provider.name()
+        )));
+    }
+    let version = record_config.get_ip_version()
+        .map_err(|e| Error::config(tr!("IP 版本无效: {}", "Invalid IP version: {}", e)))?;
+    let desired: Vec<_> = record_config.members.iter()
+        .map(|member| (member.name.clone(), ips.for_member(version, member)))
+        .collect();
+    for (member, ip) in &desired {
+        if ip.is_none() {
+            warn!(record = %record_config.name, member = %member, "{}", tr!("记录集成员未检测到地址，保留其现有记录", "No address detected for set member, keeping its existing record"));
+        }
+    }
+    let current_ip = ips.for_record(record_config)
+        .ok_or_else(|| Error::detection(ErrorCategory::Other, tr!("记录集 {} 的成员都未检测到 {} 地址", "No {1} address detected for any member of set {0}", record_config.name, record_config.ip_version)))?;
+    debug!(ip = %current_ip, "{}", tr!("记录集成员的地址", "Set member addresses"));
+
+    let existing = provider.list_records(&record_config.name, &record_config.r#type).await
+        .map_err(|e| e.map_message(|message| tr!("无法获取 DNS 记录。请检查您的 API 凭据。错误: {}", "Failed to get DNS record. Check your API credentials. Error: {}", message)))?;
+    Ok((current_ip, managed_content(&existing), diff(&existing, &desired, force)))
+}
+
+/// 按计划写入记录集的变更，`current_ip` 为各成员地址，`previous` 为原有成员记录的内容
+pub async fn apply(
+    provider: &dyn DnsProvider,
+    record_config: &DnsRecordConfig,
+    current_ip: &str,
+    previous: Option<String>,
+    members: &[SetMember],
+) -> Result<RecordOutcome> {
+    for member in members {
+        write_member(provider, record_config, member).await?;
+    }
+    Ok(match previous {
+        Some(old_ip) => RecordOutcome::Updated { old_ip, new_ip: current_ip.to_string() },
+        None => RecordOutcome::Created { new_ip: current_ip.to_string() },
+    })
+}
+
+/// 撤销已写入的记录集变更，把各成员改回原来的内容
+pub async fn revert(provider: &dyn DnsProvider, record_config: &DnsRecordConfig, members: &[SetMember]) -> Result<()> {
+    let existing = provider.list_records(&record_config.name, &record_config.r#type).await?;
+    for member in members {
+        let current = existing.iter().find(|record| tagged_member(record) == Some(member.member.as_str()));
+        let revert = SetMember {
+            member: member.member.clone(),
+            record_id: current.map(|record| record.id.clone()),
+            old_ip: current.map(|record| record.content.clone()),
+            new_ip: member.old_ip.clone(),
+        };
+        if revert.old_ip != revert.new_ip {
+            write_member(provider, record_config, &revert).await?;
+        }
+    }
+    Ok(())
+}
+
+/// 写入一个成员的变更：有地址时更新或新建，否则删除
+async fn write_member(provider: &dyn DnsProvider, record_config: &DnsRecordConfig, member: &SetMember) -> Result<()> {
+    let mut record = Record {
+        id: member.record_id.clone().unwrap_or_default(),
+        name: record_config.name.clone(),
+        r#type: record_config.r#type.clone(),
+        content: member.old_ip.clone().unwrap_or_default(),
+        ttl: record_config.ttl,
+        proxied: record_config.proxied,
+        tag: Some(member_tag(&member.member)),
+    };
+    match &member.new_ip {
+        Some(new_ip) => {
+            record.content = new_ip.clone();
+            provider.upsert_record(&record).await.map_err(|e| e.map_message(|message| tr!(
+                "无法写入记录集 {} 的成员 {}。错误: {}", "Failed to write member {1} of set {0}. Error: {2}",
+                record_config.name, member.member, message
+            )))?;
+            info!(target: logging::CHANGE_TARGET, record = %record_config.name, member = %member.member, old_ip = %member.old_ip.as_deref().unwrap_or("-"), ip = %new_ip, "{}", tr!("记录集成员已写入", "Set member written"));
+        }
+        None if member.record_id.is_some() => {
+            provider.delete_record(&record).await.map_err(|e| e.map_message(|message| tr!(
+                "无法删除记录集 {} 的成员 {}。错误: {}", "Failed to delete member {1} of set {0}. Error: {2}",
+                record_config.name, member.member, message
+            )))?;
+            info!(target: logging::CHANGE_TARGET, record = %record_config.name, member = %member.member, old_ip = %record.content, "{}", tr!("记录集成员已删除", "Set member removed"));
+        }
+        None => {}
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(id: &str, content: &str, tag: Option<&str>) -> Record {
+        Record {
+            id: id.to_string(),
+            name: "www.example.com".to_string(),
+            r#type: "A".to_string(),
+            content: content.to_string(),
+            ttl: 60,
+            proxied: false,
+            tag: tag.map(member_tag),
+        }
+    }
+
+    #[test]
+    fn diff_only_touches_tagged_members() {
+        let existing = [
+            record("1", "192.0.2.1", Some("wan1")),
+            record("2", "192.0.2.2", Some("wan2")),
+            record("3", "192.0.2.3", Some("old")),
+            record("4", "192.0.2.4", None),
+        ];
+        let desired = [
+            ("wan1".to_string(), Some("192.0.2.1".to_string())),
+            ("wan2".to_string(), None),
+            ("wan3".to_string(), Some("192.0.2.30".to_string())),
+        ];
+        let changes = diff(&existing, &desired, false);
+        // wan1 未变，wan2 没有地址时保留，wan3 新建，old 不在配置中被删除，没有标签的记录不变
+        assert_eq!(changes, [
+            SetMember { member: "wan3".to_string(), record_id: None, old_ip: None, new_ip: Some("192.0.2.30".to_string()) },
+            SetMember { member: "old".to_string(), record_id: Some("3".to_string()), old_ip: Some("192.0.2.3".to_string()), new_ip: None },
+        ]);
+        assert_eq!(managed_content(&existing).as_deref(), Some("192.0.2.1, 192.0.2.2, 192.0.2.3"));
+
+        assert_eq!(diff(&existing, &desired, true).len(), 3);
+    }
+}
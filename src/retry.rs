@@ -0,0 +1,132 @@
+// 定时模式的失败记录重试队列
+//
+// 每条失败的记录按自己的退避时间重试（`retry_backoff` 起每次翻倍，不超过执行间隔），与正常的执行间隔无关；
+// 连续重试 `retry_max_attempts` 次仍失败时发送一次 retries_exhausted 通知，之后按正常间隔重试，直到成功后移出队列。
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+/// 重试一条记录的退避设置
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// 首次重试的等待时间
+    pub backoff: Duration,
+    /// 快速重试的最大次数
+    pub max_attempts: u32,
+    /// 正常的执行间隔，退避时间不超过它
+    pub interval: Duration,
+}
+
+/// 一条记录失败后的处理
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetryStatus {
+    /// 将在给定时间后重试
+    Scheduled(Duration),
+    /// 本次失败用尽了快速重试次数，之后按正常间隔重试
+    Exhausted,
+    /// 此前已用尽快速重试次数
+    AlreadyExhausted,
+}
+
+#[derive(Debug)]
+struct RetryEntry {
+    /// 连续失败次数
+    attempts: u32,
+    next_at: Instant,
+    exhausted: bool,
+}
+
+/// 等待重试的记录，按记录名称与类型索引，双栈名称的 A 与 AAAA 记录分别重试
+#[derive(Debug, Default)]
+pub struct RetryQueue {
+    entries: BTreeMap<(String, String), RetryEntry>,
+}
+
+/// 状态转储中的一条待重试记录
+#[derive(Debug, Serialize)]
+pub struct RetryInfo {
+    attempts: u32,
+    retry_in_secs: u64,
+    exhausted: bool,
+}
+
+impl RetryQueue {
+    /// 记录一次失败并安排下一次重试
+    pub fn record_failure(&mut self, record: &str, record_type: &str, policy: RetryPolicy, now: Instant) -> RetryStatus {
+        let entry = self.entries.entry((record.to_string(), record_type.to_string())).or_insert(RetryEntry { attempts: 0, next_at: now, exhausted: false });
+        entry.attempts += 1;
+        if entry.exhausted {
+            entry.next_at = now + policy.interval;
+            return RetryStatus::AlreadyExhausted;
+        }
+        if entry.attempts > policy.max_attempts {
+            entry.exhausted = true;
+            entry.next_at = now + policy.interval;
+            return RetryStatus::Exhausted;
+        }
+        let delay = policy.backoff.saturating_mul(1 << (entry.attempts - 1).min(16)).min(policy.interval);
+        entry.next_at = now + delay;
+        RetryStatus::Scheduled(delay)
+    }
+
+    /// 记录处理成功，移出队列
+    pub fn record_success(&mut self, record: &str, record_type: &str) {
+        self.entries.remove(&(record.to_string(), record_type.to_string()));
+    }
+
+    /// 已到重试时间的记录（名称、类型）
+    pub fn due(&self, now: Instant) -> Vec<(String, String)> {
+        self.entries.iter().filter(|(_, entry)| entry.next_at <= now).map(|(key, _)| key.clone()).collect()
+    }
+
+    /// 距最早一次快速重试的时间，没有待快速重试的记录时为 `None`
+    pub fn next_retry(&self, now: Instant) -> Option<Duration> {
+        self.entries.values()
+            .filter(|entry| !entry.exhausted)
+            .map(|entry| entry.next_at.saturating_duration_since(now))
+            .min()
+    }
+
+    /// 供状态转储使用的队列内容，按 `名称/类型` 索引
+    pub fn dump(&self, now: Instant) -> BTreeMap<String, RetryInfo> {
+        self.entries.iter().map(|((name, record_type), entry)| (format!("{}/{}", name, record_type), RetryInfo {
+            attempts: entry.attempts,
+            retry_in_secs: entry.next_at.saturating_duration_since(now).as_secs(),
+            exhausted: entry.exhausted,
+        })).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retries_back_off_then_give_up() {
+        let policy = RetryPolicy { backoff: Duration::from_secs(30), max_attempts: 3, interval: Duration::from_secs(100) };
+        let now = Instant::now();
+        let mut queue = RetryQueue::default();
+
+        assert_eq!(queue.record_failure("home", "A", policy, now), RetryStatus::Scheduled(Duration::from_secs(30)));
+        assert_eq!(queue.record_failure("nas", "A", policy, now + Duration::from_secs(10)), RetryStatus::Scheduled(Duration::from_secs(30)));
+        // 同名的 AAAA 记录成功不影响失败的 A 记录
+        queue.record_success("home", "AAAA");
+        // 每条记录按自己的时间到期
+        assert_eq!(queue.due(now + Duration::from_secs(30)), [("home".to_string(), "A".to_string())]);
+        assert_eq!(queue.next_retry(now), Some(Duration::from_secs(30)));
+
+        assert_eq!(queue.record_failure("home", "A", policy, now), RetryStatus::Scheduled(Duration::from_secs(60)));
+        // 退避时间不超过执行间隔
+        assert_eq!(queue.record_failure("home", "A", policy, now), RetryStatus::Scheduled(Duration::from_secs(100)));
+        assert_eq!(queue.record_failure("home", "A", policy, now), RetryStatus::Exhausted);
+        assert_eq!(queue.record_failure("home", "A", policy, now), RetryStatus::AlreadyExhausted);
+        assert_eq!(queue.next_retry(now), Some(Duration::from_secs(40)));
+
+        queue.record_success("nas", "A");
+        assert_eq!(queue.next_retry(now), None);
+        assert_eq!(queue.due(now + policy.interval), [("home".to_string(), "A".to_string())]);
+        queue.record_success("home", "A");
+        assert!(queue.dump(now).is_empty());
+    }
+}
@@ -0,0 +1,565 @@
+// 带注释的完整示例配置，由 `generate-config` 子命令输出
+//
+// 示例由配置结构体序列化得到，注释按字段路径查表；可选的配置块整段注释掉，
+// 去掉行首的 `// ` 即可启用。
+use std::time::Duration;
+
+use serde_json::Value;
+
+use cloudflare_ddns::config::*;
+use cloudflare_ddns::i18n::tr;
+use cloudflare_ddns::notify::EventKind;
+
+/// 输出时整段注释掉的可选配置块（每个对象的最后一项不能在其中，否则取消注释后逗号会出错）
+const DISABLED: &[&str] = &[
+    "zones",
+    "uplinks",
+    "notifications.webhook",
+    "notifications.telegram",
+    "notifications.slack",
+    "notifications.email",
+    "notifications.gotify",
+    "notifications.bark",
+    "notifications.pushover",
+    "notifications.serverchan",
+    "notifications.dingtalk",
+    "notifications.wecom",
+    "notifications.matrix",
+    "notifications.hooks",
+    "notifications.templates",
+    "mqtt",
+    "logging.syslog",
+    "metrics",
+    "http",
+];
+
+/// 渲染带注释的示例配置 (JSON + `//` 注释)
+pub fn render() -> String {
+    render_with(DISABLED)
+}
+
+fn render_with(disabled: &[&str]) -> String {
+    let value = serde_json::to_value(sample()).expect("示例配置可以序列化");
+    let mut out = format!("// {}\n", tr!(
+        "CloudFlare DDNS 示例配置，支持 // 注释；去掉行首的 // 即可启用对应的配置块",
+        "CloudFlare DDNS example configuration; // comments are allowed, remove the leading // to enable a block"
+    ));
+    write_value(&mut out, &value, "", "", 0, disabled);
+    out.push('\n');
+    out
+}
+
+/// 示例配置中的所有字段路径及其说明，按出现顺序排列，供 man 页使用
+pub fn fields() -> Vec<(String, String)> {
+    let value = serde_json::to_value(sample()).expect("示例配置可以序列化");
+    let mut all = Vec::new();
+    paths(&value, "", &mut all);
+    let mut fields: Vec<(String, String)> = Vec::new();
+    for path in all {
+        if !fields.iter().any(|(p, _)| *p == path)
+            && let Some(description) = describe(&path)
+        {
+            fields.push((path, description));
+        }
+    }
+    fields
+}
+
+/// 收集值中的所有字段路径，父字段在子字段之前；数组元素的字段沿用数组本身的路径
+fn paths(value: &Value, path: &str, out: &mut Vec<String>) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map {
+                let child_path = if path.is_empty() { key.clone() } else { format!("{path}.{key}") };
+                out.push(child_path.clone());
+                paths(child, &child_path, out);
+            }
+        }
+        Value::Array(items) => items.iter().for_each(|item| paths(item, path, out)),
+        _ => {}
+    }
+}
+
+/// 每行的输出为 `prefix + 缩进 + 内容`；进入被注释的配置块时把 `// ` 并入 prefix 并重新计算缩进
+fn write_value(out: &mut String, value: &Value, path: &str, prefix: &str, depth: usize, disabled: &[&str]) {
+    match value {
+        Value::Object(map) => {
+            out.push('{');
+            for (i, (key, child)) in map.iter().enumerate() {
+                let child_path = if path.is_empty() { key.clone() } else { format!("{path}.{key}") };
+                let indent = "  ".repeat(depth + 1);
+                if let Some(comment) = describe(&child_path) {
+                    out.push_str(&format!("\n{prefix}{indent}// {comment}"));
+                }
+                let (child_prefix, child_depth) = if disabled.contains(&child_path.as_str()) {
+                    (format!("{prefix}{indent}// "), 0)
+                } else {
+                    (prefix.to_string(), depth + 1)
+                };
+                out.push_str(&format!("\n{child_prefix}{}{}: ", "  ".repeat(child_depth), Value::from(key.as_str())));
+                write_value(out, child, &child_path, &child_prefix, child_depth, disabled);
+                if i + 1 < map.len() {
+                    out.push(',');
+                }
+            }
+            out.push_str(&format!("\n{prefix}{}}}", "  ".repeat(depth)));
+        }
+        // 对象数组逐项展开，数组元素的字段沿用数组本身的路径
+        Value::Array(items) if items.iter().any(Value::is_object) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                out.push_str(&format!("\n{prefix}{}", "  ".repeat(depth + 1)));
+                write_value(out, item, path, prefix, depth + 1, disabled);
+                if i + 1 < items.len() {
+                    out.push(',');
+                }
+            }
+            out.push_str(&format!("\n{prefix}{}]", "  ".repeat(depth)));
+        }
+        Value::Array(items) => {
+            let items: Vec<String> = items.iter().map(Value::to_string).collect();
+            out.push_str(&format!("[{}]", items.join(", ")));
+        }
+        _ => out.push_str(&value.to_string()),
+    }
+}
+
+/// 示例值：默认启用的部分使用默认值，注释掉的配置块填入示例值以展示全部字段
+fn sample() -> Config {
+    let policy = NotifyPolicy::default;
+    Config {
+        cloudflare: Some(CloudflareConfig {
+            credentials: CloudflareCredentials {
+                auth_type: "token".to_string(),
+                auth_email: None,
+                auth_key: None,
+                auth_key_env: None,
+                api_token: Some("your_api_token_here".to_string()),
+                api_token_env: None,
+                api_token_credential: None,
+                api_token_keyring: None,
+                endpoint: None,
+            },
+            zone_name: "example.com".to_string(),
+        }),
+        dns_records: vec![DnsRecordConfig {
+            name: "home.example.com".to_string(),
+            r#type: "A".to_string(),
+            ttl: 60,
+            proxied: false,
+            ip_version: "v4".to_string(),
+            uplink: None,
+            members: Vec::new(),
+            failover: None,
+            also_update: Vec::new(),
+            notify: Vec::new(),
+            cooldown: None,
+            alias_of: None,
+        }],
+        zones: vec![
+            ZoneConfig {
+                zone_name: "example.cn".to_string(),
+                provider: ProviderConfig::Aliyun(AliyunConfig {
+                    access_key_id: "your_access_key_id".to_string(),
+                    access_key_secret: "your_access_key_secret".to_string(),
+                    endpoint: None,
+                }),
+                dns_records: vec![zone_record("home.example.cn")],
+            },
+            ZoneConfig {
+                zone_name: "example.net".to_string(),
+                provider: ProviderConfig::Dnspod(DnspodConfig {
+                    token_id: "your_token_id".to_string(),
+                    token: "your_token".to_string(),
+                }),
+                dns_records: vec![zone_record("home.example.net")],
+            },
+        ],
+        ip_detection: IpDetectionConfig::default(),
+        uplinks: vec![
+            UplinkConfig {
+                name: "isp1".to_string(),
+                ipv4_url: None,
+                ipv6_url: None,
+                interface: Some("ppp0".to_string()),
+                local_address: None,
+            },
+            UplinkConfig {
+                name: "isp2".to_string(),
+                ipv4_url: None,
+                ipv6_url: None,
+                interface: Some("ppp1".to_string()),
+                local_address: None,
+            },
+        ],
+        schedule: ScheduleConfig {
+            interval: None,
+            timeout: Some(DEFAULT_TIMEOUT),
+            reconcile_interval: None,
+            retry_backoff: Some(DEFAULT_RETRY_BACKOFF),
+            retry_max_attempts: Some(DEFAULT_RETRY_MAX_ATTEMPTS),
+            state_dump_file: None,
+            control_socket: None,
+            state_file: None,
+            state_backend: StateBackend::Json,
+        },
+        notifications: NotificationsConfig {
+            webhook: Some(WebhookConfig {
+                urls: vec!["https://example.com/ddns-hook".to_string()],
+                policy: policy(),
+            }),
+            telegram: Some(TelegramConfig {
+                bot_token: "123456:ABC-DEF".to_string(),
+                chat_id: "123456789".to_string(),
+                policy: policy(),
+            }),
+            slack: Some(SlackConfig {
+                webhook_url: "https://hooks.slack.com/services/T000/B000/XXXX".to_string(),
+                change_webhook_url: None,
+                failure_webhook_url: Some("https://hooks.slack.com/services/T000/B001/YYYY".to_string()),
+                change_channel: None,
+                failure_channel: None,
+                policy: policy(),
+            }),
+            email: Some(EmailConfig {
+                host: "smtp.example.com".to_string(),
+                port: Some(587),
+                username: Some("ddns@example.com".to_string()),
+                password: Some("your_password".to_string()),
+                tls: "starttls".to_string(),
+                from: "ddns@example.com".to_string(),
+                to: vec!["admin@example.com".to_string()],
+                subject: Some("[DDNS] {record}: {result}".to_string()),
+                body: None,
+                policy: policy(),
+            }),
+            gotify: Some(GotifyConfig {
+                url: "https://gotify.example.com".to_string(),
+                app_token: "your_app_token".to_string(),
+                priority: 5,
+                policy: policy(),
+            }),
+            bark: Some(BarkConfig {
+                url: "https://api.day.app/your_key".to_string(),
+                group: Some("CloudFlare DDNS".to_string()),
+                sound: None,
+                policy: policy(),
+            }),
+            pushover: Some(PushoverConfig {
+                app_token: "your_app_token".to_string(),
+                user_key: "your_user_key".to_string(),
+                priority: 0,
+                device: None,
+                sound: None,
+                retry: 60,
+                expire: 3600,
+                policy: policy(),
+            }),
+            serverchan: Some(ServerChanConfig {
+                sendkey: "SCTxxxx".to_string(),
+                policy: policy(),
+            }),
+            dingtalk: Some(DingTalkConfig {
+                webhook_url: "https://oapi.dingtalk.com/robot/send?access_token=xxxx".to_string(),
+                secret: Some("SECxxxx".to_string()),
+                policy: policy(),
+            }),
+            wecom: Some(WeComConfig {
+                key: Some("your_key".to_string()),
+                webhook_url: None,
+                policy: policy(),
+            }),
+            matrix: Some(MatrixConfig {
+                homeserver: "https://matrix.example.com".to_string(),
+                access_token: "your_access_token".to_string(),
+                room_id: "!abcdefg:example.com".to_string(),
+                policy: policy(),
+            }),
+            hooks: Some(CommandHooksConfig {
+                on_change: Some("logger \"DDNS: $RECORD -> $NEW_IP\"".to_string()),
+                on_failure: None,
+                on_recovered: None,
+                on_retries_exhausted: None,
+                on_drift: None,
+                on_flapping: None,
+                timeout: Some(Duration::from_secs(60)),
+                policy: NotifyPolicy {
+                    events: vec![EventKind::Changed, EventKind::Failed, EventKind::Recovered],
+                    failure_threshold: 3,
+                },
+            }),
+            templates: NotificationTemplates {
+                changed: Some("{record}: {old_ip} -> {new_ip}".to_string()),
+                failed: Some("{record} [{category}]: {error}".to_string()),
+                recovered: None,
+                retries_exhausted: None,
+                drift: None,
+                flapping: None,
+                startup: None,
+            },
+            retry: NotificationRetryConfig {
+                backoff: Some(Duration::from_secs(10)),
+                max_attempts: 10,
+                flush_timeout: Some(Duration::from_secs(30)),
+            },
+        },
+        mqtt: Some(MqttConfig {
+            host: "192.168.1.2".to_string(),
+            port: Some(1883),
+            client_id: "cloudflare-ddns".to_string(),
+            username: None,
+            password: None,
+            tls: false,
+            ca_file: None,
+            topic_prefix: "cloudflare_ddns".to_string(),
+            qos: 1,
+        }),
+        logging: LoggingConfig {
+            syslog: Some(SyslogConfig {
+                server: Some("192.168.1.2:514".to_string()),
+                facility: "daemon".to_string(),
+            }),
+            ..LoggingConfig::default()
+        },
+        metrics: Some(MetricsConfig {
+            textfile: Some("/var/lib/node_exporter/textfile_collector/cloudflare_ddns.prom".to_string()),
+        }),
+        http: Some(HttpConfig {
+            listen: "127.0.0.1:8080".to_string(),
+            token: Some("change-me".to_string()),
+            dyndns: Some(DyndnsReceiverConfig {
+                username: "router".to_string(),
+                password: "change-me".to_string(),
+            }),
+        }),
+        history: HistoryConfig::default(),
+        dual_stack: DualStackPolicy::Independent,
+        drift: DriftPolicy::Off,
+        update_limit: UpdateLimitConfig { per_hour: Some(6), per_day: Some(48) },
+    }
+}
+
+/// `zones` 示例中的记录
+fn zone_record(name: &str) -> DnsRecordConfig {
+    DnsRecordConfig {
+        name: name.to_string(),
+        r#type: "A".to_string(),
+        ttl: 600,
+        proxied: false,
+        ip_version: "v4".to_string(),
+        uplink: None,
+        members: Vec::new(),
+        failover: None,
+        also_update: Vec::new(),
+        notify: Vec::new(),
+        cooldown: None,
+        alias_of: None,
+    }
+}
+
+/// 字段说明，通知后端共用的策略字段按字段名查找
+fn describe(path: &str) -> Option<String> {
+    let key = path.rsplit('.').next().unwrap_or(path);
+    let text = match path {
+        "cloudflare" => tr!("CloudFlare 账户与域名", "CloudFlare account and zone"),
+        "cloudflare.auth_type" => tr!("认证方式：token（API Token，推荐）或 emailkey（邮箱 + 全局 API Key）", "Authentication: token (API Token, recommended) or emailkey (email + Global API Key)"),
+        "cloudflare.auth_email" => tr!("auth_type 为 emailkey 时的账户邮箱", "Account email when auth_type is emailkey"),
+        "cloudflare.auth_key" => tr!("auth_type 为 emailkey 时的全局 API Key", "Global API Key when auth_type is emailkey"),
+        "cloudflare.auth_key_env" => tr!("不在配置中保存全局 API Key 时，从该环境变量读取", "Read the Global API Key from this environment variable instead of storing it here"),
+        "cloudflare.api_token" => tr!("auth_type 为 token 时的 API Token，需要 DNS 编辑权限", "API Token when auth_type is token, needs DNS edit permission"),
+        "cloudflare.api_token_env" => tr!("不在配置中保存令牌时，从该环境变量读取，如 MY_CF_TOKEN", "Read the token from this environment variable instead of storing it here, e.g. MY_CF_TOKEN"),
+        "cloudflare.api_token_credential" => tr!("不在配置中保存令牌时，从该名称的 systemd 凭据（LoadCredential）读取", "Read the token from the systemd credential (LoadCredential) with this name instead of storing it here"),
+        "cloudflare.api_token_keyring" => tr!("不在配置中保存令牌时，从系统密钥环中该服务名下读取（auth login --keyring 写入），需要 keyring 功能", "Read the token from this service in the OS keyring instead of storing it here (written by auth login --keyring), needs the keyring feature"),
+        "cloudflare.endpoint" => tr!("API 地址，为 null 时使用 https://api.cloudflare.com/client/v4；可指向反向代理", "API endpoint, null uses https://api.cloudflare.com/client/v4; may point at a reverse proxy"),
+        "cloudflare.zone_name" => tr!("记录所在的域名 (zone)", "Zone the records belong to"),
+        "dns_records" => tr!("要更新的 DNS 记录，可配置多条", "DNS records to update, one entry per record"),
+        "dns_records.name" => tr!("记录全名", "Fully qualified record name"),
+        "dns_records.type" => tr!("记录类型：A (IPv4) 或 AAAA (IPv6)", "Record type: A (IPv4) or AAAA (IPv6)"),
+        "dns_records.ttl" => tr!("TTL（秒），1 表示自动", "TTL in seconds, 1 means automatic"),
+        "dns_records.proxied" => tr!("是否启用 CloudFlare 代理", "Whether to proxy through CloudFlare"),
+        "dns_records.ip_version" => tr!("使用的 IP 版本：v4 或 v6，需与 type 对应", "IP version to use: v4 or v6, must match type"),
+        "dns_records.uplink" => tr!("使用哪条上行线路检测到的地址，为 null 时使用默认检测", "Which uplink's detected address to publish, null uses the default detection"),
+        "dns_records.members" => tr!("轮询记录集的成员，每个成员一条同名记录，用标签识别（目前仅 CloudFlare）", "Members of a round-robin set, one same-named record per member identified by a tag (CloudFlare only for now)"),
+        "dns_records.members.name" => tr!("成员名称，写入记录的备注", "Member name, written to the record comment"),
+        "dns_records.members.uplink" => tr!("该成员使用哪条上行线路检测到的地址，为 null 时使用默认检测", "Which uplink's detected address the member publishes, null uses the default detection"),
+        "dns_records.members.ip" => tr!("成员的固定地址（如另一台主机），设置后不检测", "Fixed address for the member (e.g. another host), not detected when set"),
+        "dns_records.also_update" => tr!("与该记录一起更新的其他名称，如 [\"www\"]，可以是完整名称、相对名称或 @", "Other names updated together with the record, e.g. [\"www\"]; full names, names relative to the zone or @"),
+        "dns_records.notify" => tr!("接收该记录通知的后端，如 [\"slack\"]，为空时发给所有后端", "Notification backends that receive this record's events, e.g. [\"slack\"]; empty sends to all"),
+        "dns_records.cooldown" => tr!("成功发布新地址后的冷却时间，如 \"5m\"，期间不再更新该记录（--force 除外）", "Cooldown after publishing a new address, e.g. \"5m\"; the record is not updated again until it ends (except with --force)"),
+        "dns_records.failover" => tr!("故障转移：主地址健康检查失败时记录指向备用地址", "Failover: point the record at the backup address while the primary fails its health check"),
+        "dns_records.failover.primary" => tr!("主地址，为 null 时为检测到的地址", "Primary address, null uses the detected address"),
+        "dns_records.failover.backup" => tr!("主地址不可用时使用的备用地址", "Backup address used while the primary is down"),
+        "dns_records.failover.check" => tr!("健康检查方式：tcp（建立连接）或 http（返回 2xx/3xx）", "Health check: tcp (connection succeeds) or http (2xx/3xx response)"),
+        "dns_records.failover.port" => tr!("健康检查的端口", "Port to check"),
+        "dns_records.failover.path" => tr!("HTTP 检查的路径，默认 /", "Path for the HTTP check, defaults to /"),
+        "dns_records.failover.timeout" => tr!("单次检查的超时时间，为 null 时与 schedule.timeout 相同", "Timeout for one check, null uses schedule.timeout"),
+        "dns_records.failover.fail_after" => tr!("连续失败多少次后切换到备用地址，默认 3", "Consecutive failures before failing over, default 3"),
+        "dns_records.failover.recover_after" => tr!("切换后连续成功多少次才切回主地址，默认 3", "Consecutive successes before switching back, default 3"),
+        "zones" => tr!("其他区域，每个区域可以使用不同的 DNS 服务商", "Additional zones, each may use a different DNS provider"),
+        "zones.zone_name" => tr!("记录所在的域名 (zone)", "Zone the records belong to"),
+        "zones.provider" => tr!("该区域的 DNS 服务商与凭据", "DNS provider and credentials for the zone"),
+        "zones.provider.type" => tr!("服务商：cloudflare、aliyun、dnspod、route53、huawei、duckdns、dyndns2、desec、dynv6 或 porkbun；cloudflare 的凭据字段与 cloudflare 段相同", "Provider: cloudflare, aliyun, dnspod, route53, huawei, duckdns, dyndns2, desec, dynv6 or porkbun; cloudflare takes the same credential fields as the cloudflare section"),
+        "zones.provider.access_key_id" => tr!("阿里云、AWS 或华为云的 AccessKey ID，需要 DNS 的读写权限", "Aliyun, AWS or Huawei Cloud access key ID, needs read/write access to DNS"),
+        "zones.provider.access_key_secret" => tr!("阿里云 AccessKey Secret", "Aliyun AccessKey secret"),
+        "zones.provider.endpoint" => tr!("API 地址，为 null 时阿里云使用 alidns.aliyuncs.com，华为云使用 dns.myhuaweicloud.com", "API endpoint, null uses alidns.aliyuncs.com for Aliyun and dns.myhuaweicloud.com for Huawei Cloud"),
+        "zones.provider.token_id" => tr!("DNSPod API Token 的 ID", "DNSPod API token ID"),
+        "zones.provider.token" => tr!("DNSPod、DuckDNS、deSEC 或 dynv6 的 token", "DNSPod, DuckDNS, deSEC or dynv6 token"),
+        "zones.provider.api_key" => tr!("Porkbun API Key（pk1_ 开头）", "Porkbun API key (starts with pk1_)"),
+        "zones.provider.secret_api_key" => tr!("Porkbun Secret API Key（sk1_ 开头）", "Porkbun secret API key (starts with sk1_)"),
+        "zones.provider.prefix_length" => tr!("dynv6 区域前缀的长度，默认 64；更新只写了接口标识的 AAAA 记录时改写区域前缀", "Length of the dynv6 zone prefix, default 64; updating an AAAA record that holds only an interface identifier rewrites the zone prefix"),
+        "zones.provider.server" => tr!("dyndns2 服务地址，如 https://dynupdate.no-ip.com", "dyndns2 server, e.g. https://dynupdate.no-ip.com"),
+        "zones.provider.username" => tr!("dyndns2 用户名", "dyndns2 username"),
+        "zones.provider.password" => tr!("dyndns2 密码或更新密钥", "dyndns2 password or update key"),
+        "zones.provider.secret_access_key" => tr!("AWS 或华为云的 Secret Access Key", "AWS or Huawei Cloud secret access key"),
+        "zones.provider.hosted_zone_id" => tr!("Route 53 托管区域 ID，为 null 时按 zone_name 查找", "Route 53 hosted zone ID, null looks it up by zone_name"),
+        "zones.dns_records" => tr!("该区域中要更新的 DNS 记录，字段与 dns_records 相同", "DNS records to update in the zone, same fields as dns_records"),
+        _ if path.starts_with("zones.dns_records.") => return describe(&path["zones.".len()..]),
+        "ip_detection" => tr!("公网 IP 检测服务，需要以纯文本返回请求方的地址", "Public IP detection services, must answer with the caller's address as plain text"),
+        "ip_detection.ipv4_url" => tr!("IPv4 检测地址，为 null 时使用 https://4.ipw.cn", "IPv4 detection URL, null uses https://4.ipw.cn"),
+        "ip_detection.ipv6_url" => tr!("IPv6 检测地址，为 null 时使用 https://6.ipw.cn", "IPv6 detection URL, null uses https://6.ipw.cn"),
+        "uplinks" => tr!("多条上行线路（多 WAN），记录中的 uplink 选择使用哪条线路检测到的地址", "Multiple uplinks (multi-WAN); a record's uplink picks which uplink's detected address it publishes"),
+        "uplinks.name" => tr!("线路名称，记录的 uplink 引用它", "Uplink name, referenced by a record's uplink"),
+        "uplinks.ipv4_url" => tr!("该线路的 IPv4 检测地址，为 null 时与 ip_detection 相同", "IPv4 detection URL for the uplink, null uses the one in ip_detection"),
+        "uplinks.ipv6_url" => tr!("该线路的 IPv6 检测地址，为 null 时与 ip_detection 相同", "IPv6 detection URL for the uplink, null uses the one in ip_detection"),
+        "uplinks.interface" => tr!("检测请求绑定的网络接口（仅 Linux 与 macOS）", "Network interface detection requests are bound to (Linux and macOS only)"),
+        "uplinks.local_address" => tr!("检测请求使用的本地源地址，适用于按源地址选路的路由器", "Local source address for detection requests, for routers that route by source address"),
+        "schedule" => tr!("定时运行与网络请求设置，时间均支持 \"90s\"、\"5m\"、\"1h30m\" 或纯数字秒数", "Scheduling and network settings; durations accept \"90s\", \"5m\", \"1h30m\" or plain seconds"),
+        "schedule.interval" => tr!("定时运行间隔（如 \"5m\"），为 null 时单次运行；命令行 --interval 优先", "Run interval (e.g. \"5m\"), null runs once; --interval takes precedence"),
+        "schedule.timeout" => tr!("单个网络请求（IP 检测、API 调用）的超时时间", "Timeout for each network request (IP detection, API calls)"),
+        "schedule.reconcile_interval" => tr!("两级调度：每个 interval 只检测 IP，完整的 API 核对按此间隔进行", "Two-tier scheduling: only detect the IP every interval, run a full API reconcile at this interval"),
+        "schedule.retry_backoff" => tr!("记录更新失败后的首次重试等待时间，之后每次翻倍，不超过 interval", "Initial retry delay after a record fails, doubling each time up to interval"),
+        "schedule.retry_max_attempts" => tr!("失败记录的最大快速重试次数，用尽后发送 retries_exhausted 通知并改为按 interval 重试", "Maximum fast retries for a failed record, after which a retries_exhausted notification is sent and it is retried every interval"),
+        "schedule.control_socket" => tr!("定时模式监听的 Unix 控制套接字路径，供 ctl 子命令使用，为 null 时不监听（仅 Unix）", "Unix control socket the scheduled mode listens on for the ctl subcommand, null disables it (Unix only)"),
+        "schedule.state_dump_file" => tr!("收到 SIGUSR2 时写入内部状态的文件，为 null 时打印到标准错误", "File to write internal state to on SIGUSR2, null prints to stderr"),
+        "schedule.state_file" => tr!("保存运行统计、上一次的 IP 与各记录最近写入内容的状态文件，供 healthcheck 读取，为 null 时使用配置文件所在目录（systemd 下为 StateDirectory）中的 state.json", "State file holding run stats, the last IPs and the last content written to each record, read by healthcheck; null uses state.json next to the config file (the StateDirectory under systemd)"),
+        "schedule.state_backend" => tr!("状态存储后端：json，或 sqlite（状态与历史都写入 state_file 的数据库，需要启用 sqlite 功能）", "State backend: json, or sqlite (state and history both go to the database at state_file, requires the sqlite feature)"),
+        "notifications" => tr!("通知设置，取消注释即可启用对应后端", "Notification settings, uncomment a backend to enable it"),
+        "notifications.webhook" => tr!("通用 Webhook：向每个 URL POST 一个 JSON 事件", "Generic webhook: POST a JSON event to each URL"),
+        "notifications.webhook.urls" => tr!("接收事件的 URL 列表", "URLs receiving the events"),
+        "notifications.telegram" => tr!("Telegram Bot", "Telegram bot"),
+        "notifications.telegram.bot_token" => tr!("BotFather 提供的 token", "Token from BotFather"),
+        "notifications.telegram.chat_id" => tr!("接收消息的聊天 ID", "Chat ID receiving the messages"),
+        "notifications.slack" => tr!("Slack Incoming Webhook", "Slack incoming webhook"),
+        "notifications.slack.webhook_url" => tr!("Incoming Webhook 地址", "Incoming webhook URL"),
+        "notifications.slack.change_webhook_url" => tr!("变更事件使用的 Incoming Webhook 地址，为 null 时使用 webhook_url", "Incoming webhook URL for change events, null uses webhook_url"),
+        "notifications.slack.failure_webhook_url" => tr!("失败事件使用的 Incoming Webhook 地址，为 null 时使用 webhook_url", "Incoming webhook URL for failure events, null uses webhook_url"),
+        "notifications.slack.change_channel" => tr!("变更事件发送到的频道，仅旧式 webhook 支持，为 null 时使用 webhook 的默认频道", "Channel for change events, legacy webhooks only; null uses the webhook default"),
+        "notifications.slack.failure_channel" => tr!("失败事件发送到的频道，仅旧式 webhook 支持，为 null 时使用 webhook 的默认频道", "Channel for failure events, legacy webhooks only; null uses the webhook default"),
+        "notifications.email" => tr!("SMTP 邮件", "SMTP email"),
+        "notifications.email.host" => tr!("SMTP 服务器", "SMTP server"),
+        "notifications.email.port" => tr!("端口，为 null 时按 tls 使用默认端口（starttls: 587，tls: 465，none: 25）", "Port, null picks the default for tls (starttls: 587, tls: 465, none: 25)"),
+        "notifications.email.username" | "mqtt.username" => tr!("用户名", "Username"),
+        "notifications.email.password" | "mqtt.password" => tr!("密码", "Password"),
+        "notifications.email.tls" => tr!("连接加密方式：starttls、tls 或 none", "Connection security: starttls, tls or none"),
+        "notifications.email.from" => tr!("发件人", "Sender address"),
+        "notifications.email.to" => tr!("收件人列表", "Recipient addresses"),
+        "notifications.email.subject" => tr!("邮件主题模板，占位符同 templates", "Subject template, same placeholders as templates"),
+        "notifications.email.body" => tr!("邮件正文模板，为 null 时使用内置格式", "Body template, null uses the built-in format"),
+        "notifications.gotify" => tr!("Gotify 推送", "Gotify push"),
+        "notifications.gotify.url" => tr!("Gotify 服务器地址", "Gotify server URL"),
+        "notifications.gotify.app_token" => tr!("应用 token", "Application token"),
+        "notifications.gotify.priority" => tr!("消息优先级", "Message priority"),
+        "notifications.bark" => tr!("Bark (iOS) 推送", "Bark (iOS) push"),
+        "notifications.bark.url" => tr!("Bark App 中复制的推送地址", "Push URL copied from the Bark app"),
+        "notifications.bark.group" => tr!("通知分组", "Notification group"),
+        "notifications.bark.sound" | "notifications.pushover.sound" => tr!("推送铃声", "Notification sound"),
+        "notifications.pushover" => tr!("Pushover 推送", "Pushover push"),
+        "notifications.pushover.app_token" => tr!("应用 API token", "Application API token"),
+        "notifications.pushover.user_key" => tr!("用户或群组 key", "User or group key"),
+        "notifications.pushover.priority" => tr!("消息优先级 (-2 到 2)", "Message priority (-2 to 2)"),
+        "notifications.pushover.device" => tr!("只推送到指定设备", "Only push to this device"),
+        "notifications.pushover.retry" => tr!("紧急优先级 (2) 时重复提醒的间隔秒数", "Seconds between repeats for emergency priority (2)"),
+        "notifications.pushover.expire" => tr!("紧急优先级 (2) 时停止重复提醒的秒数", "Seconds before emergency priority (2) repeats stop"),
+        "notifications.serverchan" => tr!("Server酱 (方糖)", "ServerChan"),
+        "notifications.serverchan.sendkey" => tr!("SendKey，支持 Turbo 版 (SCT...) 与 Server酱³ (sctp...)", "SendKey, Turbo (SCT...) and Server3 (sctp...) are supported"),
+        "notifications.dingtalk" => tr!("钉钉群机器人", "DingTalk group robot"),
+        "notifications.dingtalk.webhook_url" => tr!("机器人 Webhook 地址（包含 access_token）", "Robot webhook URL (including access_token)"),
+        "notifications.dingtalk.secret" => tr!("加签密钥 (SEC...)，启用\"加签\"时必填", "Signing secret (SEC...), required when signing is enabled"),
+        "notifications.wecom" => tr!("企业微信群机器人，key 与 webhook_url 二选一", "WeCom group robot, set either key or webhook_url"),
+        "notifications.wecom.key" => tr!("机器人 Webhook 地址中的 key 参数", "The key parameter of the robot webhook URL"),
+        "notifications.wecom.webhook_url" => tr!("完整的机器人 Webhook 地址", "Full robot webhook URL"),
+        "notifications.matrix" => tr!("Matrix 房间", "Matrix room"),
+        "notifications.matrix.homeserver" => tr!("Matrix 服务器地址", "Matrix homeserver URL"),
+        "notifications.matrix.access_token" => tr!("发送消息所用账号的 access token", "Access token of the sending account"),
+        "notifications.matrix.room_id" => tr!("房间 ID，该账号需已加入房间", "Room ID, the account must have joined it"),
+        "notifications.hooks" => tr!("执行命令，可读取环境变量 RECORD、ZONE、OLD_IP、NEW_IP、RESULT、ERROR、TIMESTAMP", "Run commands with RECORD, ZONE, OLD_IP, NEW_IP, RESULT, ERROR, TIMESTAMP in the environment"),
+        "notifications.hooks.on_change" => tr!("记录更新或新建时执行", "Run when a record is updated or created"),
+        "notifications.hooks.on_failure" => tr!("记录更新失败时执行", "Run when a record update fails"),
+        "notifications.hooks.on_recovered" => tr!("连续失败后首次恢复成功时执行", "Run on the first success after failures"),
+        "notifications.hooks.on_retries_exhausted" => tr!("记录用尽快速重试次数仍失败时执行（仅定时模式）", "Run when a record is still failing after all fast retries (scheduled mode only)"),
+        "notifications.hooks.on_drift" => tr!("检测到记录被外部修改时执行", "Run when a record was changed externally"),
+        "notifications.hooks.on_flapping" => tr!("记录因更新过于频繁而停止更新时执行", "Run when a record stops being updated because it changes too often"),
+        "notifications.hooks.timeout" => tr!("命令的最长执行时间", "Maximum command run time"),
+        "notifications.templates" => tr!("所有后端共用的消息模板，支持 {{record}}、{{zone}}、{{old_ip}}、{{new_ip}}、{{timestamp}}、{{error}}、{{category}}、{{result}}", "Message templates shared by all backends, supporting {{record}}, {{zone}}, {{old_ip}}, {{new_ip}}, {{timestamp}}, {{error}}, {{category}}, {{result}}"),
+        "notifications.templates.changed" => tr!("记录已更新或新建", "Record updated or created"),
+        "notifications.templates.failed" => tr!("记录更新失败", "Record update failed"),
+        "notifications.templates.recovered" => tr!("记录在连续失败后恢复", "Record recovered after failures"),
+        "notifications.templates.retries_exhausted" => tr!("记录用尽快速重试次数仍失败", "Record still failing after all fast retries"),
+        "notifications.templates.drift" => tr!("记录被外部修改", "Record changed externally"),
+        "notifications.templates.flapping" => tr!("记录更新过于频繁，已停止更新", "Record updated too often, updates paused"),
+        "notifications.templates.startup" => tr!("定时模式启动", "Scheduled mode started"),
+        "notifications.retry" => tr!("通知发送失败后的重试", "Retries for failed notifications"),
+        "notifications.retry.backoff" => tr!("首次重试的等待时间，之后每次翻倍，最长 1 小时", "Initial retry delay, doubling each time up to 1 hour"),
+        "notifications.retry.max_attempts" => tr!("包括首次发送在内的最大尝试次数", "Maximum attempts including the first send"),
+        "notifications.retry.flush_timeout" => tr!("单次运行模式退出前等待重试的最长时间", "How long a single run waits for retries before exiting"),
+        "mqtt" => tr!("MQTT 状态发布（仅定时模式）", "MQTT status publishing (scheduled mode only)"),
+        "mqtt.host" => tr!("MQTT 服务器地址", "MQTT broker host"),
+        "mqtt.port" => tr!("端口，为 null 时按 tls 使用 1883 或 8883", "Port, null uses 1883 or 8883 depending on tls"),
+        "mqtt.client_id" => tr!("客户端 ID", "Client ID"),
+        "mqtt.tls" => tr!("是否使用 TLS 连接", "Whether to connect with TLS"),
+        "mqtt.ca_file" => tr!("自定义 CA 证书 (PEM) 路径，为 null 时使用内置的根证书", "Custom CA certificate (PEM) path, null uses the built-in roots"),
+        "mqtt.topic_prefix" => tr!("主题前缀", "Topic prefix"),
+        "mqtt.qos" => tr!("发布消息的 QoS (0-2)", "QoS for published messages (0-2)"),
+        "logging" => tr!("日志输出", "Log output"),
+        "logging.file" => tr!("日志文件路径，为 null 时输出到终端", "Log file path, null logs to the terminal"),
+        "logging.format" => tr!("日志格式：text 或 json", "Log format: text or json"),
+        "logging.rotation" => tr!("日志文件轮转方式：hourly、daily、size 或 never", "Log file rotation: hourly, daily, size or never"),
+        "logging.max_size_mb" => tr!("rotation 为 size 时单个文件的最大大小 (MB)", "Maximum file size in MB when rotation is size"),
+        "logging.max_files" => tr!("最多保留的日志文件数量（包括当前文件）", "Number of log files to keep, including the current one"),
+        "logging.syslog" => tr!("同时发送到 syslog (RFC 5424)", "Also send to syslog (RFC 5424)"),
+        "logging.syslog.server" => tr!("远程 syslog 服务器地址 (UDP)，为 null 时发送到本机 /dev/log", "Remote syslog server (UDP), null sends to the local /dev/log"),
+        "logging.syslog.facility" => tr!("syslog facility", "Syslog facility"),
+        "logging.journald" => tr!("同时发送到 systemd journald", "Also send to systemd journald"),
+        "logging.eventlog" => tr!("同时把警告、错误与记录变化写入 Windows 事件日志（仅 Windows）", "Also write warnings, errors and record changes to the Windows event log (Windows only)"),
+        "metrics" => tr!("运行指标（仅定时模式）", "Run metrics (scheduled mode only)"),
+        "metrics.textfile" => tr!("node_exporter textfile collector 的 .prom 文件路径", "node_exporter textfile collector .prom file path"),
+        "http" => tr!("状态 HTTP 服务（仅定时模式），提供仪表盘、/healthz、/status、/metrics 与 /update", "Status HTTP server (scheduled mode only) serving the dashboard, /healthz, /status, /metrics and /update"),
+        "http.listen" => tr!("监听地址", "Listen address"),
+        "http.dyndns" => tr!("dyndns2 协议的 /nic/update 接收端，供路由器内置的自定义 DDNS 客户端推送 IP", "dyndns2 /nic/update receiver for routers with a built-in custom DDNS client"),
+        "http.dyndns.username" => tr!("路由器中填写的用户名 (HTTP Basic 认证)", "Username configured on the router (HTTP Basic auth)"),
+        "http.dyndns.password" => tr!("路由器中填写的密码", "Password configured on the router"),
+        "http.token" => tr!("POST /update 远程触发更新所需的令牌 (Authorization: Bearer)，为 null 时不提供远程更新", "Token required by POST /update to trigger updates remotely (Authorization: Bearer), null disables remote updates"),
+        "history" => tr!("IP 变化历史", "IP change history"),
+        "history.enabled" => tr!("是否记录历史", "Whether to record history"),
+        "dual_stack" => tr!("同名的 A 与 AAAA 记录的更新策略：independent 各自更新；atomic 两条都能更新时才写入，一条失败时撤销另一条", "Update policy for names with both A and AAAA records: independent updates each on its own; atomic writes both or neither, rolling back one if the other fails"),
+        "drift" => tr!("记录被外部修改（与上一次发布的内容不一致）时的处理：off 不检查；restore 改回并通知；notify 不修改，只通知", "What to do when a record was changed externally (no longer matches what was last published): off skips the check; restore rewrites it and notifies; notify leaves it and only notifies"),
+        "update_limit" => tr!("每条记录的更新次数上限，超过时停止更新该记录并发送 flapping 通知，直到次数回到上限以内；次数从 IP 变化历史统计", "Per-record update caps; when exceeded the record is no longer updated and a flapping notification is sent until it is back under the cap. Counted from the IP change history"),
+        "update_limit.per_hour" => tr!("最近一小时内的最大更新次数，为 null 时不限制", "Maximum updates in the last hour, null for no limit"),
+        "update_limit.per_day" => tr!("最近一天内的最大更新次数，为 null 时不限制", "Maximum updates in the last day, null for no limit"),
+        "history.file" => tr!("历史文件路径，为 null 时使用配置文件所在目录（systemd 下为 StateDirectory）中的 history.jsonl", "History file path, null uses history.jsonl next to the config file (the StateDirectory under systemd)"),
+        _ if path.starts_with("notifications.") && key == "events" => tr!("接收的事件类型：changed、failed、recovered、retries_exhausted、drift、flapping、startup", "Events to send: changed, failed, recovered, retries_exhausted, drift, flapping, startup"),
+        _ if path.starts_with("notifications.") && key == "failure_threshold" => tr!("连续失败达到该次数时才发送失败通知", "Only notify after this many consecutive failures"),
+        _ => return None,
+    };
+    Some(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sample_is_documented_and_loadable() {
+        let value = serde_json::to_value(sample()).unwrap();
+        let mut all = Vec::new();
+        paths(&value, "", &mut all);
+        let missing: Vec<_> = all.iter().filter(|p| describe(p).is_none()).collect();
+        assert!(missing.is_empty(), "missing descriptions: {missing:?}");
+
+        // 默认输出与所有配置块都取消注释后（逗号位置相同）都应能被加载
+        for text in [render(), render_with(&[])] {
+            let config: Config = serde_json::from_str(&strip_comments(&text)).unwrap();
+            assert_eq!(config.dns_records.len(), 1);
+        }
+    }
+}
@@ -1,39 +1,172 @@
 // 简单的按时间间隔运行的函数
+use tokio::sync::Notify;
 use tokio::time;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
 use std::time::{Duration, SystemTime};
 use chrono::{DateTime, Local};
+use tracing::{info, warn};
 
-pub async fn run_with_schedule<F, Fut>(interval_seconds: u64, job_func: F) 
+use cloudflare_ddns::Error;
+use cloudflare_ddns::duration::format_duration;
+use cloudflare_ddns::i18n::tr;
+
+/// 任务执行后决定下一次执行的时间
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NextRun {
+    /// 按正常的执行间隔
+    #[default]
+    Interval,
+    /// 在指定时间后提前执行（例如重试失败的记录），从任务开始时计算
+    After(Duration),
+}
+
+/// 某次执行开始时传给 `on_start` 的信息
+#[derive(Debug, Clone)]
+pub struct JobStart {
+    /// 第几次执行，从 1 开始
+    pub execution: u64,
+    pub started_at: DateTime<Local>,
+}
+
+/// 某次执行成功时传给 `on_success` 的信息
+#[derive(Debug, Clone)]
+#[allow(dead_code)]
+pub struct JobSuccess {
+    pub execution: u64,
+    pub elapsed: Duration,
+    pub next_run: NextRun,
+}
+
+/// 某次执行失败时传给 `on_failure` 的信息
+#[derive(Debug, Clone)]
+#[allow(dead_code)]
+pub struct JobFailure {
+    pub execution: u64,
+    pub elapsed: Duration,
+    pub error: String,
+    /// 失败是否由 panic 引起
+    pub panicked: bool,
+    /// 截至本次累计的 panic 次数
+    pub panic_count: u64,
+}
+
+pub type HookFuture = Pin<Box<dyn Future<Output = ()> + Send>>;
+type Hook<T> = Box<dyn Fn(T) -> HookFuture + Send + Sync>;
+
+/// 定时任务的生命周期回调，均为可选的异步函数
+#[derive(Default)]
+pub struct ScheduleHooks {
+    on_start: Option<Hook<JobStart>>,
+    on_success: Option<Hook<JobSuccess>>,
+    on_failure: Option<Hook<JobFailure>>,
+    /// 等待期间收到通知时立即开始下一次执行
+    wake: Option<Arc<Notify>>,
+}
+
+impl ScheduleHooks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 每次执行开始前调用
+    pub fn on_start<H, Fut>(mut self, hook: H) -> Self
+    where
+        H: Fn(JobStart) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.on_start = Some(Box::new(move |info| Box::pin(hook(info))));
+        self
+    }
+
+    /// 每次执行成功后调用
+    pub fn on_success<H, Fut>(mut self, hook: H) -> Self
+    where
+        H: Fn(JobSuccess) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.on_success = Some(Box::new(move |info| Box::pin(hook(info))));
+        self
+    }
+
+    /// 等待下一次执行期间，`wake` 收到通知时不再等待，立即执行
+    pub fn wake_on(mut self, wake: Arc<Notify>) -> Self {
+        self.wake = Some(wake);
+        self
+    }
+
+    /// 每次执行失败（包括 panic）后调用
+    pub fn on_failure<H, Fut>(mut self, hook: H) -> Self
+    where
+        H: Fn(JobFailure) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.on_failure = Some(Box::new(move |info| Box::pin(hook(info))));
+        self
+    }
+}
+
+pub async fn run_with_schedule<F, Fut>(interval: Duration, hooks: ScheduleHooks, job_func: F) 
 where 
     F: Fn() -> Fut + Send + Sync + 'static,
-    Fut: std::future::Future<Output = Result<(), Box<dyn std::error::Error + Send + Sync>>> + Send + 'static
+    Fut: Future<Output = Result<NextRun, Error>> + Send + 'static
 {
-    let duration = Duration::from_secs(interval_seconds);
-    
-    println!("定时任务已启动，执行间隔: {} 秒", interval_seconds);
+    info!(interval = %format_duration(interval), "{}", tr!("定时任务已启动", "Scheduler started"));
     
     let mut execution_count = 0;
+    let mut panic_count = 0;
     
     loop {
         execution_count += 1;
         let start_time = SystemTime::now();
-        let datetime: DateTime<Local> = start_time.into();
         
-        println!("=== 第 {} 次执行开始 ===", execution_count);
-        println!("执行时间: {}", datetime.format("%Y-%m-%d %H:%M:%S"));
+        if let Some(on_start) = &hooks.on_start {
+            on_start(JobStart { execution: execution_count, started_at: start_time.into() }).await;
+        }
         
-        // 执行任务
-        let task_result = job_func().await;
+        // 执行任务，在独立的 task 中运行，以便捕获其中的 panic
+        let mut panicked = false;
+        let task_result = match tokio::spawn(job_func()).await {
+            Ok(result) => result,
+            Err(e) if e.is_panic() => {
+                panicked = true;
+                panic_count += 1;
+                let message = panic_message(e.into_panic());
+                Err(Error::scheduler(tr!("任务发生 panic (累计 {} 次): {}", "Job panicked ({} panics so far): {}", panic_count, message)))
+            }
+            Err(e) => Err(Error::scheduler(tr!("任务被取消: {}", "Job was cancelled: {}", e))),
+        };
         
         // 计算任务执行时间
         let end_time = SystemTime::now();
         let elapsed = end_time.duration_since(start_time)
             .unwrap_or(Duration::from_secs(0));
         
-        match task_result {
-            Ok(()) => println!("定时任务执行成功 (耗时: {:.2}秒)", elapsed.as_secs_f64()),
-            Err(e) => eprintln!("定时任务执行失败 (耗时: {:.2}秒): {}", elapsed.as_secs_f64(), e),
-        }
+        let next_run = match task_result {
+            Ok(next_run) => {
+                if let Some(on_success) = &hooks.on_success {
+                    on_success(JobSuccess { execution: execution_count, elapsed, next_run }).await;
+                }
+                next_run
+            }
+            Err(e) => {
+                if let Some(on_failure) = &hooks.on_failure {
+                    on_failure(JobFailure {
+                        execution: execution_count,
+                        elapsed,
+                        error: e.to_string(),
+                        panicked,
+                        panic_count,
+                    }).await;
+                }
+                NextRun::Interval
+            }
+        };
+        let duration = match next_run {
+            NextRun::Interval => interval,
+            NextRun::After(delay) => delay.min(interval),
+        };
         
         // 如果任务执行时间超过间隔时间，立即开始下一次执行
         // 否则等待剩余的时间
@@ -41,11 +174,35 @@ where
             let wait_time = duration - elapsed;
             let next_execution = SystemTime::now() + wait_time;
             let next_datetime: DateTime<Local> = next_execution.into();
-            println!("下一次执行时间: {}", next_datetime.format("%Y-%m-%d %H:%M:%S"));
-            println!("等待 {:.2} 秒...", wait_time.as_secs_f64());
-            time::sleep(wait_time).await;
+            info!(
+                next_run = %next_datetime.format("%Y-%m-%d %H:%M:%S"),
+                wait = format_args!("{:.2}s", wait_time.as_secs_f64()),
+                "{}", tr!("等待下一次执行", "Waiting for next run")
+            );
+            match &hooks.wake {
+                Some(wake) => tokio::select! {
+                    _ = time::sleep(wait_time) => {}
+                    _ = wake.notified() => info!("{}", tr!("收到请求，立即执行", "Run requested, starting now")),
+                },
+                None => time::sleep(wait_time).await,
+            }
         } else {
-            println!("任务执行时间 ({:.2}秒) 超过间隔时间 ({}秒)，立即开始下一次执行", elapsed.as_secs_f64(), interval_seconds);
+            warn!(
+                duration = format_args!("{:.2}s", elapsed.as_secs_f64()),
+                interval = %format_duration(duration),
+                "{}", tr!("任务执行时间超过间隔时间，立即开始下一次执行", "Job took longer than the interval, starting next run immediately")
+            );
         }
     }
-}
\ No newline at end of file
+}
+
+/// 从 panic 负载中提取可读的消息
+fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        tr!("未知的 panic", "unknown panic")
+    }
+}
@@ -0,0 +1,80 @@
+// 生成并安装系统服务定义：systemd 单元、launchd plist 与 Windows 服务
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use cloudflare_ddns::config::Config;
+use cloudflare_ddns::i18n::tr;
+use cloudflare_ddns::summary::{FailureKind, RunError};
+
+mod launchd;
+mod systemd;
+#[cfg(windows)]
+mod windows;
+
+pub use launchd::{install_launchd, LaunchdOptions};
+pub use systemd::{install_systemd, SystemdOptions};
+#[cfg(windows)]
+pub use windows::{manage as manage_windows_service, run as run_windows_service, ServiceAction};
+
+/// 当前可执行文件与配置文件的绝对路径，写入服务定义
+fn absolute_paths(config_path: &str) -> Result<(PathBuf, PathBuf), RunError> {
+    let exe = std::env::current_exe()
+        .and_then(|exe| exe.canonicalize())
+        .map_err(|e| RunError::new(FailureKind::Config, tr!("无法确定程序路径: {}", "Failed to locate the executable: {}", e)))?;
+    let config = Path::new(config_path).canonicalize()
+        .map_err(|e| RunError::new(FailureKind::Config, tr!("无法读取配置文件 {}: {}", "Failed to read config file {}: {}", config_path, e)))?;
+    Ok((exe, config))
+}
+
+/// 服务启动命令中 `-c` 之后的参数，以及定时执行单次运行时的间隔
+///
+/// `timer` 为 true 时由服务管理器按间隔执行 `once`；否则常驻执行 `run`，
+/// 只写入命令行指定的间隔，配置文件中的间隔在服务启动时读取。
+fn run_arguments(
+    config: &Config,
+    timer: bool,
+    interval: Option<Duration>,
+    reconcile_interval: Option<Duration>,
+) -> Result<(Vec<String>, Option<Duration>), RunError> {
+    if timer {
+        let interval = interval.or(config.schedule.interval).ok_or_else(|| RunError::new(
+            FailureKind::Config,
+            tr!("--timer 需要通过 --interval 或配置中的 schedule.interval 指定间隔", "--timer requires an interval via --interval or schedule.interval in the config"),
+        ))?;
+        return Ok((vec!["once".to_string()], Some(interval)));
+    }
+    if interval.is_none() && config.schedule.interval.is_none() {
+        return Err(RunError::new(
+            FailureKind::Config,
+            tr!("定时模式需要通过 --interval 或配置中的 schedule.interval 指定间隔", "Scheduled mode requires an interval via --interval or schedule.interval in the config"),
+        ));
+    }
+    let mut arguments = Vec::new();
+    for (flag, value) in [("--interval", interval), ("--reconcile-interval", reconcile_interval)] {
+        if let Some(value) = value {
+            arguments.extend([flag.to_string(), value.as_secs().to_string()]);
+        }
+    }
+    arguments.push("run".to_string());
+    Ok((arguments, None))
+}
+
+/// 写入服务定义文件
+fn write_file(path: &Path, content: &str) -> Result<(), RunError> {
+    std::fs::write(path, content)
+        .map_err(|e| RunError::new(FailureKind::Config, tr!("无法写入 {}: {}", "Failed to write {}: {}", path.display(), e)))?;
+    println!("{}", tr!("已写入 {}", "Wrote {}", path.display()));
+    Ok(())
+}
+
+/// 执行服务管理命令（如 systemctl），非零退出视为失败
+fn run_command(program: &str, args: &[&str]) -> Result<(), RunError> {
+    let command = format!("{} {}", program, args.join(" "));
+    println!("$ {}", command);
+    let status = std::process::Command::new(program).args(args).status()
+        .map_err(|e| RunError::new(FailureKind::Config, tr!("无法执行 {}: {}", "Failed to run {}: {}", command, e)))?;
+    if !status.success() {
+        return Err(RunError::new(FailureKind::Config, tr!("{} 执行失败: {}", "{} failed: {}", command, status)));
+    }
+    Ok(())
+}
@@ -0,0 +1,98 @@
+// macOS launchd plist：登录时 (LaunchAgents) 或开机时 (LaunchDaemons) 启动
+use std::path::PathBuf;
+use std::time::Duration;
+
+use cloudflare_ddns::i18n::tr;
+use cloudflare_ddns::summary::{FailureKind, RunError};
+use cloudflare_ddns::update::load_config;
+
+/// `install-launchd` 的选项
+#[derive(Debug, clap::Args)]
+pub struct LaunchdOptions {
+    /// 任务标签，也用作 plist 文件名
+    #[arg(long, default_value = "cloudflare-ddns")]
+    pub label: String,
+
+    /// 使用 StartInterval 定时执行单次运行，代替常驻的定时模式
+    #[arg(long)]
+    pub timer: bool,
+
+    /// 写入 /Library/LaunchDaemons 开机启动（需要 root），默认写入 ~/Library/LaunchAgents 登录时启动
+    #[arg(long)]
+    pub system: bool,
+
+    /// 写入 plist 文件，默认只打印到标准输出
+    #[arg(long)]
+    pub install: bool,
+
+    /// 写入后执行 launchctl load -w 立即加载
+    #[arg(long, requires = "install")]
+    pub load: bool,
+}
+
+/// 生成 launchd plist，`install` 时写入对应目录；`interval` 与 `reconcile_interval` 为命令行指定的值
+pub fn install_launchd(
+    options: &LaunchdOptions,
+    config_path: &str,
+    interval: Option<Duration>,
+    reconcile_interval: Option<Duration>,
+) -> Result<(), RunError> {
+    let config = load_config(config_path)?;
+    let (exe, config_file) = super::absolute_paths(config_path)?;
+    let (arguments, start_interval) = super::run_arguments(&config, options.timer, interval, reconcile_interval)?;
+    let mut program = vec![exe.to_string_lossy().into_owned(), "-c".to_string(), config_file.to_string_lossy().into_owned()];
+    program.extend(arguments);
+
+    let (plist_dir, log_dir) = if options.system {
+        (PathBuf::from("/Library/LaunchDaemons"), PathBuf::from("/Library/Logs"))
+    } else {
+        let home = std::env::var_os("HOME").map(PathBuf::from)
+            .ok_or_else(|| RunError::new(FailureKind::Config, tr!("未设置 HOME 环境变量", "HOME is not set")))?;
+        (home.join("Library/LaunchAgents"), home.join("Library/Logs"))
+    };
+    let log_file = log_dir.join(format!("{}.log", options.label));
+    let plist = render_plist(&options.label, &program, start_interval, &log_file.to_string_lossy());
+    if !options.install {
+        print!("{}", plist);
+        return Ok(());
+    }
+
+    let path = plist_dir.join(format!("{}.plist", options.label));
+    super::write_file(&path, &plist)?;
+    let path = path.to_string_lossy();
+    if options.load {
+        super::run_command("launchctl", &["load", "-w", &path])?;
+    } else {
+        println!("{}", tr!("执行以下命令加载: launchctl load -w {}", "Load it with: launchctl load -w {}", path));
+    }
+    Ok(())
+}
+
+/// 常驻模式异常退出时由 launchd 重新启动；timer 模式按 StartInterval 执行
+fn render_plist(label: &str, program: &[String], start_interval: Option<Duration>, log_file: &str) -> String {
+    let mut plist = String::from(concat!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+        "<!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n",
+        "<plist version=\"1.0\">\n<dict>\n",
+    ));
+    plist.push_str(&format!("  <key>Label</key>\n  <string>{}</string>\n", escape(label)));
+    plist.push_str("  <key>ProgramArguments</key>\n  <array>\n");
+    for argument in program {
+        plist.push_str(&format!("    <string>{}</string>\n", escape(argument)));
+    }
+    plist.push_str("  </array>\n  <key>RunAtLoad</key>\n  <true/>\n");
+    match start_interval {
+        Some(interval) => plist.push_str(&format!("  <key>StartInterval</key>\n  <integer>{}</integer>\n", interval.as_secs())),
+        None => plist.push_str("  <key>KeepAlive</key>\n  <dict>\n    <key>SuccessfulExit</key>\n    <false/>\n  </dict>\n  <key>ThrottleInterval</key>\n  <integer>30</integer>\n"),
+    }
+    plist.push_str("  <key>ProcessType</key>\n  <string>Background</string>\n");
+    for key in ["StandardOutPath", "StandardErrorPath"] {
+        plist.push_str(&format!("  <key>{}</key>\n  <string>{}</string>\n", key, escape(log_file)));
+    }
+    plist.push_str("</dict>\n</plist>\n");
+    plist
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
@@ -0,0 +1,186 @@
+// systemd 服务单元：DynamicUser 运行、配置文件与配置中引用的凭据（api_token_credential）通过 LoadCredential 传入
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use cloudflare_ddns::i18n::tr;
+use cloudflare_ddns::summary::RunError;
+use cloudflare_ddns::update::{default_data_file, load_config};
+
+/// 配置文件在 `$CREDENTIALS_DIRECTORY` 中的名称
+const CREDENTIAL_NAME: &str = "config.json";
+
+/// `install-service` 的选项
+#[derive(Debug, clap::Args)]
+pub struct SystemdOptions {
+    /// 单元名称
+    #[arg(long, default_value = "cloudflare-ddns")]
+    pub name: String,
+
+    /// 生成 timer 定时执行单次运行，代替常驻的定时模式
+    #[arg(long)]
+    pub timer: bool,
+
+    /// 单元文件目录
+    #[arg(long, default_value = "/etc/systemd/system")]
+    pub unit_dir: PathBuf,
+
+    /// 写入后执行 systemctl daemon-reload 并立即启用
+    #[arg(long, conflicts_with = "print")]
+    pub enable: bool,
+
+    /// 只打印单元文件内容，不写入
+    #[arg(long)]
+    pub print: bool,
+}
+
+/// 生成 systemd 单元并写入 `unit_dir`，`interval` 与 `reconcile_interval` 为命令行指定的值
+pub fn install_systemd(
+    options: &SystemdOptions,
+    config_path: &str,
+    interval: Option<Duration>,
+    reconcile_interval: Option<Duration>,
+) -> Result<(), RunError> {
+    let config = load_config(config_path)?;
+    let (exe, config_file) = super::absolute_paths(config_path)?;
+
+    let (arguments, timer_interval) = super::run_arguments(&config, options.timer, interval, reconcile_interval)?;
+    let credential = format!("${{CREDENTIALS_DIRECTORY}}/{}", CREDENTIAL_NAME);
+    let mut exec = vec![exe.to_string_lossy().into_owned(), "-c".to_string(), credential];
+    exec.extend(arguments);
+
+    // ProtectSystem=strict 下只有这些文件所在的目录可写
+    let mut writable: Vec<PathBuf> = Vec::new();
+    let history = config.history.file.filter(|file| *file != default_data_file(config_path, "history.jsonl"));
+    let state_file = config.schedule.state_file.filter(|file| *file != default_data_file(config_path, "state.json"));
+    let files = [config.logging.file, config.metrics.and_then(|m| m.textfile), config.schedule.state_dump_file, state_file, history];
+    for file in files.into_iter().flatten() {
+        if let Some(dir) = Path::new(&file).parent().filter(|dir| !dir.as_os_str().is_empty())
+            && !writable.iter().any(|d| d == dir)
+        {
+            writable.push(dir.to_path_buf());
+        }
+    }
+
+    // 配置中引用的 systemd 凭据从凭据存储目录（/etc/credstore 等）中同名的文件加载
+    let credentials: Vec<String> = config.cloudflare.iter()
+        .filter_map(|cloudflare| cloudflare.credentials.api_token_credential.clone())
+        .collect();
+
+    let service = render_service(&exec, &config_file, &credentials, &writable, options.timer);
+    let timer = timer_interval.map(render_timer);
+    let service_name = format!("{}.service", options.name);
+    let timer_name = format!("{}.timer", options.name);
+    if options.print {
+        println!("# {}\n{}", service_name, service);
+        if let Some(timer) = &timer {
+            println!("# {}\n{}", timer_name, timer);
+        }
+        return Ok(());
+    }
+
+    super::write_file(&options.unit_dir.join(&service_name), &service)?;
+    if let Some(timer) = &timer {
+        super::write_file(&options.unit_dir.join(&timer_name), timer)?;
+    }
+    let unit = if options.timer { &timer_name } else { &service_name };
+    if options.enable {
+        super::run_command("systemctl", &["daemon-reload"])?;
+        super::run_command("systemctl", &["enable", "--now", unit])?;
+    } else {
+        println!("{}", tr!("执行以下命令启用: systemctl daemon-reload && systemctl enable --now {}", "Enable it with: systemctl daemon-reload && systemctl enable --now {}", unit));
+    }
+    for name in &credentials {
+        println!("{}", tr!(
+            "请把凭据 {0} 写入 /etc/credstore/{0}（仅 root 可读）",
+            "Put credential {0} in /etc/credstore/{0} (readable by root only)",
+            name
+        ));
+    }
+    Ok(())
+}
+
+/// 服务单元；timer 模式为 oneshot，退出码 10（有记录被更新）同样视为成功
+fn render_service(exec: &[String], config_file: &Path, credentials: &[String], writable: &[PathBuf], oneshot: bool) -> String {
+    let exec: Vec<String> = exec.iter().map(|arg| quote(arg)).collect();
+    let mut unit = String::from("[Unit]\nDescription=CloudFlare DDNS\nWants=network-online.target\nAfter=network-online.target\n\n[Service]\n");
+    if oneshot {
+        unit.push_str("Type=oneshot\nSuccessExitStatus=10\n");
+    } else {
+        unit.push_str("Type=simple\nRestart=on-failure\nRestartSec=30s\n");
+    }
+    unit.push_str(&format!("ExecStart={}\n", exec.join(" ")));
+    unit.push_str(&format!("LoadCredential={}:{}\n", CREDENTIAL_NAME, quote(&config_file.to_string_lossy())));
+    for name in credentials {
+        // 只给名称时由 systemd 在凭据存储目录（/etc/credstore 等）中查找同名文件
+        unit.push_str(&format!("LoadCredential={}\n", quote(name)));
+    }
+    unit.push_str(concat!(
+        "DynamicUser=yes\n",
+        "StateDirectory=cloudflare-ddns\n",
+        "ProtectSystem=strict\n",
+        "ProtectHome=yes\n",
+        "PrivateTmp=yes\n",
+        "PrivateDevices=yes\n",
+        "NoNewPrivileges=yes\n",
+        "CapabilityBoundingSet=\n",
+        "ProtectKernelTunables=yes\n",
+        "ProtectKernelModules=yes\n",
+        "ProtectKernelLogs=yes\n",
+        "ProtectControlGroups=yes\n",
+        "ProtectClock=yes\n",
+        "ProtectHostname=yes\n",
+        "RestrictAddressFamilies=AF_INET AF_INET6 AF_UNIX\n",
+        "RestrictNamespaces=yes\n",
+        "RestrictRealtime=yes\n",
+        "LockPersonality=yes\n",
+        "MemoryDenyWriteExecute=yes\n",
+        "SystemCallArchitectures=native\n",
+        "SystemCallFilter=@system-service\n",
+    ));
+    for dir in writable {
+        // `-` 前缀：目录不存在时忽略而不是启动失败
+        unit.push_str(&format!("ReadWritePaths=-{}\n", quote(&dir.to_string_lossy())));
+    }
+    if !oneshot {
+        unit.push_str("\n[Install]\nWantedBy=multi-user.target\n");
+    }
+    unit
+}
+
+fn render_timer(interval: Duration) -> String {
+    format!(
+        "[Unit]\nDescription=CloudFlare DDNS timer\n\n[Timer]\nOnBootSec=1min\nOnUnitActiveSec={}s\n\n[Install]\nWantedBy=timers.target\n",
+        interval.as_secs()
+    )
+}
+
+/// 按 systemd 的规则转义参数：`%` 与 `$` 需要双写（`${CREDENTIALS_DIRECTORY}` 除外），含空白或引号时加双引号
+fn quote(arg: &str) -> String {
+    let escaped = if arg.starts_with("${CREDENTIALS_DIRECTORY}") {
+        arg.replace('%', "%%")
+    } else {
+        arg.replace('%', "%%").replace('$', "$$")
+    };
+    if escaped.chars().any(|c| c.is_whitespace() || c == '"' || c == '\'' || c == '\\') {
+        format!("\"{}\"", escaped.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        escaped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn service_unit_loads_config_as_credential() {
+        let exec = ["/opt/ddns bin/cloudflare_ddns", "-c", "${CREDENTIALS_DIRECTORY}/config.json", "run"].map(String::from);
+        let unit = render_service(&exec, Path::new("/etc/ddns/config.json"), &["cf_token".to_string()], &[PathBuf::from("/var/log/ddns")], false);
+        assert!(unit.contains("ExecStart=\"/opt/ddns bin/cloudflare_ddns\" -c ${CREDENTIALS_DIRECTORY}/config.json run\n"));
+        assert!(unit.contains("LoadCredential=config.json:/etc/ddns/config.json\n"));
+        assert!(unit.contains("LoadCredential=cf_token\n"));
+        assert!(unit.contains("ReadWritePaths=-/var/log/ddns\n"));
+        assert!(unit.contains("WantedBy=multi-user.target"));
+        assert_eq!(quote("50%$x"), "50%%$$x");
+    }
+}
@@ -0,0 +1,179 @@
+// Windows 服务：注册到服务控制管理器 (SCM)，由 SCM 以 `service run` 启动定时模式
+use std::ffi::OsString;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::sync::Notify;
+use tracing::{error, info};
+use windows_service::service::{
+    ServiceAccess, ServiceControl, ServiceControlAccept, ServiceErrorControl, ServiceExitCode, ServiceInfo,
+    ServiceStartType, ServiceState, ServiceStatus, ServiceType,
+};
+use windows_service::service_control_handler::{self, ServiceControlHandlerResult, ServiceStatusHandle};
+use windows_service::service_manager::{ServiceManager, ServiceManagerAccess};
+use windows_service::{define_windows_service, service_dispatcher};
+
+use cloudflare_ddns::i18n::tr;
+use cloudflare_ddns::summary::{FailureKind, RunError};
+use cloudflare_ddns::update::load_config;
+
+/// 注册到 SCM 的服务名称
+const SERVICE_NAME: &str = "cloudflare_ddns";
+
+/// `service` 子命令的操作
+#[derive(Debug, clap::Subcommand)]
+pub enum ServiceAction {
+    /// 注册为开机自动启动的服务，以 LocalSystem 运行
+    Install,
+    /// 停止并删除服务
+    Uninstall,
+    /// 启动服务
+    Start,
+    /// 停止服务
+    Stop,
+    /// 服务入口，由 SCM 调用
+    #[command(hide = true)]
+    Run,
+}
+
+/// 服务线程中运行的定时模式，由 `run` 在启动 SCM 调度之前放入
+type Daemon = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+static DAEMON: Mutex<Option<(tokio::runtime::Handle, Daemon)>> = Mutex::new(None);
+
+define_windows_service!(ffi_service_main, service_main);
+
+/// 执行 install、uninstall、start、stop；`interval` 与 `reconcile_interval` 为命令行指定的值
+pub fn manage(
+    action: &ServiceAction,
+    config_path: &str,
+    interval: Option<Duration>,
+    reconcile_interval: Option<Duration>,
+) -> Result<(), RunError> {
+    match action {
+        ServiceAction::Install => install(config_path, interval, reconcile_interval),
+        ServiceAction::Uninstall => {
+            let service = open(ServiceAccess::QUERY_STATUS | ServiceAccess::STOP | ServiceAccess::DELETE)?;
+            if service.query_status().map_err(scm_error)?.current_state != ServiceState::Stopped {
+                service.stop().map_err(scm_error)?;
+            }
+            service.delete().map_err(scm_error)?;
+            println!("{}", tr!("已删除服务 {}", "Removed service {}", SERVICE_NAME));
+            Ok(())
+        }
+        ServiceAction::Start => {
+            open(ServiceAccess::START)?.start::<&str>(&[]).map_err(scm_error)?;
+            println!("{}", tr!("已启动服务 {}", "Started service {}", SERVICE_NAME));
+            Ok(())
+        }
+        ServiceAction::Stop => {
+            open(ServiceAccess::STOP)?.stop().map_err(scm_error)?;
+            println!("{}", tr!("已停止服务 {}", "Stopped service {}", SERVICE_NAME));
+            Ok(())
+        }
+        ServiceAction::Run => Err(RunError::new(FailureKind::Config, tr!("service run 只能由服务控制管理器调用", "service run can only be called by the service control manager"))),
+    }
+}
+
+fn install(config_path: &str, interval: Option<Duration>, reconcile_interval: Option<Duration>) -> Result<(), RunError> {
+    let config = load_config(config_path)?;
+    if interval.is_none() && config.schedule.interval.is_none() {
+        return Err(RunError::new(
+            FailureKind::Config,
+            tr!("定时模式需要通过 --interval 或配置中的 schedule.interval 指定间隔", "Scheduled mode requires an interval via --interval or schedule.interval in the config"),
+        ));
+    }
+    let (exe, config_file) = super::absolute_paths(config_path)?;
+
+    let mut arguments: Vec<OsString> = vec!["-c".into(), config_file.into_os_string()];
+    for (flag, value) in [("--interval", interval), ("--reconcile-interval", reconcile_interval)] {
+        if let Some(value) = value {
+            arguments.extend([flag.into(), value.as_secs().to_string().into()]);
+        }
+    }
+    arguments.extend(["service".into(), "run".into()]);
+
+    let manager = ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CONNECT | ServiceManagerAccess::CREATE_SERVICE)
+        .map_err(scm_error)?;
+    let info = ServiceInfo {
+        name: SERVICE_NAME.into(),
+        display_name: "CloudFlare DDNS".into(),
+        service_type: ServiceType::OWN_PROCESS,
+        start_type: ServiceStartType::AutoStart,
+        error_control: ServiceErrorControl::Normal,
+        executable_path: exe,
+        launch_arguments: arguments,
+        dependencies: Vec::new(),
+        account_name: None,
+        account_password: None,
+    };
+    let service = manager.create_service(&info, ServiceAccess::CHANGE_CONFIG).map_err(scm_error)?;
+    service.set_description(tr!("把公网 IP 同步到 CloudFlare DNS 记录", "Keeps CloudFlare DNS records pointed at the public IP"))
+        .map_err(scm_error)?;
+    println!("{}", tr!("已注册服务 {}，执行 service start 启动", "Registered service {}, run service start to start it", SERVICE_NAME));
+    Ok(())
+}
+
+/// 作为服务运行：阻塞当前线程直到服务停止，`daemon` 在 SCM 的服务线程中执行
+pub fn run(daemon: impl Future<Output = ()> + Send + 'static) -> Result<(), RunError> {
+    *DAEMON.lock().unwrap() = Some((tokio::runtime::Handle::current(), Box::pin(daemon)));
+    tokio::task::block_in_place(|| service_dispatcher::start(SERVICE_NAME, ffi_service_main)).map_err(scm_error)
+}
+
+fn service_main(_arguments: Vec<OsString>) {
+    let Some((runtime, daemon)) = DAEMON.lock().unwrap().take() else {
+        return;
+    };
+    let stop = Arc::new(Notify::new());
+    let handler_stop = stop.clone();
+    let status = service_control_handler::register(SERVICE_NAME, move |control| match control {
+        ServiceControl::Stop | ServiceControl::Shutdown => {
+            handler_stop.notify_one();
+            ServiceControlHandlerResult::NoError
+        }
+        ServiceControl::Interrogate => ServiceControlHandlerResult::NoError,
+        _ => ServiceControlHandlerResult::NotImplemented,
+    });
+    let status = match status {
+        Ok(status) => status,
+        Err(e) => {
+            error!(error = %e, "{}", tr!("无法注册服务控制处理程序", "Failed to register the service control handler"));
+            return;
+        }
+    };
+
+    set_state(&status, ServiceState::Running, ServiceControlAccept::STOP | ServiceControlAccept::SHUTDOWN);
+    runtime.block_on(async {
+        tokio::select! {
+            () = daemon => {}
+            () = stop.notified() => info!("{}", tr!("收到停止请求，服务退出", "Stop requested, service exiting")),
+        }
+    });
+    set_state(&status, ServiceState::Stopped, ServiceControlAccept::empty());
+}
+
+fn set_state(status: &ServiceStatusHandle, state: ServiceState, controls: ServiceControlAccept) {
+    let result = status.set_service_status(ServiceStatus {
+        service_type: ServiceType::OWN_PROCESS,
+        current_state: state,
+        controls_accepted: controls,
+        exit_code: ServiceExitCode::Win32(0),
+        checkpoint: 0,
+        wait_hint: Duration::default(),
+        process_id: None,
+    });
+    if let Err(e) = result {
+        error!(error = %e, "{}", tr!("无法更新服务状态", "Failed to update the service status"));
+    }
+}
+
+fn open(access: ServiceAccess) -> Result<windows_service::service::Service, RunError> {
+    let manager = ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CONNECT).map_err(scm_error)?;
+    manager.open_service(SERVICE_NAME, access).map_err(scm_error)
+}
+
+fn scm_error(e: windows_service::Error) -> RunError {
+    RunError::new(FailureKind::Config, tr!("服务管理操作失败: {}", "Service management failed: {}", e))
+}
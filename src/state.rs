@@ -0,0 +1,379 @@
+// 持久化状态：上一次检测到的 IP、每条记录最近一次发布的内容、故障转移的健康状态、单次运行的通知计数、IP 变化历史与定时模式的运行统计
+//
+// 各功能通过 [`StateStore`] 读写状态，不直接操作文件。默认的 [`JsonStateStore`] 把历史追加到
+// JSONL 文件（`history.file`），其余状态写入一个 JSON 文件（`schedule.state_file`）；启用 sqlite 功能并把
+// `schedule.state_backend` 设为 sqlite 时，状态与历史都写入 `schedule.state_file` 的 SQLite 数据库。
+//
+// 整体重写的持久化文件（状态、缓存等）都经过 [`write_atomic`]：先写入同目录下的临时文件并刷到磁盘，再重命名替换原文件，
+// 路由器断电时文件要么是旧内容、要么是新内容，不会留下截断的 JSON 导致下次启动失败。历史文件只追加，见 [`history::append`]。
+use std::collections::BTreeMap;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+#[cfg(feature = "sqlite")]
+use crate::config::StateBackend;
+use crate::failover::FailoverState;
+use crate::history::{self, HistoryEntry, HistoryFilter};
+use crate::i18n::tr;
+use crate::ip_utils::DetectedIps;
+use crate::notify::FailureCounts;
+
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+
+/// 持久化状态的存储后端
+pub trait StateStore: Send + Sync {
+    /// 上一次核对时使用的 IP，从未保存过时为空
+    fn last_ips(&self) -> io::Result<Option<DetectedIps>>;
+    fn save_last_ips(&self, ips: &DetectedIps) -> io::Result<()>;
+
+    /// 每条记录最近一次由本程序写入的内容
+    fn published(&self) -> io::Result<Vec<PublishedRecord>>;
+    /// 保存一条记录最近一次写入的内容，替换名称与类型相同的旧值
+    fn save_published(&self, record: &PublishedRecord) -> io::Result<()>;
+
+    /// 各故障转移记录的健康状态，按 [`crate::failover::state_key`] 索引
+    fn failover(&self) -> io::Result<BTreeMap<String, FailoverState>>;
+    fn save_failover(&self, states: &BTreeMap<String, FailoverState>) -> io::Result<()>;
+
+    /// 单次运行之间保留的连续失败次数与已通知的漂移、频繁更新
+    fn failure_counts(&self) -> io::Result<FailureCounts>;
+    fn save_failure_counts(&self, counts: &FailureCounts) -> io::Result<()>;
+
+    /// 追加一条 IP 变化历史
+    fn append_history(&self, entry: &HistoryEntry) -> io::Result<()>;
+    /// 全部 IP 变化历史，按时间先后排列
+    fn history(&self) -> io::Result<Vec<HistoryEntry>>;
+    /// 按条件筛选的 IP 变化历史，按时间先后排列
+    fn query_history(&self, filter: &HistoryFilter) -> io::Result<Vec<HistoryEntry>> {
+        Ok(filter.apply(self.history()?))
+    }
+
+    /// 定时模式最近一次执行的统计，从未执行过时为空
+    fn run_stats(&self) -> io::Result<Option<RunStats>>;
+    fn save_run_stats(&self, stats: &RunStats) -> io::Result<()>;
+}
+
+/// 按配置打开状态存储
+pub fn open(config: &Config) -> Box<dyn StateStore> {
+    #[cfg(feature = "sqlite")]
+    if config.schedule.state_backend == StateBackend::Sqlite
+        && let Some(path) = &config.schedule.state_file
+    {
+        return Box::new(sqlite::SqliteStateStore::new(PathBuf::from(path)));
+    }
+    Box::new(JsonStateStore::new(
+        config.schedule.state_file.as_deref().map(PathBuf::from),
+        config.history.file.as_deref().map(PathBuf::from),
+    ))
+}
+
+/// 一条记录最近一次由本程序写入的内容
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublishedRecord {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub record_type: String,
+    pub zone: String,
+    pub content: String,
+    /// RFC 3339 时间
+    pub updated_at: String,
+}
+
+impl PublishedRecord {
+    pub fn new(name: &str, record_type: &str, zone: &str, content: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            record_type: record_type.to_string(),
+            zone: zone.to_string(),
+            content: content.to_string(),
+            updated_at: Local::now().to_rfc3339(),
+        }
+    }
+
+    pub(crate) fn time(&self) -> Option<DateTime<Local>> {
+        DateTime::parse_from_rfc3339(&self.updated_at).ok().map(|t| t.with_timezone(&Local))
+    }
+}
+
+/// 定时模式每次执行结束后保存的统计，时间均为 RFC 3339 格式
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunStats {
+    pub pid: u32,
+    /// 最近一次执行结束的时间
+    pub last_run: String,
+    /// 最近一次成功执行的时间
+    pub last_success: Option<String>,
+    /// 最近一次执行失败时的错误，成功时为空
+    pub error: Option<String>,
+}
+
+impl RunStats {
+    pub fn new(last_run: DateTime<Local>, last_success: Option<DateTime<Local>>, error: Option<String>) -> Self {
+        Self {
+            pid: std::process::id(),
+            last_run: last_run.to_rfc3339(),
+            last_success: last_success.map(|t| t.to_rfc3339()),
+            error,
+        }
+    }
+
+    /// 最近一次执行成功且距今不超过 `max_age` 时返回距今的时间，否则返回原因
+    pub fn check(&self, max_age: Duration, now: DateTime<Local>) -> Result<Duration, String> {
+        if let Some(error) = &self.error {
+            return Err(tr!("最近一次执行失败: {}", "Last run failed: {}", error));
+        }
+        let last_run = DateTime::parse_from_rfc3339(&self.last_run)
+            .map_err(|e| tr!("状态文件中的时间无效: {}", "Invalid time in state file: {}", e))?;
+        let age = (now - last_run.with_timezone(&Local)).to_std().unwrap_or_default();
+        if age > max_age {
+            return Err(tr!(
+                "最近一次执行在 {} 前，超过了 {}",
+                "Last run was {} ago, older than {}",
+                crate::duration::format_duration(Duration::from_secs(age.as_secs())),
+                crate::duration::format_duration(max_age)
+            ));
+        }
+        Ok(age)
+    }
+}
+
+/// 状态文件的内容；运行统计的字段位于顶层，与只保存运行统计的旧版状态文件兼容
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct StateFile {
+    #[serde(flatten)]
+    run: Option<RunStats>,
+    #[serde(default)]
+    last_ips: Option<DetectedIps>,
+    #[serde(default)]
+    records: Vec<PublishedRecord>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    failover: BTreeMap<String, FailoverState>,
+    #[serde(default, skip_serializing_if = "FailureCounts::is_empty")]
+    failure_counts: FailureCounts,
+}
+
+/// 使用 JSON 文件的状态存储，路径为空的部分不读写
+pub struct JsonStateStore {
+    state_file: Option<PathBuf>,
+    history_file: Option<PathBuf>,
+    /// 状态文件的读改写在同一个实例内串行进行
+    lock: Mutex<()>,
+}
+
+impl JsonStateStore {
+    pub fn new(state_file: Option<PathBuf>, history_file: Option<PathBuf>) -> Self {
+        Self { state_file, history_file, lock: Mutex::new(()) }
+    }
+
+    fn load(&self) -> io::Result<StateFile> {
+        let Some(path) = &self.state_file else {
+            return Ok(StateFile::default());
+        };
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(StateFile::default()),
+            Err(e) => return Err(with_path(path, e)),
+        };
+        serde_json::from_str(&content).map_err(|e| with_path(path, io::Error::new(io::ErrorKind::InvalidData, e)))
+    }
+
+    /// 读取状态文件、修改后整体写回
+    fn update(&self, change: impl FnOnce(&mut StateFile)) -> io::Result<()> {
+        let Some(path) = &self.state_file else {
+            return Ok(());
+        };
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        let mut state = self.load()?;
+        change(&mut state);
+        let content = serde_json::to_string_pretty(&state).map_err(io::Error::other)?;
+        write_atomic(path, (content + "\n").as_bytes()).map_err(|e| with_path(path, e))
+    }
+}
+
+impl StateStore for JsonStateStore {
+    fn last_ips(&self) -> io::Result<Option<DetectedIps>> {
+        Ok(self.load()?.last_ips)
+    }
+
+    fn save_last_ips(&self, ips: &DetectedIps) -> io::Result<()> {
+        self.update(|state| state.last_ips = Some(ips.clone()))
+    }
+
+    fn published(&self) -> io::Result<Vec<PublishedRecord>> {
+        Ok(self.load()?.records)
+    }
+
+    fn save_published(&self, record: &PublishedRecord) -> io::Result<()> {
+        self.update(|state| {
+            state.records.retain(|r| r.name != record.name || r.record_type != record.record_type);
+            state.records.push(record.clone());
+        })
+    }
+
+    fn failover(&self) -> io::Result<BTreeMap<String, FailoverState>> {
+        Ok(self.load()?.failover)
+    }
+
+    fn save_failover(&self, states: &BTreeMap<String, FailoverState>) -> io::Result<()> {
+        self.update(|state| state.failover = states.clone())
+    }
+
+    fn failure_counts(&self) -> io::Result<FailureCounts> {
+        Ok(self.load()?.failure_counts)
+    }
+
+    fn save_failure_counts(&self, counts: &FailureCounts) -> io::Result<()> {
+        self.update(|state| state.failure_counts = counts.clone())
+    }
+
+    fn append_history(&self, entry: &HistoryEntry) -> io::Result<()> {
+        match &self.history_file {
+            Some(path) => history::append(path, entry).map_err(|e| with_path(path, e)),
+            None => Ok(()),
+        }
+    }
+
+    fn history(&self) -> io::Result<Vec<HistoryEntry>> {
+        match &self.history_file {
+            Some(path) => history::read(path).map_err(|e| with_path(path, e)),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    fn run_stats(&self) -> io::Result<Option<RunStats>> {
+        Ok(self.load()?.run)
+    }
+
+    fn save_run_stats(&self, stats: &RunStats) -> io::Result<()> {
+        self.update(|state| state.run = Some(stats.clone()))
+    }
+}
+
+/// 原子地替换文件内容：写入同目录下的临时文件、刷到磁盘后重命名，所在目录不存在时创建
+///
+/// 替换已有文件时保留其权限。
+pub fn write_atomic(path: &Path, content: &[u8]) -> io::Result<()> {
+    write_replacing(path, content, false)
+}
+
+/// 与 [`write_atomic`] 相同，但新建的文件在 Unix 上只有所有者可读写，用于含有凭据的文件
+pub fn write_atomic_private(path: &Path, content: &[u8]) -> io::Result<()> {
+    write_replacing(path, content, true)
+}
+
+fn write_replacing(path: &Path, content: &[u8], private: bool) -> io::Result<()> {
+    let dir = path.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    std::fs::create_dir_all(dir)?;
+    let name = path.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+    let tmp = dir.join(format!(".{}.{}.tmp", name, std::process::id()));
+
+    let write = || -> io::Result<()> {
+        let mut options = OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        if private {
+            std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        }
+        let mut file = options.open(&tmp)?;
+        if let Ok(existing) = std::fs::metadata(path) {
+            file.set_permissions(existing.permissions())?;
+        }
+        file.write_all(content)?;
+        file.sync_all()?;
+        std::fs::rename(&tmp, path)
+    };
+    if let Err(e) = write() {
+        let _ = std::fs::remove_file(&tmp);
+        return Err(e);
+    }
+    // 重命名本身也要落盘，否则断电后目录中可能仍是旧文件
+    #[cfg(unix)]
+    if let Ok(dir) = std::fs::File::open(dir) {
+        let _ = dir.sync_all();
+    }
+    #[cfg(not(unix))]
+    let _ = private;
+    Ok(())
+}
+
+/// 在错误信息前加上文件路径
+fn with_path(path: &Path, error: io::Error) -> io::Error {
+    io::Error::new(error.kind(), format!("{}: {}", path.display(), error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_store_keeps_each_kind_of_state() {
+        let dir = std::env::temp_dir().join(format!("cloudflare_ddns_state_{}", std::process::id()));
+        let state_file = dir.join("state.json");
+        // 旧版状态文件只有运行统计
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(&state_file, r#"{ "pid": 1, "last_run": "2025-01-01T00:00:00+00:00", "last_success": null, "error": null }"#).unwrap();
+        let store = JsonStateStore::new(Some(state_file), Some(dir.join("history.jsonl")));
+
+        let ips = DetectedIps { v4: Some("1.2.3.4".to_string()), ..Default::default() };
+        store.save_last_ips(&ips).unwrap();
+        store.save_published(&PublishedRecord::new("home.example.com", "A", "example.com", "5.6.7.8")).unwrap();
+        store.save_published(&PublishedRecord::new("home.example.com", "A", "example.com", "1.2.3.4")).unwrap();
+        store.append_history(&HistoryEntry::new("home.example.com", "example.com", "A", Some("5.6.7.8"), "1.2.3.4")).unwrap();
+
+        let run = store.run_stats().unwrap();
+        let last_ips = store.last_ips().unwrap();
+        let published = store.published().unwrap();
+        let history = store.history().unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(run.unwrap().pid, 1);
+        assert_eq!(last_ips, Some(ips));
+        assert_eq!(published.len(), 1);
+        assert_eq!(published[0].content, "1.2.3.4");
+        assert_eq!(history.len(), 1);
+    }
+
+    #[test]
+    fn atomic_write_replaces_content_and_keeps_permissions() {
+        let dir = std::env::temp_dir().join(format!("cloudflare_ddns_atomic_{}", std::process::id()));
+        let path = dir.join("nested").join("state.json");
+        write_atomic(&path, b"old").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o640)).unwrap();
+        }
+        write_atomic(&path, b"new").unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let entries = std::fs::read_dir(path.parent().unwrap()).unwrap().count();
+        #[cfg(unix)]
+        let mode = std::os::unix::fs::PermissionsExt::mode(&std::fs::metadata(&path).unwrap().permissions()) & 0o777;
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(content, "new");
+        // 临时文件已被重命名，不会残留
+        assert_eq!(entries, 1);
+        #[cfg(unix)]
+        assert_eq!(mode, 0o640);
+    }
+
+    #[test]
+    fn stale_or_failed_run_is_unhealthy() {
+        let now = Local::now();
+        let ok = RunStats::new(now - chrono::Duration::seconds(30), Some(now), None);
+        assert!(ok.check(Duration::from_secs(60), now).is_ok());
+        assert!(ok.check(Duration::from_secs(10), now).is_err());
+
+        let failed = RunStats::new(now, None, Some("timeout".to_string()));
+        assert!(failed.check(Duration::from_secs(60), now).is_err());
+    }
+}
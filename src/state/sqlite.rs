@@ -0,0 +1,287 @@
+// SQLite 状态存储：状态与历史写入同一个数据库，每次写入都是一个事务，进程崩溃不会留下写了一半的文件
+//
+// 表结构由 `MIGRATIONS` 按顺序建立，已执行到的版本记录在 `PRAGMA user_version` 中；修改表结构时只追加新的迁移。
+use std::collections::BTreeMap;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use rusqlite::{Connection, OptionalExtension, params};
+
+use super::{PublishedRecord, RunStats, StateStore, with_path};
+use crate::failover::FailoverState;
+use crate::history::{HistoryEntry, HistoryFilter};
+use crate::i18n::tr;
+use crate::ip_utils::DetectedIps;
+use crate::notify::FailureCounts;
+
+/// 依次执行的表结构迁移，第 n 项把数据库从版本 n 升级到 n + 1
+const MIGRATIONS: &[&str] = &[
+    "CREATE TABLE history (
+        id INTEGER PRIMARY KEY,
+        timestamp TEXT NOT NULL,
+        unix_time INTEGER NOT NULL,
+        record TEXT NOT NULL,
+        zone TEXT NOT NULL,
+        type TEXT NOT NULL,
+        old_ip TEXT,
+        new_ip TEXT NOT NULL
+    );
+    CREATE INDEX history_record_time ON history (record, unix_time);
+    CREATE INDEX history_time ON history (unix_time);
+    CREATE TABLE published (
+        name TEXT NOT NULL,
+        type TEXT NOT NULL,
+        zone TEXT NOT NULL,
+        content TEXT NOT NULL,
+        updated_at TEXT NOT NULL,
+        PRIMARY KEY (name, type)
+    );
+    CREATE TABLE kv (key TEXT PRIMARY KEY, value TEXT NOT NULL);",
+];
+
+/// 另一个进程（如 `history` 子命令）正在写入时的等待时间
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 使用 SQLite 数据库的状态存储，首次访问时打开数据库并执行迁移
+pub struct SqliteStateStore {
+    path: PathBuf,
+    connection: Mutex<Option<Connection>>,
+}
+
+impl SqliteStateStore {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path, connection: Mutex::new(None) }
+    }
+
+    fn with_connection<T>(&self, f: impl FnOnce(&mut Connection) -> rusqlite::Result<T>) -> io::Result<T> {
+        let mut connection = self.connection.lock().unwrap_or_else(|e| e.into_inner());
+        if connection.is_none() {
+            *connection = Some(self.connect()?);
+        }
+        let connection = connection.as_mut().expect("connection was just opened");
+        f(connection).map_err(|e| with_path(&self.path, io::Error::other(e)))
+    }
+
+    fn connect(&self) -> io::Result<Connection> {
+        if let Some(dir) = self.path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir).map_err(|e| with_path(dir, e))?;
+        }
+        let mut connection = Connection::open(&self.path)
+            .and_then(|connection| {
+                connection.busy_timeout(BUSY_TIMEOUT)?;
+                connection.pragma_update(None, "journal_mode", "WAL")?;
+                connection.pragma_update(None, "synchronous", "NORMAL")?;
+                Ok(connection)
+            })
+            .map_err(|e| with_path(&self.path, io::Error::other(e)))?;
+        migrate(&mut connection).map_err(|e| with_path(&self.path, e))?;
+        Ok(connection)
+    }
+
+    fn get<T: serde::de::DeserializeOwned>(&self, key: &str) -> io::Result<Option<T>> {
+        let value: Option<String> = self.with_connection(|connection| {
+            connection.query_row("SELECT value FROM kv WHERE key = ?1", [key], |row| row.get(0)).optional()
+        })?;
+        value
+            .map(|value| serde_json::from_str(&value))
+            .transpose()
+            .map_err(|e| with_path(&self.path, io::Error::new(io::ErrorKind::InvalidData, e)))
+    }
+
+    fn set<T: serde::Serialize>(&self, key: &str, value: &T) -> io::Result<()> {
+        let value = serde_json::to_string(value).map_err(io::Error::other)?;
+        self.with_connection(|connection| {
+            connection.execute("INSERT OR REPLACE INTO kv (key, value) VALUES (?1, ?2)", params![key, value]).map(drop)
+        })
+    }
+
+    fn select_history(&self, filter: &HistoryFilter) -> io::Result<Vec<HistoryEntry>> {
+        // 通配符与 overrides::matches_any 一致，不区分大小写
+        let patterns: Vec<String> = filter.records.iter().map(|pattern| pattern.to_ascii_lowercase()).collect();
+        let cutoff = filter.cutoff().map_or(i64::MIN, |cutoff| cutoff.timestamp());
+        let limit = filter.limit.map_or(-1, |limit| limit as i64);
+
+        let mut sql = String::from("SELECT timestamp, record, zone, type, old_ip, new_ip FROM history WHERE unix_time >= ?1");
+        if !patterns.is_empty() {
+            let placeholders: Vec<String> = (0..patterns.len()).map(|i| format!("lower(record) GLOB ?{}", i + 3)).collect();
+            sql.push_str(&format!(" AND ({})", placeholders.join(" OR ")));
+        }
+        // 先取最近的 limit 条，再按时间先后返回
+        sql.push_str(" ORDER BY id DESC LIMIT ?2");
+
+        let mut entries = self.with_connection(|connection| {
+            let mut statement = connection.prepare(&sql)?;
+            let mut values: Vec<&dyn rusqlite::ToSql> = vec![&cutoff, &limit];
+            values.extend(patterns.iter().map(|pattern| pattern as &dyn rusqlite::ToSql));
+            statement
+                .query_map(values.as_slice(), |row| {
+                    Ok(HistoryEntry {
+                        timestamp: row.get(0)?,
+                        record: row.get(1)?,
+                        zone: row.get(2)?,
+                        record_type: row.get(3)?,
+                        old_ip: row.get(4)?,
+                        new_ip: row.get(5)?,
+                    })
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()
+        })?;
+        entries.reverse();
+        Ok(entries)
+    }
+}
+
+/// 执行尚未执行的迁移；数据库由更新的版本创建时拒绝打开，避免写坏无法识别的表
+fn migrate(connection: &mut Connection) -> io::Result<()> {
+    let to_io = |e: rusqlite::Error| io::Error::other(e);
+    let version: usize = connection.pragma_query_value(None, "user_version", |row| row.get(0)).map_err(to_io)?;
+    if version > MIGRATIONS.len() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            tr!(
+                "数据库版本 {} 高于当前程序支持的版本 {}",
+                "Database schema version {} is newer than the supported version {}",
+                version,
+                MIGRATIONS.len()
+            ),
+        ));
+    }
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+        let transaction = connection.transaction().map_err(to_io)?;
+        transaction.execute_batch(migration).map_err(to_io)?;
+        transaction.pragma_update(None, "user_version", index + 1).map_err(to_io)?;
+        transaction.commit().map_err(to_io)?;
+    }
+    Ok(())
+}
+
+impl StateStore for SqliteStateStore {
+    fn last_ips(&self) -> io::Result<Option<DetectedIps>> {
+        self.get("last_ips")
+    }
+
+    fn save_last_ips(&self, ips: &DetectedIps) -> io::Result<()> {
+        self.set("last_ips", ips)
+    }
+
+    fn published(&self) -> io::Result<Vec<PublishedRecord>> {
+        self.with_connection(|connection| {
+            let mut statement = connection.prepare("SELECT name, type, zone, content, updated_at FROM published ORDER BY rowid")?;
+            statement
+                .query_map([], |row| {
+                    Ok(PublishedRecord {
+                        name: row.get(0)?,
+                        record_type: row.get(1)?,
+                        zone: row.get(2)?,
+                        content: row.get(3)?,
+                        updated_at: row.get(4)?,
+                    })
+                })?
+                .collect()
+        })
+    }
+
+    fn save_published(&self, record: &PublishedRecord) -> io::Result<()> {
+        self.with_connection(|connection| {
+            connection
+                .execute(
+                    "INSERT OR REPLACE INTO published (name, type, zone, content, updated_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+                    params![record.name, record.record_type, record.zone, record.content, record.updated_at],
+                )
+                .map(drop)
+        })
+    }
+
+    fn failover(&self) -> io::Result<BTreeMap<String, FailoverState>> {
+        Ok(self.get("failover")?.unwrap_or_default())
+    }
+
+    fn save_failover(&self, states: &BTreeMap<String, FailoverState>) -> io::Result<()> {
+        self.set("failover", states)
+    }
+
+    fn failure_counts(&self) -> io::Result<FailureCounts> {
+        Ok(self.get("failure_counts")?.unwrap_or_default())
+    }
+
+    fn save_failure_counts(&self, counts: &FailureCounts) -> io::Result<()> {
+        self.set("failure_counts", counts)
+    }
+
+    fn append_history(&self, entry: &HistoryEntry) -> io::Result<()> {
+        let unix_time = entry.time().map_or(0, |time| time.timestamp());
+        self.with_connection(|connection| {
+            connection
+                .execute(
+                    "INSERT INTO history (timestamp, unix_time, record, zone, type, old_ip, new_ip) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                    params![entry.timestamp, unix_time, entry.record, entry.zone, entry.record_type, entry.old_ip, entry.new_ip],
+                )
+                .map(drop)
+        })
+    }
+
+    fn history(&self) -> io::Result<Vec<HistoryEntry>> {
+        self.select_history(&HistoryFilter::default())
+    }
+
+    fn query_history(&self, filter: &HistoryFilter) -> io::Result<Vec<HistoryEntry>> {
+        self.select_history(filter)
+    }
+
+    fn run_stats(&self) -> io::Result<Option<RunStats>> {
+        self.get("run_stats")
+    }
+
+    fn save_run_stats(&self, stats: &RunStats) -> io::Result<()> {
+        self.set("run_stats", stats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sqlite_store_migrates_and_queries_history() {
+        let dir = std::env::temp_dir().join(format!("cloudflare_ddns_sqlite_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join("state.db");
+
+        let store = SqliteStateStore::new(path.clone());
+        store.save_last_ips(&DetectedIps { v4: Some("1.2.3.4".to_string()), ..Default::default() }).unwrap();
+        store.save_published(&PublishedRecord::new("home.example.com", "A", "example.com", "5.6.7.8")).unwrap();
+        store.save_published(&PublishedRecord::new("home.example.com", "A", "example.com", "1.2.3.4")).unwrap();
+        for (record, ip) in [("home.example.com", "1.1.1.1"), ("nas.example.com", "2.2.2.2"), ("Home.example.com", "3.3.3.3")] {
+            store.append_history(&HistoryEntry::new(record, "example.com", "A", None, ip)).unwrap();
+        }
+        let mut old = HistoryEntry::new("home.example.com", "example.com", "A", None, "0.0.0.0");
+        old.timestamp = "2020-01-01T00:00:00+00:00".to_string();
+        store.append_history(&old).unwrap();
+        drop(store);
+
+        // 重新打开时不再执行已执行过的迁移
+        let store = SqliteStateStore::new(path);
+        let filter = HistoryFilter {
+            records: vec!["home.*".to_string()],
+            since: Some(Duration::from_secs(86400)),
+            limit: Some(1),
+        };
+        let recent = store.query_history(&filter).unwrap();
+        let all = store.history().unwrap();
+        let published = store.published().unwrap();
+        let last_ips = store.last_ips().unwrap();
+        let run = store.run_stats().unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].new_ip, "3.3.3.3");
+        assert_eq!(all.len(), 4);
+        assert_eq!(all[0].new_ip, "1.1.1.1");
+        assert_eq!(published.len(), 1);
+        assert_eq!(published[0].content, "1.2.3.4");
+        assert_eq!(last_ips.unwrap().v4.as_deref(), Some("1.2.3.4"));
+        assert!(run.is_none());
+    }
+}
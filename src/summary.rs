@@ -0,0 +1,366 @@
+// 单次运行的结构化结果，供 `--output json` 输出给脚本与 CI 使用
+use serde::Serialize;
+
+use crate::config::Config;
+use crate::error::{Error, ErrorCategory};
+use crate::i18n::tr;
+use crate::ip_utils::{DetectedIps, DetectionTimes};
+use crate::update::{Plan, RecordChange, RecordOutcome, RecordResult, RecordTiming};
+
+/// 结果的输出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// 只输出日志
+    #[default]
+    Text,
+    /// 运行结束后向标准输出打印一个 JSON 对象，日志改为输出到标准错误
+    Json,
+}
+
+/// 有记录被更新或新建时的退出码，与失败的退出码区分开
+pub const EXIT_UPDATED: u8 = 10;
+
+/// 单次运行成功、但有记录因冷却时间未更新（且没有记录被更新）时的退出码
+pub const EXIT_HELD: u8 = 11;
+
+/// 单次运行超过 `--timeout` 被中止时的退出码
+pub const EXIT_TIMEOUT: u8 = 9;
+
+/// 单次运行失败发生的阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureKind {
+    /// 配置文件缺失、无法解析或内容无效
+    Config,
+    /// 无法检测公网 IP
+    Detection,
+    /// API 请求或记录更新失败
+    Api,
+    /// 超过 `--timeout` 指定的总时长被中止
+    Timeout,
+}
+
+/// 根据失败阶段与错误类别得出退出码，配置错误之外优先按错误类别区分
+pub fn exit_code(kind: FailureKind, category: ErrorCategory) -> u8 {
+    match (kind, category) {
+        (FailureKind::Config, _) => 3,
+        (FailureKind::Timeout, _) => EXIT_TIMEOUT,
+        (_, ErrorCategory::Auth) => 5,
+        (_, ErrorCategory::Network) => 7,
+        (_, ErrorCategory::RateLimited) => 8,
+        (FailureKind::Detection, _) => 4,
+        (FailureKind::Api, _) => 6,
+    }
+}
+
+/// 单次运行的所有退出码及其含义，供 man 页使用
+pub fn exit_codes() -> Vec<(u8, String)> {
+    vec![
+        (0, tr!("成功，所有记录均无变化（或 --check-only）", "Success, no record changed (or --check-only)")),
+        (1, tr!("其他错误", "Other error")),
+        (2, tr!("命令行参数错误", "Invalid command-line arguments")),
+        (3, tr!("配置错误（文件缺失、无法解析、认证方式或 IP 版本无效）", "Configuration error (file missing or unparsable, invalid auth type or IP version)")),
+        (4, tr!("无法检测公网 IP", "Public IP detection failed")),
+        (5, tr!("API 凭据无效或权限不足", "API credentials are invalid or lack permission")),
+        (6, tr!("API 请求或记录更新失败", "API request or record update failed")),
+        (7, tr!("网络不可达（连接失败或超时）", "Network unreachable (connection failed or timed out)")),
+        (8, tr!("请求被限流", "Rate limited")),
+        (EXIT_TIMEOUT, tr!("超过 --timeout 指定的总时长，运行被中止", "Aborted after exceeding the total time allowed by --timeout")),
+        (EXIT_UPDATED, tr!("成功，有记录被更新或新建（--dry-run 时为有记录将被修改）", "Success, records were updated or created (with --dry-run: would be changed)")),
+        (EXIT_HELD, tr!("成功，有记录需要更新但在冷却时间内，本次未更新", "Success, but records that need updating are within their cooldown and were not updated")),
+    ]
+}
+
+/// 带阶段与类别的运行错误
+#[derive(Debug)]
+pub struct RunError {
+    pub kind: FailureKind,
+    pub category: ErrorCategory,
+    pub message: String,
+}
+
+impl RunError {
+    pub fn new(kind: FailureKind, error: impl std::fmt::Display) -> Self {
+        Self { kind, category: ErrorCategory::Other, message: error.to_string() }
+    }
+
+    pub fn exit_code(&self) -> u8 {
+        exit_code(self.kind, self.category)
+    }
+}
+
+impl std::fmt::Display for RunError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for RunError {}
+
+/// 按错误的阶段得出失败阶段，通知与调度错误归为 API 失败
+impl From<Error> for RunError {
+    fn from(error: Error) -> Self {
+        let kind = match &error {
+            Error::Config { .. } => FailureKind::Config,
+            Error::Detection { .. } => FailureKind::Detection,
+            Error::Provider { .. } | Error::Notification { .. } | Error::Scheduler { .. } => FailureKind::Api,
+        };
+        Self { kind, category: error.category(), message: error.to_string() }
+    }
+}
+
+/// 单次运行的结果
+#[derive(Debug, Serialize)]
+pub struct RunSummary {
+    pub success: bool,
+    /// 未能处理任何记录的错误（如读取配置、检测 IP、连接 API 失败）
+    pub error: Option<String>,
+    pub error_category: Option<ErrorCategory>,
+    pub ipv4: Option<String>,
+    pub ipv6: Option<String>,
+    pub records: Vec<RecordSummary>,
+}
+
+/// 单条记录的结果
+#[derive(Debug, Serialize)]
+pub struct RecordSummary {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub record_type: String,
+    /// 本次检测到的、记录应当指向的 IP
+    pub detected_ip: Option<String>,
+    /// 更新前的记录内容，新建或失败时为空
+    pub previous: Option<String>,
+    pub action: RecordAction,
+    pub error: Option<String>,
+    pub error_category: Option<ErrorCategory>,
+    /// 各阶段耗时，跳过的记录为空
+    pub timing: Option<TimingSummary>,
+}
+
+/// 单条记录各阶段的耗时（毫秒）
+#[derive(Debug, Serialize)]
+pub struct TimingSummary {
+    pub detection_ms: u64,
+    pub api_ms: u64,
+    pub total_ms: u64,
+}
+
+impl From<RecordTiming> for TimingSummary {
+    fn from(timing: RecordTiming) -> Self {
+        Self {
+            detection_ms: timing.detection.as_millis() as u64,
+            api_ms: timing.api.as_millis() as u64,
+            total_ms: timing.total().as_millis() as u64,
+        }
+    }
+}
+
+/// 对记录执行的操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordAction {
+    Unchanged,
+    Updated,
+    Created,
+    Failed,
+    /// 未被选中（如 `--record` 筛选）而未处理
+    Skipped,
+    /// 仅检查模式，只检测了 IP
+    Checked,
+    /// 试运行，记录将被更新
+    WouldUpdate,
+    /// 试运行，记录将被新建
+    WouldCreate,
+    /// 记录需要更新，但在发布后的冷却时间内，本次未更新
+    Held,
+}
+
+impl RunSummary {
+    /// 根据检测到的 IP 与每条记录的结果生成汇总，未出现在 `results` 中的记录标记为跳过
+    pub fn new(config: &Config, ips: &DetectedIps, results: &[RecordResult]) -> Self {
+        let records = config.records().map(|record| {
+            let detected_ip = ips.for_record(record);
+            let result = results.iter().find(|result| result.name == record.name && result.record_type == record.r#type);
+            let (action, previous, error) = match result.map(|result| &result.outcome) {
+                None => (RecordAction::Skipped, None, None),
+                Some(Ok(RecordOutcome::Unchanged)) => (RecordAction::Unchanged, detected_ip.clone(), None),
+                Some(Ok(RecordOutcome::Updated { old_ip, .. })) => (RecordAction::Updated, Some(old_ip.clone()), None),
+                Some(Ok(RecordOutcome::Created { .. })) => (RecordAction::Created, None, None),
+                Some(Ok(RecordOutcome::Held { .. })) => (RecordAction::Held, None, None),
+                Some(Err(e)) => (RecordAction::Failed, None, Some(e)),
+            };
+            RecordSummary {
+                name: record.name.clone(),
+                record_type: record.r#type.clone(),
+                detected_ip,
+                previous,
+                action,
+                error: error.map(|e| e.message.clone()),
+                error_category: error.map(|e| e.category),
+                timing: result.map(|result| result.timing.into()),
+            }
+        }).collect::<Vec<_>>();
+
+        Self {
+            success: records.iter().all(|r| matches!(r.action, RecordAction::Unchanged | RecordAction::Updated | RecordAction::Created)),
+            error: None,
+            error_category: None,
+            ipv4: ips.v4.clone(),
+            ipv6: ips.v6.clone(),
+            records,
+        }
+    }
+
+    /// 试运行的汇总，每条记录给出将要执行的变更，计划中跳过或未出现的记录标记为跳过
+    pub fn planned(config: &Config, ips: &DetectedIps, plan: &Plan) -> Self {
+        let records = config.records().map(|record| {
+            let detected_ip = ips.for_record(record);
+            let planned = plan.get(&record.name, &record.r#type);
+            let (action, previous, error) = match planned.map(|planned| &planned.change) {
+                None | Some(Ok(RecordChange::Skip)) => (RecordAction::Skipped, None, None),
+                Some(Ok(RecordChange::Noop)) => (RecordAction::Unchanged, detected_ip.clone(), None),
+                Some(Ok(RecordChange::Update { old_ip, .. })) => (RecordAction::WouldUpdate, Some(old_ip.clone()), None),
+                Some(Ok(RecordChange::Create | RecordChange::Set { previous: None, .. })) => (RecordAction::WouldCreate, None, None),
+                Some(Ok(RecordChange::Set { previous: Some(previous), .. })) => (RecordAction::WouldUpdate, Some(previous.clone()), None),
+                Some(Ok(RecordChange::Hold { .. })) => (RecordAction::Held, None, None),
+                Some(Err(e)) => (RecordAction::Failed, None, Some(e)),
+            };
+            RecordSummary {
+                name: record.name.clone(),
+                record_type: record.r#type.clone(),
+                detected_ip,
+                previous,
+                action,
+                error: error.map(|e| e.message.clone()),
+                error_category: error.map(|e| e.category),
+                timing: planned.map(|planned| planned.timing.into()),
+            }
+        }).collect::<Vec<_>>();
+
+        Self {
+            success: records.iter().all(|r| r.error.is_none() && r.action != RecordAction::Skipped),
+            error: None,
+            error_category: None,
+            ipv4: ips.v4.clone(),
+            ipv6: ips.v6.clone(),
+            records,
+        }
+    }
+
+    /// 仅检查模式的汇总，每条记录只包含检测到的 IP 与检测耗时
+    pub fn checked(config: &Config, ips: &DetectedIps, times: &DetectionTimes) -> Self {
+        Self {
+            success: true,
+            error: None,
+            error_category: None,
+            ipv4: ips.v4.clone(),
+            ipv6: ips.v6.clone(),
+            records: config.records().map(|record| RecordSummary {
+                name: record.name.clone(),
+                record_type: record.r#type.clone(),
+                detected_ip: ips.for_record(record),
+                previous: None,
+                action: RecordAction::Checked,
+                error: None,
+                error_category: None,
+                timing: Some(RecordTiming {
+                    detection: times.for_record(record).unwrap_or_default(),
+                    api: Default::default(),
+                }.into()),
+            }).collect(),
+        }
+    }
+
+    /// 在处理任何记录之前就失败时的汇总
+    pub fn error(error: &RunError) -> Self {
+        Self {
+            success: false,
+            error: Some(error.message.clone()),
+            error_category: Some(error.category),
+            ipv4: None,
+            ipv6: None,
+            records: Vec::new(),
+        }
+    }
+
+    /// 根据所有记录的结果得出退出码：有失败时（即使其余记录成功）按第一条失败记录的错误类别，有（或试运行时将有）更新或新建为 [`EXIT_UPDATED`]，
+    /// 否则有记录因冷却时间未更新时为 [`EXIT_HELD`]，都没有时为 0
+    pub fn exit_code(&self) -> u8 {
+        if let Some(category) = self.records.iter().find_map(|r| r.error_category) {
+            exit_code(FailureKind::Api, category)
+        } else if self.records.iter().any(|r| matches!(
+            r.action,
+            RecordAction::Updated | RecordAction::Created | RecordAction::WouldUpdate | RecordAction::WouldCreate
+        )) {
+            EXIT_UPDATED
+        } else if self.records.iter().any(|r| r.action == RecordAction::Held) {
+            EXIT_HELD
+        } else {
+            0
+        }
+    }
+
+    /// 以可读的差异格式把试运行的计划打印到标准输出
+    pub fn print_plan(&self) {
+        let changes = self.print_changes();
+        println!("{}", tr!("试运行：{} 条记录将被修改，未写入任何变更", "Dry run: {} record(s) would change, nothing was written", changes));
+    }
+
+    /// 以差异格式逐条打印计划中的变更，返回将被修改的记录数
+    ///
+    /// `~` 为更新，`+` 为新建，`=` 为无变化，`*` 为冷却时间内暂不更新，`!` 为失败，`-` 为跳过。
+    pub fn print_changes(&self) -> usize {
+        for record in &self.records {
+            let ip = record.detected_ip.as_deref().unwrap_or("-");
+            match record.action {
+                RecordAction::WouldUpdate => println!(
+                    "~ {} {} {} -> {}",
+                    record.name, record.record_type, record.previous.as_deref().unwrap_or("-"), ip
+                ),
+                RecordAction::WouldCreate => println!("+ {} {} {}", record.name, record.record_type, ip),
+                RecordAction::Failed => println!(
+                    "! {} {} {}",
+                    record.name, record.record_type, record.error.as_deref().unwrap_or("-")
+                ),
+                RecordAction::Skipped => println!("- {} {}", record.name, record.record_type),
+                RecordAction::Held => println!("* {} {} {}", record.name, record.record_type, ip),
+                _ => println!("= {} {} {}", record.name, record.record_type, ip),
+            }
+        }
+        self.records.iter()
+            .filter(|r| matches!(r.action, RecordAction::WouldUpdate | RecordAction::WouldCreate))
+            .count()
+    }
+
+    /// 为每条失败的记录输出一条错误日志，有失败时再输出一行汇总，列出失败与成功的记录数
+    pub fn log_failures(&self) {
+        let failed: Vec<&RecordSummary> = self.records.iter().filter(|r| r.error.is_some()).collect();
+        for record in &failed {
+            tracing::error!(
+                record = %record.name,
+                error = %record.error.as_deref().unwrap_or_default(),
+                category = record.error_category.map_or("other", |c| c.as_str()),
+                "{}", tr!("DNS 记录处理失败", "Failed to process DNS record")
+            );
+        }
+        if failed.is_empty() {
+            return;
+        }
+        let processed = self.records.iter().filter(|r| r.action != RecordAction::Skipped).count();
+        let names: Vec<&str> = failed.iter().map(|r| r.name.as_str()).collect();
+        tracing::warn!(
+            failed = failed.len(),
+            succeeded = processed - failed.len(),
+            records = %names.join(", "),
+            "{}", tr!("部分记录处理失败，其余记录已正常处理", "Some records failed, the others were processed normally")
+        );
+    }
+
+    /// 以 JSON 打印到标准输出
+    pub fn print(&self) {
+        match serde_json::to_string_pretty(self) {
+            Ok(json) => println!("{}", json),
+            Err(e) => tracing::error!(error = %e, "{}", tr!("无法序列化运行结果", "Failed to serialize run summary")),
+        }
+    }
+}
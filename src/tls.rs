@@ -0,0 +1,41 @@
+// HTTP 客户端的 TLS 设置：默认使用 rustls 与系统证书库，启用 native-tls 功能时使用系统的 TLS 实现，
+// 启用 bundled-roots 功能时使用内置的 Mozilla 根证书
+
+#[cfg(not(any(feature = "rustls", feature = "native-tls")))]
+compile_error!("at least one TLS backend feature must be enabled: rustls (default) or native-tls");
+
+/// 所有 HTTP 请求共用的客户端构造器
+#[cfg(not(any(feature = "bundled-roots", feature = "native-tls")))]
+pub fn client_builder() -> reqwest::ClientBuilder {
+    reqwest::Client::builder()
+}
+
+/// 所有 HTTP 请求共用的客户端构造器，使用系统的 TLS 实现
+#[cfg(all(feature = "native-tls", not(feature = "bundled-roots")))]
+pub fn client_builder() -> reqwest::ClientBuilder {
+    reqwest::Client::builder().tls_backend_native()
+}
+
+/// 所有 HTTP 请求共用的客户端构造器，证书只使用编译进程序的根证书
+#[cfg(feature = "bundled-roots")]
+pub fn client_builder() -> reqwest::ClientBuilder {
+    reqwest::Client::builder().tls_backend_preconfigured(bundled_config())
+}
+
+#[cfg(feature = "bundled-roots")]
+fn bundled_config() -> rustls::ClientConfig {
+    let mut roots = rustls::RootCertStore::empty();
+    roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+    let provider = std::sync::Arc::new(rustls::crypto::aws_lc_rs::default_provider());
+    let mut config = rustls::ClientConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .expect("default TLS protocol versions are supported")
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    // 预配置的 TLS 不会自动协商 HTTP/2
+    if cfg!(feature = "http2") {
+        config.alpn_protocols.push(b"h2".to_vec());
+    }
+    config.alpn_protocols.push(b"http/1.1".to_vec());
+    config
+}
@@ -0,0 +1,1424 @@
+// 更新流程：加载配置、连接各区域的 DNS 服务商、核对并更新每条记录
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+use tracing::{debug, error, info, warn};
+
+use crate::cloudflare;
+use crate::config::{self, Config, StateBackend};
+use crate::error::{self, Error, Result};
+use crate::i18n::tr;
+use crate::provider::{self, DnsProvider};
+use crate::{encryption, failover, history, ip_utils, logging, notify, overrides, record_set, state, summary};
+
+/// 加载配置、检测 IP 并核对所有记录，返回本次运行的结构化结果
+///
+/// 依次执行检测、计划、执行三个阶段：`check_only` 时只检测 IP，`dry_run` 时只生成计划，均不写入记录。
+pub async fn run_once(config_path: &str, force: bool, check_only: bool, dry_run: bool) -> Result<summary::RunSummary, summary::RunError> {
+    // 从配置文件加载配置
+    let config = load_config(config_path)?;
+    run(&config, force, check_only, dry_run).await
+}
+
+/// 与 [`run_once`] 相同，但使用已有的配置，不读取配置文件
+pub async fn run(config: &Config, force: bool, check_only: bool, dry_run: bool) -> Result<summary::RunSummary, summary::RunError> {
+    // 在 check_only 模式下，我们只获取外部 IP，不进行 API 调用
+    if check_only {
+        info!("{}", tr!("仅检查模式 - 正在获取配置中的记录的外部 IP 地址", "Check-only mode - fetching external IP addresses for configured records"));
+        let (ips, times) = ip_utils::detect_for(config).await?;
+
+        for record_config in config.records() {
+            let current_ip = ips.for_record(record_config).unwrap_or_else(|| "-".to_string());
+            info!(record = %record_config.name, ip_version = %record_config.ip_version, ip = %current_ip, "{}", tr!("外部 IP 地址", "External IP address"));
+        }
+
+        info!("{}", tr!("仅检查模式完成 - 未更新任何 DNS 记录", "Check-only mode finished - no DNS records were updated"));
+        return Ok(summary::RunSummary::checked(config, &ips, &times));
+    }
+
+    let (ips, times) = ip_utils::detect_for(config).await?;
+    let mut pipeline = Pipeline::new(config);
+    let plan = pipeline.plan(&ips, &times, force, None).await?;
+    if dry_run {
+        return Ok(summary::RunSummary::planned(config, &ips, &plan));
+    }
+    let results = apply_once(&mut pipeline, &plan).await?;
+    log_heartbeat(&ips, &results);
+    Ok(summary::RunSummary::new(config, &ips, &results))
+}
+
+/// 单条记录的处理结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecordOutcome {
+    /// 记录内容与当前 IP 一致，无需更新
+    Unchanged,
+    /// 已将记录从旧 IP 更新为新 IP
+    Updated { old_ip: String, new_ip: String },
+    /// 记录不存在，已新建
+    Created { new_ip: String },
+    /// 记录在发布后的冷却时间内，本次未更新；`until` 为冷却结束的时间（RFC 3339）
+    Held { until: String },
+}
+
+impl RecordOutcome {
+    /// 记录处于冷却时间时距冷却结束的时间，其余结果为 `None`
+    pub fn held_for(&self) -> Option<Duration> {
+        let RecordOutcome::Held { until } = self else { return None };
+        let until = chrono::DateTime::parse_from_rfc3339(until).ok()?;
+        Some((until.with_timezone(&chrono::Local) - chrono::Local::now()).to_std().unwrap_or_default())
+    }
+}
+
+/// 使用已检测到的 IP 核对并更新配置中的所有 DNS 记录，单条记录失败不影响其余记录，失败记在该记录的结果中
+pub async fn reconcile(
+    config: &Config,
+    ips: &ip_utils::DetectedIps,
+    times: &ip_utils::DetectionTimes,
+    force: bool,
+) -> Result<Vec<RecordResult>> {
+    let mut pipeline = Pipeline::new(config);
+    let plan = pipeline.plan(ips, times, force, None).await?;
+    apply_once(&mut pipeline, &plan).await
+}
+
+/// 单次运行的执行阶段：按计划写入所有记录，退出前尽量把发送失败的通知重试完
+///
+/// 连续失败次数等通知状态从状态存储读取、执行后写回，失败阈值与恢复通知跨多次单次运行生效。
+pub async fn apply_once(pipeline: &mut Pipeline<'_>, plan: &Plan) -> Result<Vec<RecordResult>> {
+    let config = pipeline.config;
+    let notifiers = notify::Notifiers::from_config(&config.notifications, config.schedule.timeout());
+    let store = state::open(config);
+    let mut failure_counts = store.failure_counts().unwrap_or_else(|e| {
+        warn!(error = %e, "{}", tr!("无法读取通知状态，从零开始计数", "Failed to read notification state, counting from zero"));
+        notify::FailureCounts::default()
+    });
+    let results = pipeline.apply(plan, &notifiers, &mut failure_counts).await;
+    if let Err(e) = store.save_failure_counts(&failure_counts) {
+        warn!(error = %e, "{}", tr!("无法保存通知状态", "Failed to save notification state"));
+    }
+    notifiers.flush(config.notifications.retry.flush_timeout()).await;
+    results
+}
+
+/// 每次执行结束后输出一行汇总，安静模式下也会输出，用于确认程序仍在运行
+pub fn log_heartbeat(ips: &ip_utils::DetectedIps, results: &[RecordResult]) {
+    let updated = results.iter()
+        .filter(|result| matches!(result.outcome, Ok(RecordOutcome::Updated { .. } | RecordOutcome::Created { .. })))
+        .count();
+    let failed = results.iter().filter(|result| result.outcome.is_err()).count();
+    info!(
+        target: logging::HEARTBEAT_TARGET,
+        checked = results.len(),
+        updated,
+        failed,
+        ipv4 = %ips.v4.as_deref().unwrap_or("-"),
+        ipv6 = %ips.v6.as_deref().unwrap_or("-"),
+        "{}", tr!("本次执行完成", "Cycle complete")
+    );
+}
+
+/// 试运行：只读取现有记录，计算每条记录将要执行的变更
+pub async fn plan(
+    config: &Config,
+    ips: &ip_utils::DetectedIps,
+    times: &ip_utils::DetectionTimes,
+    force: bool,
+) -> Result<Plan> {
+    Pipeline::new(config).plan(ips, times, force, None).await
+}
+
+/// 计划阶段的结果：配置中每条记录将要执行的变更，按区域与记录的配置顺序排列
+#[derive(Debug, Default)]
+pub struct Plan {
+    pub records: Vec<PlannedRecord>,
+    /// 故障转移检查的结果，执行时保存其中的健康状态
+    pub failover: failover::Targets,
+}
+
+impl Plan {
+    /// 是否有记录将被更新或新建
+    pub fn has_changes(&self) -> bool {
+        self.records.iter().any(|record| matches!(record.change, Ok(RecordChange::Update { .. } | RecordChange::Create | RecordChange::Set { .. })))
+    }
+
+    pub fn get(&self, name: &str, record_type: &str) -> Option<&PlannedRecord> {
+        self.records.iter().find(|record| record.name == name && record.record_type == record_type)
+    }
+
+    /// 第 `index` 条记录在同一区域中同名的另一地址族记录（A 对应 AAAA），跳过的记录不计
+    pub fn dual_stack_peer(&self, index: usize) -> Option<usize> {
+        let record = &self.records[index];
+        let peer_type = match record.record_type.as_str() {
+            "A" => "AAAA",
+            "AAAA" => "A",
+            _ => return None,
+        };
+        self.records.iter().position(|peer| {
+            peer.zone == record.zone
+                && peer.name == record.name
+                && peer.record_type == peer_type
+                && !matches!(peer.change, Ok(RecordChange::Skip))
+        })
+    }
+}
+
+/// 计划中单条记录的变更
+#[derive(Debug)]
+pub struct PlannedRecord {
+    pub name: String,
+    pub record_type: String,
+    pub zone: String,
+    /// 记录应指向的 IP（未检测到时为空）
+    pub ip: Option<String>,
+    pub change: Result<RecordChange, RecordError>,
+    pub timing: RecordTiming,
+    /// 记录内容与上一次发布的不一致，只在启用漂移检测时检查
+    pub drift: Option<Drift>,
+    /// 记录更新过于频繁、本次不更新时为超过的上限说明，只在配置了 `update_limit` 时检查
+    pub flapping: Option<String>,
+}
+
+/// 记录被外部修改或删除：当前内容与本程序上一次发布的内容不一致
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Drift {
+    /// 记录当前的内容，记录已被删除时为空
+    pub found: Option<String>,
+    /// 上一次发布的内容
+    pub published: String,
+}
+
+/// 检测→计划→执行流程中计划与执行阶段共用的上下文
+///
+/// 按区域缓存已连接的 DNS 服务商，执行阶段不必重新连接。
+pub struct Pipeline<'a> {
+    config: &'a Config,
+    providers: HashMap<String, Box<dyn DnsProvider>>,
+}
+
+impl<'a> Pipeline<'a> {
+    pub fn new(config: &'a Config) -> Self {
+        Self { config, providers: HashMap::new() }
+    }
+
+    /// 区域使用给定的服务商，不再按配置连接，用于测试或嵌入时提供自定义服务商
+    pub fn with_provider(mut self, zone_name: &str, provider: Box<dyn DnsProvider>) -> Self {
+        self.providers.insert(zone_name.to_string(), provider);
+        self
+    }
+
+    /// 区域的服务商，首次使用时连接
+    async fn provider(&mut self, zone: &config::ZoneConfig) -> Result<&dyn DnsProvider> {
+        if !self.providers.contains_key(&zone.zone_name) {
+            let provider = zone_provider(self.config, zone).await?;
+            self.providers.insert(zone.zone_name.clone(), provider);
+        }
+        Ok(&*self.providers[&zone.zone_name])
+    }
+
+    /// 计划阶段：只读取现有记录，计算每条记录的变更
+    ///
+    /// `only` 不为空时其余记录标记为跳过，没有选中记录的区域不会连接。单条记录读取失败记在计划中；
+    /// 连接区域失败时该区域的记录都记为失败，其余区域照常处理，所有区域都连接失败时返回第一个错误。
+    /// 启用漂移检测时与上一次发布的内容比较，策略为 notify 的漂移记录不做修改，见 [`config::DriftPolicy`]。
+    /// 非强制更新时，处于冷却时间内或更新次数达到 `update_limit` 上限的记录也不做修改。
+    pub async fn plan(
+        &mut self,
+        ips: &ip_utils::DetectedIps,
+        times: &ip_utils::DetectionTimes,
+        force: bool,
+        only: Option<&HashSet<(String, String)>>,
+    ) -> Result<Plan> {
+        let mut plan = Plan::default();
+        let (mut connected, mut connect_error) = (false, None);
+        let drift_policy = self.config.drift;
+        let cooldowns = !force && self.config.records().any(|record| record.cooldown.is_some());
+        let published = match drift_policy {
+            config::DriftPolicy::Off if !cooldowns => Vec::new(),
+            _ => state::open(self.config).published().unwrap_or_else(|e| {
+                warn!(error = %e, "{}", tr!("无法读取状态文件，跳过漂移检测与冷却时间", "Failed to read state file, skipping drift detection and cooldowns"));
+                Vec::new()
+            }),
+        };
+        let update_limit = self.config.update_limit;
+        let recent = match (update_limit.per_hour, update_limit.per_day) {
+            (None, None) => Vec::new(),
+            _ => {
+                let filter = history::HistoryFilter { since: Some(Duration::from_secs(24 * 3600)), ..Default::default() };
+                state::open(self.config).query_history(&filter).unwrap_or_else(|e| {
+                    warn!(error = %e, "{}", tr!("无法读取 IP 变化历史，跳过更新次数检查", "Failed to read IP change history, skipping the update limit check"));
+                    Vec::new()
+                })
+            }
+        };
+        let targets = failover::check(self.config, ips, only).await;
+        for zone in self.config.zones() {
+            let selected = |record_config: &config::DnsRecordConfig| self.config.is_selected(record_config, only);
+            let provider = match zone.dns_records.iter().any(selected) {
+                true => match self.provider(&zone).await {
+                    Ok(provider) => {
+                        connected = true;
+                        Some(Ok(provider))
+                    }
+                    Err(e) => {
+                        error!(zone = %zone.zone_name, error = %e, "{}", tr!("无法连接区域，跳过其中的记录", "Failed to connect to zone, skipping its records"));
+                        let record_error = RecordError { category: e.category(), message: e.to_string() };
+                        connect_error.get_or_insert(e);
+                        Some(Err(record_error))
+                    }
+                },
+                false => None,
+            };
+            for record_config in &zone.dns_records {
+                let ips = targets.ips_for(record_config, ips);
+                let mut timing = RecordTiming {
+                    detection: times.for_record(record_config).unwrap_or_default(),
+                    api: Duration::ZERO,
+                };
+                let mut change = match &provider {
+                    Some(Err(e)) if selected(record_config) => Err(e.clone()),
+                    Some(Ok(provider)) if selected(record_config) => {
+                        let started = Instant::now();
+                        let change = plan_record(*provider, record_config, &ips, force).await
+                            .map(|(_, change)| change)
+                            .map_err(RecordError::from);
+                        timing.api = started.elapsed();
+                        change
+                    }
+                    _ => Ok(RecordChange::Skip),
+                };
+                let drift = change.as_ref().ok().and_then(|change| detect_drift(&published, record_config, change));
+                if let Some(drift) = &drift {
+                    warn!(
+                        record = %record_config.name, r#type = %record_config.r#type,
+                        found = drift.found.as_deref().unwrap_or("-"), published = %drift.published,
+                        "{}", tr!("记录被外部修改，与上一次发布的内容不一致", "Record was changed externally and no longer matches what was last published")
+                    );
+                    if drift_policy == config::DriftPolicy::Notify {
+                        change = Ok(RecordChange::Noop);
+                    }
+                }
+                let writes = matches!(change, Ok(RecordChange::Update { .. } | RecordChange::Create | RecordChange::Set { .. }));
+                let cooling = (writes && !force).then(|| cooldown_end(&published, record_config)).flatten();
+                if let Some(until) = cooling {
+                    info!(
+                        record = %record_config.name, r#type = %record_config.r#type, until = %until.to_rfc3339(),
+                        "{}", tr!("记录在发布后的冷却时间内，本次不更新", "Record is within its cooldown after the last publish, not updating it this time")
+                    );
+                    change = Ok(RecordChange::Hold { until: until.to_rfc3339() });
+                }
+                let writes = writes && cooling.is_none();
+                let flapping = (writes && !force).then(|| exceeded_update_limit(update_limit, &recent, record_config)).flatten();
+                if let Some(detail) = &flapping {
+                    warn!(record = %record_config.name, r#type = %record_config.r#type, limit = %detail, "{}", tr!("记录更新过于频繁，本次不更新", "Record is flapping, not updating it this time"));
+                    change = Ok(RecordChange::Noop);
+                }
+                plan.records.push(PlannedRecord {
+                    name: record_config.name.clone(),
+                    record_type: record_config.r#type.clone(),
+                    zone: zone.zone_name.clone(),
+                    ip: ips.for_record(record_config),
+                    change,
+                    timing,
+                    drift,
+                    flapping,
+                });
+            }
+        }
+        plan.failover = targets;
+        match connect_error {
+            Some(e) if !connected => Err(e),
+            _ => Ok(plan),
+        }
+    }
+
+    /// 执行阶段：按计划写入记录，记录历史并发送通知，跳过的记录不出现在结果中
+    ///
+    /// 某条记录（包括计划阶段）失败后继续处理其余记录，失败记在该记录的结果中。
+    /// 双栈策略为 atomic 时，同名的 A 与 AAAA 记录一起写入，见 [`config::DualStackPolicy`]。
+    pub async fn apply(
+        &mut self,
+        plan: &Plan,
+        notifiers: &notify::Notifiers,
+        failure_counts: &mut notify::FailureCounts,
+    ) -> Result<Vec<RecordResult>> {
+        plan.failover.save(self.config);
+        let zones = self.config.zones();
+        let atomic = self.config.dual_stack == config::DualStackPolicy::Atomic;
+        let mut paired = HashSet::new();
+        let mut results = Vec::new();
+        for (index, planned) in plan.records.iter().enumerate() {
+            if matches!(planned.change, Ok(RecordChange::Skip)) || paired.contains(&index) {
+                continue;
+            }
+            let (zone, record_config) = find_record(&zones, planned)?;
+
+            let written = match plan.dual_stack_peer(index).filter(|_| atomic) {
+                Some(peer_index) => {
+                    paired.insert(peer_index);
+                    let peer = &plan.records[peer_index];
+                    let (peer_zone, peer_config) = find_record(&zones, peer)?;
+                    let (first, second) = self.write_pair((zone, record_config, planned), (peer_zone, peer_config, peer)).await;
+                    vec![(zone, record_config, planned, first), (peer_zone, peer_config, peer, second)]
+                }
+                None => {
+                    let started = Instant::now();
+                    let result = self.write(zone, record_config, planned).await;
+                    vec![(zone, record_config, planned, (result, started.elapsed()))]
+                }
+            };
+            for (zone, record_config, planned, (result, elapsed)) in written {
+                let api_elapsed = planned.timing.api + elapsed;
+                record_history(self.config, &zone.zone_name, record_config, &result);
+                notify_outcome(notifiers, failure_counts, &zone.zone_name, record_config, planned.ip.as_deref(), &result).await;
+                notify_drift(notifiers, failure_counts, self.config.drift, &zone.zone_name, record_config, planned, &result).await;
+                notify_flapping(notifiers, failure_counts, &zone.zone_name, record_config, planned).await;
+                results.push(RecordResult::new(record_config, planned.ip.clone(), planned.timing.detection, api_elapsed, result));
+            }
+        }
+        Ok(results)
+    }
+
+    /// 按计划写入单条记录
+    async fn write(
+        &mut self,
+        zone: &config::ZoneConfig,
+        record_config: &config::DnsRecordConfig,
+        planned: &PlannedRecord,
+    ) -> std::result::Result<RecordOutcome, RecordError> {
+        let change = planned.change.clone()?;
+        let provider = self.provider(zone).await?;
+        let ip = planned.ip.as_deref().unwrap_or_default();
+        Ok(apply_change(provider, record_config, ip, change).await?)
+    }
+
+    /// 一起写入双栈名称的两条记录：任一条计划失败时两条都不写入，后一条写入失败时撤销前一条
+    async fn write_pair(
+        &mut self,
+        (zone, record_config, planned): (&config::ZoneConfig, &config::DnsRecordConfig, &PlannedRecord),
+        (peer_zone, peer_config, peer): (&config::ZoneConfig, &config::DnsRecordConfig, &PlannedRecord),
+    ) -> (WriteResult, WriteResult) {
+        let started = Instant::now();
+        let mut first = match (&planned.change, &peer.change) {
+            (Ok(_), Err(e)) => Err(peer_failed(peer, e)),
+            _ => self.write(zone, record_config, planned).await,
+        };
+        let first_elapsed = started.elapsed();
+
+        let started = Instant::now();
+        let second = match (&first, &peer.change) {
+            (_, Err(e)) => Err(e.clone()),
+            (Err(e), Ok(_)) => Err(peer_failed(planned, e)),
+            (Ok(_), Ok(_)) => self.write(peer_zone, peer_config, peer).await,
+        };
+        let second_elapsed = started.elapsed();
+
+        if let (Ok(outcome), Err(e), Ok(change)) = (&first, &second, &planned.change)
+            && !matches!(outcome, RecordOutcome::Unchanged | RecordOutcome::Held { .. })
+        {
+            let rolled_back = match self.provider(zone).await {
+                Ok(provider) => roll_back(provider, record_config, change, outcome).await,
+                Err(rollback_error) => Err(rollback_error),
+            };
+            first = Err(match rolled_back {
+                Ok(()) => {
+                    warn!(record = %record_config.name, "{}", tr!("同名记录更新失败，已撤销本记录的更新", "The other record of the name failed, this update was rolled back"));
+                    RecordError {
+                        message: tr!("同名的 {} 记录更新失败，已撤销本记录的更新: {}", "The {} record of the same name failed, this update was rolled back: {}", peer.record_type, e.message),
+                        category: e.category,
+                    }
+                }
+                Err(rollback_error) => {
+                    error!(record = %record_config.name, error = %rollback_error, "{}", tr!("撤销失败，双栈记录不一致", "Rollback failed, the dual-stack records are inconsistent"));
+                    RecordError {
+                        message: tr!(
+                            "同名的 {} 记录更新失败，且无法撤销本记录的更新，双栈记录不一致: {}",
+                            "The {} record of the same name failed and this update could not be rolled back, the dual-stack records are inconsistent: {}",
+                            peer.record_type,
+                            rollback_error
+                        ),
+                        category: rollback_error.category(),
+                    }
+                }
+            });
+        }
+        ((first, first_elapsed), (second, second_elapsed))
+    }
+}
+
+type WriteResult = (std::result::Result<RecordOutcome, RecordError>, Duration);
+
+/// 计划中记录对应的区域与记录配置
+fn find_record<'z>(zones: &'z [config::ZoneConfig], planned: &PlannedRecord) -> Result<(&'z config::ZoneConfig, &'z config::DnsRecordConfig)> {
+    zones.iter()
+        .filter(|zone| zone.zone_name == planned.zone)
+        .find_map(|zone| {
+            zone.dns_records.iter()
+                .find(|r| r.name == planned.name && r.r#type == planned.record_type)
+                .map(|r| (zone, r))
+        })
+        .ok_or_else(|| Error::config(tr!("计划中的记录 {} 不在配置中", "Planned record {} is not in the configuration", planned.name)))
+}
+
+/// 记录当前内容与上一次发布的不一致时的漂移，只检查要更新或新建的记录
+///
+/// 本程序从未发布过的记录不算漂移；IP 变化期间未运行时，记录仍是上一次发布的内容，也不算漂移。
+fn detect_drift(published: &[state::PublishedRecord], record_config: &config::DnsRecordConfig, change: &RecordChange) -> Option<Drift> {
+    let published = published.iter().find(|p| p.name == record_config.name && p.record_type == record_config.r#type)?;
+    let found = match change {
+        RecordChange::Update { old_ip, .. } if *old_ip != published.content => Some(old_ip.clone()),
+        RecordChange::Create | RecordChange::Set { previous: None, .. } => None,
+        RecordChange::Set { previous: Some(previous), .. } if *previous != published.content => Some(previous.clone()),
+        _ => return None,
+    };
+    Some(Drift { found, published: published.content.clone() })
+}
+
+/// 记录上一次发布后的冷却时间尚未结束时返回结束的时间
+fn cooldown_end(published: &[state::PublishedRecord], record_config: &config::DnsRecordConfig) -> Option<chrono::DateTime<chrono::Local>> {
+    let cooldown = chrono::Duration::from_std(record_config.cooldown?).ok()?;
+    let published = published.iter().find(|p| p.name == record_config.name && p.record_type == record_config.r#type)?;
+    let end = published.time()? + cooldown;
+    (chrono::Local::now() < end).then_some(end)
+}
+
+/// 发送漂移通知，同一漂移只通知一次
+async fn notify_drift(
+    notifiers: &notify::Notifiers,
+    failure_counts: &mut notify::FailureCounts,
+    policy: config::DriftPolicy,
+    zone: &str,
+    record_config: &config::DnsRecordConfig,
+    planned: &PlannedRecord,
+    result: &Result<RecordOutcome, RecordError>,
+) {
+    let Some(drift) = &planned.drift else {
+        failure_counts.clear_drift(zone, &planned.name, &planned.record_type);
+        return;
+    };
+    let restored = policy == config::DriftPolicy::Restore;
+    // 改回失败时已有失败通知，下次改回成功后再通知漂移
+    if restored && result.is_err() || !failure_counts.record_drift(zone, &planned.name, &planned.record_type, drift.found.as_deref()) {
+        return;
+    }
+    let expected = match restored {
+        true => planned.ip.as_deref().unwrap_or(&drift.published),
+        false => &drift.published,
+    };
+    let event = notify::NotificationEvent::drift(&planned.name, zone, drift.found.as_deref(), expected, restored);
+    notifiers.notify(&event.routed_to(&record_config.notify)).await;
+}
+
+/// 记录在最近一小时或一天内的更新次数已达到上限时返回说明，`recent` 为最近一天的 IP 变化历史
+fn exceeded_update_limit(limit: config::UpdateLimitConfig, recent: &[history::HistoryEntry], record_config: &config::DnsRecordConfig) -> Option<String> {
+    let now = chrono::Local::now();
+    let updates_within = |window: chrono::Duration| {
+        recent.iter()
+            .filter(|entry| entry.record == record_config.name && entry.record_type == record_config.r#type)
+            .filter(|entry| entry.time().is_some_and(|time| now - time <= window))
+            .count() as u32
+    };
+    if let Some(max) = limit.per_hour {
+        let updates = updates_within(chrono::Duration::hours(1));
+        if updates >= max {
+            return Some(tr!("最近一小时内已更新 {} 次，上限为 {}", "{} updates in the last hour, limit is {}", updates, max));
+        }
+    }
+    if let Some(max) = limit.per_day {
+        let updates = updates_within(chrono::Duration::days(1));
+        if updates >= max {
+            return Some(tr!("最近一天内已更新 {} 次，上限为 {}", "{} updates in the last day, limit is {}", updates, max));
+        }
+    }
+    None
+}
+
+/// 记录因更新过于频繁而未更新时发送通知，直到更新次数回到上限以内只通知一次
+async fn notify_flapping(
+    notifiers: &notify::Notifiers,
+    failure_counts: &mut notify::FailureCounts,
+    zone: &str,
+    record_config: &config::DnsRecordConfig,
+    planned: &PlannedRecord,
+) {
+    let Some(detail) = &planned.flapping else {
+        failure_counts.clear_flapping(zone, &planned.name, &planned.record_type);
+        return;
+    };
+    if failure_counts.record_flapping(zone, &planned.name, &planned.record_type) {
+        let event = notify::NotificationEvent::flapping(&planned.name, zone, planned.ip.as_deref(), detail);
+        notifiers.notify(&event.routed_to(&record_config.notify)).await;
+    }
+}
+
+/// 双栈名称中另一条记录失败时本记录的错误
+fn peer_failed(peer: &PlannedRecord, error: &RecordError) -> RecordError {
+    RecordError {
+        message: tr!(
+            "同名的 {} 记录无法更新，为保持双栈一致未写入: {}",
+            "The {} record of the same name cannot be updated, not written to keep the dual-stack name consistent: {}",
+            peer.record_type,
+            error.message
+        ),
+        category: error.category,
+    }
+}
+
+/// 把已写入的记录恢复到计划前的状态：更新的记录改回旧 IP，新建的记录删除
+async fn roll_back(
+    provider: &dyn DnsProvider,
+    record_config: &config::DnsRecordConfig,
+    change: &RecordChange,
+    outcome: &RecordOutcome,
+) -> Result<()> {
+    match (change, outcome) {
+        (RecordChange::Set { members, .. }, _) => record_set::revert(provider, record_config, members).await,
+        (RecordChange::Update { record_id, old_ip }, RecordOutcome::Updated { new_ip, .. }) => {
+            let revert = RecordChange::Update { record_id: record_id.clone(), old_ip: new_ip.clone() };
+            apply_change(provider, record_config, old_ip, revert).await.map(drop)
+        }
+        (_, RecordOutcome::Created { .. }) => {
+            if let Some(record) = provider.get_record(&record_config.name, &record_config.r#type).await? {
+                provider.delete_record(&record).await?;
+            }
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// 单条记录在一次执行中的处理结果
+#[derive(Debug)]
+pub struct RecordResult {
+    pub name: String,
+    pub record_type: String,
+    /// 本次要发布的 IP（未检测到时为空）
+    pub ip: Option<String>,
+    pub outcome: Result<RecordOutcome, RecordError>,
+    pub timing: RecordTiming,
+}
+
+/// 单条记录处理失败的错误信息与类别
+#[derive(Debug, Clone)]
+pub struct RecordError {
+    pub message: String,
+    pub category: error::ErrorCategory,
+}
+
+impl From<Error> for RecordError {
+    fn from(error: Error) -> Self {
+        Self { category: error.category(), message: error.to_string() }
+    }
+}
+
+/// 单条记录的耗时
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RecordTiming {
+    /// 检测该记录所需 IP 的耗时
+    pub detection: Duration,
+    /// 查询与更新记录的 API 调用耗时
+    pub api: Duration,
+}
+
+impl RecordTiming {
+    pub fn total(&self) -> Duration {
+        self.detection + self.api
+    }
+}
+
+impl RecordResult {
+    fn new(
+        record_config: &config::DnsRecordConfig,
+        ip: Option<String>,
+        detection: Duration,
+        api_elapsed: Duration,
+        outcome: Result<RecordOutcome, RecordError>,
+    ) -> Self {
+        let timing = RecordTiming { detection, api: api_elapsed };
+        debug!(
+            record = %record_config.name,
+            detection = format_args!("{:.3}s", timing.detection.as_secs_f64()),
+            api = format_args!("{:.3}s", timing.api.as_secs_f64()),
+            total = format_args!("{:.3}s", timing.total().as_secs_f64()),
+            "{}", tr!("记录处理耗时", "Record timing")
+        );
+        Self {
+            name: record_config.name.clone(),
+            record_type: record_config.r#type.clone(),
+            ip,
+            outcome,
+            timing,
+        }
+    }
+}
+
+/// 定时模式使用的 `reconcile`：通知后端与失败计数由调用方跨多次执行保持，每条失败的记录输出一条错误日志
+///
+/// `only` 不为空时只处理其中列出的记录。
+pub async fn reconcile_tracked(
+    config: &Config,
+    notifiers: &notify::Notifiers,
+    ips: &ip_utils::DetectedIps,
+    times: &ip_utils::DetectionTimes,
+    force: bool,
+    only: Option<&HashSet<(String, String)>>,
+    failure_counts: &mut notify::FailureCounts,
+) -> Result<Vec<RecordResult>> {
+    let mut pipeline = Pipeline::new(config);
+    let plan = pipeline.plan(ips, times, force, only).await?;
+    let results = pipeline.apply(&plan, notifiers, failure_counts).await?;
+    for result in &results {
+        if let Err(e) = &result.outcome {
+            error!(record = %result.name, error = %e.message, category = %e.category, "{}", tr!("记录处理失败", "Failed to process record"));
+        }
+    }
+    Ok(results)
+}
+
+/// 记录发生变化、处理失败或从失败中恢复时发送通知，未变化时不通知
+async fn notify_outcome(
+    notifiers: &notify::Notifiers,
+    failure_counts: &mut notify::FailureCounts,
+    zone: &str,
+    record_config: &config::DnsRecordConfig,
+    new_ip: Option<&str>,
+    result: &Result<RecordOutcome, RecordError>,
+) {
+    let name = &record_config.name;
+    let routes = &record_config.notify;
+
+    let outcome = match result {
+        Ok(outcome) => outcome,
+        Err(e) => {
+            let failures = failure_counts.record_failure(zone, name, &record_config.r#type);
+            notifiers.notify(&notify::NotificationEvent::failed(name, zone, new_ip, &e.message, e.category, failures).routed_to(routes)).await;
+            return;
+        }
+    };
+
+    let previous_failures = failure_counts.record_success(zone, name, &record_config.r#type);
+    if previous_failures > 0 {
+        notifiers.notify(&notify::NotificationEvent::recovered(name, zone, new_ip, previous_failures).routed_to(routes)).await;
+    }
+
+    match outcome {
+        RecordOutcome::Unchanged | RecordOutcome::Held { .. } => {}
+        RecordOutcome::Updated { old_ip, new_ip } => {
+            notifiers.notify(&notify::NotificationEvent::changed(name, zone, Some(old_ip), new_ip).routed_to(routes)).await;
+        }
+        RecordOutcome::Created { new_ip } => {
+            notifiers.notify(&notify::NotificationEvent::changed(name, zone, None, new_ip).routed_to(routes)).await;
+        }
+    }
+}
+
+/// 记录更新或新建时向历史追加一条记录，并保存该记录最近一次写入的内容
+pub fn record_history<E>(
+    config: &Config,
+    zone: &str,
+    record_config: &config::DnsRecordConfig,
+    result: &std::result::Result<RecordOutcome, E>,
+) {
+    let (old_ip, new_ip) = match result {
+        Ok(RecordOutcome::Updated { old_ip, new_ip }) => (Some(old_ip.as_str()), new_ip),
+        Ok(RecordOutcome::Created { new_ip }) => (None, new_ip),
+        _ => return,
+    };
+    let store = state::open(config);
+    if config.history.enabled {
+        let entry = history::HistoryEntry::new(
+            &record_config.name,
+            zone,
+            &record_config.r#type,
+            old_ip,
+            new_ip,
+        );
+        if let Err(e) = store.append_history(&entry) {
+            warn!(error = %e, "{}", tr!("无法写入历史文件", "Failed to write history file"));
+        }
+    }
+    let published = state::PublishedRecord::new(&record_config.name, &record_config.r#type, zone, new_ip);
+    if let Err(e) = store.save_published(&published) {
+        warn!(error = %e, "{}", tr!("无法写入状态文件", "Failed to write state file"));
+    }
+}
+
+/// 根据配置中的 `cloudflare` 段创建 CloudFlare 客户端，缺少该段或认证配置不完整时返回配置错误
+pub fn client_from_config(config: &Config) -> Result<cloudflare::CloudflareClient> {
+    let cloudflare = config.cloudflare.as_ref()
+        .ok_or_else(|| Error::config(tr!("配置中缺少 cloudflare 段", "The configuration has no cloudflare section")))?;
+    cloudflare_client(&cloudflare.credentials, config.schedule.timeout())
+}
+
+/// 根据认证信息创建 CloudFlare 客户端，认证配置不完整时返回配置错误
+pub fn cloudflare_client(credentials: &config::CloudflareCredentials, timeout: Duration) -> Result<cloudflare::CloudflareClient> {
+
+    let auth_type = credentials.get_auth_type()
+        .map_err(|e| Error::config(tr!("认证类型无效: {}", "Invalid auth type: {}", e)))?;
+    let cf_client = match auth_type {
+        config::AuthType::EmailKey => {
+            let email = credentials.auth_email
+                .as_ref()
+                .ok_or_else(|| Error::config(tr!("使用邮箱+密钥认证时，邮箱是必需的", "auth_email is required for email + key authentication")))?;
+            let key = crate::credentials::auth_key(credentials)?
+                .ok_or_else(|| Error::config(tr!("使用邮箱+密钥认证时，密钥是必需的", "auth_key is required for email + key authentication")))?;
+            cloudflare::CloudflareClient::new(email.clone(), key, timeout)
+        },
+        config::AuthType::Token => {
+            let token = crate::credentials::api_token(credentials)?
+                .ok_or_else(|| Error::config(tr!("使用令牌认证时，API 令牌是必需的", "api_token is required for token authentication")))?;
+            cloudflare::CloudflareClient::new_with_token(token, timeout)
+        }
+    };
+    Ok(match &credentials.endpoint {
+        Some(endpoint) => cf_client.with_endpoint(endpoint),
+        None => cf_client,
+    })
+}
+
+/// 根据配置中的 `cloudflare` 段创建 CloudFlare 客户端并获取 Zone ID
+pub async fn connect(config: &Config) -> Result<(cloudflare::CloudflareClient, String)> {
+    let cf_client = client_from_config(config)?;
+    let zone_name = config.cloudflare.as_ref().map(|cloudflare| cloudflare.zone_name.as_str()).unwrap_or_default();
+    let zone_id = zone_id(&cf_client, zone_name).await?;
+    Ok((cf_client, zone_id))
+}
+
+async fn zone_id(cf_client: &cloudflare::CloudflareClient, zone_name: &str) -> Result<String> {
+    // 获取 Zone ID - 添加更友好的错误处理
+    match cf_client.get_zone_id(zone_name).await {
+        Ok(id) => {
+            debug!(zone = %zone_name, zone_id = %id, "{}", tr!("已获取区域 ID", "Resolved zone ID"));
+            Ok(id)
+        },
+        Err(e) => Err(e.map_message(|message| tr!(
+            "无法获取区域 ID。请检查您的 API 凭据和域名。错误: {}", "Failed to get zone ID. Check your API credentials and zone name. Error: {}", message
+        ))),
+    }
+}
+
+/// 连接区域配置的 DNS 服务商，更新流程通过它读写记录
+pub async fn zone_provider(config: &Config, zone: &config::ZoneConfig) -> Result<Box<dyn DnsProvider>> {
+    let timeout = config.schedule.timeout();
+    match &zone.provider {
+        config::ProviderConfig::Cloudflare(credentials) => {
+            let cf_client = cloudflare_client(credentials, timeout)?;
+            let zone_id = zone_id(&cf_client, &zone.zone_name).await?;
+            Ok(Box::new(cloudflare::CloudflareProvider::new(cf_client, zone_id)))
+        }
+        #[cfg(feature = "aliyun")]
+        config::ProviderConfig::Aliyun(aliyun) => Ok(Box::new(provider::aliyun::AliyunProvider::new(aliyun, &zone.zone_name, timeout))),
+        #[cfg(feature = "dnspod")]
+        config::ProviderConfig::Dnspod(dnspod) => Ok(Box::new(provider::dnspod::DnspodProvider::new(dnspod, &zone.zone_name, timeout))),
+        #[cfg(feature = "route53")]
+        config::ProviderConfig::Route53(route53) => Ok(Box::new(provider::route53::Route53Provider::connect(route53, &zone.zone_name, timeout).await?)),
+        #[cfg(feature = "huawei")]
+        config::ProviderConfig::Huawei(huawei) => Ok(Box::new(provider::huawei::HuaweiProvider::connect(huawei, &zone.zone_name, timeout).await?)),
+        #[cfg(feature = "dyndns")]
+        config::ProviderConfig::Duckdns(duckdns) => Ok(Box::new(provider::dyndns::DuckDnsProvider::new(duckdns, timeout))),
+        #[cfg(feature = "dyndns")]
+        config::ProviderConfig::Dyndns2(dyndns2) => Ok(Box::new(provider::dyndns::Dyndns2Provider::new(dyndns2, timeout))),
+        #[cfg(feature = "desec")]
+        config::ProviderConfig::Desec(desec) => Ok(Box::new(provider::desec::DesecProvider::new(desec, &zone.zone_name, timeout))),
+        #[cfg(feature = "dynv6")]
+        config::ProviderConfig::Dynv6(dynv6) => Ok(Box::new(provider::dynv6::Dynv6Provider::connect(dynv6, &zone.zone_name, timeout).await?)),
+        #[cfg(feature = "porkbun")]
+        config::ProviderConfig::Porkbun(porkbun) => Ok(Box::new(provider::porkbun::PorkbunProvider::new(porkbun, &zone.zone_name, timeout))),
+        #[allow(unreachable_patterns)]
+        _ => Err(provider_disabled(zone)),
+    }
+}
+
+/// 检查每个区域的服务商配置是否完整、是否已在编译时启用，不访问网络
+pub fn check_providers(config: &Config) -> Result<()> {
+    for zone in config.zones() {
+        match &zone.provider {
+            config::ProviderConfig::Cloudflare(credentials) => {
+                cloudflare_client(credentials, config.schedule.timeout())?;
+            }
+            provider if !provider.enabled() => return Err(provider_disabled(&zone)),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn provider_disabled(zone: &config::ZoneConfig) -> Error {
+    Error::config(tr!("区域 {} 使用的服务商 {} 未在编译时启用", "Provider {1} used by zone {0} was not enabled at compile time", zone.zone_name, zone.provider.name()))
+}
+
+/// 单条记录需要执行的变更
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecordChange {
+    /// 记录内容与当前 IP 一致
+    Noop,
+    /// 将已有记录从旧 IP 更新为当前 IP
+    Update { record_id: String, old_ip: String },
+    /// 记录不存在，需要新建
+    Create,
+    /// 记录未被选中，本次不处理
+    Skip,
+    /// 轮询记录集的成员变更，`previous` 为原有成员记录的内容（以 `, ` 连接），此前没有成员记录时为空
+    Set { members: Vec<record_set::SetMember>, previous: Option<String> },
+    /// 记录需要更新，但在发布后的冷却时间内，`until` 之前不更新
+    Hold { until: String },
+}
+
+/// 只读取现有记录，计算单条记录需要执行的变更，返回记录应指向的 IP 与变更
+pub async fn plan_record(
+    provider: &dyn DnsProvider,
+    record_config: &config::DnsRecordConfig,
+    ips: &ip_utils::DetectedIps,
+    force: bool,
+) -> Result<(String, RecordChange)> {
+    if !record_config.members.is_empty() {
+        let (current_ip, previous, members) = record_set::plan(provider, record_config, ips, force).await?;
+        let change = match members.is_empty() {
+            true => RecordChange::Noop,
+            false => RecordChange::Set { members, previous },
+        };
+        return Ok((current_ip, change));
+    }
+    record_config.get_ip_version()
+        .map_err(|e| Error::config(tr!("IP 版本无效: {}", "Invalid IP version: {}", e)))?;
+    let current_ip = ips.for_record(record_config)
+        .ok_or_else(|| Error::detection(error::ErrorCategory::Other, match &record_config.uplink {
+            Some(uplink) => tr!("上行线路 {} 未检测到记录 {} 所需的 {} 地址", "No {2} address detected on uplink {0} for record {1}", uplink, record_config.name, record_config.ip_version),
+            None => tr!("未检测到记录 {} 所需的 {} 地址", "No {1} address detected for record {0}", record_config.name, record_config.ip_version),
+        }))?
+        .to_string();
+    
+    debug!(ip = %current_ip, "{}", tr!("当前外部 IP", "Current external IP"));
+
+    // 获取现有的 DNS 记录
+    let existing_record = provider.get_record(&record_config.name, &record_config.r#type).await
+        .map_err(|e| e.map_message(|message| tr!("无法获取 DNS 记录。请检查您的 API 凭据。错误: {}", "Failed to get DNS record. Check your API credentials. Error: {}", message)))?;
+    let change = match existing_record {
+        Some(existing_record) => {
+            // 检查 IP 是否发生变化，或者是否强制更新；命令行覆盖了 TTL 或代理设置时，设置不同也需要更新
+            let settings_changed = overrides::settings_overridden()
+                && (existing_record.ttl != record_config.ttl || existing_record.proxied != record_config.proxied);
+            if existing_record.content != current_ip || force || settings_changed {
+                RecordChange::Update { record_id: existing_record.id, old_ip: existing_record.content }
+            } else {
+                RecordChange::Noop
+            }
+        }
+        // 如果记录不存在，需要创建新的记录
+        None => RecordChange::Create,
+    };
+    Ok((current_ip, change))
+}
+
+/// 核对并更新单条 DNS 记录
+pub async fn process_record(
+    provider: &dyn DnsProvider,
+    record_config: &config::DnsRecordConfig,
+    ips: &ip_utils::DetectedIps,
+    force: bool,
+) -> Result<RecordOutcome> {
+    let (current_ip, change) = plan_record(provider, record_config, ips, force).await?;
+    apply_change(provider, record_config, &current_ip, change).await
+}
+
+/// 按计划阶段得到的变更写入单条 DNS 记录，`current_ip` 为记录应指向的 IP
+#[tracing::instrument(skip_all, fields(record = %record_config.name))]
+pub async fn apply_change(
+    provider: &dyn DnsProvider,
+    record_config: &config::DnsRecordConfig,
+    current_ip: &str,
+    change: RecordChange,
+) -> Result<RecordOutcome> {
+    debug!("{}", tr!("正在处理记录", "Processing record"));
+
+    let (record_id, old_ip) = match change {
+        RecordChange::Noop | RecordChange::Skip => {
+            debug!(target: logging::NOOP_TARGET, ip = %current_ip, "{}", tr!("IP 未更改，无需更新", "IP unchanged, no update needed"));
+            return Ok(RecordOutcome::Unchanged);
+        }
+        RecordChange::Hold { until } => return Ok(RecordOutcome::Held { until }),
+        RecordChange::Set { members, previous } => return record_set::apply(provider, record_config, current_ip, previous, &members).await,
+        RecordChange::Update { record_id, old_ip } => {
+            debug!(old_ip = %old_ip, ip = %current_ip, "{}", tr!("IP 已更改或强制更新，正在更新 DNS 记录", "IP changed or update forced, updating DNS record"));
+            (record_id, Some(old_ip))
+        }
+        RecordChange::Create => {
+            debug!("{}", tr!("DNS 记录不存在，正在创建新记录", "DNS record does not exist, creating it"));
+            (String::new(), None)
+        }
+    };
+
+    let record = provider::Record {
+        id: record_id,
+        name: record_config.name.clone(),
+        r#type: record_config.r#type.clone(),
+        content: current_ip.to_string(),
+        ttl: record_config.ttl,
+        proxied: record_config.proxied,
+        tag: None,
+    };
+    let written = provider.upsert_record(&record).await.map_err(|e| e.map_message(|message| match old_ip {
+        Some(_) => tr!("无法更新 DNS 记录。请检查您的 API 凭据和权限。错误: {}", "Failed to update DNS record. Check your API credentials and permissions. Error: {}", message),
+        None => tr!("无法创建 DNS 记录。请检查您的 API 凭据和权限。错误: {}", "Failed to create DNS record. Check your API credentials and permissions. Error: {}", message),
+    }))?;
+
+    match old_ip {
+        Some(old_ip) => {
+            info!(target: logging::CHANGE_TARGET, record = %record_config.name, old_ip = %old_ip, ip = %written.content, "{}", tr!("DNS 记录更新成功", "DNS record updated"));
+            Ok(RecordOutcome::Updated { old_ip, new_ip: written.content })
+        }
+        None => {
+            info!(target: logging::CHANGE_TARGET, record = %record_config.name, ip = %written.content, "{}", tr!("新的 DNS 记录已创建", "DNS record created"));
+            Ok(RecordOutcome::Created { new_ip: written.content })
+        }
+    }
+}
+
+/// 能够解析、但运行时会静默失效的设置，加载配置时拒绝，`validate` 也报告这些问题
+///
+/// failure_threshold 为 0 时失败通知永远不会发送、恢复通知却每次都会发送；update_limit 的上限为 0 时阻止所有更新，
+/// 未开启 history.enabled 时没有历史可以统计，上限不起作用。
+pub fn setting_problems(config: &Config) -> Vec<String> {
+    let mut problems = Vec::new();
+    for (name, policy) in notify::policies(&config.notifications) {
+        if policy.failure_threshold == 0 {
+            problems.push(tr!("notifications.{}.failure_threshold 不能为 0", "notifications.{}.failure_threshold cannot be 0", name));
+        }
+    }
+    let limit = config.update_limit;
+    if (limit.per_hour.is_some() || limit.per_day.is_some()) && !config.history.enabled {
+        problems.push(tr!("设置了 update_limit 但未开启 history.enabled，无法统计更新次数", "update_limit is set but history.enabled is off, so updates cannot be counted"));
+    }
+    if limit.per_hour == Some(0) || limit.per_day == Some(0) {
+        problems.push(tr!("update_limit 的上限不能为 0", "update_limit caps cannot be 0"));
+    }
+    problems
+}
+
+/// 读取并解析配置文件，补全数据文件的默认路径并应用命令行的筛选与覆盖
+pub fn load_config(config_path: &str) -> Result<Config> {
+    let content = std::fs::read(config_path)
+        .map_err(|e| Error::config(tr!("无法读取配置文件 {}: {}", "Failed to read config file {}: {}", config_path, e)))?;
+    let content = match encryption::is_encrypted(&content) {
+        true => encryption::decrypt(&content)?,
+        false => String::from_utf8(content)
+            .map_err(|e| Error::config(tr!("无法读取配置文件 {}: {}", "Failed to read config file {}: {}", config_path, e)))?,
+    };
+    debug!(path = %config_path, "{}", tr!("正在加载配置文件", "Loading config file"));
+
+    let mut config: Config = match serde_json::from_str(&config::strip_comments(&content)) {
+        Ok(config) => config,
+        Err(e) => {
+            error!(path = %config_path, line = e.line(), column = e.column(), error = %e, "{}", tr!("JSON 解析错误", "JSON parse error"));
+            return Err(Error::config(tr!("配置文件 {} 解析失败: {}", "Failed to parse config file {}: {}", config_path, e)));
+        }
+    };
+
+    // 先登记密钥再输出配置，日志中的密钥会被替换为 ***
+    logging::redact::register(config.secrets());
+    debug!(config = %serde_json::to_string(&config).unwrap_or_default(), "{}", tr!("配置文件内容", "Config file contents"));
+
+    let problems = setting_problems(&config);
+    if !problems.is_empty() {
+        return Err(Error::config(problems.join("; ")));
+    }
+    config.history.file.get_or_insert_with(|| default_data_file(config_path, "history.jsonl"));
+    let state_file = match config.schedule.state_backend {
+        StateBackend::Json => "state.json",
+        StateBackend::Sqlite if cfg!(feature = "sqlite") => "state.db",
+        StateBackend::Sqlite => {
+            return Err(Error::config(tr!(
+                "schedule.state_backend 为 sqlite，但编译时未启用 sqlite 功能",
+                "schedule.state_backend is sqlite but the sqlite feature was not enabled at build time"
+            )));
+        }
+    };
+    config.schedule.state_file.get_or_insert_with(|| default_data_file(config_path, state_file));
+    overrides::apply(&mut config).map_err(Error::config)?;
+    config.expand_aliases().map_err(Error::config)?;
+    Ok(config)
+}
+
+/// 历史、状态等数据文件的默认路径：systemd 的 StateDirectory（`$STATE_DIRECTORY`）下，否则放在配置文件旁边
+pub fn default_data_file(config_path: &str, name: &str) -> String {
+    let dir = match std::env::var_os("STATE_DIRECTORY") {
+        // 配置了多个 StateDirectory 时以冒号分隔，取第一个
+        Some(dirs) => std::path::PathBuf::from(dirs.to_string_lossy().split(':').next().unwrap_or_default()),
+        None => std::path::Path::new(config_path).parent().unwrap_or(std::path::Path::new("")).to_path_buf(),
+    };
+    dir.join(name).to_string_lossy().into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    use crate::provider::{ProviderFuture, Record};
+
+    /// 把记录保存在内存中的服务商，写入 `failing` 中的名称与类型时返回错误
+    #[derive(Default)]
+    struct MemoryProvider {
+        records: Mutex<Vec<Record>>,
+        failing: Vec<(&'static str, &'static str)>,
+    }
+
+    impl DnsProvider for MemoryProvider {
+        fn name(&self) -> &str {
+            "memory"
+        }
+
+        fn get_record<'a>(&'a self, name: &'a str, record_type: &'a str) -> ProviderFuture<'a, Option<Record>> {
+            let found = self.records.lock().unwrap().iter().find(|r| r.name == name && r.r#type == record_type).cloned();
+            Box::pin(async move { Ok(found) })
+        }
+
+        fn list_records<'a>(&'a self, name: &'a str, record_type: &'a str) -> ProviderFuture<'a, Vec<Record>> {
+            let found = self.records.lock().unwrap().iter().filter(|r| r.name == name && r.r#type == record_type).cloned().collect();
+            Box::pin(async move { Ok(found) })
+        }
+
+        fn supports_tags(&self) -> bool {
+            true
+        }
+
+        fn upsert_record<'a>(&'a self, record: &'a Record) -> ProviderFuture<'a, Record> {
+            if self.failing.contains(&(record.name.as_str(), record.r#type.as_str())) {
+                return Box::pin(async { Err(Error::provider(error::ErrorCategory::RateLimited, "rate limited")) });
+            }
+            let mut records = self.records.lock().unwrap();
+            let mut record = record.clone();
+            match records.iter_mut().find(|r| r.id == record.id && !record.id.is_empty()) {
+                Some(existing) => *existing = record.clone(),
+                None => {
+                    record.id = (0..).map(|i: usize| i.to_string()).find(|id| records.iter().all(|r| &r.id != id)).unwrap();
+                    records.push(record.clone());
+                }
+            }
+            Box::pin(async move { Ok(record) })
+        }
+
+        fn delete_record<'a>(&'a self, record: &'a Record) -> ProviderFuture<'a, ()> {
+            self.records.lock().unwrap().retain(|r| r.id != record.id);
+            Box::pin(async { Ok(()) })
+        }
+    }
+
+    #[tokio::test]
+    async fn process_record_creates_then_updates() {
+        let provider = MemoryProvider::default();
+        let record_config = config::DnsRecordConfig {
+            name: "home.example.com".to_string(),
+            r#type: "A".to_string(),
+            ttl: 1,
+            proxied: false,
+            ip_version: "v4".to_string(),
+            uplink: None,
+            members: Vec::new(),
+            failover: None,
+            also_update: Vec::new(),
+            notify: Vec::new(),
+            cooldown: None,
+            alias_of: None,
+        };
+        let ips = |ip: &str| ip_utils::DetectedIps { v4: Some(ip.to_string()), ..Default::default() };
+
+        let created = process_record(&provider, &record_config, &ips("1.2.3.4"), false).await.unwrap();
+        assert_eq!(created, RecordOutcome::Created { new_ip: "1.2.3.4".to_string() });
+        let unchanged = process_record(&provider, &record_config, &ips("1.2.3.4"), false).await.unwrap();
+        assert_eq!(unchanged, RecordOutcome::Unchanged);
+        let updated = process_record(&provider, &record_config, &ips("5.6.7.8"), false).await.unwrap();
+        assert_eq!(updated, RecordOutcome::Updated { old_ip: "1.2.3.4".to_string(), new_ip: "5.6.7.8".to_string() });
+        assert_eq!(provider.records.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn record_set_tracks_its_members() {
+        let provider = MemoryProvider::default();
+        let mut record_config: config::DnsRecordConfig = serde_json::from_str(r#"{
+            "name": "www.example.com", "type": "A", "ttl": 60, "proxied": false, "ip_version": "v4",
+            "members": [ { "name": "wan1" }, { "name": "wan2", "uplink": "isp2" }, { "name": "backup", "ip": "192.0.2.9" } ]
+        }"#).unwrap();
+        // 手动添加的同名记录不由本程序管理
+        provider.upsert_record(&Record {
+            id: String::new(),
+            name: "www.example.com".to_string(),
+            r#type: "A".to_string(),
+            content: "192.0.2.100".to_string(),
+            ttl: 60,
+            proxied: false,
+            tag: None,
+        }).await.unwrap();
+        let isp2 = ip_utils::DetectedIps { v4: Some("198.51.100.2".to_string()), ..Default::default() };
+        let mut ips = ip_utils::DetectedIps { v4: Some("203.0.113.1".to_string()), ..Default::default() };
+        ips.uplinks.insert("isp2".to_string(), isp2);
+        let contents = || {
+            let mut contents: Vec<_> = provider.records.lock().unwrap().iter().map(|r| r.content.clone()).collect();
+            contents.sort();
+            contents
+        };
+
+        let created = process_record(&provider, &record_config, &ips, false).await.unwrap();
+        assert_eq!(created, RecordOutcome::Created { new_ip: "203.0.113.1, 198.51.100.2, 192.0.2.9".to_string() });
+        assert_eq!(contents(), ["192.0.2.100", "192.0.2.9", "198.51.100.2", "203.0.113.1"]);
+        assert_eq!(process_record(&provider, &record_config, &ips, false).await.unwrap(), RecordOutcome::Unchanged);
+
+        // wan1 地址变化，backup 移出记录集
+        ips.v4 = Some("203.0.113.5".to_string());
+        record_config.members.pop();
+        let (current_ip, change) = plan_record(&provider, &record_config, &ips, false).await.unwrap();
+        let RecordChange::Set { members, previous } = &change else { panic!("{change:?}") };
+        assert_eq!(members.len(), 2);
+        assert_eq!(previous.as_deref(), Some("203.0.113.1, 198.51.100.2, 192.0.2.9"));
+        let outcome = apply_change(&provider, &record_config, &current_ip, change.clone()).await.unwrap();
+        assert_eq!(contents(), ["192.0.2.100", "198.51.100.2", "203.0.113.5"]);
+
+        // 回滚恢复原来的成员
+        roll_back(&provider, &record_config, &change, &outcome).await.unwrap();
+        assert_eq!(contents(), ["192.0.2.100", "192.0.2.9", "198.51.100.2", "203.0.113.1"]);
+    }
+
+    #[tokio::test]
+    async fn pipeline_plans_then_applies() {
+        let config: Config = serde_json::from_str(r#"{
+            "cloudflare": { "auth_type": "token", "api_token": "token", "zone_name": "example.com" },
+            "dns_records": [
+                { "name": "home.example.com", "type": "A", "ttl": 1, "proxied": false, "ip_version": "v4" },
+                { "name": "nas.example.com", "type": "A", "ttl": 1, "proxied": false, "ip_version": "v4" }
+            ]
+        }"#).unwrap();
+        let ips = ip_utils::DetectedIps { v4: Some("1.2.3.4".to_string()), ..Default::default() };
+        let times = ip_utils::DetectionTimes::default();
+        let only = HashSet::from([("home.example.com".to_string(), "A".to_string())]);
+        let mut pipeline = Pipeline::new(&config).with_provider("example.com", Box::new(MemoryProvider::default()));
+
+        let plan = pipeline.plan(&ips, &times, false, Some(&only)).await.unwrap();
+        assert!(plan.has_changes());
+        assert!(matches!(plan.get("home.example.com", "A").unwrap().change, Ok(RecordChange::Create)));
+        assert!(matches!(plan.get("nas.example.com", "A").unwrap().change, Ok(RecordChange::Skip)));
+
+        let notifiers = notify::Notifiers::from_config(&config.notifications, config.schedule.timeout());
+        let mut failure_counts = notify::FailureCounts::default();
+        let results = pipeline.apply(&plan, &notifiers, &mut failure_counts).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].outcome.as_ref().unwrap(), &RecordOutcome::Created { new_ip: "1.2.3.4".to_string() });
+
+        let replanned = pipeline.plan(&ips, &times, false, Some(&only)).await.unwrap();
+        assert!(!replanned.has_changes());
+    }
+
+    #[tokio::test]
+    async fn only_selects_by_name_and_type() {
+        let config: Config = serde_json::from_str(r#"{
+            "cloudflare": { "auth_type": "token", "api_token": "token", "zone_name": "example.com" },
+            "dns_records": [
+                { "name": "home.example.com", "type": "A", "ttl": 1, "proxied": false, "ip_version": "v4" },
+                { "name": "home.example.com", "type": "AAAA", "ttl": 1, "proxied": false, "ip_version": "v6" }
+            ]
+        }"#).unwrap();
+        let ips = ip_utils::DetectedIps { v4: Some("1.2.3.4".to_string()), v6: Some("2001:db8::1".to_string()), ..Default::default() };
+        // 只重试失败的 AAAA 记录时，同名的 A 记录不被重新计划
+        let only = HashSet::from([("home.example.com".to_string(), "AAAA".to_string())]);
+        let mut pipeline = Pipeline::new(&config).with_provider("example.com", Box::new(MemoryProvider::default()));
+        let plan = pipeline.plan(&ips, &ip_utils::DetectionTimes::default(), false, Some(&only)).await.unwrap();
+        assert!(matches!(plan.get("home.example.com", "A").unwrap().change, Ok(RecordChange::Skip)));
+        assert!(matches!(plan.get("home.example.com", "AAAA").unwrap().change, Ok(RecordChange::Create)));
+    }
+
+    #[tokio::test]
+    async fn aliases_move_with_their_record() {
+        let mut config: Config = serde_json::from_str(r#"{
+            "cloudflare": { "auth_type": "token", "api_token": "token", "zone_name": "example.com" },
+            "dns_records": [
+                { "name": "example.com", "type": "A", "ttl": 1, "proxied": false, "ip_version": "v4", "also_update": ["www", "shop.example.com"] },
+                { "name": "nas.example.com", "type": "A", "ttl": 1, "proxied": false, "ip_version": "v4" }
+            ]
+        }"#).unwrap();
+        config.expand_aliases().unwrap();
+        let names: Vec<_> = config.records().map(|record| record.name.as_str()).collect();
+        assert_eq!(names, ["example.com", "www.example.com", "shop.example.com", "nas.example.com"]);
+
+        let ips = ip_utils::DetectedIps { v4: Some("1.2.3.4".to_string()), ..Default::default() };
+        let only = HashSet::from([("example.com".to_string(), "A".to_string())]);
+        let mut pipeline = Pipeline::new(&config).with_provider("example.com", Box::new(MemoryProvider::default()));
+        let plan = pipeline.plan(&ips, &ip_utils::DetectionTimes::default(), false, Some(&only)).await.unwrap();
+        let changes: Vec<_> = plan.records.iter().map(|record| record.change.as_ref().unwrap()).collect();
+        assert_eq!(changes, [&RecordChange::Create, &RecordChange::Create, &RecordChange::Create, &RecordChange::Skip]);
+
+        let mut duplicate: Config = serde_json::from_str(r#"{
+            "cloudflare": { "auth_type": "token", "api_token": "token", "zone_name": "example.com" },
+            "dns_records": [
+                { "name": "example.com", "type": "A", "ttl": 1, "proxied": false, "ip_version": "v4", "also_update": ["www"] },
+                { "name": "www.example.com", "type": "A", "ttl": 1, "proxied": false, "ip_version": "v4" }
+            ]
+        }"#).unwrap();
+        assert!(duplicate.expand_aliases().is_err());
+    }
+
+    #[tokio::test]
+    async fn failed_record_does_not_stop_the_others() {
+        let config: Config = serde_json::from_str(r#"{
+            "zones": [
+                { "zone_name": "example.com", "provider": { "type": "cloudflare", "auth_type": "token", "api_token": "token" }, "dns_records": [
+                    { "name": "home.example.com", "type": "A", "ttl": 1, "proxied": false, "ip_version": "v4" },
+                    { "name": "nas.example.com", "type": "A", "ttl": 1, "proxied": false, "ip_version": "v4" }
+                ] },
+                { "zone_name": "example.net", "provider": { "type": "cloudflare", "auth_type": "token" }, "dns_records": [
+                    { "name": "home.example.net", "type": "A", "ttl": 1, "proxied": false, "ip_version": "v4" }
+                ] }
+            ]
+        }"#).unwrap();
+        let ips = ip_utils::DetectedIps { v4: Some("1.2.3.4".to_string()), ..Default::default() };
+        let provider = MemoryProvider { failing: vec![("home.example.com", "A")], ..Default::default() };
+        // example.net 缺少令牌，连接失败
+        let mut pipeline = Pipeline::new(&config).with_provider("example.com", Box::new(provider));
+
+        let plan = pipeline.plan(&ips, &ip_utils::DetectionTimes::default(), false, None).await.unwrap();
+        let results = apply_once(&mut pipeline, &plan).await.unwrap();
+        let summary = summary::RunSummary::new(&config, &ips, &results);
+
+        let actions: Vec<_> = summary.records.iter().map(|record| record.action).collect();
+        assert_eq!(actions, [summary::RecordAction::Failed, summary::RecordAction::Created, summary::RecordAction::Failed]);
+        assert_eq!(summary.exit_code(), summary::exit_code(summary::FailureKind::Api, error::ErrorCategory::RateLimited));
+    }
+
+    #[tokio::test]
+    async fn atomic_dual_stack_rolls_back() {
+        let config: Config = serde_json::from_str(r#"{
+            "cloudflare": { "auth_type": "token", "api_token": "token", "zone_name": "example.com" },
+            "dual_stack": "atomic",
+            "dns_records": [
+                { "name": "home.example.com", "type": "A", "ttl": 1, "proxied": false, "ip_version": "v4" },
+                { "name": "nas.example.com", "type": "A", "ttl": 1, "proxied": false, "ip_version": "v4" },
+                { "name": "home.example.com", "type": "AAAA", "ttl": 1, "proxied": false, "ip_version": "v6" },
+                { "name": "nas.example.com", "type": "AAAA", "ttl": 1, "proxied": false, "ip_version": "v6" }
+            ]
+        }"#).unwrap();
+        let ips = ip_utils::DetectedIps { v4: Some("1.2.3.4".to_string()), v6: Some("2001:db8::1".to_string()), ..Default::default() };
+        let provider = MemoryProvider { failing: vec![("home.example.com", "AAAA")], ..Default::default() };
+        for (name, content) in [("home.example.com", "5.6.7.8"), ("nas.example.com", "5.6.7.8")] {
+            let record = Record { id: String::new(), name: name.to_string(), r#type: "A".to_string(), content: content.to_string(), ttl: 1, proxied: false, tag: None };
+            provider.upsert_record(&record).await.unwrap();
+        }
+        let mut pipeline = Pipeline::new(&config).with_provider("example.com", Box::new(provider));
+
+        let plan = pipeline.plan(&ips, &ip_utils::DetectionTimes::default(), false, None).await.unwrap();
+        let results = apply_once(&mut pipeline, &plan).await.unwrap();
+        let outcome = |name: &str, record_type: &str| &results.iter().find(|r| r.name == name && r.record_type == record_type).unwrap().outcome;
+
+        // AAAA 写入失败，已更新的 A 记录被撤销
+        assert!(outcome("home.example.com", "AAAA").is_err());
+        assert!(outcome("home.example.com", "A").as_ref().unwrap_err().message.contains("AAAA"));
+        assert!(outcome("nas.example.com", "A").is_ok());
+        assert!(outcome("nas.example.com", "AAAA").is_ok());
+        let replanned = pipeline.plan(&ips, &ip_utils::DetectionTimes::default(), false, None).await.unwrap();
+        assert!(matches!(&replanned.get("home.example.com", "A").unwrap().change, Ok(RecordChange::Update { old_ip, .. }) if old_ip == "5.6.7.8"));
+        assert!(matches!(replanned.get("home.example.com", "AAAA").unwrap().change, Ok(RecordChange::Create)));
+    }
+
+    #[tokio::test]
+    async fn failover_state_is_saved_only_when_applied() {
+        let dir = std::env::temp_dir().join(format!("cloudflare_ddns_failover_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut config: Config = serde_json::from_value(serde_json::json!({
+            "cloudflare": { "auth_type": "token", "api_token": "token", "zone_name": "example.com" },
+            "schedule": { "state_file": dir.join("state.json") },
+            "history": { "enabled": false },
+            "dns_records": [{
+                "name": "home.example.com", "type": "A", "ttl": 1, "proxied": false, "ip_version": "v4", "also_update": ["www"],
+                // 本机未监听的端口，连接立即被拒绝
+                "failover": { "primary": "127.0.0.1", "backup": "192.0.2.2", "port": 1, "timeout": "1s", "fail_after": 1 }
+            }]
+        })).unwrap();
+        config.expand_aliases().unwrap();
+        let ips = ip_utils::DetectedIps::default();
+        let times = ip_utils::DetectionTimes::default();
+        let mut pipeline = Pipeline::new(&config).with_provider("example.com", Box::new(MemoryProvider::default()));
+
+        // 只生成计划时切换到备用地址，但不保存健康状态；展开的名称使用原记录的检查结果
+        let only = HashSet::from([("www.example.com".to_string(), "A".to_string())]);
+        let plan = pipeline.plan(&ips, &times, false, Some(&only)).await.unwrap();
+        assert_eq!(plan.get("www.example.com", "A").unwrap().ip.as_deref(), Some("192.0.2.2"));
+        assert!(state::open(&config).failover().unwrap().is_empty());
+
+        let plan = pipeline.plan(&ips, &times, false, None).await.unwrap();
+        let addresses: Vec<_> = plan.records.iter().map(|record| record.ip.as_deref()).collect();
+        assert_eq!(addresses, [Some("192.0.2.2"), Some("192.0.2.2")]);
+        apply_once(&mut pipeline, &plan).await.unwrap();
+        let saved = state::open(&config).failover().unwrap();
+        assert_eq!(saved.keys().collect::<Vec<_>>(), ["home.example.com/A"]);
+        assert!(saved["home.example.com/A"].on_backup);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn drift_is_reported_then_restored() {
+        let dir = std::env::temp_dir().join(format!("cloudflare_ddns_drift_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let state_file = dir.join("state.json");
+        let config: Config = serde_json::from_value(serde_json::json!({
+            "cloudflare": { "auth_type": "token", "api_token": "token", "zone_name": "example.com" },
+            "schedule": { "state_file": state_file },
+            "history": { "enabled": false },
+            "drift": "notify",
+            "dns_records": [
+                { "name": "home.example.com", "type": "A", "ttl": 1, "proxied": false, "ip_version": "v4" }
+            ]
+        })).unwrap();
+        let ips = ip_utils::DetectedIps { v4: Some("1.2.3.4".to_string()), ..Default::default() };
+        let times = ip_utils::DetectionTimes::default();
+        let drifted = Record { id: "0".to_string(), name: "home.example.com".to_string(), r#type: "A".to_string(), content: "9.9.9.9".to_string(), ttl: 1, proxied: false, tag: None };
+
+        let mut pipeline = Pipeline::new(&config).with_provider("example.com", Box::new(MemoryProvider::default()));
+        let plan = pipeline.plan(&ips, &times, false, None).await.unwrap();
+        assert_eq!(plan.records[0].drift, None);
+        apply_once(&mut pipeline, &plan).await.unwrap();
+
+        // 记录被外部改为 9.9.9.9，notify 策略只报告不修改
+        pipeline.providers["example.com"].upsert_record(&drifted).await.unwrap();
+        let plan = pipeline.plan(&ips, &times, false, None).await.unwrap();
+        let expected = Drift { found: Some("9.9.9.9".to_string()), published: "1.2.3.4".to_string() };
+        assert_eq!(plan.records[0].drift.as_ref(), Some(&expected));
+        assert!(matches!(plan.records[0].change, Ok(RecordChange::Noop)));
+
+        // restore 策略改回当前 IP
+        let restore = Config { drift: config::DriftPolicy::Restore, ..config.clone() };
+        let mut pipeline = Pipeline { config: &restore, providers: pipeline.providers };
+        let plan = pipeline.plan(&ips, &times, false, None).await.unwrap();
+        let results = apply_once(&mut pipeline, &plan).await.unwrap();
+        assert_eq!(results[0].outcome.as_ref().unwrap(), &RecordOutcome::Updated { old_ip: "9.9.9.9".to_string(), new_ip: "1.2.3.4".to_string() });
+        let replanned = pipeline.plan(&ips, &times, false, None).await.unwrap();
+        assert_eq!(replanned.records[0].drift, None);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn load_config_rejects_unusable_update_limits() {
+        let dir = std::env::temp_dir().join(format!("cloudflare_ddns_limits_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.json");
+        let config_path = path.to_str().unwrap();
+        let write = |extra: serde_json::Value| {
+            let mut config = serde_json::json!({
+                "cloudflare": { "auth_type": "token", "api_token": "token", "zone_name": "example.com" },
+                "dns_records": [
+                    { "name": "home.example.com", "type": "A", "ttl": 1, "proxied": false, "ip_version": "v4" }
+                ]
+            });
+            config.as_object_mut().unwrap().extend(extra.as_object().unwrap().clone());
+            std::fs::write(&path, config.to_string()).unwrap();
+        };
+
+        write(serde_json::json!({ "update_limit": { "per_hour": 6 } }));
+        assert!(load_config(config_path).is_ok());
+        // 上限为 0 时阻止所有更新
+        write(serde_json::json!({ "update_limit": { "per_day": 0 } }));
+        assert!(load_config(config_path).is_err());
+        // 未开启历史时无法统计更新次数
+        write(serde_json::json!({ "update_limit": { "per_hour": 6 }, "history": { "enabled": false } }));
+        assert!(load_config(config_path).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
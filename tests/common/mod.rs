@@ -0,0 +1,310 @@
+// 集成测试共用的模拟服务：在本地端口上同时提供 IP 检测接口与 CloudFlare API 的记录接口
+#![allow(dead_code)]
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use serde_json::{json, Value};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+pub const ZONE_ID: &str = "zone-1";
+pub const ZONE_NAME: &str = "example.com";
+
+/// 模拟服务收到的一个请求
+#[derive(Debug, Clone)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub query: String,
+    pub body: String,
+}
+
+impl Request {
+    pub fn json(&self) -> Value {
+        serde_json::from_str(&self.body).unwrap_or(Value::Null)
+    }
+
+    /// 写入记录的请求（新建或更新）
+    pub fn is_write(&self) -> bool {
+        matches!(self.method.as_str(), "POST" | "PUT")
+    }
+}
+
+#[derive(Default)]
+struct State {
+    ipv4: Option<String>,
+    ipv6: Option<String>,
+    records: Vec<Value>,
+    next_id: u32,
+    /// 写入记录时返回的错误状态码
+    write_status: Option<u16>,
+    requests: Vec<Request>,
+}
+
+/// 模拟的 IP 检测服务与 CloudFlare API
+#[derive(Clone)]
+pub struct MockServer {
+    url: String,
+    state: Arc<Mutex<State>>,
+}
+
+impl MockServer {
+    pub async fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let server = Self { url, state: Arc::default() };
+        let state = server.state.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(serve(stream, state.clone()));
+            }
+        });
+        server
+    }
+
+    /// IPv4 检测接口返回的地址，未设置时返回 500
+    pub fn set_ipv4(&self, ip: &str) {
+        self.state.lock().unwrap().ipv4 = Some(ip.to_string());
+    }
+
+    /// IPv6 检测接口返回的地址，未设置时返回 500
+    pub fn set_ipv6(&self, ip: &str) {
+        self.state.lock().unwrap().ipv6 = Some(ip.to_string());
+    }
+
+    /// 预置一条已有记录
+    pub fn add_record(&self, name: &str, record_type: &str, content: &str) {
+        let mut state = self.state.lock().unwrap();
+        state.next_id += 1;
+        let id = format!("record-{}", state.next_id);
+        state.records.push(json!({ "id": id, "name": name, "type": record_type, "content": content, "ttl": 1, "proxied": false }));
+    }
+
+    /// 之后写入记录的请求都以该状态码失败
+    pub fn fail_writes(&self, status: u16) {
+        self.state.lock().unwrap().write_status = Some(status);
+    }
+
+    /// 恢复正常处理写入记录的请求
+    pub fn accept_writes(&self) {
+        self.state.lock().unwrap().write_status = None;
+    }
+
+    /// 发送到 `/webhook` 的通知中的事件类型，按收到的顺序排列
+    pub fn webhook_events(&self) -> Vec<String> {
+        self.requests().iter()
+            .filter(|r| r.method == "POST" && r.path == "/webhook")
+            .map(|r| r.json()["event"].as_str().unwrap_or_default().to_string())
+            .collect()
+    }
+
+    pub fn record_content(&self, name: &str, record_type: &str) -> Option<String> {
+        self.state.lock().unwrap().records.iter()
+            .find(|r| r["name"] == name && r["type"] == record_type)
+            .map(|r| r["content"].as_str().unwrap_or_default().to_string())
+    }
+
+    pub fn requests(&self) -> Vec<Request> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    pub fn writes(&self) -> Vec<Request> {
+        self.requests().into_iter().filter(Request::is_write).collect()
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// 使用该服务的配置：CloudFlare API 与 IP 检测都指向模拟服务
+    pub fn config(&self, records: Value) -> Value {
+        json!({
+            "cloudflare": {
+                "auth_type": "token",
+                "api_token": "test-token",
+                "zone_name": ZONE_NAME,
+                "endpoint": format!("{}/client/v4", self.url),
+            },
+            "dns_records": records,
+            "ip_detection": {
+                "ipv4_url": format!("{}/ipv4", self.url),
+                "ipv6_url": format!("{}/ipv6", self.url),
+            },
+            "schedule": { "timeout": "5s" },
+        })
+    }
+}
+
+/// 一条 dns_records 配置
+pub fn record(name: &str, record_type: &str) -> Value {
+    let ip_version = if record_type == "AAAA" { "v6" } else { "v4" };
+    json!({ "name": name, "type": record_type, "ttl": 1, "proxied": false, "ip_version": ip_version })
+}
+
+/// 测试用的临时目录，配置文件与历史文件都写在其中，离开作用域时删除
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    pub fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("cloudflare_ddns_it_{}_{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        Self(dir)
+    }
+
+    /// 写入配置文件并返回其路径
+    pub fn write_config(&self, config: &Value) -> String {
+        let path = self.0.join("config.json");
+        std::fs::write(&path, serde_json::to_string_pretty(config).unwrap()).unwrap();
+        path.to_str().unwrap().to_string()
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+async fn serve(mut stream: TcpStream, state: Arc<Mutex<State>>) {
+    let Some(request) = read_request(&mut stream).await else {
+        return;
+    };
+    let (status, content_type, body) = respond(&mut state.lock().unwrap(), request);
+    let response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status, if status < 400 { "OK" } else { "Error" }, content_type, body.len(), body
+    );
+    let _ = stream.write_all(response.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
+
+async fn read_request(stream: &mut TcpStream) -> Option<Request> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let head_end = loop {
+        if let Some(end) = buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+            break end + 4;
+        }
+        let read = stream.read(&mut chunk).await.ok()?;
+        if read == 0 {
+            return None;
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+    };
+    let head = String::from_utf8_lossy(&buffer[..head_end]).to_string();
+    let content_length = head.lines()
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, value)| value.trim().parse::<usize>().ok())
+        .unwrap_or(0);
+    while buffer.len() < head_end + content_length {
+        let read = stream.read(&mut chunk).await.ok()?;
+        if read == 0 {
+            break;
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+    }
+
+    let mut request_line = head.lines().next()?.split_whitespace();
+    let method = request_line.next()?.to_string();
+    let target = request_line.next()?;
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    Some(Request {
+        method,
+        path: path.to_string(),
+        query: query.to_string(),
+        body: String::from_utf8_lossy(&buffer[head_end..]).to_string(),
+    })
+}
+
+/// 按路径分发请求，返回状态码、Content-Type 与响应体
+fn respond(state: &mut State, request: Request) -> (u16, &'static str, String) {
+    state.requests.push(request.clone());
+    let query = |key: &str| request.query.split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(k, _)| *k == key)
+        .map(|(_, v)| v.to_string());
+    let records_path = format!("/client/v4/zones/{}/dns_records", ZONE_ID);
+
+    match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/ipv4") => text(state.ipv4.clone()),
+        ("GET", "/ipv6") => text(state.ipv6.clone()),
+        ("POST", "/webhook") => (200, "text/plain", "ok".to_string()),
+        ("POST", "/webhook/down") => (500, "text/plain", "unavailable".to_string()),
+        ("GET", "/client/v4/zones") => {
+            let zones = match query("name") {
+                Some(name) if name != ZONE_NAME => vec![],
+                _ => vec![json!({ "id": ZONE_ID, "name": ZONE_NAME, "status": "active" })],
+            };
+            list(zones)
+        }
+        ("GET", path) if path == records_path => {
+            let records = state.records.iter()
+                .filter(|r| query("name").is_none_or(|name| r["name"] == name.as_str()))
+                .filter(|r| query("type").is_none_or(|record_type| r["type"] == record_type.as_str()))
+                .cloned()
+                .collect();
+            list(records)
+        }
+        ("POST", path) if path == records_path => {
+            if let Some(status) = state.write_status {
+                return failure(status);
+            }
+            state.next_id += 1;
+            let mut record = request.json();
+            record["id"] = json!(format!("record-{}", state.next_id));
+            state.records.push(record.clone());
+            single(record)
+        }
+        ("PUT", path) if path.starts_with(&format!("{}/", records_path)) => {
+            if let Some(status) = state.write_status {
+                return failure(status);
+            }
+            let id = &path[records_path.len() + 1..];
+            let mut record = request.json();
+            record["id"] = json!(id);
+            match state.records.iter_mut().find(|r| r["id"] == id) {
+                Some(existing) => {
+                    *existing = record.clone();
+                    single(record)
+                }
+                None => failure(404),
+            }
+        }
+        _ => failure(404),
+    }
+}
+
+fn text(ip: Option<String>) -> (u16, &'static str, String) {
+    match ip {
+        Some(ip) => (200, "text/plain", ip),
+        None => (500, "text/plain", "unavailable".to_string()),
+    }
+}
+
+fn list(result: Vec<Value>) -> (u16, &'static str, String) {
+    let count = result.len();
+    let body = json!({
+        "success": true,
+        "errors": [],
+        "messages": [],
+        "result": result,
+        "result_info": { "page": 1, "per_page": 100, "total_pages": 1, "count": count, "total_count": count },
+    });
+    (200, "application/json", body.to_string())
+}
+
+fn single(result: Value) -> (u16, &'static str, String) {
+    let body = json!({ "success": true, "errors": [], "messages": [], "result": result });
+    (200, "application/json", body.to_string())
+}
+
+fn failure(status: u16) -> (u16, &'static str, String) {
+    let body = json!({ "success": false, "errors": [{ "code": status, "message": "mock failure" }], "messages": [], "result": null });
+    (status, "application/json", body.to_string())
+}
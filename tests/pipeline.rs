@@ -0,0 +1,250 @@
+// 端到端测试：对模拟的 CloudFlare API 与 IP 检测服务运行完整的更新流程
+mod common;
+
+use serde_json::json;
+
+use cloudflare_ddns::DdnsUpdater;
+use cloudflare_ddns::error::ErrorCategory;
+use cloudflare_ddns::summary::{self, FailureKind, RecordAction};
+use cloudflare_ddns::update::run_once;
+
+use common::{record, MockServer, TempDir};
+
+#[tokio::test]
+async fn creates_missing_record() {
+    let server = MockServer::start().await;
+    server.set_ipv4("203.0.113.10");
+    let dir = TempDir::new("create");
+    let config = dir.write_config(&server.config(json!([record("home.example.com", "A")])));
+
+    let summary = run_once(&config, false, false, false).await.unwrap();
+
+    assert_eq!(summary.records[0].action, RecordAction::Created);
+    assert_eq!(summary.exit_code(), summary::EXIT_UPDATED);
+    let writes = server.writes();
+    assert_eq!(writes.len(), 1);
+    assert_eq!(writes[0].method, "POST");
+    assert_eq!(writes[0].json()["content"], "203.0.113.10");
+    assert_eq!(server.record_content("home.example.com", "A").as_deref(), Some("203.0.113.10"));
+    assert!(dir.path().join("history.jsonl").exists());
+}
+
+#[tokio::test]
+async fn unchanged_record_is_not_written() {
+    let server = MockServer::start().await;
+    server.set_ipv4("203.0.113.10");
+    server.add_record("home.example.com", "A", "203.0.113.10");
+    let dir = TempDir::new("noop");
+    let config = dir.write_config(&server.config(json!([record("home.example.com", "A")])));
+
+    let summary = run_once(&config, false, false, false).await.unwrap();
+
+    assert_eq!(summary.records[0].action, RecordAction::Unchanged);
+    assert_eq!(summary.exit_code(), 0);
+    assert!(server.writes().is_empty());
+}
+
+#[tokio::test]
+async fn force_rewrites_unchanged_record() {
+    let server = MockServer::start().await;
+    server.set_ipv4("203.0.113.10");
+    server.add_record("home.example.com", "A", "203.0.113.10");
+    let dir = TempDir::new("force");
+    let config = dir.write_config(&server.config(json!([record("home.example.com", "A")])));
+
+    let summary = run_once(&config, true, false, false).await.unwrap();
+
+    assert_eq!(summary.records[0].action, RecordAction::Updated);
+    let writes = server.writes();
+    assert_eq!(writes.len(), 1);
+    assert_eq!(writes[0].method, "PUT");
+    assert!(writes[0].path.ends_with("/dns_records/record-1"));
+}
+
+#[tokio::test]
+async fn updates_multiple_records() {
+    let server = MockServer::start().await;
+    server.set_ipv4("203.0.113.10");
+    server.set_ipv6("2001:db8::10");
+    server.add_record("home.example.com", "A", "198.51.100.1");
+    server.add_record("home.example.com", "AAAA", "2001:db8::1");
+    server.add_record("nas.example.com", "A", "203.0.113.10");
+    let dir = TempDir::new("multi");
+    let records = json!([
+        record("home.example.com", "A"),
+        record("home.example.com", "AAAA"),
+        record("nas.example.com", "A"),
+        record("new.example.com", "A"),
+    ]);
+    let config = dir.write_config(&server.config(records));
+
+    let summary = run_once(&config, false, false, false).await.unwrap();
+
+    let actions: Vec<_> = summary.records.iter().map(|r| r.action).collect();
+    assert_eq!(actions, [RecordAction::Updated, RecordAction::Updated, RecordAction::Unchanged, RecordAction::Created]);
+    assert_eq!(summary.ipv6.as_deref(), Some("2001:db8::10"));
+    assert_eq!(server.record_content("home.example.com", "A").as_deref(), Some("203.0.113.10"));
+    assert_eq!(server.record_content("home.example.com", "AAAA").as_deref(), Some("2001:db8::10"));
+    assert_eq!(server.record_content("new.example.com", "A").as_deref(), Some("203.0.113.10"));
+    assert_eq!(server.writes().len(), 3);
+}
+
+#[tokio::test]
+async fn update_limit_stops_a_flapping_record() {
+    let server = MockServer::start().await;
+    server.set_ipv4("203.0.113.10");
+    server.add_record("home.example.com", "A", "198.51.100.1");
+    let dir = TempDir::new("update_limit");
+    let mut config = server.config(json!([record("home.example.com", "A")]));
+    config["update_limit"] = json!({ "per_hour": 1 });
+    let config = dir.write_config(&config);
+
+    let summary = run_once(&config, false, false, false).await.unwrap();
+    assert_eq!(summary.records[0].action, RecordAction::Updated);
+
+    // 一小时内的第二次更新超过上限，记录保持不变
+    server.set_ipv4("203.0.113.20");
+    let summary = run_once(&config, false, false, false).await.unwrap();
+    assert_eq!(summary.records[0].action, RecordAction::Unchanged);
+    assert_eq!(server.record_content("home.example.com", "A").as_deref(), Some("203.0.113.10"));
+    assert_eq!(server.writes().len(), 1);
+}
+
+#[tokio::test]
+async fn cooldown_holds_back_updates_unless_forced() {
+    let server = MockServer::start().await;
+    server.set_ipv4("203.0.113.10");
+    let dir = TempDir::new("cooldown");
+    let mut home = record("home.example.com", "A");
+    home["cooldown"] = json!("1h");
+    let config = dir.write_config(&server.config(json!([home])));
+
+    let summary = run_once(&config, false, false, false).await.unwrap();
+    assert_eq!(summary.records[0].action, RecordAction::Created);
+
+    server.set_ipv4("203.0.113.20");
+    let summary = run_once(&config, false, false, false).await.unwrap();
+    assert_eq!(summary.records[0].action, RecordAction::Held);
+    assert_eq!(summary.exit_code(), summary::EXIT_HELD);
+    assert_eq!(server.record_content("home.example.com", "A").as_deref(), Some("203.0.113.10"));
+
+    let summary = run_once(&config, true, false, false).await.unwrap();
+    assert_eq!(summary.records[0].action, RecordAction::Updated);
+    assert_eq!(server.record_content("home.example.com", "A").as_deref(), Some("203.0.113.20"));
+}
+
+#[tokio::test]
+async fn dry_run_writes_nothing() {
+    let server = MockServer::start().await;
+    server.set_ipv4("203.0.113.10");
+    server.add_record("home.example.com", "A", "198.51.100.1");
+    let dir = TempDir::new("dry_run");
+    let records = json!([record("home.example.com", "A"), record("new.example.com", "A")]);
+    let config = dir.write_config(&server.config(records));
+
+    let summary = run_once(&config, false, false, true).await.unwrap();
+
+    let actions: Vec<_> = summary.records.iter().map(|r| r.action).collect();
+    assert_eq!(actions, [RecordAction::WouldUpdate, RecordAction::WouldCreate]);
+    assert_eq!(summary.records[0].previous.as_deref(), Some("198.51.100.1"));
+    assert!(server.writes().is_empty());
+}
+
+#[tokio::test]
+async fn rejected_write_fails_the_run() {
+    let server = MockServer::start().await;
+    server.set_ipv4("203.0.113.10");
+    server.add_record("home.example.com", "A", "198.51.100.1");
+    server.fail_writes(403);
+    let dir = TempDir::new("rejected");
+    let records = json!([record("home.example.com", "A"), record("nas.example.com", "A")]);
+    let config = dir.write_config(&server.config(records));
+
+    let summary = run_once(&config, false, false, false).await.unwrap();
+
+    assert!(!summary.success);
+    assert_eq!(summary.records[0].action, RecordAction::Failed);
+    assert_eq!(summary.records[0].error_category, Some(ErrorCategory::Auth));
+    // 第一条记录失败后继续处理其余记录
+    assert_eq!(summary.records[1].action, RecordAction::Failed);
+    assert_eq!(summary.exit_code(), summary::exit_code(FailureKind::Api, ErrorCategory::Auth));
+    assert_eq!(server.record_content("home.example.com", "A").as_deref(), Some("198.51.100.1"));
+}
+
+#[tokio::test]
+async fn failure_threshold_counts_across_single_runs() {
+    let server = MockServer::start().await;
+    server.set_ipv4("203.0.113.10");
+    server.add_record("home.example.com", "A", "198.51.100.1");
+    server.fail_writes(403);
+    let dir = TempDir::new("failure_threshold");
+    let mut config = server.config(json!([record("home.example.com", "A")]));
+    config["notifications"] = json!({
+        "webhook": { "urls": [format!("{}/webhook", server.url())], "events": ["failed", "recovered"], "failure_threshold": 2 },
+    });
+    let config = dir.write_config(&config);
+
+    run_once(&config, false, false, false).await.unwrap();
+    assert!(server.webhook_events().is_empty());
+
+    // 第二次单次运行读取上一次保存的失败次数，达到阈值
+    run_once(&config, false, false, false).await.unwrap();
+    assert_eq!(server.webhook_events(), ["failed"]);
+
+    server.accept_writes();
+    let summary = run_once(&config, false, false, false).await.unwrap();
+    assert_eq!(summary.records[0].action, RecordAction::Updated);
+    assert_eq!(server.webhook_events(), ["failed", "recovered"]);
+}
+
+#[tokio::test]
+async fn webhook_retries_only_the_failed_url() {
+    let server = MockServer::start().await;
+    server.set_ipv4("203.0.113.10");
+    server.add_record("home.example.com", "A", "198.51.100.1");
+    let dir = TempDir::new("webhook_retry");
+    let mut config = server.config(json!([record("home.example.com", "A")]));
+    config["notifications"] = json!({
+        "webhook": { "urls": [format!("{}/webhook", server.url()), format!("{}/webhook/down", server.url())] },
+        "retry": { "backoff": "10ms", "max_attempts": 3, "flush_timeout": "5s" },
+    });
+    let config = dir.write_config(&config);
+
+    run_once(&config, false, false, false).await.unwrap();
+
+    let posts = |path: &str| server.requests().iter().filter(|r| r.method == "POST" && r.path == path).count();
+    assert_eq!(posts("/webhook"), 1);
+    assert_eq!(posts("/webhook/down"), 3);
+}
+
+#[tokio::test]
+async fn detection_failure_stops_before_api_calls() {
+    let server = MockServer::start().await;
+    let dir = TempDir::new("detection");
+    let config = dir.write_config(&server.config(json!([record("home.example.com", "A")])));
+
+    let error = run_once(&config, false, false, false).await.unwrap_err();
+
+    assert_eq!(error.kind, FailureKind::Detection);
+    assert!(server.requests().iter().all(|r| !r.path.starts_with("/client/v4")));
+}
+
+#[tokio::test]
+async fn builder_updates_without_a_config_file() {
+    let server = MockServer::start().await;
+    server.set_ipv4("203.0.113.10");
+    server.add_record("home.example.com", "A", "198.51.100.1");
+    let updater = DdnsUpdater::builder()
+        .api_token("test-token")
+        .endpoint(format!("{}/client/v4", server.url()))
+        .zone(common::ZONE_NAME)
+        .ipv4_url(format!("{}/ipv4", server.url()))
+        .a_record("home.example.com")
+        .updater()
+        .unwrap();
+
+    let summary = updater.run(false).await.unwrap();
+
+    assert_eq!(summary.records[0].action, RecordAction::Updated);
+    assert_eq!(server.record_content("home.example.com", "A").as_deref(), Some("203.0.113.10"));
+}